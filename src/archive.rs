@@ -0,0 +1,317 @@
+//! Compact binary archives of sessions and media for long-term storage.
+//!
+//! An archive is the magic bytes `OWNMARC`, a one-byte format version, then
+//! the [`Archive`] encoded with postcard. Records keep their stored timestamp
+//! strings and integrity fields unchanged so re-imported sessions still verify.
+//!
+//! postcard is not self-describing, so a field added to a record needs a new
+//! format version; older versions are decoded through their own structs.
+
+use serde::{Deserialize, Serialize};
+
+/// Magic bytes at the start of every archive.
+pub const ARCHIVE_MAGIC: &[u8; 7] = b"OWNMARC";
+
+/// Current archive format version.
+///
+/// - 1: the original session and media columns.
+/// - 2: input breakdowns, URL, process path, monitor, idle reason and tags
+///   on sessions, track length on media.
+pub const ARCHIVE_VERSION: u8 = 2;
+
+/// A window session as stored in the database.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedSession {
+    pub process_name: String,
+    pub window_title: Option<String>,
+    pub start_time: String,
+    pub end_time: Option<String>,
+    pub keystrokes: i64,
+    pub clicks: i64,
+    pub scrolls: i64,
+    pub is_idle: bool,
+    pub shortcuts: i64,
+    pub is_fullscreen: bool,
+    pub category_override: Option<i64>,
+    pub scroll_up: i64,
+    pub scroll_down: i64,
+    pub scroll_left: i64,
+    pub scroll_right: i64,
+    pub hash: Option<String>,
+    pub signature: Option<String>,
+    pub prev_hash: Option<String>,
+    #[serde(default)]
+    pub mouse_distance_px: i64,
+    #[serde(default)]
+    pub double_clicks: i64,
+    #[serde(default)]
+    pub keys_alphanumeric: i64,
+    #[serde(default)]
+    pub keys_whitespace: i64,
+    #[serde(default)]
+    pub keys_navigation: i64,
+    #[serde(default)]
+    pub keys_modifier: i64,
+    #[serde(default)]
+    pub keys_other: i64,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub process_path: Option<String>,
+    #[serde(default)]
+    pub monitor: Option<String>,
+    #[serde(default)]
+    pub idle_reason: Option<String>,
+    /// The session's tags, alphabetically.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// A media session as stored in the database.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedMedia {
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub source_app: Option<String>,
+    pub start_time: String,
+    pub end_time: Option<String>,
+    pub duration_secs: i64,
+    #[serde(default)]
+    pub track_duration_secs: Option<i64>,
+}
+
+/// Contents of an archive.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Archive {
+    pub sessions: Vec<ArchivedSession>,
+    pub media: Vec<ArchivedMedia>,
+}
+
+/// A version 1 session record.
+#[derive(Deserialize)]
+struct ArchivedSessionV1 {
+    process_name: String,
+    window_title: Option<String>,
+    start_time: String,
+    end_time: Option<String>,
+    keystrokes: i64,
+    clicks: i64,
+    scrolls: i64,
+    is_idle: bool,
+    shortcuts: i64,
+    is_fullscreen: bool,
+    category_override: Option<i64>,
+    scroll_up: i64,
+    scroll_down: i64,
+    scroll_left: i64,
+    scroll_right: i64,
+    hash: Option<String>,
+    signature: Option<String>,
+    prev_hash: Option<String>,
+}
+
+/// A version 1 media record.
+#[derive(Deserialize)]
+struct ArchivedMediaV1 {
+    title: String,
+    artist: Option<String>,
+    album: Option<String>,
+    source_app: Option<String>,
+    start_time: String,
+    end_time: Option<String>,
+    duration_secs: i64,
+}
+
+/// Contents of a version 1 archive.
+#[derive(Deserialize)]
+struct ArchiveV1 {
+    sessions: Vec<ArchivedSessionV1>,
+    media: Vec<ArchivedMediaV1>,
+}
+
+impl From<ArchiveV1> for Archive {
+    fn from(v1: ArchiveV1) -> Self {
+        let sessions = v1
+            .sessions
+            .into_iter()
+            .map(|s| ArchivedSession {
+                process_name: s.process_name,
+                window_title: s.window_title,
+                start_time: s.start_time,
+                end_time: s.end_time,
+                keystrokes: s.keystrokes,
+                clicks: s.clicks,
+                scrolls: s.scrolls,
+                is_idle: s.is_idle,
+                shortcuts: s.shortcuts,
+                is_fullscreen: s.is_fullscreen,
+                category_override: s.category_override,
+                scroll_up: s.scroll_up,
+                scroll_down: s.scroll_down,
+                scroll_left: s.scroll_left,
+                scroll_right: s.scroll_right,
+                hash: s.hash,
+                signature: s.signature,
+                prev_hash: s.prev_hash,
+                mouse_distance_px: 0,
+                double_clicks: 0,
+                keys_alphanumeric: 0,
+                keys_whitespace: 0,
+                keys_navigation: 0,
+                keys_modifier: 0,
+                keys_other: 0,
+                url: None,
+                process_path: None,
+                monitor: None,
+                idle_reason: None,
+                tags: Vec::new(),
+            })
+            .collect();
+        let media = v1
+            .media
+            .into_iter()
+            .map(|m| ArchivedMedia {
+                title: m.title,
+                artist: m.artist,
+                album: m.album,
+                source_app: m.source_app,
+                start_time: m.start_time,
+                end_time: m.end_time,
+                duration_secs: m.duration_secs,
+                track_duration_secs: None,
+            })
+            .collect();
+        Archive { sessions, media }
+    }
+}
+
+/// Counts of rows added by an archive import.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ArchiveImportSummary {
+    pub sessions: usize,
+    pub media: usize,
+    /// Records already present in the database.
+    pub skipped: usize,
+    /// Imported sessions stored without their hashes and signatures, because
+    /// the database already had its own hash chain.
+    pub unsigned: usize,
+}
+
+/// Errors that can occur while reading an archive.
+#[derive(Debug)]
+pub enum ArchiveError {
+    BadMagic,
+    UnsupportedVersion(u8),
+    Encoding(String),
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::BadMagic => write!(f, "Not an OwnMon archive"),
+            ArchiveError::UnsupportedVersion(v) => {
+                write!(f, "Unsupported archive version: {}", v)
+            }
+            ArchiveError::Encoding(e) => write!(f, "Invalid archive data: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+/// Encodes an archive with its header.
+pub fn encode_archive(archive: &Archive) -> Result<Vec<u8>, ArchiveError> {
+    let mut bytes = Vec::with_capacity(ARCHIVE_MAGIC.len() + 1);
+    bytes.extend_from_slice(ARCHIVE_MAGIC);
+    bytes.push(ARCHIVE_VERSION);
+    postcard::to_extend(archive, bytes).map_err(|e| ArchiveError::Encoding(e.to_string()))
+}
+
+/// Decodes an archive, checking its header.
+pub fn decode_archive(bytes: &[u8]) -> Result<Archive, ArchiveError> {
+    let body = bytes
+        .strip_prefix(ARCHIVE_MAGIC.as_slice())
+        .ok_or(ArchiveError::BadMagic)?;
+    let (&version, body) = body.split_first().ok_or(ArchiveError::BadMagic)?;
+    let encoding = |e: postcard::Error| ArchiveError::Encoding(e.to_string());
+    match version {
+        1 => postcard::from_bytes::<ArchiveV1>(body)
+            .map(Archive::from)
+            .map_err(encoding),
+        ARCHIVE_VERSION => postcard::from_bytes(body).map_err(encoding),
+        _ => Err(ArchiveError::UnsupportedVersion(version)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{Database, SessionIntegrity};
+    use crate::store::WindowSession;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_archive_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+        let start = Utc::now() - Duration::hours(2);
+        for (i, name) in ["code.exe", "chrome.exe", "slack.exe"].iter().enumerate() {
+            let mut session = WindowSession::new(1, 100, name.to_string(), format!("Window {}", i));
+            session.start_time = start + Duration::minutes(i as i64 * 10);
+            session.keystrokes = 100 * i as u64;
+            session.scroll_down = 7;
+            let end = session.start_time + Duration::minutes(5);
+            db.save_session(&session, end, SessionIntegrity::default())
+                .unwrap();
+        }
+        db.save_media(
+            "Song",
+            "Artist",
+            "Album",
+            "Spotify.exe",
+            start,
+            start + Duration::minutes(3),
+            Some(200),
+        )
+        .unwrap();
+
+        let archive = db.export_archive().unwrap();
+        assert_eq!(archive.sessions.len(), 3);
+        assert_eq!(archive.media.len(), 1);
+
+        let bytes = encode_archive(&archive).unwrap();
+        assert!(bytes.starts_with(ARCHIVE_MAGIC));
+        assert_eq!(decode_archive(&bytes).unwrap(), archive);
+
+        // Smaller than the same records as JSON
+        let json = serde_json::to_vec(&archive).unwrap();
+        assert!(bytes.len() < json.len());
+
+        // Re-import into a fresh database, then again to check duplicates are skipped
+        let restored = Database::open_in_memory().unwrap();
+        let summary = restored.import_archive(&archive).unwrap();
+        assert_eq!(
+            (summary.sessions, summary.media, summary.skipped),
+            (3, 1, 0)
+        );
+        assert_eq!(restored.export_archive().unwrap(), archive);
+
+        let again = restored.import_archive(&archive).unwrap();
+        assert_eq!((again.sessions, again.media, again.skipped), (0, 0, 4));
+    }
+
+    #[test]
+    fn test_decode_archive_rejects_bad_header() {
+        assert!(matches!(
+            decode_archive(b"{\"sessions\":[]}"),
+            Err(ArchiveError::BadMagic)
+        ));
+
+        let mut bytes = encode_archive(&Archive::default()).unwrap();
+        bytes[ARCHIVE_MAGIC.len()] = ARCHIVE_VERSION + 1;
+        assert!(matches!(
+            decode_archive(&bytes),
+            Err(ArchiveError::UnsupportedVersion(_))
+        ));
+    }
+}
@@ -0,0 +1,124 @@
+//! Start OwnMon when the user logs in.
+//!
+//! Adds or removes an `OwnMon` value under
+//! `HKCU\Software\Microsoft\Windows\CurrentVersion\Run` that launches the
+//! running executable.
+
+use std::path::Path;
+use windows::core::{w, PCWSTR};
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegDeleteValueW, RegGetValueW, RegOpenKeyExW, RegSetValueExW, HKEY,
+    HKEY_CURRENT_USER, KEY_SET_VALUE, REG_SZ, RRF_RT_REG_SZ,
+};
+
+/// Per-user Run key.
+const RUN_KEY: PCWSTR = w!("Software\\Microsoft\\Windows\\CurrentVersion\\Run");
+
+/// Name of the value under [`RUN_KEY`].
+const VALUE_NAME: PCWSTR = w!("OwnMon");
+
+/// Builds the Run command for `exe`, quoted so paths with spaces work.
+pub fn autostart_command(exe: &Path) -> String {
+    format!("\"{}\"", exe.display())
+}
+
+/// Registers the current executable to start at login.
+pub fn enable_autostart() -> windows::core::Result<()> {
+    let exe = std::env::current_exe()?;
+    let command = autostart_command(&exe);
+
+    // REG_SZ data is the nul-terminated UTF-16 string as bytes
+    let data: Vec<u8> = command
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(u16::to_le_bytes)
+        .collect();
+
+    with_run_key(|key| unsafe { RegSetValueExW(key, VALUE_NAME, 0, REG_SZ, Some(&data)).ok() })?;
+    tracing::info!(command = %command, "Autostart enabled");
+    Ok(())
+}
+
+/// Removes the login entry. Succeeds if there was none.
+pub fn disable_autostart() -> windows::core::Result<()> {
+    with_run_key(|key| {
+        let result = unsafe { RegDeleteValueW(key, VALUE_NAME) };
+        if result == windows::Win32::Foundation::ERROR_FILE_NOT_FOUND {
+            return Ok(());
+        }
+        result.ok()
+    })?;
+    tracing::info!("Autostart disabled");
+    Ok(())
+}
+
+/// Returns true if the login entry points at the current executable.
+pub fn is_autostart_enabled() -> bool {
+    let Some(registered) = registered_command() else {
+        return false;
+    };
+    std::env::current_exe()
+        .map(|exe| registered.eq_ignore_ascii_case(&autostart_command(&exe)))
+        .unwrap_or(false)
+}
+
+/// Reads the current login entry, if any.
+fn registered_command() -> Option<String> {
+    let mut size = 0u32;
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            RUN_KEY,
+            VALUE_NAME,
+            RRF_RT_REG_SZ,
+            None,
+            None,
+            Some(&mut size),
+        )
+        .ok()
+        .ok()?;
+    }
+
+    let mut buffer = vec![0u16; size as usize / 2 + 1];
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            RUN_KEY,
+            VALUE_NAME,
+            RRF_RT_REG_SZ,
+            None,
+            Some(buffer.as_mut_ptr().cast()),
+            Some(&mut size),
+        )
+        .ok()
+        .ok()?;
+    }
+
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
+
+/// Opens the Run key for writing and closes it after `f`.
+fn with_run_key(f: impl FnOnce(HKEY) -> windows::core::Result<()>) -> windows::core::Result<()> {
+    let mut key = HKEY::default();
+    unsafe { RegOpenKeyExW(HKEY_CURRENT_USER, RUN_KEY, 0, KEY_SET_VALUE, &mut key).ok()? };
+    let result = f(key);
+    unsafe {
+        let _ = RegCloseKey(key);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autostart_command_is_quoted() {
+        let exe = Path::new(r"C:\Program Files\OwnMon\ownmon.exe");
+        assert_eq!(
+            autostart_command(exe),
+            r#""C:\Program Files\OwnMon\ownmon.exe""#
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! Database backups and restore.
+//!
+//! `POST /api/backup` copies the live database into `backups/` with SQLite's
+//! online backup API. `ownmon --restore <path>` stages a backup, which is
+//! swapped in on the next start before the database is opened.
+
+use crate::database::Database;
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// File name of a staged restore inside the data directory.
+const PENDING_RESTORE: &str = "restore-pending.db";
+
+/// Timestamp format embedded in backup file names.
+const NAME_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// Prefixes of the backup file names OwnMon writes.
+const NAME_PREFIXES: [&str; 2] = ["activity-", "before-restore-"];
+
+/// A backup file on disk.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub file_name: String,
+    pub size_bytes: u64,
+    /// Last modified time (RFC 3339, local).
+    pub modified: Option<String>,
+}
+
+impl BackupInfo {
+    fn from_path(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(Self {
+            path: path.display().to_string(),
+            file_name: path.file_name()?.to_string_lossy().into_owned(),
+            size_bytes: metadata.len(),
+            modified: metadata
+                .modified()
+                .ok()
+                .map(|t| DateTime::<Local>::from(t).to_rfc3339()),
+        })
+    }
+}
+
+/// Returns the backups directory (%APPDATA%/ownmon/backups).
+pub fn backups_dir() -> PathBuf {
+    Database::data_dir().join("backups")
+}
+
+/// Backup file stem for a point in time, e.g. `activity-20251213-154500`.
+pub fn backup_file_stem(time: DateTime<Local>) -> String {
+    format!("activity-{}", time.format(NAME_TIME_FORMAT))
+}
+
+/// Returns `dir/<stem>.db`, or `dir/<stem>-2.db`, `-3`, ... if that's taken,
+/// so two backups made in the same second don't overwrite each other.
+fn unique_backup_path(dir: &Path, stem: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}.db", stem));
+    let mut counter = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.db", stem, counter));
+        counter += 1;
+    }
+    path
+}
+
+/// Time and same-second counter embedded in a backup file name, e.g.
+/// `before-restore-20251213-154500-2.db`. None for names OwnMon didn't write.
+fn name_order(file_name: &str) -> Option<(NaiveDateTime, u32)> {
+    let stem = file_name.strip_suffix(".db")?;
+    let rest = NAME_PREFIXES
+        .iter()
+        .find_map(|prefix| stem.strip_prefix(prefix))?;
+    let stamp = rest.get(..15)?;
+    let counter = match &rest[15..] {
+        "" => 1,
+        suffix => suffix.strip_prefix('-')?.parse().ok()?,
+    };
+    let time = NaiveDateTime::parse_from_str(stamp, NAME_TIME_FORMAT).ok()?;
+    Some((time, counter))
+}
+
+/// Sort key for a backup: the time in its name, else its modified time.
+fn backup_order(backup: &BackupInfo) -> (NaiveDateTime, u32) {
+    name_order(&backup.file_name).unwrap_or_else(|| {
+        let modified = backup
+            .modified
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.naive_local())
+            .unwrap_or(NaiveDateTime::MIN);
+        (modified, 0)
+    })
+}
+
+/// Copies the live database into `dir` and returns the new backup.
+pub fn create_backup(db: &Database, dir: &Path) -> Result<BackupInfo, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+
+    let path = unique_backup_path(dir, &backup_file_stem(Local::now()));
+    db.backup_to(&path)
+        .map_err(|e| format!("Backup to {} failed: {}", path.display(), e))?;
+    tracing::info!(path = %path.display(), "Database backed up");
+
+    BackupInfo::from_path(&path).ok_or_else(|| format!("Backup {} is missing", path.display()))
+}
+
+/// Lists the `.db` files in `dir`, newest first.
+pub fn list_backups(dir: &Path) -> Vec<BackupInfo> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<BackupInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "db"))
+        .filter_map(|path| BackupInfo::from_path(&path))
+        .collect();
+    // Sort on the embedded time rather than the name, which would put every
+    // `before-restore-*` ahead of the `activity-*` backups
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup_order(backup)));
+    backups
+}
+
+/// Parses `--restore <path>` from the command line (without the program name).
+///
+/// Returns `Ok(None)` when `--restore` isn't given.
+pub fn parse_restore_arg(
+    args: impl IntoIterator<Item = String>,
+) -> Result<Option<PathBuf>, String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--restore" {
+            let path = args.next().ok_or("--restore needs a path")?;
+            return Ok(Some(PathBuf::from(path)));
+        }
+    }
+    Ok(None)
+}
+
+/// Checks that `backup` is an OwnMon database and stages it in `data_dir`
+/// to replace the live database on the next start.
+pub fn stage_restore(backup: &Path, data_dir: &Path) -> Result<PathBuf, String> {
+    let db = Database::open_read_only(backup)
+        .map_err(|e| format!("Cannot open {}: {}", backup.display(), e))?;
+    db.get_storage_stats()
+        .map_err(|e| format!("{} is not an OwnMon database: {}", backup.display(), e))?;
+    drop(db);
+
+    std::fs::create_dir_all(data_dir)
+        .map_err(|e| format!("Cannot create {}: {}", data_dir.display(), e))?;
+    let pending = data_dir.join(PENDING_RESTORE);
+    std::fs::copy(backup, &pending)
+        .map_err(|e| format!("Cannot stage {}: {}", backup.display(), e))?;
+    Ok(pending)
+}
+
+/// Swaps a staged restore in for `db_path`, if there is one.
+///
+/// The current database (with its `-wal`/`-shm` files) is moved into
+/// `backups_dir` first, so nothing is lost. Must run before the database is
+/// opened. Returns true if a restore was applied.
+pub fn apply_pending_restore(
+    data_dir: &Path,
+    db_path: &Path,
+    backups_dir: &Path,
+) -> Result<bool, String> {
+    let pending = data_dir.join(PENDING_RESTORE);
+    if !pending.exists() {
+        return Ok(false);
+    }
+
+    if db_path.exists() {
+        std::fs::create_dir_all(backups_dir)
+            .map_err(|e| format!("Cannot create {}: {}", backups_dir.display(), e))?;
+        let stem = format!("before-restore-{}", Local::now().format(NAME_TIME_FORMAT));
+        let target = unique_backup_path(backups_dir, &stem);
+        for suffix in ["", "-wal", "-shm"] {
+            let from = PathBuf::from(format!("{}{}", db_path.display(), suffix));
+            if from.exists() {
+                let to = PathBuf::from(format!("{}{}", target.display(), suffix));
+                std::fs::rename(&from, &to)
+                    .map_err(|e| format!("Cannot move {}: {}", from.display(), e))?;
+            }
+        }
+    }
+
+    std::fs::rename(&pending, db_path)
+        .map_err(|e| format!("Cannot restore into {}: {}", db_path.display(), e))?;
+    tracing::info!(path = %db_path.display(), "Restored database from backup");
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_restore_arg() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(parse_restore_arg(args(&[])), Ok(None));
+        assert_eq!(
+            parse_restore_arg(args(&["--restore", "b.db"])),
+            Ok(Some(PathBuf::from("b.db")))
+        );
+        assert!(parse_restore_arg(args(&["--restore"])).is_err());
+    }
+
+    #[test]
+    fn test_apply_pending_restore() {
+        let dir = std::env::temp_dir().join(format!("ownmon-restore-{}", std::process::id()));
+        let backups = dir.join("backups");
+        let db_path = dir.join("activity.db");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Nothing staged
+        assert_eq!(apply_pending_restore(&dir, &db_path, &backups), Ok(false));
+
+        std::fs::write(&db_path, b"live").unwrap();
+        std::fs::write(dir.join(PENDING_RESTORE), b"backup").unwrap();
+        assert_eq!(apply_pending_restore(&dir, &db_path, &backups), Ok(true));
+
+        assert_eq!(std::fs::read(&db_path).unwrap(), b"backup");
+        assert!(!dir.join(PENDING_RESTORE).exists());
+        let saved = list_backups(&backups);
+        assert_eq!(saved.len(), 1);
+        assert!(saved[0].file_name.starts_with("before-restore-"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_list_backups_sorts_by_name_time() {
+        let dir = std::env::temp_dir().join(format!("ownmon-backups-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "activity-20251213-154500.db",
+            "activity-20251213-154500-2.db",
+            "before-restore-20251210-090000.db",
+            "activity-20251211-120000.db",
+        ] {
+            std::fs::write(dir.join(name), b"db").unwrap();
+        }
+
+        let names: Vec<String> = list_backups(&dir)
+            .into_iter()
+            .map(|b| b.file_name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "activity-20251213-154500-2.db",
+                "activity-20251213-154500.db",
+                "activity-20251211-120000.db",
+                "before-restore-20251210-090000.db",
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unique_backup_path_adds_counter() {
+        let dir = std::env::temp_dir().join(format!("ownmon-unique-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let first = unique_backup_path(&dir, "activity-20251213-154500");
+        assert_eq!(first, dir.join("activity-20251213-154500.db"));
+        std::fs::write(&first, b"db").unwrap();
+        let second = unique_backup_path(&dir, "activity-20251213-154500");
+        assert_eq!(second, dir.join("activity-20251213-154500-2.db"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
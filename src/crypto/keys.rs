@@ -2,7 +2,7 @@
 //!
 //! - Generates keypair on first run
 //! - Stores private key in Windows Credential Manager
-//! - Stores public key in config directory
+//! - Stores public key in the data directory
 
 use base64::Engine;
 use ed25519_dalek::{SigningKey, VerifyingKey, SECRET_KEY_LENGTH};
@@ -76,12 +76,9 @@ impl KeyManager {
         base64::engine::general_purpose::STANDARD.encode(self.verifying_key.as_bytes())
     }
 
-    /// Get public key file path.
+    /// Get public key file path (in [`Database::data_dir`](crate::database::Database::data_dir)).
     pub fn public_key_path() -> PathBuf {
-        dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("ownmon")
-            .join("public_key.txt")
+        crate::database::Database::data_dir().join("public_key.txt")
     }
 
     /// Load private key from Windows Credential Manager.
@@ -18,22 +18,67 @@ pub fn build_merkle_root(hashes: &[String]) -> Option<String> {
     let mut current_level: Vec<String> = hashes.to_vec();
 
     while current_level.len() > 1 {
-        let mut next_level = Vec::new();
+        current_level = parent_level(&current_level);
+    }
 
-        for chunk in current_level.chunks(2) {
-            let combined_hash = if chunk.len() == 2 {
-                hash_pair(&chunk[0], &chunk[1])
-            } else {
-                // Odd number of hashes: duplicate the last one
-                hash_pair(&chunk[0], &chunk[0])
-            };
-            next_level.push(combined_hash);
-        }
+    Some(current_level[0].clone())
+}
 
-        current_level = next_level;
+/// Hashes each pair of a level into the level above.
+fn parent_level(level: &[String]) -> Vec<String> {
+    level
+        .chunks(2)
+        .map(|chunk| match chunk {
+            [left, right] => hash_pair(left, right),
+            // Odd number of hashes: duplicate the last one
+            [last] => hash_pair(last, last),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Builds an inclusion proof for the hash at `index`.
+///
+/// Returns the sibling hash at each level from the leaf up, flagged `true`
+/// when the sibling is the left node. An odd last node is paired with
+/// itself, as in [`build_merkle_root`]. Empty for a single hash or an
+/// out-of-range index.
+pub fn merkle_proof(hashes: &[String], index: usize) -> Vec<(String, bool)> {
+    if index >= hashes.len() {
+        return Vec::new();
     }
 
-    Some(current_level[0].clone())
+    let mut proof = Vec::new();
+    let mut level = hashes.to_vec();
+    let mut index = index;
+
+    while level.len() > 1 {
+        let sibling = if index % 2 == 1 {
+            (level[index - 1].clone(), true)
+        } else {
+            let right = level.get(index + 1).unwrap_or(&level[index]);
+            (right.clone(), false)
+        };
+        proof.push(sibling);
+        level = parent_level(&level);
+        index /= 2;
+    }
+
+    proof
+}
+
+/// Checks that `leaf` combined with `proof` hashes up to `root`.
+pub fn verify_merkle_proof(leaf: &str, proof: &[(String, bool)], root: &str) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf.to_string(), |node, (sibling, is_left)| {
+            if *is_left {
+                hash_pair(sibling, &node)
+            } else {
+                hash_pair(&node, sibling)
+            }
+        });
+    computed == root
 }
 
 /// Hash two strings together to form a parent node.
@@ -120,6 +165,31 @@ mod tests {
         assert!(!verify_merkle_root(&hashes, "wrong_root"));
     }
 
+    #[test]
+    fn test_merkle_proof_round_trip() {
+        for n in 1..=9 {
+            let hashes: Vec<String> = (0..n).map(|i| format!("h{}", i)).collect();
+            let root = build_merkle_root(&hashes).unwrap();
+
+            for (index, leaf) in hashes.iter().enumerate() {
+                let proof = merkle_proof(&hashes, index);
+                assert!(
+                    verify_merkle_proof(leaf, &proof, &root),
+                    "n={n} index={index}"
+                );
+                assert!(!verify_merkle_proof("forged", &proof, &root));
+            }
+        }
+
+        // The odd last node is paired with itself
+        let hashes = vec!["h1".to_string(), "h2".to_string(), "h3".to_string()];
+        let proof = merkle_proof(&hashes, 2);
+        assert_eq!(proof[0], ("h3".to_string(), false));
+        assert_eq!(proof[1], (hash_pair("h1", "h2"), true));
+
+        assert!(merkle_proof(&hashes, 3).is_empty());
+    }
+
     #[test]
     fn test_deterministic() {
         let hashes = vec!["a".to_string(), "b".to_string(), "c".to_string()];
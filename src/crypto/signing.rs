@@ -44,6 +44,28 @@ pub fn hash_session_data(
     hex::encode(result)
 }
 
+/// Compute the fingerprint (hex SHA256) of a public key.
+pub fn key_fingerprint(key: &VerifyingKey) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Build the payload signed for a daily integrity record.
+///
+/// Records without a key fingerprint use the original
+/// `merkle_root|prev_day_root|date` format.
+pub fn daily_sign_data(
+    merkle_root: &str,
+    prev_day_root: Option<&str>,
+    date: &str,
+    key_fingerprint: Option<&str>,
+) -> String {
+    let prev = prev_day_root.unwrap_or("genesis");
+    match key_fingerprint {
+        Some(fp) => format!("{}|{}|{}|{}", merkle_root, prev, date, fp),
+        None => format!("{}|{}|{}", merkle_root, prev, date),
+    }
+}
+
 /// Sign a hash with the signing key.
 pub fn sign_hash(hash: &str, key: &SigningKey) -> String {
     let signature: Signature = key.sign(hash.as_bytes());
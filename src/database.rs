@@ -3,10 +3,17 @@
 //! This module provides crash-safe persistence for activity data.
 //! Data is saved periodically and on session changes to minimize loss.
 
-use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, Result as SqlResult};
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use crate::archive::{Archive, ArchiveImportSummary, ArchivedMedia, ArchivedSession};
+use crate::rules::{RuleImportSummary, RulePack};
+use crate::store::{
+    timezone_policy, IdleReason, KeyBreakdown, RunSummary, TimezonePolicy, TypingSample,
+    WindowSession,
+};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, Result as SqlResult};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 /// Database wrapper with thread-safe connection.
 pub struct Database {
@@ -22,11 +29,64 @@ pub struct DailyIntegrityRecord {
     pub session_count: u32,
     pub signature: String,
     pub created_at: String,
+    /// Fingerprint of the public key that signed this record (`None` for legacy records).
+    pub key_fingerprint: Option<String>,
 }
+
+/// Environment variable that overrides [`Database::data_dir`], e.g. for a
+/// portable copy or separate profiles. `--data-dir` sets it on startup.
+pub const DATA_DIR_ENV: &str = "OWNMON_DATA_DIR";
+
+/// Removes `--data-dir <path>` from the command-line arguments.
+///
+/// Returns the directory, if given, and the remaining arguments.
+pub fn take_data_dir_arg(
+    args: impl IntoIterator<Item = String>,
+) -> Result<(Option<PathBuf>, Vec<String>), String> {
+    let mut data_dir = None;
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--data-dir" {
+            let path = args.next().ok_or("--data-dir needs a path")?;
+            data_dir = Some(PathBuf::from(path));
+        } else {
+            rest.push(arg);
+        }
+    }
+    Ok((data_dir, rest))
+}
+
+/// A schema migration step.
+type Migration = (&'static str, fn(&Connection) -> SqlResult<()>);
+
+/// Schema migrations in order: `MIGRATIONS[i]` upgrades version `i + 1` to
+/// `i + 2`. Version 1 is the original schema.
+///
+/// `init_schema` creates missing tables with their latest columns before
+/// these run, so steps must be idempotent (see `add_column_if_missing`).
+/// To change the schema, append a step here.
+const MIGRATIONS: &[Migration] = &[
+    ("input metrics", Database::migrate_v2_input_metrics),
+    ("integrity binding", Database::migrate_v3_integrity_binding),
+    ("media track duration", Database::migrate_v4_track_duration),
+    ("browser urls", Database::migrate_v5_browser_urls),
+    (
+        "distracting categories",
+        Database::migrate_v6_distracting_categories,
+    ),
+    ("process paths", Database::migrate_v7_process_paths),
+    ("monitors", Database::migrate_v8_monitors),
+    ("idle reasons", Database::migrate_v9_idle_reasons),
+];
+
+/// Schema version of a fully migrated database (`PRAGMA user_version`).
+pub const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64 + 1;
+
 impl Database {
     /// Opens or creates the database at the default location.
     ///
-    /// Creates %APPDATA%/ownmon/activity.db if it doesn't exist.
+    /// Creates activity.db in [`data_dir`](Self::data_dir) if it doesn't exist.
     pub fn open() -> SqlResult<Self> {
         let db_path = Self::get_db_path();
 
@@ -52,6 +112,19 @@ impl Database {
         Ok(db)
     }
 
+    /// Opens an existing database read-only, without touching its schema.
+    ///
+    /// Used by offline tools such as `ownmon --verify`.
+    pub fn open_read_only(path: &Path) -> SqlResult<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
     /// Opens an in-memory database (for testing).
     #[cfg(test)]
     pub fn open_in_memory() -> SqlResult<Self> {
@@ -63,17 +136,42 @@ impl Database {
         Ok(db)
     }
 
+    /// Locks the connection, recovering it if a panic poisoned the lock.
+    ///
+    /// A panic mid-query leaves no transaction open (rusqlite rolls back on
+    /// drop), so the connection is still usable and later queries shouldn't
+    /// all fail because of one.
+    fn lock_conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|poisoned| {
+            self.conn.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+
+    /// Returns the default database path (%APPDATA%/ownmon/activity.db).
+    pub fn default_path() -> PathBuf {
+        Self::get_db_path()
+    }
+
+    /// Returns the data directory: `OWNMON_DATA_DIR` if set, otherwise
+    /// %APPDATA%/ownmon.
+    pub fn data_dir() -> PathBuf {
+        match std::env::var_os(DATA_DIR_ENV) {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("ownmon"),
+        }
+    }
+
     /// Returns the default database path.
     fn get_db_path() -> PathBuf {
-        dirs::data_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("ownmon")
-            .join("activity.db")
+        Self::data_dir().join("activity.db")
     }
 
     /// Initializes the database schema.
     fn init_schema(&self) -> SqlResult<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
 
         conn.execute_batch(
             r#"
@@ -90,7 +188,13 @@ impl Database {
                 is_idle BOOLEAN DEFAULT 0,
                 hash TEXT,
                 signature TEXT,
-                prev_hash TEXT
+                prev_hash TEXT,
+                shortcuts INTEGER DEFAULT 0,
+                is_fullscreen BOOLEAN DEFAULT 0,
+                url TEXT,
+                process_path TEXT,
+                monitor TEXT,
+                idle_reason TEXT
             );
 
             -- Media playback
@@ -102,7 +206,8 @@ impl Database {
                 source_app TEXT,
                 start_time TEXT NOT NULL,
                 end_time TEXT,
-                duration_secs INTEGER DEFAULT 0
+                duration_secs INTEGER DEFAULT 0,
+                track_duration_secs INTEGER
             );
 
             -- Blacklist for apps to ignore
@@ -120,7 +225,8 @@ impl Database {
                 name TEXT NOT NULL UNIQUE,
                 color TEXT NOT NULL,
                 icon TEXT,
-                created_at TEXT NOT NULL
+                created_at TEXT NOT NULL,
+                distracting BOOLEAN DEFAULT 0
             );
 
             -- App to category mapping (patterns support wildcards)
@@ -129,6 +235,28 @@ impl Database {
                 category_id INTEGER REFERENCES categories(id)
             );
 
+            -- Per-app idle thresholds (override afk_threshold_secs)
+            CREATE TABLE IF NOT EXISTS app_idle_overrides (
+                process_pattern TEXT PRIMARY KEY,
+                threshold_secs INTEGER NOT NULL
+            );
+
+            -- Regexes for sensitive text in window titles (see title_redaction)
+            CREATE TABLE IF NOT EXISTS redaction_patterns (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                pattern TEXT NOT NULL UNIQUE,
+                created_at TEXT NOT NULL
+            );
+
+            -- Goals that notify once reached (e.g. 10000 keystrokes a day)
+            CREATE TABLE IF NOT EXISTS goals (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                metric TEXT NOT NULL,
+                threshold INTEGER NOT NULL,
+                period TEXT NOT NULL DEFAULT 'daily',
+                created_at TEXT NOT NULL
+            );
+
             -- Configuration settings
             CREATE TABLE IF NOT EXISTS config (
                 key TEXT PRIMARY KEY,
@@ -144,18 +272,79 @@ impl Database {
                 prev_day_root TEXT,
                 session_count INTEGER NOT NULL,
                 signature TEXT NOT NULL,
-                created_at TEXT NOT NULL
+                created_at TEXT NOT NULL,
+                key_fingerprint TEXT
+            );
+
+            -- End-of-run summaries saved at shutdown
+            CREATE TABLE IF NOT EXISTS session_summary (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                ended_at TEXT NOT NULL,
+                summary_json TEXT NOT NULL
+            );
+
+            -- Running counts of the current session, for crash recovery
+            CREATE TABLE IF NOT EXISTS session_checkpoint (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                process_name TEXT NOT NULL,
+                window_title TEXT,
+                start_time TEXT NOT NULL,
+                checkpoint_time TEXT NOT NULL,
+                keystrokes INTEGER DEFAULT 0,
+                clicks INTEGER DEFAULT 0,
+                scrolls INTEGER DEFAULT 0,
+                shortcuts INTEGER DEFAULT 0,
+                is_idle BOOLEAN DEFAULT 0,
+                is_fullscreen BOOLEAN DEFAULT 0,
+                category_override INTEGER,
+                scroll_up INTEGER DEFAULT 0,
+                scroll_down INTEGER DEFAULT 0,
+                scroll_left INTEGER DEFAULT 0,
+                scroll_right INTEGER DEFAULT 0,
+                mouse_distance_px INTEGER DEFAULT 0,
+                double_clicks INTEGER DEFAULT 0,
+                keys_alphanumeric INTEGER DEFAULT 0,
+                keys_whitespace INTEGER DEFAULT 0,
+                keys_navigation INTEGER DEFAULT 0,
+                keys_modifier INTEGER DEFAULT 0,
+                keys_other INTEGER DEFAULT 0,
+                url TEXT,
+                process_path TEXT,
+                monitor TEXT,
+                idle_reason TEXT
+            );
+
+            -- Free-form labels the user attaches to sessions
+            CREATE TABLE IF NOT EXISTS session_tags (
+                session_id INTEGER NOT NULL REFERENCES sessions(id),
+                tag TEXT NOT NULL,
+                PRIMARY KEY (session_id, tag)
+            );
+
+            -- Keystrokes per clock minute, for typing cadence
+            CREATE TABLE IF NOT EXISTS typing_samples (
+                minute TEXT PRIMARY KEY,
+                keystrokes INTEGER NOT NULL
+            );
+
+            -- Hash of the last signed session deleted by retention, which the
+            -- oldest remaining session chains to
+            CREATE TABLE IF NOT EXISTS chain_anchor (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                hash TEXT NOT NULL,
+                updated_at TEXT NOT NULL
             );
 
             -- Indexes for date queries
             CREATE INDEX IF NOT EXISTS idx_sessions_start ON sessions(start_time);
             CREATE INDEX IF NOT EXISTS idx_media_start ON media(start_time);
             CREATE INDEX IF NOT EXISTS idx_sessions_date ON sessions(date(start_time));
+            CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags(tag);
             "#,
         )?;
 
-        // Migration: Add integrity columns if they don't exist
-        Self::migrate_integrity_columns(&conn)?;
+        // Bring existing databases up to the current schema version
+        Self::run_migrations(&conn)?;
 
         // Insert default blacklist entries if table is empty
         let count: i64 = conn.query_row("SELECT COUNT(*) FROM blacklist", [], |r| r.get(0))?;
@@ -237,9 +426,8 @@ impl Database {
             );
         }
 
-        // Seed default config if empty
-        let config_count: i64 = conn.query_row("SELECT COUNT(*) FROM config", [], |r| r.get(0))?;
-        if config_count == 0 {
+        // Seed default config (missing keys are also added to existing databases)
+        {
             let now = Utc::now().to_rfc3339();
             let defaults = [
                 (
@@ -247,6 +435,11 @@ impl Database {
                     "10",
                     "Minimum session duration to save (seconds)",
                 ),
+                (
+                    "min_media_duration_secs",
+                    "30",
+                    "Minimum media session duration to save (seconds)",
+                ),
                 (
                     "afk_threshold_secs",
                     "300",
@@ -268,22 +461,315 @@ impl Database {
                     "3600",
                     "How often to prune old sessions (seconds)",
                 ),
+                (
+                    "wal_checkpoint_secs",
+                    "600",
+                    "How often the write-ahead log is checkpointed and truncated (seconds, 0 = off)",
+                ),
+                (
+                    "retention_days",
+                    "0",
+                    "Delete sessions and media older than this many days (0 = keep forever)",
+                ),
+                (
+                    "vacuum_after_prune",
+                    "false",
+                    "Rebuild the database file after retention deletes rows (tracking waits while it runs)",
+                ),
+                (
+                    "api_token",
+                    "",
+                    "Bearer token required for /api/* and /ws (empty disables auth)",
+                ),
+                (
+                    "bind_address",
+                    "127.0.0.1",
+                    "Address the HTTP server binds to (0.0.0.0 exposes it on the LAN; restart required)",
+                ),
+                (
+                    "http_port",
+                    "13234",
+                    "HTTP server port; the next free port is used if taken (restart required)",
+                ),
+                (
+                    "debug_event_log",
+                    "false",
+                    "Keep the last 1024 raw keyboard and mouse events in memory for /api/debug/events",
+                ),
+                (
+                    "privacy_mode",
+                    "false",
+                    "Only count keystrokes, never which keys: no key breakdown, shortcuts or debug event log",
+                ),
+                (
+                    "ws_channel_capacity",
+                    "1024",
+                    "WebSocket broadcasts buffered per client before it is sent a resync (restart required)",
+                ),
+                (
+                    "count_idle_in_focus",
+                    "false",
+                    "Count idle (AFK) sessions toward focus time in /api/stats",
+                ),
+                (
+                    "dashboard_mode",
+                    "off",
+                    "How time viewing the OwnMon dashboard in a browser is tracked: off, tag or exclude",
+                ),
+                (
+                    "dashboard_browsers",
+                    "chrome.exe,msedge.exe,firefox.exe,brave.exe,opera.exe,vivaldi.exe",
+                    "Comma-separated browser patterns checked for the dashboard",
+                ),
+                (
+                    "dashboard_title_patterns",
+                    "OwnMon*,*localhost:13234*,*127.0.0.1:13234*",
+                    "Comma-separated window title patterns that identify the dashboard",
+                ),
+                (
+                    "category_weights",
+                    crate::store::DEFAULT_CATEGORY_WEIGHTS,
+                    "JSON object of category name to productivity weight (-1 to 1) for /api/stats/score",
+                ),
+                (
+                    "category_alerts",
+                    "",
+                    "Comma-separated category names that trigger an alert when focused during work hours (empty = off)",
+                ),
+                (
+                    "work_hours",
+                    "09:00-17:00",
+                    "Local work hours for category alerts (HH:MM-HH:MM)",
+                ),
+                (
+                    "category_alert_debounce_secs",
+                    "300",
+                    "Minimum time between two category alerts (seconds)",
+                ),
+                (
+                    "focus_mode",
+                    "false",
+                    "Notify when a window in a distracting category is focused",
+                ),
+                (
+                    "focus_mode_debounce_secs",
+                    "300",
+                    "Minimum time between two focus mode notifications for the same app (seconds)",
+                ),
+                (
+                    "max_media_session_secs",
+                    "14400",
+                    "Longest media session counted; longer or unreported ones are closed. Window sessions aren't capped (seconds, 0 = no limit)",
+                ),
+                (
+                    "normalize_media_titles",
+                    "false",
+                    "Treat media titles that differ only by tags like (Remastered) or - Live as the same track",
+                ),
+                (
+                    "media_title_tags",
+                    "",
+                    "JSON array of regexes stripped from media titles when normalizing (empty = built-in list)",
+                ),
+                (
+                    "default_session_limit",
+                    "500",
+                    "Default number of rows returned by /api/sessions and /api/media",
+                ),
+                (
+                    "max_session_limit",
+                    "2000",
+                    "Largest `limit` accepted by /api/sessions and /api/media",
+                ),
+                (
+                    "privacy_patterns",
+                    "",
+                    "Comma-separated app patterns that pause tracking while focused",
+                ),
+                (
+                    "track_desktop_time",
+                    "false",
+                    "Track time on the desktop or with no focused window as [Desktop]/[No window] sessions",
+                ),
+                (
+                    "current_save_interval_secs",
+                    "30",
+                    "How often the current session's running counts are saved for crash recovery (seconds, 0 = off)",
+                ),
+                (
+                    "rules_path",
+                    "",
+                    "Rules file merged into categories/blacklist at startup (empty = rules.json in the data folder)",
+                ),
+                (
+                    "fullscreen_suppresses_idle",
+                    "false",
+                    "Don't split idle time while a full-screen app is focused (e.g. watching a movie)",
+                ),
+                (
+                    "integrity_key_fingerprint",
+                    "true",
+                    "Bind daily integrity records to the signing key fingerprint",
+                ),
+                (
+                    "focus_grace_ms",
+                    "0",
+                    "How long a newly focused window must hold focus before it starts a session (milliseconds)",
+                ),
+                (
+                    "timezone",
+                    "local",
+                    "Time zone used for dates (today, daily stats, sealed days): local, utc or +HH:MM",
+                ),
+                (
+                    "title_redaction",
+                    "none",
+                    "How window titles matching redaction_patterns are stored: none, hash or strip",
+                ),
+                (
+                    "track_browser_urls",
+                    "true",
+                    "Record the address bar URL of Chrome, Edge and Firefox sessions",
+                ),
+                (
+                    "media_tracking_enabled",
+                    "true",
+                    "Track currently playing media (turn off to skip media polling)",
+                ),
+                (
+                    "merge_gap_secs",
+                    "10",
+                    "Largest gap (seconds) between sessions of the same app joined by /api/sessions?merge=true",
+                ),
             ];
 
+            let mut added = 0;
             for (key, value, description) in defaults {
-                conn.execute(
-                    "INSERT INTO config (key, value, description, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                added += conn.execute(
+                    "INSERT OR IGNORE INTO config (key, value, description, updated_at) VALUES (?1, ?2, ?3, ?4)",
                     params![key, value, description, &now],
                 )?;
             }
 
-            tracing::info!("Added {} default config settings", defaults.len());
+            if added > 0 {
+                tracing::info!("Added {} default config settings", added);
+            }
         }
 
         tracing::debug!("Database schema initialized");
         Ok(())
     }
 
+    /// Gets the schema version stored in `PRAGMA user_version`.
+    pub fn schema_version(&self) -> SqlResult<i64> {
+        let conn = self.lock_conn();
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+    }
+
+    /// Applies the [`MIGRATIONS`] newer than the database's schema version.
+    ///
+    /// Databases created before versioning report version 0; they get the
+    /// integrity columns and are then treated as version 1. Each step runs in
+    /// its own transaction together with the version update.
+    fn run_migrations(conn: &Connection) -> SqlResult<()> {
+        let mut version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version == 0 {
+            Self::migrate_integrity_columns(conn)?;
+            version = 1;
+        }
+        if version > SCHEMA_VERSION {
+            tracing::warn!(
+                version,
+                supported = SCHEMA_VERSION,
+                "Database schema is newer than this build"
+            );
+            return Ok(());
+        }
+
+        for (index, (name, migrate)) in MIGRATIONS.iter().enumerate() {
+            let target = index as i64 + 2;
+            if target <= version {
+                continue;
+            }
+            tracing::info!(
+                version = target,
+                migration = name,
+                "Migrating database schema"
+            );
+            let tx = conn.unchecked_transaction()?;
+            migrate(&tx)?;
+            tx.pragma_update(None, "user_version", target)?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+
+    /// v2: per-session input metrics and the manual category override.
+    fn migrate_v2_input_metrics(conn: &Connection) -> SqlResult<()> {
+        Self::add_column_if_missing(conn, "sessions", "shortcuts", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(conn, "sessions", "is_fullscreen", "BOOLEAN DEFAULT 0")?;
+        Self::add_column_if_missing(conn, "sessions", "category_override", "INTEGER")?;
+        Self::add_column_if_missing(conn, "session_checkpoint", "category_override", "INTEGER")?;
+        for column in [
+            "scroll_up",
+            "scroll_down",
+            "scroll_left",
+            "scroll_right",
+            "mouse_distance_px",
+            "double_clicks",
+            "keys_alphanumeric",
+            "keys_whitespace",
+            "keys_navigation",
+            "keys_modifier",
+            "keys_other",
+        ] {
+            Self::add_column_if_missing(conn, "sessions", column, "INTEGER DEFAULT 0")?;
+            Self::add_column_if_missing(conn, "session_checkpoint", column, "INTEGER DEFAULT 0")?;
+        }
+        Ok(())
+    }
+
+    /// v3: signing key fingerprint and UTC range of sealed days.
+    fn migrate_v3_integrity_binding(conn: &Connection) -> SqlResult<()> {
+        Self::add_column_if_missing(conn, "daily_integrity", "key_fingerprint", "TEXT")?;
+        Self::add_column_if_missing(conn, "daily_integrity", "range_start", "TEXT")?;
+        Self::add_column_if_missing(conn, "daily_integrity", "range_end", "TEXT")
+    }
+
+    /// v4: media track length.
+    fn migrate_v4_track_duration(conn: &Connection) -> SqlResult<()> {
+        Self::add_column_if_missing(conn, "media", "track_duration_secs", "INTEGER")
+    }
+
+    /// v5: browser URLs.
+    fn migrate_v5_browser_urls(conn: &Connection) -> SqlResult<()> {
+        Self::add_column_if_missing(conn, "sessions", "url", "TEXT")?;
+        Self::add_column_if_missing(conn, "session_checkpoint", "url", "TEXT")
+    }
+
+    /// v6: categories flagged as distracting for focus mode.
+    fn migrate_v6_distracting_categories(conn: &Connection) -> SqlResult<()> {
+        Self::add_column_if_missing(conn, "categories", "distracting", "BOOLEAN DEFAULT 0")
+    }
+
+    /// v7: executable paths on sessions.
+    fn migrate_v7_process_paths(conn: &Connection) -> SqlResult<()> {
+        Self::add_column_if_missing(conn, "sessions", "process_path", "TEXT")?;
+        Self::add_column_if_missing(conn, "session_checkpoint", "process_path", "TEXT")
+    }
+
+    /// v8: monitor device names on sessions.
+    fn migrate_v8_monitors(conn: &Connection) -> SqlResult<()> {
+        Self::add_column_if_missing(conn, "sessions", "monitor", "TEXT")?;
+        Self::add_column_if_missing(conn, "session_checkpoint", "monitor", "TEXT")
+    }
+
+    /// v9: why idle sessions were idle (away, locked, screensaver).
+    fn migrate_v9_idle_reasons(conn: &Connection) -> SqlResult<()> {
+        Self::add_column_if_missing(conn, "sessions", "idle_reason", "TEXT")?;
+        Self::add_column_if_missing(conn, "session_checkpoint", "idle_reason", "TEXT")
+    }
+
     /// Migrates existing database to add integrity columns.
     fn migrate_integrity_columns(conn: &Connection) -> SqlResult<()> {
         // Check if hash column exists
@@ -304,39 +790,139 @@ impl Database {
         Ok(())
     }
 
+    /// Adds a column to an existing table if it isn't there yet.
+    fn add_column_if_missing(
+        conn: &Connection,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> SqlResult<()> {
+        let exists = conn
+            .prepare(&format!("SELECT {} FROM {} LIMIT 1", column, table))
+            .is_ok();
+
+        if !exists {
+            tracing::info!(table, column, "Migrating database: adding column");
+            conn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {};",
+                table, column, definition
+            ))?;
+        }
+
+        Ok(())
+    }
+
     /// Gets the last session hash for chaining.
+    ///
+    /// Falls back to the chain anchor when retention deleted every signed session.
     pub fn get_last_session_hash(&self) -> SqlResult<Option<String>> {
-        let conn = self.conn.lock().unwrap();
-        let result: Result<String, _> = conn.query_row(
-            "SELECT hash FROM sessions WHERE hash IS NOT NULL ORDER BY id DESC LIMIT 1",
+        let conn = self.lock_conn();
+        conn.query_row(
+            "SELECT COALESCE(
+                (SELECT hash FROM sessions WHERE hash IS NOT NULL ORDER BY id DESC LIMIT 1),
+                (SELECT hash FROM chain_anchor WHERE id = 1))",
             [],
             |row| row.get(0),
+        )
+    }
+
+    /// The UTC range `[start, end)` of sessions sealed under a date.
+    ///
+    /// A sealed day keeps the range it was sealed with, so changing
+    /// `timezone` doesn't break its verification. Records sealed before
+    /// ranges were stored cover the UTC date. Other days use the current
+    /// timezone policy. Returns `None` for an invalid date.
+    fn integrity_day_range(conn: &Connection, date: &str) -> SqlResult<Option<(String, String)>> {
+        let Ok(day) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+            return Ok(None);
+        };
+        let stored = conn.query_row(
+            "SELECT range_start, range_end FROM daily_integrity WHERE date = ?1",
+            params![date],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                ))
+            },
         );
-        match result {
-            Ok(hash) => Ok(Some(hash)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
-        }
+        let (start, end) = match stored {
+            Ok((Some(start), Some(end))) => return Ok(Some((start, end))),
+            Ok(_) => TimezonePolicy::Utc.day_range(day),
+            Err(rusqlite::Error::QueryReturnedNoRows) => timezone_policy().day_range(day),
+            Err(e) => return Err(e),
+        };
+        Ok(Some((start.to_rfc3339(), end.to_rfc3339())))
     }
 
     /// Gets all session hashes for a specific date (for Merkle tree).
     pub fn get_session_hashes_for_date(&self, date: &str) -> SqlResult<Vec<String>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
+        let Some((start, end)) = Self::integrity_day_range(&conn, date)? else {
+            return Ok(Vec::new());
+        };
         let mut stmt = conn.prepare(
-            "SELECT hash FROM sessions WHERE hash IS NOT NULL AND date(start_time) = ?1 ORDER BY id",
+            "SELECT hash FROM sessions WHERE hash IS NOT NULL
+               AND julianday(start_time) >= julianday(?1) AND julianday(start_time) < julianday(?2)
+             ORDER BY id",
         )?;
         let hashes = stmt
-            .query_map(params![date], |row| row.get(0))?
+            .query_map(params![start, end], |row| row.get(0))?
             .collect::<Result<Vec<String>, _>>()?;
         Ok(hashes)
     }
 
+    /// Gets the signed sessions for a date, in the same order as
+    /// [`Database::get_session_hashes_for_date`].
+    pub fn get_signed_sessions_for_date(&self, date: &str) -> SqlResult<Vec<SignedSessionRecord>> {
+        let conn = self.lock_conn();
+        let Some((start, end)) = Self::integrity_day_range(&conn, date)? else {
+            return Ok(Vec::new());
+        };
+        let mut stmt = conn.prepare(
+            "SELECT id, process_name, window_title, start_time, end_time, keystrokes, clicks, scrolls, hash, signature, prev_hash
+             FROM sessions WHERE hash IS NOT NULL
+               AND julianday(start_time) >= julianday(?1) AND julianday(start_time) < julianday(?2)
+             ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![start, end], |row| {
+            Ok(SignedSessionRecord {
+                id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                start_time: row.get(3)?,
+                end_time: row.get(4)?,
+                keystrokes: row.get(5)?,
+                clicks: row.get(6)?,
+                scrolls: row.get(7)?,
+                hash: row.get(8)?,
+                signature: row.get(9)?,
+                prev_hash: row.get(10)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Gets the hash of the last signed session saved before session `id`.
+    ///
+    /// Falls back to the chain anchor when retention deleted every earlier one.
+    pub fn get_session_hash_before(&self, id: i64) -> SqlResult<Option<String>> {
+        let conn = self.lock_conn();
+        conn.query_row(
+            "SELECT COALESCE(
+                (SELECT hash FROM sessions WHERE hash IS NOT NULL AND id < ?1 ORDER BY id DESC LIMIT 1),
+                (SELECT hash FROM chain_anchor WHERE id = 1))",
+            params![id],
+            |row| row.get(0),
+        )
+    }
+
     /// Gets the count of sessions for a specific date.
     pub fn get_session_count_for_date(&self, date: &str) -> SqlResult<u32> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         let count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM sessions WHERE date(start_time) = ?1",
-            params![date],
+            "SELECT COUNT(*) FROM sessions WHERE date(start_time, ?2) = ?1",
+            params![date, timezone_policy().sql_modifier()],
             |row| row.get(0),
         )?;
         Ok(count as u32)
@@ -344,7 +930,7 @@ impl Database {
 
     /// Gets the previous day's Merkle root for chaining.
     pub fn get_previous_day_root(&self, date: &str) -> SqlResult<Option<String>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         let result: Result<String, _> = conn.query_row(
             "SELECT merkle_root FROM daily_integrity WHERE date < ?1 ORDER BY date DESC LIMIT 1",
             params![date],
@@ -365,34 +951,47 @@ impl Database {
         prev_day_root: Option<&str>,
         session_count: u32,
         signature: &str,
+        key_fingerprint: Option<&str>,
     ) -> SqlResult<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         let now = Utc::now().to_rfc3339();
+        let (range_start, range_end) = Self::integrity_day_range(&conn, date)?.unzip();
+        conn.execute(
+            "INSERT OR REPLACE INTO daily_integrity (date, merkle_root, prev_day_root, session_count, signature, created_at, key_fingerprint, range_start, range_end)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![date, merkle_root, prev_day_root, session_count as i64, signature, now, key_fingerprint, range_start, range_end],
+        )?;
+        Ok(())
+    }
+
+    /// Re-links a sealed day to a new previous-day root.
+    ///
+    /// Only the link and its signature change; the day keeps its Merkle
+    /// root, session count and the UTC range it was sealed with.
+    pub fn relink_daily_integrity(
+        &self,
+        date: &str,
+        prev_day_root: Option<&str>,
+        signature: &str,
+        key_fingerprint: Option<&str>,
+    ) -> SqlResult<()> {
+        let conn = self.lock_conn();
         conn.execute(
-            "INSERT OR REPLACE INTO daily_integrity (date, merkle_root, prev_day_root, session_count, signature, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![date, merkle_root, prev_day_root, session_count as i64, signature, now],
+            "UPDATE daily_integrity SET prev_day_root = ?2, signature = ?3, key_fingerprint = ?4
+             WHERE date = ?1",
+            params![date, prev_day_root, signature, key_fingerprint],
         )?;
         Ok(())
     }
 
     /// Gets daily integrity for a specific date.
     pub fn get_daily_integrity(&self, date: &str) -> SqlResult<Option<DailyIntegrityRecord>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         let result = conn.query_row(
-            "SELECT date, merkle_root, prev_day_root, session_count, signature, created_at 
+            "SELECT date, merkle_root, prev_day_root, session_count, signature, created_at, key_fingerprint
              FROM daily_integrity WHERE date = ?1",
             params![date],
-            |row| {
-                Ok(DailyIntegrityRecord {
-                    date: row.get(0)?,
-                    merkle_root: row.get(1)?,
-                    prev_day_root: row.get(2)?,
-                    session_count: row.get::<_, i64>(3)? as u32,
-                    signature: row.get(4)?,
-                    created_at: row.get(5)?,
-                })
-            },
+            daily_integrity_from_row,
         );
         match result {
             Ok(record) => Ok(Some(record)),
@@ -403,83 +1002,522 @@ impl Database {
 
     /// Gets all daily integrity records for verification.
     pub fn get_all_daily_integrity(&self) -> SqlResult<Vec<DailyIntegrityRecord>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         let mut stmt = conn.prepare(
-            "SELECT date, merkle_root, prev_day_root, session_count, signature, created_at 
+            "SELECT date, merkle_root, prev_day_root, session_count, signature, created_at, key_fingerprint
              FROM daily_integrity ORDER BY date",
         )?;
         let records = stmt
-            .query_map([], |row| {
-                Ok(DailyIntegrityRecord {
-                    date: row.get(0)?,
-                    merkle_root: row.get(1)?,
-                    prev_day_root: row.get(2)?,
-                    session_count: row.get::<_, i64>(3)? as u32,
-                    signature: row.get(4)?,
-                    created_at: row.get(5)?,
-                })
-            })?
+            .query_map([], daily_integrity_from_row)?
             .collect::<Result<Vec<_>, _>>()?;
         Ok(records)
     }
 
-    /// Gets dates that have sessions but no daily integrity record (incomplete days).
-    pub fn get_dates_missing_integrity(&self, exclude_today: &str) -> SqlResult<Vec<String>> {
-        let conn = self.conn.lock().unwrap();
+    /// Gets daily integrity records for dates in `from..=to`, oldest first.
+    pub fn get_daily_integrity_range(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> SqlResult<Vec<DailyIntegrityRecord>> {
+        let conn = self.lock_conn();
         let mut stmt = conn.prepare(
-            "SELECT DISTINCT date(start_time) as session_date 
-             FROM sessions 
-             WHERE hash IS NOT NULL 
-               AND date(start_time) < ?1
-               AND date(start_time) NOT IN (SELECT date FROM daily_integrity)
-             ORDER BY session_date",
+            "SELECT date, merkle_root, prev_day_root, session_count, signature, created_at, key_fingerprint
+             FROM daily_integrity WHERE date >= ?1 AND date <= ?2 ORDER BY date",
         )?;
-        let dates = stmt
-            .query_map(params![exclude_today], |row| row.get(0))?
-            .collect::<Result<Vec<String>, _>>()?;
-        Ok(dates)
+        let records = stmt
+            .query_map(params![from, to], daily_integrity_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(records)
+    }
+
+    /// Gets the most recent date with a daily integrity record.
+    pub fn get_latest_integrity_date(&self) -> SqlResult<Option<String>> {
+        let conn = self.lock_conn();
+        conn.query_row("SELECT MAX(date) FROM daily_integrity", [], |row| {
+            row.get(0)
+        })
+    }
+
+    /// Saves the end-of-run summary.
+    pub fn save_run_summary(&self, summary: &RunSummary) -> SqlResult<i64> {
+        let json = serde_json::to_string(summary)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let conn = self.lock_conn();
+        conn.execute(
+            "INSERT INTO session_summary (ended_at, summary_json) VALUES (?1, ?2)",
+            params![summary.ended_at.to_rfc3339(), json],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Gets the most recently saved end-of-run summary.
+    pub fn get_last_run_summary(&self) -> SqlResult<Option<RunSummary>> {
+        let conn = self.lock_conn();
+        let result: Result<String, _> = conn.query_row(
+            "SELECT summary_json FROM session_summary ORDER BY id DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(json) => serde_json::from_str(&json).map(Some).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            }),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Gets every date with signed sessions or a daily integrity record, oldest first.
+    pub fn get_integrity_dates(&self) -> SqlResult<Vec<String>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT date(start_time, ?1) AS d FROM sessions WHERE hash IS NOT NULL
+             UNION
+             SELECT date FROM daily_integrity
+             ORDER BY d",
+        )?;
+        let dates = stmt
+            .query_map(params![timezone_policy().sql_modifier()], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(dates)
+    }
+
+    /// Gets dates that have sessions but no daily integrity record (incomplete days).
+    pub fn get_dates_missing_integrity(&self, exclude_today: &str) -> SqlResult<Vec<String>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT date(start_time, ?2) as session_date 
+             FROM sessions 
+             WHERE hash IS NOT NULL 
+               AND session_date < ?1
+               AND session_date NOT IN (SELECT date FROM daily_integrity)
+             ORDER BY session_date",
+        )?;
+        let dates = stmt
+            .query_map(
+                params![exclude_today, timezone_policy().sql_modifier()],
+                |row| row.get(0),
+            )?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(dates)
     }
 
     /// Saves a completed window session with integrity data.
-    #[allow(clippy::too_many_arguments)]
     pub fn save_session(
         &self,
-        process_name: &str,
-        window_title: &str,
-        start_time: DateTime<Utc>,
+        session: &WindowSession,
         end_time: DateTime<Utc>,
-        keystrokes: u64,
-        clicks: u64,
-        scrolls: u64,
-        is_idle: bool,
-        hash: Option<&str>,
-        signature: Option<&str>,
-        prev_hash: Option<&str>,
+        integrity: SessionIntegrity<'_>,
     ) -> SqlResult<i64> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
 
         conn.execute(
-            "INSERT INTO sessions (process_name, window_title, start_time, end_time, keystrokes, clicks, scrolls, is_idle, hash, signature, prev_hash)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            "INSERT INTO sessions (process_name, window_title, start_time, end_time, keystrokes, clicks, scrolls, is_idle, hash, signature, prev_hash, shortcuts, is_fullscreen, category_override, scroll_up, scroll_down, scroll_left, scroll_right, mouse_distance_px, double_clicks,
+                                   keys_alphanumeric, keys_whitespace, keys_navigation, keys_modifier, keys_other, url, process_path, monitor, idle_reason)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29)",
             params![
-                process_name,
-                window_title,
-                start_time.to_rfc3339(),
+                session.process_name,
+                session.window_title,
+                session.start_time.to_rfc3339(),
                 end_time.to_rfc3339(),
-                keystrokes as i64,
-                clicks as i64,
-                scrolls as i64,
-                is_idle,
-                hash,
-                signature,
-                prev_hash,
+                session.keystrokes as i64,
+                session.mouse_clicks as i64,
+                session.mouse_scrolls as i64,
+                session.is_idle,
+                integrity.hash,
+                integrity.signature,
+                integrity.prev_hash,
+                session.shortcuts as i64,
+                session.is_fullscreen,
+                session.category_override,
+                session.scroll_up as i64,
+                session.scroll_down as i64,
+                session.scroll_left as i64,
+                session.scroll_right as i64,
+                session.mouse_distance_px as i64,
+                session.double_clicks as i64,
+                session.key_breakdown.alphanumeric as i64,
+                session.key_breakdown.whitespace as i64,
+                session.key_breakdown.navigation as i64,
+                session.key_breakdown.modifier as i64,
+                session.key_breakdown.other as i64,
+                session.url,
+                session.process_path,
+                session.monitor,
+                session.idle_reason.map(IdleReason::as_str),
             ],
         )?;
 
         Ok(conn.last_insert_rowid())
     }
 
+    /// Checks whether a session with this process and start time is already saved.
+    pub fn session_exists(&self, process_name: &str, start_time: DateTime<Utc>) -> SqlResult<bool> {
+        let conn = self.lock_conn();
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sessions WHERE process_name = ?1 AND start_time = ?2",
+            params![process_name, start_time.to_rfc3339()],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Copies the database to `path` with SQLite's online backup API.
+    ///
+    /// Holds the connection lock for the duration, so the copy is consistent
+    /// and includes changes still in the WAL.
+    pub fn backup_to(&self, path: &Path) -> SqlResult<()> {
+        let conn = self.lock_conn();
+        conn.backup(rusqlite::DatabaseName::Main, path, None)
+    }
+
+    // === Retention ===
+
+    /// Start of the first day kept when retaining `days` days (whole days
+    /// under the timezone policy), as a UTC timestamp.
+    fn retention_cutoff(days: i64) -> String {
+        let policy = timezone_policy();
+        let first_kept = policy.date_of(Utc::now()) - chrono::Duration::days(days);
+        policy.day_start(first_kept).to_rfc3339()
+    }
+
+    /// Deletes sessions from days more than `days` days ago (0 or less keeps everything).
+    ///
+    /// Days already sealed into `daily_integrity` are deleted too, with a warning:
+    /// their record is kept so later days still chain, but they no longer verify.
+    /// The hash of the last deleted signed session becomes the chain anchor,
+    /// so the oldest remaining session still verifies.
+    /// Returns the number of sessions deleted.
+    pub fn prune_sessions_older_than(&self, days: i64) -> SqlResult<usize> {
+        if days <= 0 {
+            return Ok(0);
+        }
+        let cutoff = Self::retention_cutoff(days);
+        let conn = self.lock_conn();
+
+        let sealed: Vec<String> = {
+            let mut stmt = conn.prepare(
+                "SELECT date FROM daily_integrity
+                 WHERE date IN (SELECT DISTINCT date(start_time, ?2) FROM sessions WHERE start_time < ?1)
+                 ORDER BY date",
+            )?;
+            let rows = stmt
+                .query_map(params![cutoff, timezone_policy().sql_modifier()], |row| {
+                    row.get(0)
+                })?;
+            rows.collect::<SqlResult<_>>()?
+        };
+        if let (Some(first), Some(last)) = (sealed.first(), sealed.last()) {
+            tracing::warn!(
+                days = sealed.len(),
+                first = %first,
+                last = %last,
+                "Pruning sessions from sealed days; they will no longer verify"
+            );
+        }
+
+        let tx = conn.unchecked_transaction()?;
+        let anchor: Result<String, _> = tx.query_row(
+            "SELECT hash FROM sessions WHERE hash IS NOT NULL AND start_time < ?1
+             ORDER BY id DESC LIMIT 1",
+            params![cutoff],
+            |row| row.get(0),
+        );
+        match anchor {
+            Ok(hash) => {
+                tx.execute(
+                    "INSERT OR REPLACE INTO chain_anchor (id, hash, updated_at) VALUES (1, ?1, ?2)",
+                    params![hash, Utc::now().to_rfc3339()],
+                )?;
+            }
+            Err(rusqlite::Error::QueryReturnedNoRows) => {}
+            Err(e) => return Err(e),
+        }
+        tx.execute(
+            "DELETE FROM session_tags
+             WHERE session_id IN (SELECT id FROM sessions WHERE start_time < ?1)",
+            params![cutoff],
+        )?;
+        let deleted = tx.execute(
+            "DELETE FROM sessions WHERE start_time < ?1",
+            params![cutoff],
+        )?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// Deletes media from days more than `days` days ago (0 or less keeps everything).
+    /// Returns the number of rows deleted.
+    pub fn prune_media_older_than(&self, days: i64) -> SqlResult<usize> {
+        if days <= 0 {
+            return Ok(0);
+        }
+        let cutoff = Self::retention_cutoff(days);
+        let conn = self.lock_conn();
+        conn.execute("DELETE FROM media WHERE start_time < ?1", params![cutoff])
+    }
+
+    /// Deletes typing samples older than the given number of days.
+    pub fn prune_typing_samples_older_than(&self, days: i64) -> SqlResult<usize> {
+        if days <= 0 {
+            return Ok(0);
+        }
+        let cutoff = Self::retention_cutoff(days);
+        let conn = self.lock_conn();
+        conn.execute(
+            "DELETE FROM typing_samples WHERE minute < ?1",
+            params![cutoff],
+        )
+    }
+
+    /// Rebuilds the database file to reclaim space freed by deletes.
+    pub fn vacuum(&self) -> SqlResult<()> {
+        let conn = self.lock_conn();
+        conn.execute_batch("VACUUM")
+    }
+
+    // === Diagnostics ===
+
+    /// Gets database size and row counts for maintenance decisions.
+    pub fn get_storage_stats(&self) -> SqlResult<StorageStats> {
+        let conn = self.lock_conn();
+
+        let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+        let sessions: i64 =
+            conn.query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))?;
+        let media: i64 = conn.query_row("SELECT COUNT(*) FROM media", [], |row| row.get(0))?;
+        let oldest_record: Option<String> = conn.query_row(
+            "SELECT MIN(d) FROM (
+                SELECT MIN(start_time) AS d FROM sessions
+                UNION ALL
+                SELECT MIN(start_time) AS d FROM media
+             )",
+            [],
+            |row| row.get(0),
+        )?;
+
+        // File sizes on disk (in-memory databases have no path)
+        let db_path = conn.path().filter(|p| !p.is_empty()).map(|p| p.to_string());
+        let file_size_bytes = db_path
+            .as_deref()
+            .and_then(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .unwrap_or(0);
+        let wal_size_bytes = wal_file_size(&conn);
+
+        let data_size_bytes = page_count * page_size;
+        let bytes_per_day = oldest_record
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|oldest| (Utc::now() - oldest.with_timezone(&Utc)).num_seconds() as f64 / 86400.0)
+            .map(|days| data_size_bytes as f64 / days.max(1.0));
+
+        Ok(StorageStats {
+            db_path,
+            file_size_bytes,
+            wal_size_bytes,
+            data_size_bytes,
+            sessions,
+            media,
+            oldest_record,
+            bytes_per_day,
+        })
+    }
+
+    /// Gets the size of the `-wal` file on disk (0 for in-memory databases).
+    pub fn wal_size_bytes(&self) -> u64 {
+        let conn = self.lock_conn();
+        wal_file_size(&conn)
+    }
+
+    /// Copies the WAL back into the database file and truncates it to zero bytes.
+    ///
+    /// Returns false if a reader or writer kept the checkpoint from finishing;
+    /// the remaining frames are copied by a later checkpoint.
+    pub fn checkpoint_wal(&self) -> SqlResult<bool> {
+        let conn = self.lock_conn();
+        let busy: i64 = conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))?;
+        Ok(busy == 0)
+    }
+
+    // === Typing Samples ===
+
+    /// Saves per-minute keystroke counts, adding to a minute already saved
+    /// (e.g. one split by a restart).
+    pub fn save_typing_samples(&self, samples: &[TypingSample]) -> SqlResult<()> {
+        let conn = self.lock_conn();
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO typing_samples (minute, keystrokes) VALUES (?1, ?2)
+                 ON CONFLICT(minute) DO UPDATE SET keystrokes = keystrokes + excluded.keystrokes",
+            )?;
+            for sample in samples {
+                stmt.execute(params![
+                    sample.minute.to_rfc3339(),
+                    sample.keystrokes as i64
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Gets the typing samples of minutes starting in `[from, to)`, oldest first.
+    pub fn get_typing_samples(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> SqlResult<Vec<TypingSample>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT minute, keystrokes FROM typing_samples
+             WHERE julianday(minute) >= julianday(?1) AND julianday(minute) < julianday(?2)
+             ORDER BY minute",
+        )?;
+        let rows = stmt.query_map(params![from.to_rfc3339(), to.to_rfc3339()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+
+        let mut samples = Vec::new();
+        for row in rows {
+            let (minute, keystrokes) = row?;
+            if let Ok(minute) = DateTime::parse_from_rfc3339(&minute) {
+                samples.push(TypingSample {
+                    minute: minute.with_timezone(&Utc),
+                    keystrokes: keystrokes.max(0) as u64,
+                });
+            }
+        }
+        Ok(samples)
+    }
+
+    // === Session Checkpoint (crash recovery) ===
+
+    /// Saves the running counts of the current session, replacing any previous checkpoint.
+    pub fn save_session_checkpoint(
+        &self,
+        session: &WindowSession,
+        checkpoint_time: DateTime<Utc>,
+    ) -> SqlResult<()> {
+        let conn = self.lock_conn();
+        conn.execute(
+            "INSERT OR REPLACE INTO session_checkpoint
+                (id, process_name, window_title, start_time, checkpoint_time, keystrokes, clicks, scrolls, shortcuts, is_idle, is_fullscreen, category_override, scroll_up, scroll_down, scroll_left, scroll_right, mouse_distance_px, double_clicks,
+                 keys_alphanumeric, keys_whitespace, keys_navigation, keys_modifier, keys_other, url, process_path, monitor, idle_reason)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26)",
+            params![
+                session.process_name,
+                session.window_title,
+                session.start_time.to_rfc3339(),
+                checkpoint_time.to_rfc3339(),
+                session.keystrokes as i64,
+                session.mouse_clicks as i64,
+                session.mouse_scrolls as i64,
+                session.shortcuts as i64,
+                session.is_idle,
+                session.is_fullscreen,
+                session.category_override,
+                session.scroll_up as i64,
+                session.scroll_down as i64,
+                session.scroll_left as i64,
+                session.scroll_right as i64,
+                session.mouse_distance_px as i64,
+                session.double_clicks as i64,
+                session.key_breakdown.alphanumeric as i64,
+                session.key_breakdown.whitespace as i64,
+                session.key_breakdown.navigation as i64,
+                session.key_breakdown.modifier as i64,
+                session.key_breakdown.other as i64,
+                session.url,
+                session.process_path,
+                session.monitor,
+                session.idle_reason.map(IdleReason::as_str),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Removes and returns the saved checkpoint, ended at its checkpoint time.
+    pub fn take_session_checkpoint(&self) -> SqlResult<Option<WindowSession>> {
+        let conn = self.lock_conn();
+        let result = conn.query_row(
+            "SELECT process_name, window_title, start_time, checkpoint_time, keystrokes, clicks, scrolls, shortcuts, is_idle, is_fullscreen, category_override,
+                    scroll_up, scroll_down, scroll_left, scroll_right, mouse_distance_px, double_clicks,
+                    keys_alphanumeric, keys_whitespace, keys_navigation, keys_modifier, keys_other, url, process_path, monitor, idle_reason
+             FROM session_checkpoint WHERE id = 1",
+            [],
+            |row| {
+                let parse_time = |idx: usize, value: String| {
+                    DateTime::parse_from_rfc3339(&value)
+                        .map(|t| t.with_timezone(&Utc))
+                        .map_err(|e| {
+                            rusqlite::Error::FromSqlConversionFailure(
+                                idx,
+                                rusqlite::types::Type::Text,
+                                Box::new(e),
+                            )
+                        })
+                };
+
+                let mut session = WindowSession::new(
+                    0,
+                    0,
+                    row.get(0)?,
+                    row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                );
+                session.start_time = parse_time(2, row.get(2)?)?;
+                session.end_time = Some(parse_time(3, row.get(3)?)?);
+                session.keystrokes = row.get::<_, i64>(4)? as u64;
+                session.mouse_clicks = row.get::<_, i64>(5)? as u64;
+                session.mouse_scrolls = row.get::<_, i64>(6)? as u64;
+                session.shortcuts = row.get::<_, i64>(7)? as u64;
+                session.is_idle = row.get(8)?;
+                session.is_fullscreen = row.get(9)?;
+                session.category_override = row.get(10)?;
+                session.scroll_up = row.get::<_, Option<i64>>(11)?.unwrap_or(0) as u64;
+                session.scroll_down = row.get::<_, Option<i64>>(12)?.unwrap_or(0) as u64;
+                session.scroll_left = row.get::<_, Option<i64>>(13)?.unwrap_or(0) as u64;
+                session.scroll_right = row.get::<_, Option<i64>>(14)?.unwrap_or(0) as u64;
+                session.mouse_distance_px = row.get::<_, Option<i64>>(15)?.unwrap_or(0) as u64;
+                session.double_clicks = row.get::<_, Option<i64>>(16)?.unwrap_or(0) as u64;
+                session.key_breakdown = key_breakdown_from_row(row, 17)?;
+                session.url = row.get(22)?;
+                session.process_path = row.get(23)?;
+                session.monitor = row.get(24)?;
+                session.idle_reason = row
+                    .get::<_, Option<String>>(25)?
+                    .as_deref()
+                    .and_then(IdleReason::parse);
+                Ok(session)
+            },
+        );
+
+        let session = match result {
+            Ok(session) => Some(session),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e),
+        };
+
+        conn.execute("DELETE FROM session_checkpoint", [])?;
+        Ok(session)
+    }
+
+    /// Removes the saved checkpoint (after a clean shutdown).
+    pub fn clear_session_checkpoint(&self) -> SqlResult<()> {
+        let conn = self.lock_conn();
+        conn.execute("DELETE FROM session_checkpoint", [])?;
+        Ok(())
+    }
+
     /// Saves a completed media session.
+    ///
+    /// `track_duration_secs` is the track length reported by the player, if any.
+    #[allow(clippy::too_many_arguments)]
     pub fn save_media(
         &self,
         title: &str,
@@ -488,13 +1526,14 @@ impl Database {
         source_app: &str,
         start_time: DateTime<Utc>,
         end_time: DateTime<Utc>,
+        track_duration_secs: Option<i64>,
     ) -> SqlResult<i64> {
         let duration_secs = (end_time - start_time).num_seconds().max(0);
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
 
         conn.execute(
-            "INSERT INTO media (title, artist, album, source_app, start_time, end_time, duration_secs)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            "INSERT INTO media (title, artist, album, source_app, start_time, end_time, duration_secs, track_duration_secs)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             params![
                 title,
                 artist,
@@ -503,6 +1542,7 @@ impl Database {
                 start_time.to_rfc3339(),
                 end_time.to_rfc3339(),
                 duration_secs,
+                track_duration_secs,
             ],
         )?;
 
@@ -523,34 +1563,25 @@ impl Database {
         offset: usize,
         order_desc: bool,
     ) -> SqlResult<(Vec<MediaRecord>, i64)> {
-        let conn = self.conn.lock().unwrap();
+        let limit = limit.min(self.query_limits().max);
+        let conn = self.lock_conn();
 
         let mut conditions = vec!["end_time IS NOT NULL".to_string()];
 
         if let Some(d) = date {
-            conditions.push(format!("start_time LIKE '{}%'", d));
+            conditions.push(date_condition(d));
         }
         if let Some(f) = from {
-            conditions.push(format!("start_time >= '{}'", f));
+            conditions.push(format!("start_time >= '{}'", escape_sql_literal(f)));
         }
         if let Some(t) = to {
-            conditions.push(format!("start_time <= '{}'", t));
+            conditions.push(format!("start_time <= '{}'", escape_sql_literal(t)));
         }
         if let Some(a) = artist {
-            if a.contains('*') {
-                let pattern = a.replace('*', "%");
-                conditions.push(format!("artist LIKE '{}'", pattern));
-            } else {
-                conditions.push(format!("artist = '{}'", a));
-            }
+            conditions.push(wildcard_condition("artist", a));
         }
         if let Some(s) = source_app {
-            if s.contains('*') {
-                let pattern = s.replace('*', "%");
-                conditions.push(format!("source_app LIKE '{}'", pattern));
-            } else {
-                conditions.push(format!("source_app = '{}'", s));
-            }
+            conditions.push(wildcard_condition("source_app", s));
         }
 
         let where_clause = conditions.join(" AND ");
@@ -564,7 +1595,7 @@ impl Database {
 
         // Get media
         let sql = format!(
-            "SELECT id, title, artist, album, source_app, start_time, end_time, duration_secs
+            "SELECT id, title, artist, album, source_app, start_time, end_time, duration_secs, track_duration_secs
              FROM media 
              WHERE {}
              ORDER BY start_time {}
@@ -583,6 +1614,7 @@ impl Database {
                 start_time: row.get(5)?,
                 end_time: row.get(6)?,
                 duration_secs: row.get(7)?,
+                track_duration_secs: row.get(8)?,
             })
         })?;
 
@@ -590,21 +1622,166 @@ impl Database {
         Ok((media, total))
     }
 
+    /// Gets the most listened artists between two dates (YYYY-MM-DD, inclusive).
+    ///
+    /// Media with no artist is left out. Sorted by listen time, longest first.
+    pub fn get_top_artists(
+        &self,
+        from: &str,
+        to: &str,
+        limit: usize,
+    ) -> SqlResult<Vec<ArtistListenStats>> {
+        let mut artists: HashMap<String, (ArtistListenStats, HashSet<String>)> = HashMap::new();
+        for play in self.media_plays(from, to)? {
+            if play.artist.is_empty() {
+                continue;
+            }
+            let (entry, tracks) = artists.entry(play.artist.clone()).or_insert_with(|| {
+                let stats = ArtistListenStats {
+                    artist: play.artist.clone(),
+                    listen_secs: 0,
+                    play_count: 0,
+                    track_count: 0,
+                };
+                (stats, HashSet::new())
+            });
+            entry.listen_secs += play.listen_secs;
+            entry.play_count += 1;
+            tracks.insert(play.title);
+            entry.track_count = tracks.len() as i64;
+        }
+
+        let mut artists: Vec<ArtistListenStats> =
+            artists.into_values().map(|(stats, _)| stats).collect();
+        artists.sort_by(|a, b| {
+            b.listen_secs
+                .cmp(&a.listen_secs)
+                .then_with(|| b.play_count.cmp(&a.play_count))
+                .then_with(|| a.artist.cmp(&b.artist))
+        });
+        artists.truncate(limit);
+        Ok(artists)
+    }
+
+    /// Gets the most listened tracks between two dates (YYYY-MM-DD, inclusive).
+    ///
+    /// Sorted by listen time, longest first.
+    pub fn get_top_tracks(
+        &self,
+        from: &str,
+        to: &str,
+        limit: usize,
+    ) -> SqlResult<Vec<TrackListenStats>> {
+        let mut tracks: HashMap<(String, String), TrackListenStats> = HashMap::new();
+        for play in self.media_plays(from, to)? {
+            let entry = tracks
+                .entry((play.title.clone(), play.artist.clone()))
+                .or_insert_with(|| TrackListenStats {
+                    title: play.title,
+                    artist: play.artist,
+                    listen_secs: 0,
+                    play_count: 0,
+                });
+            entry.listen_secs += play.listen_secs;
+            entry.play_count += 1;
+        }
+
+        let mut tracks: Vec<TrackListenStats> = tracks.into_values().collect();
+        tracks.sort_by(|a, b| {
+            b.listen_secs
+                .cmp(&a.listen_secs)
+                .then_with(|| b.play_count.cmp(&a.play_count))
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        tracks.truncate(limit);
+        Ok(tracks)
+    }
+
+    /// Gets the plays of each track between two dates, oldest first.
+    ///
+    /// Pausing a track saves the part played so far as its own media row, so
+    /// a row continuing the track of the row just before it, less than
+    /// [`PLAY_RESUME_GAP_SECS`] later, counts toward the same play. Rows
+    /// saved twice with the same start time are counted once. Titles are
+    /// normalized first when `normalize_media_titles` is on, so rows saved
+    /// before it was turned on group with the same track.
+    fn media_plays(&self, from: &str, to: &str) -> SqlResult<Vec<MediaPlay>> {
+        let rows: Vec<(String, String, String, Option<String>, i64)> = {
+            let conn = self.lock_conn();
+            let mut stmt = conn.prepare(
+                "SELECT title, artist, start_time, end_time, duration_secs
+                 FROM media
+                 WHERE end_time IS NOT NULL AND date(start_time, ?3) BETWEEN ?1 AND ?2
+                 ORDER BY start_time, id",
+            )?;
+            let rows =
+                stmt.query_map(params![from, to, timezone_policy().sql_modifier()], |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                })?;
+            rows.collect::<SqlResult<_>>()?
+        };
+
+        let normalizer = crate::media::title_normalizer_from(self);
+        let parse = |time: &str| DateTime::parse_from_rfc3339(time).ok();
+        let mut plays: Vec<MediaPlay> = Vec::new();
+        // Start and end of the previous row, for spotting resumes and duplicates
+        let mut last: Option<(String, Option<String>)> = None;
+        for (title, artist, start_time, end_time, duration_secs) in rows {
+            let title = match &normalizer {
+                Some(normalizer) => normalizer.normalize(&title),
+                None => title,
+            };
+            let continues = match (plays.last(), &last) {
+                (Some(play), Some((last_start, last_end)))
+                    if play.title == title && play.artist == artist =>
+                {
+                    if *last_start == start_time {
+                        continue;
+                    }
+                    let gap = last_end
+                        .as_deref()
+                        .and_then(parse)
+                        .zip(parse(&start_time))
+                        .map(|(end, start)| (start - end).num_seconds());
+                    gap.is_some_and(|gap| gap < PLAY_RESUME_GAP_SECS)
+                }
+                _ => false,
+            };
+            last = Some((start_time, end_time));
+
+            match plays.last_mut() {
+                Some(play) if continues => play.listen_secs += duration_secs,
+                _ => plays.push(MediaPlay {
+                    title,
+                    artist,
+                    listen_secs: duration_secs,
+                }),
+            }
+        }
+        Ok(plays)
+    }
+
     /// Gets session count for today.
     pub fn get_today_session_count(&self) -> SqlResult<i64> {
-        let today = Utc::now().format("%Y-%m-%d").to_string();
-        let conn = self.conn.lock().unwrap();
+        let today = crate::store::local_date_string();
+        let conn = self.lock_conn();
 
         conn.query_row(
-            "SELECT COUNT(*) FROM sessions WHERE start_time LIKE ?1 || '%'",
-            params![today],
+            "SELECT COUNT(*) FROM sessions WHERE date(start_time, ?2) = ?1",
+            params![today, timezone_policy().sql_modifier()],
             |row| row.get(0),
         )
     }
 
     /// Gets aggregated stats for a specific date (computed from sessions).
     pub fn get_stats_for_date(&self, date: &str) -> SqlResult<(i64, i64, i64)> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
 
         conn.query_row(
             "SELECT 
@@ -614,18 +1791,62 @@ impl Database {
                     CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER)
                 ), 0)
              FROM sessions 
-             WHERE start_time LIKE ?1 || '%' AND end_time IS NOT NULL",
-            params![date],
+             WHERE date(start_time, ?2) = ?1 AND end_time IS NOT NULL",
+            params![date, timezone_policy().sql_modifier()],
             |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         )
     }
 
-    /// Gets hourly breakdown for a specific date (for charts).
+    /// Counts focus changes on a date: non-idle sessions started that day.
+    pub fn get_switch_count(&self, date: &str) -> SqlResult<i64> {
+        let conn = self.lock_conn();
+
+        conn.query_row(
+            "SELECT COUNT(*) FROM sessions
+             WHERE date(start_time, ?2) = ?1 AND is_idle = 0",
+            params![date, timezone_policy().sql_modifier()],
+            |row| row.get(0),
+        )
+    }
+
+    /// Gets the `limit` longest non-idle sessions started on a date,
+    /// longest first.
+    pub fn get_focus_streaks(&self, date: &str, limit: usize) -> SqlResult<Vec<FocusStreak>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, process_name, window_title, start_time, end_time,
+                    CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER) as duration
+             FROM sessions
+             WHERE date(start_time, ?2) = ?1 AND is_idle = 0 AND end_time IS NOT NULL
+             ORDER BY duration DESC, start_time
+             LIMIT ?3",
+        )?;
+
+        let rows = stmt.query_map(
+            params![date, timezone_policy().sql_modifier(), limit as i64],
+            |row| {
+                Ok(FocusStreak {
+                    id: row.get(0)?,
+                    process_name: row.get(1)?,
+                    window_title: row.get(2)?,
+                    start_time: row.get(3)?,
+                    end_time: row.get(4)?,
+                    duration_secs: row.get(5)?,
+                })
+            },
+        )?;
+        rows.collect()
+    }
+
+    /// Gets hourly breakdown for a date (for charts).
+    ///
+    /// Sessions are bucketed by the hour they started in, under the
+    /// timezone policy.
     pub fn get_hourly_stats(&self, date: &str) -> SqlResult<Vec<HourlyStats>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         let mut stmt = conn.prepare(
             "SELECT 
-                CAST(strftime('%H', start_time) AS INTEGER) as hour,
+                CAST(strftime('%H', start_time, ?2) AS INTEGER) as hour,
                 COALESCE(SUM(keystrokes), 0) as keystrokes,
                 COALESCE(SUM(clicks), 0) as clicks,
                 COUNT(*) as sessions,
@@ -633,12 +1854,12 @@ impl Database {
                     CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER)
                 ), 0) as focus_secs
              FROM sessions 
-             WHERE start_time LIKE ?1 || '%' AND end_time IS NOT NULL
+             WHERE date(start_time, ?2) = ?1 AND end_time IS NOT NULL
              GROUP BY hour
              ORDER BY hour",
         )?;
 
-        let rows = stmt.query_map(params![date], |row| {
+        let rows = stmt.query_map(params![date, timezone_policy().sql_modifier()], |row| {
             Ok(HourlyStats {
                 hour: row.get(0)?,
                 keystrokes: row.get(1)?,
@@ -651,12 +1872,66 @@ impl Database {
         rows.collect()
     }
 
+    /// Gets focus seconds per hour (under the timezone policy) for each day
+    /// from `from_date` to `to_date` (inclusive), oldest first.
+    ///
+    /// Sessions are split at hour boundaries, so one from 9:50 to 10:20 adds
+    /// 10 minutes to 9:00 and 20 minutes to 10:00. Idle sessions are left out.
+    pub fn get_hourly_heatmap(
+        &self,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+    ) -> SqlResult<Vec<[u64; 24]>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT start_time, end_time
+             FROM sessions
+             WHERE end_time IS NOT NULL AND is_idle = 0
+               AND date(end_time, ?3) >= ?1 AND date(start_time, ?3) <= ?2",
+        )?;
+
+        let policy = timezone_policy();
+        let rows = stmt.query_map(
+            params![
+                from_date.format("%Y-%m-%d").to_string(),
+                to_date.format("%Y-%m-%d").to_string(),
+                policy.sql_modifier()
+            ],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        )?;
+
+        let parse = |value: &str| {
+            DateTime::parse_from_rfc3339(value)
+                .ok()
+                .map(|t| t.with_timezone(&Utc))
+        };
+        let mut intervals = Vec::new();
+        for row in rows {
+            let (start, end) = row?;
+            if let (Some(start), Some(end)) = (parse(&start), parse(&end)) {
+                intervals.push((start, end));
+            }
+        }
+
+        Ok(match policy {
+            TimezonePolicy::Local => {
+                crate::store::hourly_heatmap(&intervals, from_date, to_date, &Local)
+            }
+            TimezonePolicy::Utc => {
+                crate::store::hourly_heatmap(&intervals, from_date, to_date, &Utc)
+            }
+            TimezonePolicy::Fixed(offset) => {
+                crate::store::hourly_heatmap(&intervals, from_date, to_date, &offset)
+            }
+        })
+    }
+
     /// Gets daily timeline for the last N days (for trend charts).
     pub fn get_timeline(&self, days: i32) -> SqlResult<Vec<DailyTimeline>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         let mut stmt = conn.prepare(
             "SELECT 
-                DATE(start_time) as date,
+                date(start_time, ?2) as date,
                 COALESCE(SUM(keystrokes), 0) as keystrokes,
                 COALESCE(SUM(clicks), 0) as clicks,
                 COUNT(*) as sessions,
@@ -664,13 +1939,13 @@ impl Database {
                     CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER)
                 ), 0) as focus_secs
              FROM sessions 
-             WHERE start_time >= date('now', ?1 || ' days') AND end_time IS NOT NULL
+             WHERE date(start_time, ?2) >= date('now', ?2, ?1 || ' days') AND end_time IS NOT NULL
              GROUP BY date
              ORDER BY date",
         )?;
 
         let offset = format!("-{}", days);
-        let rows = stmt.query_map(params![offset], |row| {
+        let rows = stmt.query_map(params![offset, timezone_policy().sql_modifier()], |row| {
             Ok(DailyTimeline {
                 date: row.get(0)?,
                 keystrokes: row.get(1)?,
@@ -683,92 +1958,615 @@ impl Database {
         rows.collect()
     }
 
-    // === Category Methods ===
-
-    /// Gets all categories.
-    pub fn get_categories(&self) -> SqlResult<Vec<Category>> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT id, name, color, icon FROM categories ORDER BY id")?;
+    /// Gets weekly totals for the last N weeks, including the current one.
+    ///
+    /// Weeks start on Monday (`%W`); `week_start` is that Monday's date under
+    /// the timezone policy. Weeks without activity are left out.
+    pub fn get_weekly_stats(&self, weeks: i32) -> SqlResult<Vec<WeeklyStats>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT
+                date(start_time, ?2, 'weekday 0', '-6 days') as week_start,
+                strftime('%Y-%W', start_time, ?2, 'weekday 0', '-6 days') as week,
+                COALESCE(SUM(keystrokes), 0) as keystrokes,
+                COALESCE(SUM(clicks), 0) as clicks,
+                COUNT(*) as sessions,
+                COALESCE(SUM(
+                    CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER)
+                ), 0) as focus_secs
+             FROM sessions
+             WHERE date(start_time, ?2, 'weekday 0', '-6 days')
+                   >= date('now', ?2, 'weekday 0', '-6 days', ?1 || ' days')
+               AND end_time IS NOT NULL
+             GROUP BY week_start
+             ORDER BY week_start",
+        )?;
 
-        let rows = stmt.query_map([], |row| {
-            Ok(Category {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-                icon: row.get(3)?,
+        let offset = format!("-{}", (i64::from(weeks.max(1)) - 1) * 7);
+        let rows = stmt.query_map(params![offset, timezone_policy().sql_modifier()], |row| {
+            Ok(WeeklyStats {
+                week_start: row.get(0)?,
+                week: row.get(1)?,
+                keystrokes: row.get(2)?,
+                clicks: row.get(3)?,
+                sessions: row.get(4)?,
+                focus_secs: row.get(5)?,
             })
         })?;
 
         rows.collect()
     }
 
-    /// Gets the category for a process name (returns "Other" category ID=1 if not found).
-    pub fn get_category_for_app(&self, process_name: &str) -> SqlResult<Category> {
-        let conn = self.conn.lock().unwrap();
+    /// Gets totals grouped by day of the week (0 = Sunday .. 6 = Saturday).
+    ///
+    /// Dates are `YYYY-MM-DD` under the timezone policy (inclusive). Always returns 7 buckets,
+    /// with zeros for weekdays without activity.
+    pub fn stats_by_weekday(&self, from_date: &str, to_date: &str) -> SqlResult<Vec<WeekdayStats>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT
+                CAST(strftime('%w', start_time, ?3) AS INTEGER) as weekday,
+                COALESCE(SUM(keystrokes), 0) as keystrokes,
+                COALESCE(SUM(clicks), 0) as clicks,
+                COUNT(*) as sessions,
+                COALESCE(SUM(
+                    CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER)
+                ), 0) as focus_secs
+             FROM sessions
+             WHERE date(start_time, ?3) BETWEEN ?1 AND ?2 AND end_time IS NOT NULL
+             GROUP BY weekday",
+        )?;
 
-        // Try exact match first
-        if let Ok(cat) = conn.query_row(
-            "SELECT c.id, c.name, c.color, c.icon 
-             FROM categories c
-             JOIN app_categories ac ON ac.category_id = c.id
-             WHERE ac.process_pattern = ?1",
-            params![process_name],
+        const NAMES: [&str; 7] = [
+            "Sunday",
+            "Monday",
+            "Tuesday",
+            "Wednesday",
+            "Thursday",
+            "Friday",
+            "Saturday",
+        ];
+        let mut buckets: Vec<WeekdayStats> = NAMES
+            .iter()
+            .enumerate()
+            .map(|(i, name)| WeekdayStats {
+                weekday: i as i32,
+                name: name.to_string(),
+                keystrokes: 0,
+                clicks: 0,
+                sessions: 0,
+                focus_secs: 0,
+            })
+            .collect();
+
+        let rows = stmt.query_map(
+            params![from_date, to_date, timezone_policy().sql_modifier()],
             |row| {
-                Ok(Category {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    color: row.get(2)?,
-                    icon: row.get(3)?,
-                })
+                Ok((
+                    row.get::<_, i32>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, i64>(4)?,
+                ))
             },
-        ) {
-            return Ok(cat);
+        )?;
+
+        for row in rows {
+            let (weekday, keystrokes, clicks, sessions, focus_secs) = row?;
+            if let Some(bucket) = buckets.get_mut(weekday as usize) {
+                bucket.keystrokes = keystrokes;
+                bucket.clicks = clicks;
+                bucket.sessions = sessions;
+                bucket.focus_secs = focus_secs;
+            }
         }
 
-        // Try pattern matching with wildcards
-        let patterns: Vec<(String, i64)> = {
-            let mut stmt =
-                conn.prepare("SELECT process_pattern, category_id FROM app_categories")?;
-            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
-            rows.filter_map(|r| r.ok()).collect()
-        };
+        Ok(buckets)
+    }
+
+    /// Gets every distinct process name with sessions between two dates
+    /// (YYYY-MM-DD, inclusive; `None` leaves that end open), alphabetically.
+    ///
+    /// Idle sessions count too, since their app is still a valid filter.
+    pub fn get_distinct_apps(
+        &self,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> SqlResult<Vec<AppSeen>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT process_name, COUNT(*), MAX(COALESCE(end_time, start_time))
+             FROM sessions
+             WHERE (?1 IS NULL OR date(start_time, ?3) >= ?1)
+               AND (?2 IS NULL OR date(start_time, ?3) <= ?2)
+             GROUP BY process_name
+             ORDER BY process_name COLLATE NOCASE, process_name",
+        )?;
+        let rows = stmt.query_map(params![from, to, timezone_policy().sql_modifier()], |row| {
+            Ok(AppSeen {
+                process_name: row.get(0)?,
+                session_count: row.get(1)?,
+                last_seen: row.get(2)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Gets when an app was first seen: the start of its earliest session.
+    ///
+    /// Process names are matched case-insensitively.
+    pub fn get_app_first_seen(&self, process_name: &str) -> SqlResult<Option<String>> {
+        let conn = self.lock_conn();
+        conn.query_row(
+            "SELECT MIN(start_time) FROM sessions WHERE process_name = ?1 COLLATE NOCASE",
+            params![process_name],
+            |row| row.get(0),
+        )
+    }
+
+    /// Gets an app's focus time and session count per day between two
+    /// dates (inclusive). Days without use are omitted.
+    pub fn get_app_daily_usage(
+        &self,
+        process_name: &str,
+        from_date: &str,
+        to_date: &str,
+    ) -> SqlResult<Vec<AppDailyUsage>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT
+                date(start_time, ?4) as day,
+                COALESCE(SUM(CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER)), 0),
+                COUNT(*)
+             FROM sessions
+             WHERE process_name = ?1 COLLATE NOCASE AND is_idle = 0 AND end_time IS NOT NULL
+               AND date(start_time, ?4) BETWEEN ?2 AND ?3
+             GROUP BY day
+             ORDER BY day",
+        )?;
+
+        let rows = stmt.query_map(
+            params![
+                process_name,
+                from_date,
+                to_date,
+                timezone_policy().sql_modifier()
+            ],
+            |row| {
+                Ok(AppDailyUsage {
+                    date: row.get(0)?,
+                    focus_secs: row.get(1)?,
+                    sessions: row.get(2)?,
+                })
+            },
+        )?;
+
+        rows.collect()
+    }
+
+    /// Gets every date an app was used, most recent first.
+    pub fn get_app_usage_dates(&self, process_name: &str) -> SqlResult<Vec<String>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT date(start_time, ?2) as day
+             FROM sessions
+             WHERE process_name = ?1 COLLATE NOCASE AND is_idle = 0 AND end_time IS NOT NULL
+             ORDER BY day DESC",
+        )?;
+        let rows = stmt.query_map(
+            params![process_name, timezone_policy().sql_modifier()],
+            |row| row.get(0),
+        )?;
+        rows.collect()
+    }
+
+    /// Gets an app's lifetime focus time (seconds) and session count.
+    pub fn get_app_lifetime_totals(&self, process_name: &str) -> SqlResult<(i64, i64)> {
+        let conn = self.lock_conn();
+        conn.query_row(
+            "SELECT
+                COALESCE(SUM(CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER)), 0),
+                COUNT(*)
+             FROM sessions
+             WHERE process_name = ?1 COLLATE NOCASE AND is_idle = 0 AND end_time IS NOT NULL",
+            params![process_name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+    }
+
+    /// Gets per-day totals up to a local time of day (for pace comparisons).
+    ///
+    /// Dates are local `YYYY-MM-DD` (inclusive). Only sessions starting before
+    /// `cutoff_secs` (seconds since local midnight) are counted, and their focus
+    /// time is clipped at the cutoff. Every day with activity is returned, even
+    /// if nothing happened before the cutoff.
+    pub fn get_totals_until_time_of_day(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        cutoff_secs: i64,
+    ) -> SqlResult<Vec<DailyPaceTotals>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT
+                day,
+                COALESCE(SUM(CASE WHEN start_sod < ?3 THEN keystrokes ELSE 0 END), 0) as keystrokes,
+                COALESCE(SUM(CASE WHEN start_sod < ?3
+                    THEN CAST(ROUND(MIN(end_sod, ?3) - start_sod) AS INTEGER)
+                    ELSE 0 END), 0) as focus_secs
+             FROM (
+                SELECT
+                    date(start_time, ?4) as day,
+                    keystrokes,
+                    (julianday(start_time, ?4) - julianday(date(start_time, ?4))) * 86400 as start_sod,
+                    (julianday(end_time, ?4) - julianday(date(start_time, ?4))) * 86400 as end_sod
+                FROM sessions
+                WHERE end_time IS NOT NULL
+             )
+             WHERE day BETWEEN ?1 AND ?2
+             GROUP BY day
+             ORDER BY day",
+        )?;
+
+        let rows = stmt.query_map(
+            params![
+                from_date,
+                to_date,
+                cutoff_secs,
+                timezone_policy().sql_modifier()
+            ],
+            |row| {
+                Ok(DailyPaceTotals {
+                    date: row.get(0)?,
+                    keystrokes: row.get(1)?,
+                    focus_secs: row.get(2)?,
+                })
+            },
+        )?;
+
+        rows.collect()
+    }
+
+    // === Category Methods ===
+
+    /// Gets all categories.
+    pub fn get_categories(&self) -> SqlResult<Vec<Category>> {
+        let conn = self.lock_conn();
+        let mut stmt =
+            conn.prepare("SELECT id, name, color, icon, distracting FROM categories ORDER BY id")?;
+
+        let rows = stmt.query_map([], category_from_row)?;
+
+        rows.collect()
+    }
+
+    /// Gets all categories with the app patterns mapped to each.
+    pub fn get_category_apps(&self) -> SqlResult<Vec<CategoryWithApps>> {
+        let mut categories: Vec<CategoryWithApps> = self
+            .get_categories()?
+            .into_iter()
+            .map(|category| CategoryWithApps {
+                category,
+                apps: Vec::new(),
+            })
+            .collect();
+
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT category_id, process_pattern FROM app_categories ORDER BY process_pattern",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        for row in rows {
+            let (category_id, pattern) = row?;
+            if let Some(entry) = categories.iter_mut().find(|c| c.category.id == category_id) {
+                entry.apps.push(pattern);
+            }
+        }
+
+        Ok(categories)
+    }
+
+    /// Gets a category by ID.
+    pub fn get_category(&self, id: i64) -> SqlResult<Option<Category>> {
+        let conn = self.lock_conn();
+        match conn.query_row(
+            "SELECT id, name, color, icon, distracting FROM categories WHERE id = ?1",
+            params![id],
+            category_from_row,
+        ) {
+            Ok(category) => Ok(Some(category)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Creates a category and returns its ID.
+    ///
+    /// Fails with a constraint violation if the name is taken.
+    pub fn create_category(&self, name: &str, color: &str, icon: Option<&str>) -> SqlResult<i64> {
+        let conn = self.lock_conn();
+        conn.execute(
+            "INSERT INTO categories (name, color, icon, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![name, color, icon, Utc::now().to_rfc3339()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Renames or recolors a category. Returns false if it doesn't exist.
+    ///
+    /// Fails with a constraint violation if the new name is taken.
+    pub fn update_category(
+        &self,
+        id: i64,
+        name: &str,
+        color: &str,
+        icon: Option<&str>,
+    ) -> SqlResult<bool> {
+        let conn = self.lock_conn();
+        let affected = conn.execute(
+            "UPDATE categories SET name = ?1, color = ?2, icon = ?3 WHERE id = ?4",
+            params![name, color, icon, id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Marks a category as distracting (or not) for focus mode. Returns
+    /// false if it doesn't exist.
+    pub fn set_category_distracting(&self, id: i64, distracting: bool) -> SqlResult<bool> {
+        let conn = self.lock_conn();
+        let affected = conn.execute(
+            "UPDATE categories SET distracting = ?1 WHERE id = ?2",
+            params![distracting, id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Deletes a category. Returns false if it doesn't exist or is "Other".
+    ///
+    /// App patterns mapped to it move to "Other", and sessions overridden to
+    /// it go back to their app's mapping.
+    pub fn delete_category(&self, id: i64) -> SqlResult<bool> {
+        if id == OTHER_CATEGORY_ID {
+            return Ok(false);
+        }
+
+        let conn = self.lock_conn();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE app_categories SET category_id = ?1 WHERE category_id = ?2",
+            params![OTHER_CATEGORY_ID, id],
+        )?;
+        tx.execute(
+            "UPDATE sessions SET category_override = NULL WHERE category_override = ?1",
+            params![id],
+        )?;
+        tx.execute(
+            "UPDATE session_checkpoint SET category_override = NULL WHERE category_override = ?1",
+            params![id],
+        )?;
+        let affected = tx.execute("DELETE FROM categories WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(affected > 0)
+    }
+
+    /// Gets the category for a saved session: its override if set, else the app's mapping.
+    pub fn get_category_for_session(&self, session: &SessionWithDuration) -> SqlResult<Category> {
+        if let Some(category) = session
+            .category_override
+            .map(|id| self.get_category(id))
+            .transpose()?
+            .flatten()
+        {
+            return Ok(category);
+        }
+        self.get_category_for_app(&session.process_name)
+    }
+
+    /// Gets the category for a process name (returns "Other" category ID=1 if not found).
+    pub fn get_category_for_app(&self, process_name: &str) -> SqlResult<Category> {
+        let conn = self.lock_conn();
+
+        // Try exact match first
+        if let Ok(cat) = conn.query_row(
+            "SELECT c.id, c.name, c.color, c.icon, c.distracting
+             FROM categories c
+             JOIN app_categories ac ON ac.category_id = c.id
+             WHERE ac.process_pattern = ?1",
+            params![process_name],
+            category_from_row,
+        ) {
+            return Ok(cat);
+        }
+
+        // Try pattern matching with wildcards
+        let patterns: Vec<(String, i64)> = {
+            let mut stmt =
+                conn.prepare("SELECT process_pattern, category_id FROM app_categories")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.filter_map(|r| r.ok()).collect()
+        };
 
         let name_lower = process_name.to_lowercase();
         for (pattern, cat_id) in patterns {
             if pattern_matches(&pattern.to_lowercase(), &name_lower) {
                 return conn.query_row(
-                    "SELECT id, name, color, icon FROM categories WHERE id = ?1",
+                    "SELECT id, name, color, icon, distracting FROM categories WHERE id = ?1",
                     params![cat_id],
-                    |row| {
-                        Ok(Category {
-                            id: row.get(0)?,
-                            name: row.get(1)?,
-                            color: row.get(2)?,
-                            icon: row.get(3)?,
-                        })
-                    },
+                    category_from_row,
                 );
             }
         }
 
         // Default to "Other" (ID=1)
         conn.query_row(
-            "SELECT id, name, color, icon FROM categories WHERE id = 1",
+            "SELECT id, name, color, icon, distracting FROM categories WHERE id = 1",
             [],
-            |row| {
-                Ok(Category {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    color: row.get(2)?,
-                    icon: row.get(3)?,
-                })
-            },
+            category_from_row,
         )
     }
 
+    /// Gets focus time, input counts and session counts per category for a date.
+    ///
+    /// Non-idle sessions are summed per app and category override in SQL,
+    /// then each distinct app (or override) is resolved to its category once.
+    /// Sorted by focus time, longest first.
+    pub fn get_category_stats(&self, date: &str) -> SqlResult<Vec<CategoryStats>> {
+        let groups: Vec<(String, Option<i64>, i64, i64, i64, i64)> = {
+            let conn = self.lock_conn();
+            let mut stmt = conn.prepare(
+                "SELECT process_name, category_override,
+                        COALESCE(SUM(CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER)), 0),
+                        COALESCE(SUM(keystrokes), 0),
+                        COALESCE(SUM(clicks), 0),
+                        COUNT(*)
+                 FROM sessions
+                 WHERE date(start_time, ?2) = ?1 AND is_idle = 0 AND end_time IS NOT NULL
+                 GROUP BY process_name, category_override",
+            )?;
+            let rows = stmt.query_map(params![date, timezone_policy().sql_modifier()], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            })?;
+            rows.collect::<SqlResult<_>>()?
+        };
+
+        let mut app_categories: HashMap<String, Category> = HashMap::new();
+        let mut override_categories: HashMap<i64, Option<Category>> = HashMap::new();
+        let mut totals: HashMap<i64, CategoryStats> = HashMap::new();
+
+        for (process_name, category_override, focus_secs, keystrokes, clicks, sessions) in groups {
+            let overridden = match category_override {
+                Some(id) => match override_categories.get(&id) {
+                    Some(category) => category.clone(),
+                    None => {
+                        let category = self.get_category(id)?;
+                        override_categories.insert(id, category.clone());
+                        category
+                    }
+                },
+                None => None,
+            };
+            let category = match overridden {
+                Some(category) => category,
+                None => match app_categories.get(&process_name) {
+                    Some(category) => category.clone(),
+                    None => {
+                        let category = self.get_category_for_app(&process_name)?;
+                        app_categories.insert(process_name, category.clone());
+                        category
+                    }
+                },
+            };
+
+            let entry = totals.entry(category.id).or_insert_with(|| CategoryStats {
+                category,
+                focus_secs: 0,
+                keystrokes: 0,
+                clicks: 0,
+                sessions: 0,
+            });
+            entry.focus_secs += focus_secs;
+            entry.keystrokes += keystrokes;
+            entry.clicks += clicks;
+            entry.sessions += sessions;
+        }
+
+        let mut stats: Vec<CategoryStats> = totals.into_values().collect();
+        stats.sort_by(|a, b| {
+            b.focus_secs
+                .cmp(&a.focus_secs)
+                .then_with(|| a.category.name.cmp(&b.category.name))
+        });
+        Ok(stats)
+    }
+
+    /// Gets focus time, input counts and session counts per app for a date.
+    ///
+    /// Idle sessions are excluded. Each app is resolved to its category with
+    /// [`Database::get_category_for_app`]; per-session overrides are ignored.
+    /// Sorted by focus time, longest first.
+    pub fn get_app_totals(&self, date: &str) -> SqlResult<Vec<AppTotals>> {
+        let groups: Vec<(String, i64, i64, i64, i64)> = {
+            let conn = self.lock_conn();
+            let mut stmt = conn.prepare(
+                "SELECT process_name,
+                        COALESCE(SUM(CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER)), 0) as focus,
+                        COALESCE(SUM(keystrokes), 0),
+                        COALESCE(SUM(clicks), 0),
+                        COUNT(*)
+                 FROM sessions
+                 WHERE date(start_time, ?2) = ?1 AND is_idle = 0 AND end_time IS NOT NULL
+                 GROUP BY process_name
+                 ORDER BY focus DESC, process_name",
+            )?;
+            let rows = stmt.query_map(params![date, timezone_policy().sql_modifier()], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            })?;
+            rows.collect::<SqlResult<_>>()?
+        };
+
+        groups
+            .into_iter()
+            .map(|(process_name, focus_secs, keystrokes, clicks, sessions)| {
+                Ok(AppTotals {
+                    category: self.get_category_for_app(&process_name)?,
+                    process_name,
+                    focus_secs,
+                    keystrokes,
+                    clicks,
+                    sessions,
+                })
+            })
+            .collect()
+    }
+
+    /// Gets focus time and input per monitor for a date, busiest first.
+    ///
+    /// Idle sessions are excluded.
+    pub fn get_monitor_stats(&self, date: &str) -> SqlResult<Vec<MonitorStats>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT monitor,
+                    COALESCE(SUM(CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER)), 0) as focus,
+                    COALESCE(SUM(keystrokes), 0),
+                    COALESCE(SUM(clicks), 0),
+                    COUNT(*)
+             FROM sessions
+             WHERE date(start_time, ?2) = ?1 AND is_idle = 0 AND end_time IS NOT NULL
+             GROUP BY monitor
+             ORDER BY focus DESC, monitor",
+        )?;
+        let rows = stmt.query_map(params![date, timezone_policy().sql_modifier()], |row| {
+            Ok(MonitorStats {
+                monitor: row.get(0)?,
+                focus_secs: row.get(1)?,
+                keystrokes: row.get(2)?,
+                clicks: row.get(3)?,
+                sessions: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
     /// Assigns an app to a category.
     pub fn set_app_category(&self, process_pattern: &str, category_id: i64) -> SqlResult<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         conn.execute(
             "INSERT OR REPLACE INTO app_categories (process_pattern, category_id) VALUES (?1, ?2)",
             params![process_pattern, category_id],
@@ -776,11 +2574,21 @@ impl Database {
         Ok(())
     }
 
+    /// Removes an app pattern's category mapping. Returns false if it wasn't mapped.
+    pub fn remove_app_category(&self, process_pattern: &str) -> SqlResult<bool> {
+        let conn = self.lock_conn();
+        let affected = conn.execute(
+            "DELETE FROM app_categories WHERE process_pattern = ?1",
+            params![process_pattern],
+        )?;
+        Ok(affected > 0)
+    }
+
     // === Config Methods ===
 
     /// Gets a configuration value by key.
     pub fn get_config(&self, key: &str) -> SqlResult<Option<String>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         match conn.query_row(
             "SELECT value FROM config WHERE key = ?1",
             params![key],
@@ -794,7 +2602,7 @@ impl Database {
 
     /// Sets a configuration value.
     pub fn set_config(&self, key: &str, value: &str) -> SqlResult<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         let now = Utc::now().to_rfc3339();
         conn.execute(
             "UPDATE config SET value = ?1, updated_at = ?2 WHERE key = ?3",
@@ -803,9 +2611,31 @@ impl Database {
         Ok(())
     }
 
+    /// Gets the configured list limits, falling back to the defaults.
+    pub fn query_limits(&self) -> QueryLimits {
+        let read = |key: &str, default: usize| {
+            self.get_config(key)
+                .ok()
+                .flatten()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        let defaults = QueryLimits::default();
+        QueryLimits {
+            default: read("default_session_limit", defaults.default),
+            max: read("max_session_limit", defaults.max),
+        }
+    }
+
+    /// Gets the configured timezone policy (see [`crate::store::timezone`]).
+    pub fn timezone_policy(&self) -> TimezonePolicy {
+        let value = self.get_config("timezone").ok().flatten();
+        TimezonePolicy::from_config(value.as_deref())
+    }
+
     /// Gets all config settings.
     pub fn get_all_config(&self) -> SqlResult<Vec<(String, String, Option<String>)>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         let mut stmt = conn.prepare("SELECT key, value, description FROM config ORDER BY key")?;
         let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
         rows.collect()
@@ -813,7 +2643,7 @@ impl Database {
 
     /// Gets recent sessions (for reports).
     pub fn get_recent_sessions(&self, limit: usize) -> SqlResult<Vec<SessionRecord>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         let mut stmt = conn.prepare(
             "SELECT id, process_name, window_title, start_time, end_time, keystrokes, clicks, scrolls, is_idle
              FROM sessions ORDER BY id DESC LIMIT ?1"
@@ -838,6 +2668,11 @@ impl Database {
 
     /// Queries sessions with flexible filtering.
     /// Returns (sessions, total_count).
+    ///
+    /// `category` filters on each session's resolved category (see
+    /// [`get_category_for_session`](Self::get_category_for_session)) and
+    /// `tag` on an exact tag; both apply before pagination, so `total_count`
+    /// matches the filtered rows.
     #[allow(clippy::too_many_arguments)]
     pub fn query_sessions_flexible(
         &self,
@@ -845,31 +2680,37 @@ impl Database {
         from: Option<&str>,
         to: Option<&str>,
         app: Option<&str>,
+        category: Option<i64>,
+        tag: Option<&str>,
         limit: usize,
         offset: usize,
         order_desc: bool,
     ) -> SqlResult<(Vec<SessionWithDuration>, i64)> {
-        let conn = self.conn.lock().unwrap();
+        let limit = limit.min(self.query_limits().max);
+        let conn = self.lock_conn();
 
         let mut conditions = vec!["end_time IS NOT NULL".to_string()];
 
         // Build WHERE clause
         if let Some(d) = date {
-            conditions.push(format!("start_time LIKE '{}%'", d));
+            conditions.push(date_condition(d));
         }
         if let Some(f) = from {
-            conditions.push(format!("start_time >= '{}'", f));
+            conditions.push(format!("start_time >= '{}'", escape_sql_literal(f)));
         }
         if let Some(t) = to {
-            conditions.push(format!("start_time <= '{}'", t));
+            conditions.push(format!("start_time <= '{}'", escape_sql_literal(t)));
         }
         if let Some(a) = app {
-            if a.contains('*') {
-                let pattern = a.replace('*', "%");
-                conditions.push(format!("process_name LIKE '{}'", pattern));
-            } else {
-                conditions.push(format!("process_name = '{}'", a));
-            }
+            conditions.push(wildcard_condition("process_name", a));
+        }
+        if let Some(c) = category {
+            conditions.push(format!("{} = {}", SESSION_CATEGORY_SQL, c));
+        }
+        // The tag is bound as ?1 rather than written into the SQL
+        if tag.is_some() {
+            conditions
+                .push("id IN (SELECT session_id FROM session_tags WHERE tag = ?1)".to_string());
         }
 
         let where_clause = conditions.join(" AND ");
@@ -878,87 +2719,381 @@ impl Database {
         // Get total count
         let count_sql = format!("SELECT COUNT(*) FROM sessions WHERE {}", where_clause);
         let total: i64 = conn
-            .query_row(&count_sql, [], |row| row.get(0))
+            .query_row(&count_sql, params_from_iter(tag), |row| row.get(0))
             .unwrap_or(0);
 
         // Get sessions with duration
         let sql = format!(
-            "SELECT id, process_name, window_title, start_time, end_time, keystrokes, clicks, scrolls, is_idle,
-                    CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER) as duration
+            "SELECT {}
              FROM sessions 
              WHERE {}
              ORDER BY start_time {}
              LIMIT {} OFFSET {}",
-            where_clause, order_sql, limit, offset
+            SESSION_WITH_DURATION_COLUMNS, where_clause, order_sql, limit, offset
         );
 
         let mut stmt = conn.prepare(&sql)?;
-        let rows = stmt.query_map([], |row| {
-            Ok(SessionWithDuration {
-                id: row.get(0)?,
-                process_name: row.get(1)?,
-                window_title: row.get(2)?,
-                start_time: row.get(3)?,
-                end_time: row.get(4)?,
-                keystrokes: row.get(5)?,
-                clicks: row.get(6)?,
-                scrolls: row.get(7)?,
-                is_idle: row.get(8)?,
-                duration_secs: row.get(9)?,
-            })
-        })?;
+        let rows = stmt.query_map(params_from_iter(tag), session_with_duration_from_row)?;
 
         let sessions: Vec<SessionWithDuration> = rows.filter_map(|r| r.ok()).collect();
         Ok((sessions, total))
     }
 
-    // === Blacklist Methods ===
-
-    /// Gets all blacklist patterns.
-    pub fn get_blacklist(&self) -> SqlResult<Vec<BlacklistEntry>> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt =
-            conn.prepare("SELECT id, pattern, description, created_at FROM blacklist ORDER BY id")?;
-
-        let rows = stmt.query_map([], |row| {
-            Ok(BlacklistEntry {
-                id: row.get(0)?,
-                pattern: row.get(1)?,
-                description: row.get(2)?,
-                created_at: row.get(3)?,
-            })
-        })?;
-
-        rows.collect()
+    /// Gets a saved session by ID, with its hash chain fields.
+    pub fn get_session(&self, id: i64) -> SqlResult<Option<SessionDetail>> {
+        let conn = self.lock_conn();
+        match conn.query_row(
+            &format!(
+                "SELECT {}, hash, signature, prev_hash FROM sessions WHERE id = ?1",
+                SESSION_WITH_DURATION_COLUMNS
+            ),
+            params![id],
+            |row| {
+                Ok(SessionDetail {
+                    session: session_with_duration_from_row(row)?,
+                    hash: row.get(26)?,
+                    signature: row.get(27)?,
+                    prev_hash: row.get(28)?,
+                })
+            },
+        ) {
+            Ok(detail) => Ok(Some(detail)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
-    /// Adds a pattern to the blacklist.
-    pub fn add_to_blacklist(&self, pattern: &str, description: Option<&str>) -> SqlResult<i64> {
-        let conn = self.conn.lock().unwrap();
-        let now = Utc::now().to_rfc3339();
+    /// Deletes a saved session. Returns `None` if it didn't exist.
+    ///
+    /// The session's hash stays referenced by the next session's `prev_hash`,
+    /// so the hash chain no longer verifies; if its day is already sealed
+    /// into `daily_integrity`, that day's Merkle root no longer matches either.
+    pub fn delete_session(&self, id: i64) -> SqlResult<Option<DeletedSession>> {
+        let conn = self.lock_conn();
+        let (date, hashed, start_time, process_name): (String, bool, String, String) = match conn
+            .query_row(
+                "SELECT date(start_time, ?2), hash IS NOT NULL, start_time, process_name
+                 FROM sessions WHERE id = ?1",
+                params![id, timezone_policy().sql_modifier()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            ) {
+            Ok(found) => found,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let sealed: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM daily_integrity WHERE date = ?1)",
+            params![date],
+            |row| row.get(0),
+        )?;
 
         conn.execute(
-            "INSERT OR IGNORE INTO blacklist (pattern, description, created_at) VALUES (?1, ?2, ?3)",
-            params![pattern, description, now],
+            "DELETE FROM session_tags WHERE session_id = ?1",
+            params![id],
         )?;
-
-        Ok(conn.last_insert_rowid())
+        conn.execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
+        if sealed {
+            tracing::warn!(id, date = %date, "Deleted a session from a sealed day; it will no longer verify");
+        }
+        Ok(Some(DeletedSession {
+            id,
+            date,
+            sealed,
+            integrity_invalidated: hashed || sealed,
+            start_time,
+            process_name,
+        }))
     }
 
-    /// Removes a pattern from the blacklist.
-    pub fn remove_from_blacklist(&self, pattern: &str) -> SqlResult<bool> {
-        let conn = self.conn.lock().unwrap();
-        let affected =
-            conn.execute("DELETE FROM blacklist WHERE pattern = ?1", params![pattern])?;
-        Ok(affected > 0)
-    }
+    /// Gets every session for a date (YYYY-MM-DD), oldest first.
+    /// Pages through `query_sessions_flexible` so the limit cap doesn't truncate totals.
+    pub fn get_all_sessions_for_date(&self, date: &str) -> SqlResult<Vec<SessionWithDuration>> {
+        let page_size = self.query_limits().max.max(1);
+        let mut sessions = Vec::new();
 
-    /// Checks if a process name matches any blacklist pattern.
-    pub fn is_blacklisted(&self, process_name: &str) -> bool {
-        let patterns = match self.get_blacklist() {
-            Ok(entries) => entries,
-            Err(_) => return false,
-        };
+        loop {
+            let (page, total) = self.query_sessions_flexible(
+                Some(date),
+                None,
+                None,
+                None,
+                None,
+                None,
+                page_size,
+                sessions.len(),
+                false,
+            )?;
+            let done = page.is_empty();
+            sessions.extend(page);
+            if done || sessions.len() as i64 >= total {
+                return Ok(sessions);
+            }
+        }
+    }
+
+    // === Session Tag Methods ===
+
+    /// Tags a saved session. Returns `false` if the session doesn't exist;
+    /// adding a tag it already has is a no-op.
+    pub fn add_session_tag(&self, session_id: i64, tag: &str) -> SqlResult<bool> {
+        let conn = self.lock_conn();
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sessions WHERE id = ?1)",
+            params![session_id],
+            |row| row.get(0),
+        )?;
+        if exists {
+            conn.execute(
+                "INSERT OR IGNORE INTO session_tags (session_id, tag) VALUES (?1, ?2)",
+                params![session_id, tag],
+            )?;
+        }
+        Ok(exists)
+    }
+
+    /// Removes a tag from a session. Returns `false` if it wasn't tagged.
+    pub fn remove_session_tag(&self, session_id: i64, tag: &str) -> SqlResult<bool> {
+        let conn = self.lock_conn();
+        let removed = conn.execute(
+            "DELETE FROM session_tags WHERE session_id = ?1 AND tag = ?2",
+            params![session_id, tag],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Gets a session's tags, alphabetically.
+    pub fn get_session_tags(&self, session_id: i64) -> SqlResult<Vec<String>> {
+        let conn = self.lock_conn();
+        let mut stmt =
+            conn.prepare("SELECT tag FROM session_tags WHERE session_id = ?1 ORDER BY tag")?;
+        let rows = stmt.query_map(params![session_id], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    // === Idle Threshold Methods ===
+
+    /// Gets all per-app idle threshold overrides.
+    pub fn get_idle_overrides(&self) -> SqlResult<Vec<IdleOverride>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT process_pattern, threshold_secs FROM app_idle_overrides ORDER BY process_pattern",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(IdleOverride {
+                process_pattern: row.get(0)?,
+                threshold_secs: row.get(1)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Sets the idle threshold for an app or wildcard pattern, replacing any existing one.
+    pub fn set_idle_override(&self, process_pattern: &str, threshold_secs: i64) -> SqlResult<()> {
+        let conn = self.lock_conn();
+        conn.execute(
+            "INSERT OR REPLACE INTO app_idle_overrides (process_pattern, threshold_secs) VALUES (?1, ?2)",
+            params![process_pattern, threshold_secs],
+        )?;
+        Ok(())
+    }
+
+    /// Removes the idle threshold override for an exact pattern.
+    pub fn remove_idle_override(&self, process_pattern: &str) -> SqlResult<bool> {
+        let conn = self.lock_conn();
+        let affected = conn.execute(
+            "DELETE FROM app_idle_overrides WHERE process_pattern = ?1",
+            params![process_pattern],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Gets the idle threshold (seconds) for a process.
+    ///
+    /// An exact override wins over wildcard patterns; without one, falls
+    /// back to the global `afk_threshold_secs` (default 300).
+    pub fn get_idle_threshold_for_app(&self, process_name: &str) -> i64 {
+        let overrides = self.get_idle_overrides().unwrap_or_default();
+        let name_lower = process_name.to_lowercase();
+
+        overrides
+            .iter()
+            .find(|o| o.process_pattern.eq_ignore_ascii_case(process_name))
+            .or_else(|| {
+                overrides
+                    .iter()
+                    .find(|o| pattern_matches(&o.process_pattern.to_lowercase(), &name_lower))
+            })
+            .map(|o| o.threshold_secs)
+            .unwrap_or_else(|| {
+                self.get_config("afk_threshold_secs")
+                    .ok()
+                    .flatten()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(300)
+            })
+    }
+
+    // === Goal Methods ===
+
+    /// Gets all goals, oldest first.
+    pub fn get_goals(&self) -> SqlResult<Vec<Goal>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn
+            .prepare("SELECT id, metric, threshold, period, created_at FROM goals ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Goal {
+                id: row.get(0)?,
+                metric: row.get(1)?,
+                threshold: row.get(2)?,
+                period: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Adds a goal and returns it.
+    pub fn add_goal(&self, metric: &str, threshold: i64, period: &str) -> SqlResult<Goal> {
+        let conn = self.lock_conn();
+        let created_at = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO goals (metric, threshold, period, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![metric, threshold, period, created_at],
+        )?;
+        Ok(Goal {
+            id: conn.last_insert_rowid(),
+            metric: metric.to_string(),
+            threshold,
+            period: period.to_string(),
+            created_at,
+        })
+    }
+
+    /// Deletes a goal. Returns false if it didn't exist.
+    pub fn delete_goal(&self, id: i64) -> SqlResult<bool> {
+        let conn = self.lock_conn();
+        let affected = conn.execute("DELETE FROM goals WHERE id = ?1", params![id])?;
+        Ok(affected > 0)
+    }
+
+    // === Blacklist Methods ===
+
+    /// Gets all blacklist patterns.
+    pub fn get_blacklist(&self) -> SqlResult<Vec<BlacklistEntry>> {
+        let conn = self.lock_conn();
+        let mut stmt =
+            conn.prepare("SELECT id, pattern, description, created_at FROM blacklist ORDER BY id")?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(BlacklistEntry {
+                id: row.get(0)?,
+                pattern: row.get(1)?,
+                description: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    /// Adds a pattern to the blacklist.
+    pub fn add_to_blacklist(&self, pattern: &str, description: Option<&str>) -> SqlResult<i64> {
+        let conn = self.lock_conn();
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT OR IGNORE INTO blacklist (pattern, description, created_at) VALUES (?1, ?2, ?3)",
+            params![pattern, description, now],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Gets the blacklist entry for an exact pattern.
+    pub fn get_blacklist_entry(&self, pattern: &str) -> SqlResult<Option<BlacklistEntry>> {
+        let conn = self.lock_conn();
+        match conn.query_row(
+            "SELECT id, pattern, description, created_at FROM blacklist WHERE pattern = ?1",
+            params![pattern],
+            |row| {
+                Ok(BlacklistEntry {
+                    id: row.get(0)?,
+                    pattern: row.get(1)?,
+                    description: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            },
+        ) {
+            Ok(entry) => Ok(Some(entry)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Removes a pattern from the blacklist.
+    pub fn remove_from_blacklist(&self, pattern: &str) -> SqlResult<bool> {
+        let conn = self.lock_conn();
+        let affected =
+            conn.execute("DELETE FROM blacklist WHERE pattern = ?1", params![pattern])?;
+        Ok(affected > 0)
+    }
+
+    // === Redaction Pattern Methods ===
+
+    /// Gets all title redaction patterns.
+    pub fn get_redaction_patterns(&self) -> SqlResult<Vec<RedactionPattern>> {
+        let conn = self.lock_conn();
+        let mut stmt =
+            conn.prepare("SELECT id, pattern, created_at FROM redaction_patterns ORDER BY id")?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(RedactionPattern {
+                id: row.get(0)?,
+                pattern: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    /// Adds a title redaction pattern, returning the stored entry.
+    ///
+    /// Adding an existing pattern returns the existing entry.
+    pub fn add_redaction_pattern(&self, pattern: &str) -> SqlResult<RedactionPattern> {
+        let conn = self.lock_conn();
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT OR IGNORE INTO redaction_patterns (pattern, created_at) VALUES (?1, ?2)",
+            params![pattern, now],
+        )?;
+        conn.query_row(
+            "SELECT id, pattern, created_at FROM redaction_patterns WHERE pattern = ?1",
+            params![pattern],
+            |row| {
+                Ok(RedactionPattern {
+                    id: row.get(0)?,
+                    pattern: row.get(1)?,
+                    created_at: row.get(2)?,
+                })
+            },
+        )
+    }
+
+    /// Removes a title redaction pattern. Returns false if it didn't exist.
+    pub fn remove_redaction_pattern(&self, id: i64) -> SqlResult<bool> {
+        let conn = self.lock_conn();
+        let affected = conn.execute("DELETE FROM redaction_patterns WHERE id = ?1", params![id])?;
+        Ok(affected > 0)
+    }
+
+    /// Checks if a process name matches any blacklist pattern.
+    pub fn is_blacklisted(&self, process_name: &str) -> bool {
+        let patterns = match self.get_blacklist() {
+            Ok(entries) => entries,
+            Err(_) => return false,
+        };
 
         let name_lower = process_name.to_lowercase();
 
@@ -969,6 +3104,499 @@ impl Database {
         }
         false
     }
+
+    /// Checks if a process name matches any configured privacy pattern.
+    ///
+    /// Patterns come from the comma-separated `privacy_patterns` config and
+    /// support the same wildcards as the blacklist.
+    pub fn is_privacy_app(&self, process_name: &str) -> bool {
+        self.matches_config_patterns("privacy_patterns", process_name)
+    }
+
+    /// Checks if a window is the OwnMon dashboard open in a browser.
+    ///
+    /// The process must match `dashboard_browsers` and the title
+    /// `dashboard_title_patterns` (comma-separated, blacklist wildcards).
+    pub fn is_dashboard_window(&self, process_name: &str, window_title: &str) -> bool {
+        self.matches_config_patterns("dashboard_browsers", process_name)
+            && self.is_dashboard_title(window_title)
+    }
+
+    /// Checks if a window title matches `dashboard_title_patterns`.
+    pub fn is_dashboard_title(&self, window_title: &str) -> bool {
+        self.matches_config_patterns("dashboard_title_patterns", window_title)
+    }
+
+    /// Checks `text` against the comma-separated wildcard patterns in a config key.
+    fn matches_config_patterns(&self, key: &str, text: &str) -> bool {
+        let patterns = match self.get_config(key) {
+            Ok(Some(value)) => value,
+            _ => return false,
+        };
+
+        let text_lower = text.to_lowercase();
+
+        patterns
+            .split(',')
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .any(|p| pattern_matches(&p.to_lowercase(), &text_lower))
+    }
+
+    // === Archive Export/Import ===
+
+    /// Reads all sessions (with their tags) and media into an [`Archive`],
+    /// oldest first.
+    pub fn export_archive(&self) -> SqlResult<Archive> {
+        let conn = self.lock_conn();
+
+        let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+        let mut stmt = conn.prepare("SELECT session_id, tag FROM session_tags ORDER BY tag")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))?;
+        for row in rows {
+            let (session_id, tag) = row?;
+            tags.entry(session_id).or_default().push(tag);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT process_name, window_title, start_time, end_time, keystrokes, clicks, scrolls, is_idle,
+                    shortcuts, is_fullscreen, category_override, scroll_up, scroll_down, scroll_left, scroll_right,
+                    hash, signature, prev_hash, mouse_distance_px, double_clicks, keys_alphanumeric,
+                    keys_whitespace, keys_navigation, keys_modifier, keys_other, url, process_path, monitor,
+                    idle_reason, id
+             FROM sessions ORDER BY id",
+        )?;
+        let sessions = stmt
+            .query_map([], |row| {
+                Ok(ArchivedSession {
+                    process_name: row.get(0)?,
+                    window_title: row.get(1)?,
+                    start_time: row.get(2)?,
+                    end_time: row.get(3)?,
+                    keystrokes: row.get(4)?,
+                    clicks: row.get(5)?,
+                    scrolls: row.get(6)?,
+                    is_idle: row.get(7)?,
+                    shortcuts: row.get(8)?,
+                    is_fullscreen: row.get(9)?,
+                    category_override: row.get(10)?,
+                    scroll_up: row.get(11)?,
+                    scroll_down: row.get(12)?,
+                    scroll_left: row.get(13)?,
+                    scroll_right: row.get(14)?,
+                    hash: row.get(15)?,
+                    signature: row.get(16)?,
+                    prev_hash: row.get(17)?,
+                    mouse_distance_px: row.get(18)?,
+                    double_clicks: row.get(19)?,
+                    keys_alphanumeric: row.get(20)?,
+                    keys_whitespace: row.get(21)?,
+                    keys_navigation: row.get(22)?,
+                    keys_modifier: row.get(23)?,
+                    keys_other: row.get(24)?,
+                    url: row.get(25)?,
+                    process_path: row.get(26)?,
+                    monitor: row.get(27)?,
+                    idle_reason: row.get(28)?,
+                    tags: tags.remove(&row.get::<_, i64>(29)?).unwrap_or_default(),
+                })
+            })?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT title, artist, album, source_app, start_time, end_time, duration_secs, track_duration_secs
+             FROM media ORDER BY id",
+        )?;
+        let media = stmt
+            .query_map([], |row| {
+                Ok(ArchivedMedia {
+                    title: row.get(0)?,
+                    artist: row.get(1)?,
+                    album: row.get(2)?,
+                    source_app: row.get(3)?,
+                    start_time: row.get(4)?,
+                    end_time: row.get(5)?,
+                    duration_secs: row.get(6)?,
+                    track_duration_secs: row.get(7)?,
+                })
+            })?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        Ok(Archive { sessions, media })
+    }
+
+    /// Streams up to `limit` sessions and media records after `cursor`,
+    /// ordered by start time, to `on_record`.
+    ///
+    /// Rows are read straight from the two queries and merged, so nothing
+    /// beyond the current row is held in memory. Returns the cursor to pass
+    /// for the next page, or `None` once every record has been emitted.
+    pub fn for_each_export_record<F>(
+        &self,
+        cursor: &ExportCursor,
+        limit: usize,
+        mut on_record: F,
+    ) -> SqlResult<Option<ExportCursor>>
+    where
+        F: FnMut(&ExportRecord),
+    {
+        let conn = self.lock_conn();
+        let limit = limit.max(1) as i64;
+        let after = params![cursor.start_time, cursor.kind, cursor.id, limit];
+
+        let mut session_stmt = conn.prepare(
+            "SELECT id, process_name, window_title, start_time, end_time, keystrokes, clicks, scrolls, is_idle,
+                    shortcuts, is_fullscreen, category_override, url, hash, signature, prev_hash
+             FROM sessions
+             WHERE (start_time, 0, id) > (?1, ?2, ?3)
+             ORDER BY start_time, id
+             LIMIT ?4",
+        )?;
+        let mut sessions = session_stmt
+            .query_map(after, |row| {
+                Ok(ExportRecord::Session(ExportedSession {
+                    id: row.get(0)?,
+                    process_name: row.get(1)?,
+                    window_title: row.get(2)?,
+                    start_time: row.get(3)?,
+                    end_time: row.get(4)?,
+                    keystrokes: row.get(5)?,
+                    clicks: row.get(6)?,
+                    scrolls: row.get(7)?,
+                    is_idle: row.get(8)?,
+                    shortcuts: row.get::<_, Option<i64>>(9)?.unwrap_or(0),
+                    is_fullscreen: row.get::<_, Option<bool>>(10)?.unwrap_or(false),
+                    category_override: row.get(11)?,
+                    url: row.get(12)?,
+                    hash: row.get(13)?,
+                    signature: row.get(14)?,
+                    prev_hash: row.get(15)?,
+                    tags: Vec::new(),
+                }))
+            })?
+            .peekable();
+
+        let mut media_stmt = conn.prepare(
+            "SELECT id, title, artist, album, source_app, start_time, end_time, duration_secs, track_duration_secs
+             FROM media
+             WHERE (start_time, 1, id) > (?1, ?2, ?3)
+             ORDER BY start_time, id
+             LIMIT ?4",
+        )?;
+        let mut media = media_stmt
+            .query_map(after, |row| {
+                Ok(ExportRecord::Media(ExportedMedia {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    artist: row.get(2)?,
+                    album: row.get(3)?,
+                    source_app: row.get(4)?,
+                    start_time: row.get(5)?,
+                    end_time: row.get(6)?,
+                    duration_secs: row.get::<_, Option<i64>>(7)?.unwrap_or(0),
+                    track_duration_secs: row.get(8)?,
+                }))
+            })?
+            .peekable();
+
+        let mut tag_stmt =
+            conn.prepare("SELECT tag FROM session_tags WHERE session_id = ?1 ORDER BY tag")?;
+
+        let mut last = None;
+        for _ in 0..limit {
+            // Sessions sort before media that start at the same instant
+            let take_session = match (sessions.peek(), media.peek()) {
+                (Some(Ok(s)), Some(Ok(m))) => s.cursor() <= m.cursor(),
+                (Some(_), _) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let record = if take_session {
+                sessions.next()
+            } else {
+                media.next()
+            };
+            let Some(mut record) = record.transpose()? else {
+                break;
+            };
+            if let ExportRecord::Session(session) = &mut record {
+                session.tags = tag_stmt
+                    .query_map(params![session.id], |row| row.get(0))?
+                    .collect::<SqlResult<_>>()?;
+            }
+            on_record(&record);
+            last = Some(record.cursor());
+        }
+
+        Ok(last)
+    }
+
+    /// Inserts the records of an [`Archive`] in one transaction.
+    ///
+    /// Sessions with the same process and start time, and media with the same
+    /// title and start time, are already present and skipped.
+    ///
+    /// Integrity fields are only kept when the database has no signed
+    /// sessions yet. Otherwise the imported rows would be interleaved into
+    /// the existing hash chain and break it, so they are stored unsigned.
+    pub fn import_archive(&self, archive: &Archive) -> SqlResult<ArchiveImportSummary> {
+        let conn = self.lock_conn();
+        let tx = conn.unchecked_transaction()?;
+        let mut summary = ArchiveImportSummary::default();
+
+        let keep_integrity: bool = tx.query_row(
+            "SELECT NOT EXISTS(SELECT 1 FROM sessions WHERE hash IS NOT NULL)",
+            [],
+            |row| row.get(0),
+        )?;
+
+        for s in &archive.sessions {
+            let exists: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM sessions WHERE process_name = ?1 AND start_time = ?2)",
+                params![s.process_name, s.start_time],
+                |row| row.get(0),
+            )?;
+            if exists {
+                summary.skipped += 1;
+                continue;
+            }
+
+            let (hash, signature, prev_hash) = if keep_integrity {
+                (
+                    s.hash.as_deref(),
+                    s.signature.as_deref(),
+                    s.prev_hash.as_deref(),
+                )
+            } else {
+                if s.hash.is_some() {
+                    summary.unsigned += 1;
+                }
+                (None, None, None)
+            };
+            tx.execute(
+                "INSERT INTO sessions (process_name, window_title, start_time, end_time, keystrokes, clicks, scrolls, is_idle, hash, signature, prev_hash, shortcuts, is_fullscreen, category_override, scroll_up, scroll_down, scroll_left, scroll_right, mouse_distance_px, double_clicks,
+                                       keys_alphanumeric, keys_whitespace, keys_navigation, keys_modifier, keys_other, url, process_path, monitor, idle_reason)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29)",
+                params![
+                    s.process_name,
+                    s.window_title,
+                    s.start_time,
+                    s.end_time,
+                    s.keystrokes,
+                    s.clicks,
+                    s.scrolls,
+                    s.is_idle,
+                    hash,
+                    signature,
+                    prev_hash,
+                    s.shortcuts,
+                    s.is_fullscreen,
+                    s.category_override,
+                    s.scroll_up,
+                    s.scroll_down,
+                    s.scroll_left,
+                    s.scroll_right,
+                    s.mouse_distance_px,
+                    s.double_clicks,
+                    s.keys_alphanumeric,
+                    s.keys_whitespace,
+                    s.keys_navigation,
+                    s.keys_modifier,
+                    s.keys_other,
+                    s.url,
+                    s.process_path,
+                    s.monitor,
+                    s.idle_reason,
+                ],
+            )?;
+            let session_id = tx.last_insert_rowid();
+            for tag in &s.tags {
+                tx.execute(
+                    "INSERT OR IGNORE INTO session_tags (session_id, tag) VALUES (?1, ?2)",
+                    params![session_id, tag],
+                )?;
+            }
+            summary.sessions += 1;
+        }
+
+        for m in &archive.media {
+            let exists: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM media WHERE title = ?1 AND start_time = ?2)",
+                params![m.title, m.start_time],
+                |row| row.get(0),
+            )?;
+            if exists {
+                summary.skipped += 1;
+                continue;
+            }
+
+            tx.execute(
+                "INSERT INTO media (title, artist, album, source_app, start_time, end_time, duration_secs, track_duration_secs)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    m.title,
+                    m.artist,
+                    m.album,
+                    m.source_app,
+                    m.start_time,
+                    m.end_time,
+                    m.duration_secs,
+                    m.track_duration_secs,
+                ],
+            )?;
+            summary.media += 1;
+        }
+
+        tx.commit()?;
+        Ok(summary)
+    }
+
+    // === Rule Pack Import ===
+
+    /// Merges a rule pack into the categories, app mappings and blacklist.
+    ///
+    /// Existing entries are kept unless `rules.force` is set. Mappings that
+    /// name an unknown category are skipped.
+    pub fn import_rules(&self, rules: &RulePack) -> SqlResult<RuleImportSummary> {
+        let conn = self.lock_conn();
+        let tx = conn.unchecked_transaction()?;
+        let now = Utc::now().to_rfc3339();
+        let mut summary = RuleImportSummary::default();
+
+        for cat in &rules.categories {
+            summary.categories += if rules.force {
+                tx.execute(
+                    "INSERT INTO categories (name, color, icon, created_at) VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT(name) DO UPDATE SET color = excluded.color, icon = excluded.icon",
+                    params![cat.name, cat.color, cat.icon, now],
+                )?
+            } else {
+                tx.execute(
+                    "INSERT OR IGNORE INTO categories (name, color, icon, created_at) VALUES (?1, ?2, ?3, ?4)",
+                    params![cat.name, cat.color, cat.icon, now],
+                )?
+            };
+        }
+
+        for mapping in &rules.app_categories {
+            let category_id: Option<i64> = match tx.query_row(
+                "SELECT id FROM categories WHERE name = ?1",
+                params![mapping.category],
+                |row| row.get(0),
+            ) {
+                Ok(id) => Some(id),
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e),
+            };
+
+            let Some(category_id) = category_id else {
+                tracing::warn!(
+                    pattern = %mapping.pattern,
+                    category = %mapping.category,
+                    "Skipping rule for unknown category"
+                );
+                continue;
+            };
+
+            let verb = if rules.force {
+                "INSERT OR REPLACE"
+            } else {
+                "INSERT OR IGNORE"
+            };
+            summary.app_categories += tx.execute(
+                &format!(
+                    "{} INTO app_categories (process_pattern, category_id) VALUES (?1, ?2)",
+                    verb
+                ),
+                params![mapping.pattern, category_id],
+            )?;
+        }
+
+        for entry in &rules.blacklist {
+            summary.blacklist += if rules.force {
+                tx.execute(
+                    "INSERT INTO blacklist (pattern, description, created_at) VALUES (?1, ?2, ?3)
+                     ON CONFLICT(pattern) DO UPDATE SET description = excluded.description",
+                    params![entry.pattern, entry.description, now],
+                )?
+            } else {
+                tx.execute(
+                    "INSERT OR IGNORE INTO blacklist (pattern, description, created_at) VALUES (?1, ?2, ?3)",
+                    params![entry.pattern, entry.description, now],
+                )?
+            };
+        }
+
+        tx.commit()?;
+        Ok(summary)
+    }
+}
+
+/// Columns read by [`session_with_duration_from_row`], in order.
+const SESSION_WITH_DURATION_COLUMNS: &str = "id, process_name, window_title, start_time, end_time, keystrokes, clicks, scrolls, is_idle, shortcuts, is_fullscreen, category_override,
+                    scroll_up, scroll_down, scroll_left, scroll_right, mouse_distance_px, double_clicks,
+                    keys_alphanumeric, keys_whitespace, keys_navigation, keys_modifier, keys_other,
+                    CAST((julianday(end_time) - julianday(start_time)) * 86400 AS INTEGER) as duration, url, process_path, monitor, idle_reason";
+
+/// Reads a record selected as `date, merkle_root, prev_day_root,
+/// session_count, signature, created_at, key_fingerprint`.
+fn daily_integrity_from_row(row: &rusqlite::Row<'_>) -> SqlResult<DailyIntegrityRecord> {
+    Ok(DailyIntegrityRecord {
+        date: row.get(0)?,
+        merkle_root: row.get(1)?,
+        prev_day_root: row.get(2)?,
+        session_count: row.get::<_, i64>(3)? as u32,
+        signature: row.get(4)?,
+        created_at: row.get(5)?,
+        key_fingerprint: row.get(6)?,
+    })
+}
+
+/// Reads a session selected with [`SESSION_WITH_DURATION_COLUMNS`].
+fn session_with_duration_from_row(row: &rusqlite::Row<'_>) -> SqlResult<SessionWithDuration> {
+    Ok(SessionWithDuration {
+        id: row.get(0)?,
+        process_name: row.get(1)?,
+        window_title: row.get(2)?,
+        start_time: row.get(3)?,
+        end_time: row.get(4)?,
+        keystrokes: row.get(5)?,
+        clicks: row.get(6)?,
+        scrolls: row.get(7)?,
+        is_idle: row.get(8)?,
+        shortcuts: row.get::<_, Option<i64>>(9)?.unwrap_or(0),
+        is_fullscreen: row.get::<_, Option<bool>>(10)?.unwrap_or(false),
+        category_override: row.get(11)?,
+        scroll_up: row.get::<_, Option<i64>>(12)?.unwrap_or(0),
+        scroll_down: row.get::<_, Option<i64>>(13)?.unwrap_or(0),
+        scroll_left: row.get::<_, Option<i64>>(14)?.unwrap_or(0),
+        scroll_right: row.get::<_, Option<i64>>(15)?.unwrap_or(0),
+        mouse_distance_px: row.get::<_, Option<i64>>(16)?.unwrap_or(0),
+        double_clicks: row.get::<_, Option<i64>>(17)?.unwrap_or(0),
+        key_breakdown: key_breakdown_from_row(row, 18)?,
+        duration_secs: row.get(23)?,
+        url: row.get(24)?,
+        process_path: row.get(25)?,
+        monitor: row.get(26)?,
+        idle_reason: row
+            .get::<_, Option<String>>(27)?
+            .as_deref()
+            .and_then(IdleReason::parse),
+    })
+}
+
+/// Reads the five `keys_*` columns starting at `first`.
+fn key_breakdown_from_row(row: &rusqlite::Row<'_>, first: usize) -> SqlResult<KeyBreakdown> {
+    let count = |idx: usize| -> SqlResult<u64> {
+        Ok(row.get::<_, Option<i64>>(first + idx)?.unwrap_or(0).max(0) as u64)
+    };
+    Ok(KeyBreakdown {
+        alphanumeric: count(0)?,
+        whitespace: count(1)?,
+        navigation: count(2)?,
+        modifier: count(3)?,
+        other: count(4)?,
+    })
 }
 
 /// Matches a pattern with wildcards against a string.
@@ -1023,15 +3651,155 @@ fn pattern_matches(pattern: &str, text: &str) -> bool {
     match_helper(p_chars, t_chars)
 }
 
-/// A blacklist entry from the database.
-#[derive(Debug, Clone)]
-pub struct BlacklistEntry {
+/// SQL expression resolving a `sessions` row's category ID.
+///
+/// Mirrors `get_category_for_session`: a valid override wins, then an exact
+/// pattern match, then the first wildcard pattern (case-insensitive), then "Other".
+const SESSION_CATEGORY_SQL: &str = r"COALESCE(
+    (SELECT c.id FROM categories c WHERE c.id = sessions.category_override),
+    (SELECT ac.category_id FROM app_categories ac WHERE ac.process_pattern = sessions.process_name),
+    (SELECT ac.category_id FROM app_categories ac
+     WHERE lower(sessions.process_name) LIKE
+        replace(replace(replace(replace(replace(lower(ac.process_pattern),
+            '\', '\\'), '%', '\%'), '_', '\_'), '*', '%'), '?', '_') ESCAPE '\'
+     ORDER BY ac.rowid LIMIT 1),
+    1)";
+
+/// Maps a `id, name, color, icon, distracting` row to a [`Category`].
+fn category_from_row(row: &rusqlite::Row<'_>) -> SqlResult<Category> {
+    Ok(Category {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        icon: row.get(3)?,
+        distracting: row.get::<_, Option<bool>>(4)?.unwrap_or(false),
+    })
+}
+
+/// Escapes a value for embedding in a single-quoted SQL string literal.
+fn escape_sql_literal(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+/// Translates a wildcard pattern into a SQL `LIKE` pattern.
+///
+/// `*` becomes `%` and `?` becomes `_`. Literal `%`, `_` and the escape
+/// character are escaped, and single quotes are doubled so the pattern can
+/// be embedded in a string literal. Use with `LIKE '...' ESCAPE '<char>'`.
+///
+/// Returns the pattern and the escape character.
+pub fn wildcard_to_like(pattern: &str) -> (String, char) {
+    const ESCAPE: char = '\\';
+
+    let mut like = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        match c {
+            '*' => like.push('%'),
+            '?' => like.push('_'),
+            '%' | '_' | ESCAPE => {
+                like.push(ESCAPE);
+                like.push(c);
+            }
+            '\'' => like.push_str("''"),
+            _ => like.push(c),
+        }
+    }
+
+    (like, ESCAPE)
+}
+
+/// Size of a connection's `-wal` file, or 0 if it has none.
+fn wal_file_size(conn: &Connection) -> u64 {
+    conn.path()
+        .filter(|p| !p.is_empty())
+        .and_then(|p| std::fs::metadata(format!("{}-wal", p)).ok())
+        .map(|m| m.len())
+        .unwrap_or(0)
+}
+
+/// Builds a WHERE condition matching rows whose `start_time` falls on
+/// `date` (`YYYY-MM-DD`) under the timezone policy.
+fn date_condition(date: &str) -> String {
+    format!(
+        "date(start_time, '{}') = '{}'",
+        escape_sql_literal(&timezone_policy().sql_modifier()),
+        escape_sql_literal(date)
+    )
+}
+
+/// Builds a WHERE condition matching a column against a value.
+///
+/// Values containing `*` or `?` are matched as wildcard patterns,
+/// anything else must match exactly.
+fn wildcard_condition(column: &str, value: &str) -> String {
+    if value.contains(['*', '?']) {
+        let (pattern, escape) = wildcard_to_like(value);
+        format!("{} LIKE '{}' ESCAPE '{}'", column, pattern, escape)
+    } else {
+        format!("{} = '{}'", column, escape_sql_literal(value))
+    }
+}
+
+/// A per-app idle threshold override.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct IdleOverride {
+    pub process_pattern: String,
+    pub threshold_secs: i64,
+}
+
+/// A goal on one of the day's totals.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Goal {
+    pub id: i64,
+    /// `keystrokes`, `clicks` or `focus_time_secs`.
+    pub metric: String,
+    pub threshold: i64,
+    /// Only `daily` for now.
+    pub period: String,
+    pub created_at: String,
+}
+
+/// A blacklist entry from the database.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BlacklistEntry {
     pub id: i64,
     pub pattern: String,
     pub description: Option<String>,
     pub created_at: String,
 }
 
+/// A regex for sensitive text in window titles.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RedactionPattern {
+    pub id: i64,
+    pub pattern: String,
+    pub created_at: String,
+}
+
+/// A stored session with the fields covered by its hash.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SignedSessionRecord {
+    pub id: i64,
+    pub process_name: String,
+    pub window_title: Option<String>,
+    pub start_time: String,
+    pub end_time: Option<String>,
+    pub keystrokes: i64,
+    pub clicks: i64,
+    pub scrolls: i64,
+    pub hash: String,
+    pub signature: Option<String>,
+    pub prev_hash: Option<String>,
+}
+
+/// Integrity chain data stored alongside a session.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionIntegrity<'a> {
+    pub hash: Option<&'a str>,
+    pub signature: Option<&'a str>,
+    pub prev_hash: Option<&'a str>,
+}
+
 /// A session record from the database.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct SessionRecord {
@@ -1066,6 +3834,111 @@ pub struct DailyTimeline {
     pub focus_secs: i64,
 }
 
+/// Weekly totals for week-over-week comparison.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WeeklyStats {
+    /// Monday the week starts on (YYYY-MM-DD)
+    pub week_start: String,
+    /// Year and Monday-based week number (`%Y-%W`)
+    pub week: String,
+    pub keystrokes: i64,
+    pub clicks: i64,
+    pub sessions: i64,
+    pub focus_secs: i64,
+}
+
+/// An uninterrupted, non-idle focus session.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FocusStreak {
+    pub id: i64,
+    pub process_name: String,
+    pub window_title: Option<String>,
+    pub start_time: String,
+    pub end_time: String,
+    pub duration_secs: i64,
+}
+
+/// Row limits for list endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryLimits {
+    /// Rows returned when no `limit` is given.
+    pub default: usize,
+    /// Largest `limit` honored.
+    pub max: usize,
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        Self {
+            default: 500,
+            max: 2000,
+        }
+    }
+}
+
+impl QueryLimits {
+    /// Resolves a requested limit, clamped to the configured maximum.
+    pub fn resolve(&self, requested: Option<usize>) -> usize {
+        requested.unwrap_or(self.default).min(self.max)
+    }
+}
+
+/// Database size and row counts.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StorageStats {
+    /// Database file path (`None` for in-memory databases).
+    pub db_path: Option<String>,
+    /// Size of the main database file on disk.
+    pub file_size_bytes: u64,
+    /// Size of the write-ahead log on disk.
+    pub wal_size_bytes: u64,
+    /// Size of the database pages (`page_count * page_size`).
+    pub data_size_bytes: i64,
+    pub sessions: i64,
+    pub media: i64,
+    /// Start time of the oldest session or media record.
+    pub oldest_record: Option<String>,
+    /// Average growth since the oldest record.
+    pub bytes_per_day: Option<f64>,
+}
+
+/// Totals for one day of the week.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WeekdayStats {
+    /// 0 = Sunday .. 6 = Saturday.
+    pub weekday: i32,
+    pub name: String,
+    pub keystrokes: i64,
+    pub clicks: i64,
+    pub sessions: i64,
+    pub focus_secs: i64,
+}
+
+/// A process name seen in the sessions table.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct AppSeen {
+    pub process_name: String,
+    pub session_count: i64,
+    /// End of the app's latest session (its start, if it has no end yet).
+    pub last_seen: String,
+}
+
+/// One app's usage on one day.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct AppDailyUsage {
+    pub date: String,
+    pub focus_secs: i64,
+    pub sessions: i64,
+}
+
+/// Per-day totals up to a time of day (for pace comparisons).
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DailyPaceTotals {
+    pub date: String,
+    pub keystrokes: i64,
+    pub focus_secs: i64,
+}
+
 /// App category.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct Category {
@@ -1073,10 +3946,57 @@ pub struct Category {
     pub name: String,
     pub color: String,
     pub icon: Option<String>,
+    /// Focus mode notifies when an app in this category is focused.
+    pub distracting: bool,
 }
 
-/// Session record with computed duration.
+/// Totals for one category over a day.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CategoryStats {
+    #[serde(flatten)]
+    pub category: Category,
+    pub focus_secs: i64,
+    pub keystrokes: i64,
+    pub clicks: i64,
+    pub sessions: i64,
+}
+
+/// Totals for one app over a day.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AppTotals {
+    pub process_name: String,
+    pub category: Category,
+    pub focus_secs: i64,
+    pub keystrokes: i64,
+    pub clicks: i64,
+    pub sessions: i64,
+}
+
+/// Totals for one monitor over a day.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MonitorStats {
+    /// Device name (e.g., `\\.\DISPLAY1`); `None` for sessions recorded
+    /// before monitors were tracked or whose monitor couldn't be read.
+    pub monitor: Option<String>,
+    pub focus_secs: i64,
+    pub keystrokes: i64,
+    pub clicks: i64,
+    pub sessions: i64,
+}
+
+/// ID of the built-in "Other" category, used for unmapped apps.
+pub const OTHER_CATEGORY_ID: i64 = 1;
+
+/// App category with the process patterns mapped to it.
 #[derive(Debug, Clone, serde::Serialize)]
+pub struct CategoryWithApps {
+    #[serde(flatten)]
+    pub category: Category,
+    pub apps: Vec<String>,
+}
+
+/// Session record with computed duration.
+#[derive(Debug, Clone, Default, serde::Serialize)]
 pub struct SessionWithDuration {
     pub id: i64,
     pub process_name: String,
@@ -1087,7 +4007,157 @@ pub struct SessionWithDuration {
     pub clicks: i64,
     pub scrolls: i64,
     pub is_idle: bool,
+    pub shortcuts: i64,
+    pub is_fullscreen: bool,
+    /// Manually set category ID, if any (wins over the app's pattern mapping).
+    pub category_override: Option<i64>,
+    pub scroll_up: i64,
+    pub scroll_down: i64,
+    pub scroll_left: i64,
+    pub scroll_right: i64,
+    pub mouse_distance_px: i64,
+    pub double_clicks: i64,
+    pub key_breakdown: KeyBreakdown,
+    pub duration_secs: i64,
+    /// Address bar URL at session start (browsers only).
+    pub url: Option<String>,
+    /// Full executable path, if the process could be opened.
+    pub process_path: Option<String>,
+    /// Device name of the monitor showing the window.
+    pub monitor: Option<String>,
+    /// Why the session was idle (`None` for active sessions and older idle ones).
+    pub idle_reason: Option<IdleReason>,
+}
+
+/// A single session with its integrity chain fields.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionDetail {
+    #[serde(flatten)]
+    pub session: SessionWithDuration,
+    pub hash: Option<String>,
+    pub signature: Option<String>,
+    pub prev_hash: Option<String>,
+}
+
+/// Result of [`Database::delete_session`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DeletedSession {
+    pub id: i64,
+    /// Date the session belonged to, in the configured timezone.
+    pub date: String,
+    /// True if the date already has a `daily_integrity` record.
+    pub sealed: bool,
+    /// True if the hash chain or the sealed day no longer verifies.
+    pub integrity_invalidated: bool,
+    /// Start time (RFC 3339) and process of the session, used to drop its
+    /// in-memory copy.
+    #[serde(skip)]
+    pub start_time: String,
+    #[serde(skip)]
+    pub process_name: String,
+}
+
+/// Position in the start-time ordered stream of exported records.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ExportCursor {
+    pub start_time: String,
+    /// 0 for sessions, 1 for media.
+    pub kind: i64,
+    pub id: i64,
+}
+
+/// One line of the JSON Lines export, tagged with its `type`.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportRecord {
+    Session(ExportedSession),
+    Media(ExportedMedia),
+}
+
+impl ExportRecord {
+    /// Returns the cursor just past this record.
+    pub fn cursor(&self) -> ExportCursor {
+        match self {
+            Self::Session(s) => ExportCursor {
+                start_time: s.start_time.clone(),
+                kind: 0,
+                id: s.id,
+            },
+            Self::Media(m) => ExportCursor {
+                start_time: m.start_time.clone(),
+                kind: 1,
+                id: m.id,
+            },
+        }
+    }
+}
+
+/// A session in the JSON Lines export, with its integrity chain fields.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExportedSession {
+    pub id: i64,
+    pub process_name: String,
+    pub window_title: Option<String>,
+    pub start_time: String,
+    pub end_time: Option<String>,
+    pub keystrokes: i64,
+    pub clicks: i64,
+    pub scrolls: i64,
+    pub is_idle: bool,
+    pub shortcuts: i64,
+    pub is_fullscreen: bool,
+    pub category_override: Option<i64>,
+    pub url: Option<String>,
+    pub hash: Option<String>,
+    pub signature: Option<String>,
+    pub prev_hash: Option<String>,
+    /// Labels from `session_tags`, alphabetically.
+    pub tags: Vec<String>,
+}
+
+/// A media record in the JSON Lines export.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExportedMedia {
+    pub id: i64,
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub source_app: Option<String>,
+    pub start_time: String,
+    pub end_time: Option<String>,
     pub duration_secs: i64,
+    pub track_duration_secs: Option<i64>,
+}
+
+/// Longest pause, in seconds, after which resuming a track counts as a new play.
+pub const PLAY_RESUME_GAP_SECS: i64 = 30 * 60;
+
+/// One play of a track, possibly spread over several media rows.
+struct MediaPlay {
+    title: String,
+    artist: String,
+    listen_secs: i64,
+}
+
+/// Listening totals for one artist over a date range.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ArtistListenStats {
+    pub artist: String,
+    pub listen_secs: i64,
+    /// Plays of any of the artist's tracks (resuming after a pause isn't a new play)
+    pub play_count: i64,
+    /// Distinct tracks played
+    pub track_count: i64,
+}
+
+/// Listening totals for one track over a date range.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TrackListenStats {
+    pub title: String,
+    pub artist: String,
+    pub listen_secs: i64,
+    /// Plays (resuming after a pause isn't a new play)
+    pub play_count: i64,
 }
 
 /// Media record from the database.
@@ -1101,12 +4171,63 @@ pub struct MediaRecord {
     pub start_time: String,
     pub end_time: Option<String>,
     pub duration_secs: i64,
+    /// Track length reported by the player (None if unknown).
+    pub track_duration_secs: Option<i64>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn test_session(
+        process_name: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        keystrokes: u64,
+    ) -> WindowSession {
+        let mut session = WindowSession::new(0, 0, process_name.to_string(), "Window".to_string());
+        session.start_time = start;
+        session.end_time = Some(end);
+        session.keystrokes = keystrokes;
+        session
+    }
+
+    #[test]
+    fn test_poisoned_connection_lock_recovered() {
+        let db = Database::open_in_memory().unwrap();
+        let conn = Arc::clone(&db.conn);
+        let _ = std::thread::spawn(move || {
+            let _guard = conn.lock().unwrap();
+            panic!("query panicked while holding the connection");
+        })
+        .join();
+        assert!(db.conn.is_poisoned());
+
+        assert!(db.get_config("afk_threshold_secs").unwrap().is_some());
+        assert!(!db.conn.is_poisoned());
+    }
+
+    #[test]
+    fn test_take_data_dir_arg() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(take_data_dir_arg(args(&[])), Ok((None, vec![])));
+        assert_eq!(
+            take_data_dir_arg(args(&[
+                "--verify",
+                "--data-dir",
+                r"E:\ownmon",
+                "--db",
+                "a.db"
+            ])),
+            Ok((
+                Some(PathBuf::from(r"E:\ownmon")),
+                args(&["--verify", "--db", "a.db"])
+            ))
+        );
+        assert!(take_data_dir_arg(args(&["--data-dir"])).is_err());
+    }
+
     #[test]
     fn test_create_database() {
         let db = Database::open_in_memory().unwrap();
@@ -1120,20 +4241,16 @@ mod tests {
         let start = Utc::now();
         let end = start + chrono::Duration::seconds(60);
 
+        let mut session = test_session("test.exe", start, end, 100);
+        session.window_title = "Test Window".to_string();
+        session.mouse_clicks = 50;
+        session.mouse_scrolls = 10;
+        session.shortcuts = 4;
+        session.key_breakdown.alphanumeric = 90;
+        session.key_breakdown.navigation = 10;
+
         let id = db
-            .save_session(
-                "test.exe",
-                "Test Window",
-                start,
-                end,
-                100,
-                50,
-                10,
-                false,
-                None,
-                None,
-                None,
-            )
+            .save_session(&session, end, SessionIntegrity::default())
             .unwrap();
 
         assert!(id > 0);
@@ -1142,5 +4259,1408 @@ mod tests {
         assert_eq!(sessions.len(), 1);
         assert_eq!(sessions[0].process_name, "test.exe");
         assert_eq!(sessions[0].keystrokes, 100);
+
+        let (sessions, _) = db
+            .query_sessions_flexible(None, None, None, None, None, None, 10, 0, true)
+            .unwrap();
+        assert_eq!(sessions[0].shortcuts, 4);
+        assert_eq!(sessions[0].key_breakdown, session.key_breakdown);
+    }
+
+    #[test]
+    fn test_get_and_delete_session_flags_sealed_day() {
+        let db = Database::open_in_memory().unwrap();
+        let policy = timezone_policy();
+        let date = policy.date_of(Utc::now()) - chrono::Duration::days(1);
+        let start = policy.day_start(date) + chrono::Duration::hours(12);
+        let end = start + chrono::Duration::minutes(5);
+
+        let session = test_session("code.exe", start, end, 7);
+        let integrity = SessionIntegrity {
+            hash: Some("abc"),
+            signature: Some("sig"),
+            prev_hash: None,
+        };
+        let hashed = db.save_session(&session, end, integrity).unwrap();
+        let plain = db
+            .save_session(&session, end, SessionIntegrity::default())
+            .unwrap();
+
+        let detail = db.get_session(hashed).unwrap().unwrap();
+        assert_eq!(detail.session.process_name, "code.exe");
+        assert_eq!(detail.session.duration_secs, 300);
+        assert_eq!(detail.hash.as_deref(), Some("abc"));
+        assert!(db.get_session(9999).unwrap().is_none());
+
+        // Unsealed, unhashed sessions can go without breaking integrity
+        let date = date.format("%Y-%m-%d").to_string();
+        let deleted = db.delete_session(plain).unwrap().unwrap();
+        assert_eq!(deleted.date, date);
+        assert!(!deleted.sealed);
+        assert!(!deleted.integrity_invalidated);
+
+        db.save_daily_integrity(&date, "root", None, 1, "sig", None)
+            .unwrap();
+        let deleted = db.delete_session(hashed).unwrap().unwrap();
+        assert!(deleted.sealed);
+        assert!(deleted.integrity_invalidated);
+        assert!(db.get_session(hashed).unwrap().is_none());
+        assert!(db.delete_session(hashed).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_relink_daily_integrity_keeps_sealed_range() {
+        let db = Database::open_in_memory().unwrap();
+        db.save_daily_integrity("2025-01-16", "root", Some("old"), 2, "sig", None)
+            .unwrap();
+        // Sealed under a different time zone than the current one
+        let range = || {
+            db.lock_conn()
+                .query_row(
+                    "SELECT range_start, range_end FROM daily_integrity WHERE date = '2025-01-16'",
+                    [],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+                )
+                .unwrap()
+        };
+        db.lock_conn()
+            .execute(
+                "UPDATE daily_integrity SET range_start = '2025-01-15T22:00:00+00:00',
+                   range_end = '2025-01-16T22:00:00+00:00'",
+                [],
+            )
+            .unwrap();
+        let sealed = range();
+
+        db.relink_daily_integrity("2025-01-16", Some("new"), "sig2", Some("fp"))
+            .unwrap();
+
+        let record = db.get_daily_integrity("2025-01-16").unwrap().unwrap();
+        assert_eq!(record.prev_day_root.as_deref(), Some("new"));
+        assert_eq!(record.signature, "sig2");
+        assert_eq!(record.key_fingerprint.as_deref(), Some("fp"));
+        assert_eq!(record.merkle_root, "root");
+        assert_eq!(record.session_count, 2);
+        assert_eq!(range(), sealed);
+    }
+
+    /// Every stored value of a table except `id`, by column name.
+    fn table_rows(db: &Database, table: &str) -> Vec<Vec<(String, rusqlite::types::Value)>> {
+        let conn = db.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(&format!("SELECT * FROM {} ORDER BY id", table))
+            .unwrap();
+        let names: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(names
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, name)| *name != "id")
+                    .map(|(i, name)| (name.clone(), row.get(i).unwrap()))
+                    .collect())
+            })
+            .unwrap();
+        rows.collect::<SqlResult<_>>().unwrap()
+    }
+
+    #[test]
+    fn test_archive_round_trip_keeps_every_column() {
+        use crate::archive::{decode_archive, encode_archive};
+
+        let db = Database::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::hours(1);
+        let mut session = test_session("code.exe", start, start, 1);
+        session.window_title = "main.rs".into();
+        session.process_path = Some(r"C:\Code\code.exe".into());
+        session.url = Some("https://example.com".into());
+        session.monitor = Some(r"\\.\DISPLAY1".into());
+        session.mouse_clicks = 2;
+        session.mouse_scrolls = 3;
+        session.scroll_up = 4;
+        session.scroll_down = 5;
+        session.scroll_left = 6;
+        session.scroll_right = 7;
+        session.shortcuts = 8;
+        session.double_clicks = 9;
+        session.mouse_distance_px = 10;
+        session.key_breakdown.alphanumeric = 11;
+        session.key_breakdown.whitespace = 12;
+        session.key_breakdown.navigation = 13;
+        session.key_breakdown.modifier = 14;
+        session.key_breakdown.other = 15;
+        session.is_fullscreen = true;
+        session.is_idle = true;
+        session.idle_reason = Some(IdleReason::Locked);
+        session.category_override = Some(2);
+        let integrity = SessionIntegrity {
+            hash: Some("hash"),
+            signature: Some("sig"),
+            prev_hash: Some("prev"),
+        };
+        let id = db
+            .save_session(&session, start + chrono::Duration::minutes(5), integrity)
+            .unwrap();
+        db.add_session_tag(id, "review").unwrap();
+        db.add_session_tag(id, "billable").unwrap();
+        db.save_media(
+            "Song",
+            "Artist",
+            "Album",
+            "Spotify.exe",
+            start,
+            start + chrono::Duration::minutes(3),
+            Some(200),
+        )
+        .unwrap();
+
+        let bytes = encode_archive(&db.export_archive().unwrap()).unwrap();
+        let restored = Database::open_in_memory().unwrap();
+        let summary = restored
+            .import_archive(&decode_archive(&bytes).unwrap())
+            .unwrap();
+        assert_eq!(summary.unsigned, 0);
+
+        for table in ["sessions", "media"] {
+            assert_eq!(table_rows(&restored, table), table_rows(&db, table));
+        }
+        assert_eq!(
+            restored.get_session_tags(1).unwrap(),
+            vec!["billable".to_string(), "review".to_string()]
+        );
+
+        // Into a database with its own chain, sessions come in unsigned
+        let chained = Database::open_in_memory().unwrap();
+        let mut own = test_session("notepad.exe", start, start, 1);
+        own.start_time = start - chrono::Duration::hours(1);
+        chained
+            .save_session(
+                &own,
+                start,
+                SessionIntegrity {
+                    hash: Some("own"),
+                    signature: Some("sig"),
+                    prev_hash: None,
+                },
+            )
+            .unwrap();
+        let summary = chained
+            .import_archive(&decode_archive(&bytes).unwrap())
+            .unwrap();
+        assert_eq!((summary.sessions, summary.unsigned), (1, 1));
+        assert_eq!(
+            chained.get_last_session_hash().unwrap().as_deref(),
+            Some("own")
+        );
+    }
+
+    #[test]
+    fn test_session_tags_filter_and_follow_deletes() {
+        let db = Database::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::hours(2);
+        let end = start + chrono::Duration::minutes(5);
+        let session = test_session("code.exe", start, end, 7);
+        let first = db
+            .save_session(&session, end, SessionIntegrity::default())
+            .unwrap();
+        let second = db
+            .save_session(&session, end, SessionIntegrity::default())
+            .unwrap();
+
+        assert!(db.add_session_tag(first, "client-a").unwrap());
+        assert!(db.add_session_tag(first, "billable").unwrap());
+        assert!(db.add_session_tag(first, "billable").unwrap());
+        assert!(db.add_session_tag(second, "client-b").unwrap());
+        assert!(!db.add_session_tag(9999, "client-a").unwrap());
+        assert_eq!(
+            db.get_session_tags(first).unwrap(),
+            vec!["billable", "client-a"]
+        );
+
+        let (sessions, total) = db
+            .query_sessions_flexible(None, None, None, None, None, Some("client-a"), 10, 0, true)
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(sessions[0].id, first);
+
+        // The tag is bound, not spliced into the SQL
+        assert!(db.add_session_tag(second, "it's").unwrap());
+        let (sessions, total) = db
+            .query_sessions_flexible(None, None, None, None, None, Some("it's"), 10, 0, true)
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(sessions[0].id, second);
+
+        assert!(db.remove_session_tag(first, "client-a").unwrap());
+        assert!(!db.remove_session_tag(first, "client-a").unwrap());
+        let (_, total) = db
+            .query_sessions_flexible(None, None, None, None, None, Some("client-a"), 10, 0, true)
+            .unwrap();
+        assert_eq!(total, 0);
+
+        // Deleting a session drops its tags
+        db.delete_session(second).unwrap();
+        assert!(db.get_session_tags(second).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_category_override_wins_for_session() {
+        let db = Database::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::hours(1);
+
+        let mut work = test_session("chrome.exe", start, start + chrono::Duration::minutes(5), 0);
+        work.category_override = Some(2);
+        let leisure_start = start + chrono::Duration::minutes(10);
+        let leisure = test_session(
+            "chrome.exe",
+            leisure_start,
+            leisure_start + chrono::Duration::minutes(5),
+            0,
+        );
+        for session in [&work, &leisure] {
+            db.save_session(
+                session,
+                session.end_time.unwrap(),
+                SessionIntegrity::default(),
+            )
+            .unwrap();
+        }
+
+        let (sessions, _) = db
+            .query_sessions_flexible(
+                None,
+                None,
+                None,
+                Some("chrome.exe"),
+                None,
+                None,
+                10,
+                0,
+                false,
+            )
+            .unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].category_override, Some(2));
+        assert_eq!(sessions[1].category_override, None);
+
+        // The override wins over the chrome.exe -> Browser mapping, for that session only
+        assert_eq!(
+            db.get_category_for_session(&sessions[0]).unwrap().name,
+            "Work"
+        );
+        assert_eq!(
+            db.get_category_for_session(&sessions[1]).unwrap().name,
+            "Browser"
+        );
+    }
+
+    #[test]
+    fn test_category_filter_paginates_with_consistent_total() {
+        let db = Database::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::hours(2);
+
+        // Work: code.exe (exact) x2, a chrome.exe override x1; also Browser and a wildcard match
+        let apps = [
+            ("code.exe", None),
+            ("chrome.exe", None),
+            ("code.exe", None),
+            ("chrome.exe", Some(2)),
+            ("chrome.exe", None),
+            ("Netflix Desktop.exe", None),
+        ];
+        for (i, (app, category_override)) in apps.iter().enumerate() {
+            let s = start + chrono::Duration::minutes(i as i64 * 10);
+            let mut session = test_session(app, s, s + chrono::Duration::minutes(5), 0);
+            session.category_override = *category_override;
+            db.save_session(
+                &session,
+                session.end_time.unwrap(),
+                SessionIntegrity::default(),
+            )
+            .unwrap();
+        }
+
+        let page = |category: i64, offset: usize| {
+            db.query_sessions_flexible(
+                None,
+                None,
+                None,
+                None,
+                Some(category),
+                None,
+                2,
+                offset,
+                false,
+            )
+            .unwrap()
+        };
+
+        let (first, total) = page(2, 0);
+        assert_eq!(total, 3);
+        assert_eq!(first.len(), 2);
+        let (second, total) = page(2, 2);
+        assert_eq!(total, 3);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].process_name, "chrome.exe");
+        assert_eq!(second[0].category_override, Some(2));
+        assert!(page(2, 4).0.is_empty());
+
+        assert_eq!(page(5, 0).1, 2);
+        assert_eq!(page(3, 0).1, 1);
+        assert_eq!(page(1, 0).1, 0);
+
+        // SQL filter agrees with per-session resolution
+        for session in first.iter().chain(second.iter()) {
+            assert_eq!(db.get_category_for_session(session).unwrap().id, 2);
+        }
+    }
+
+    #[test]
+    fn test_category_stats_groups_by_resolved_category() {
+        use chrono::TimeZone;
+
+        let db = Database::open_in_memory().unwrap();
+        let gaming = db.create_category("Gaming", "#8B5CF6", Some("🕹️")).unwrap();
+        db.set_app_category("steam*.exe", gaming).unwrap();
+
+        let start = |h: u32| {
+            chrono::Local
+                .with_ymd_and_hms(2025, 1, 10, h, 0, 0)
+                .single()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let seed = |app, h, mins, keys, override_id: Option<i64>, idle| {
+            let mut session = test_session(
+                app,
+                start(h),
+                start(h) + chrono::Duration::minutes(mins),
+                keys,
+            );
+            session.category_override = override_id;
+            session.is_idle = idle;
+            let end = session.end_time.unwrap();
+            db.save_session(&session, end, SessionIntegrity::default())
+                .unwrap();
+        };
+
+        seed("steam.exe", 8, 60, 10, None, false);
+        seed("steamwebhelper.exe", 10, 30, 5, None, false);
+        seed("unmapped-app.exe", 11, 20, 7, None, false);
+        seed("unmapped-app.exe", 12, 15, 3, Some(gaming), false);
+        seed("steam.exe", 13, 90, 0, None, true);
+
+        let stats = db.get_category_stats("2025-01-10").unwrap();
+        assert_eq!(stats.len(), 2);
+
+        assert_eq!(stats[0].category.name, "Gaming");
+        assert_eq!(stats[0].category.icon.as_deref(), Some("🕹️"));
+        assert_eq!(stats[0].focus_secs, 105 * 60);
+        assert_eq!(stats[0].keystrokes, 18);
+        assert_eq!(stats[0].sessions, 3);
+
+        assert_eq!(stats[1].category.id, OTHER_CATEGORY_ID);
+        assert_eq!(stats[1].focus_secs, 20 * 60);
+        assert_eq!(stats[1].sessions, 1);
+
+        assert!(db.get_category_stats("2025-01-11").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_app_totals_include_category() {
+        use chrono::TimeZone;
+
+        let db = Database::open_in_memory().unwrap();
+        let gaming = db.create_category("Gaming", "#8B5CF6", None).unwrap();
+        db.set_app_category("steam*.exe", gaming).unwrap();
+
+        let start = |h: u32| {
+            chrono::Local
+                .with_ymd_and_hms(2025, 1, 10, h, 0, 0)
+                .single()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let seed = |app, h, mins, keys, idle| {
+            let mut session = test_session(
+                app,
+                start(h),
+                start(h) + chrono::Duration::minutes(mins),
+                keys,
+            );
+            session.is_idle = idle;
+            let end = session.end_time.unwrap();
+            db.save_session(&session, end, SessionIntegrity::default())
+                .unwrap();
+        };
+
+        seed("unmapped-app.exe", 8, 20, 50, false);
+        seed("steam.exe", 9, 30, 5, false);
+        seed("steam.exe", 10, 15, 2, false);
+        seed("unmapped-app.exe", 11, 120, 0, true);
+
+        let apps = db.get_app_totals("2025-01-10").unwrap();
+        assert_eq!(apps.len(), 2);
+
+        assert_eq!(apps[0].process_name, "steam.exe");
+        assert_eq!(apps[0].category.id, gaming);
+        assert_eq!(apps[0].focus_secs, 45 * 60);
+        assert_eq!(apps[0].keystrokes, 7);
+        assert_eq!(apps[0].sessions, 2);
+
+        assert_eq!(apps[1].process_name, "unmapped-app.exe");
+        assert_eq!(apps[1].category.id, OTHER_CATEGORY_ID);
+        assert_eq!(apps[1].focus_secs, 20 * 60);
+
+        assert!(db.get_app_totals("2025-01-11").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_monitor_stats_groups_by_monitor() {
+        use chrono::TimeZone;
+
+        let db = Database::open_in_memory().unwrap();
+        let start = |h: u32| {
+            chrono::Local
+                .with_ymd_and_hms(2025, 1, 10, h, 0, 0)
+                .single()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let seed = |monitor: Option<&str>, h, mins, keys, idle| {
+            let mut session = test_session(
+                "code.exe",
+                start(h),
+                start(h) + chrono::Duration::minutes(mins),
+                keys,
+            );
+            session.monitor = monitor.map(str::to_string);
+            session.is_idle = idle;
+            let end = session.end_time.unwrap();
+            db.save_session(&session, end, SessionIntegrity::default())
+                .unwrap();
+        };
+
+        seed(Some(r"\\.\DISPLAY1"), 8, 30, 10, false);
+        seed(Some(r"\\.\DISPLAY2"), 9, 45, 4, false);
+        seed(Some(r"\\.\DISPLAY1"), 10, 20, 6, false);
+        seed(Some(r"\\.\DISPLAY2"), 11, 120, 0, true);
+        seed(None, 12, 5, 1, false);
+
+        let stats = db.get_monitor_stats("2025-01-10").unwrap();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[0].monitor.as_deref(), Some(r"\\.\DISPLAY1"));
+        assert_eq!(stats[0].focus_secs, 50 * 60);
+        assert_eq!(stats[0].keystrokes, 16);
+        assert_eq!(stats[0].sessions, 2);
+        assert_eq!(stats[1].monitor.as_deref(), Some(r"\\.\DISPLAY2"));
+        assert_eq!(stats[1].focus_secs, 45 * 60);
+        assert_eq!(stats[2].monitor, None);
+
+        assert!(db.get_monitor_stats("2025-01-11").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_category_crud_reassigns_apps_on_delete() {
+        let db = Database::open_in_memory().unwrap();
+
+        let id = db.create_category("Gaming", "#8B5CF6", Some("🕹️")).unwrap();
+        assert!(db.create_category("Gaming", "#000000", None).is_err());
+        db.set_app_category("steam.exe", id).unwrap();
+
+        assert!(db.update_category(id, "Games", "#7C3AED", None).unwrap());
+        let category = db.get_category(id).unwrap().unwrap();
+        assert_eq!((category.name.as_str(), category.icon), ("Games", None));
+        assert!(!category.distracting);
+        assert!(db.set_category_distracting(id, true).unwrap());
+        assert!(db.get_category_for_app("steam.exe").unwrap().distracting);
+        assert!(!db.update_category(9999, "Nope", "#000000", None).unwrap());
+
+        assert!(!db.delete_category(OTHER_CATEGORY_ID).unwrap());
+        assert!(db.delete_category(id).unwrap());
+        assert!(db.get_category(id).unwrap().is_none());
+        assert_eq!(
+            db.get_category_for_app("steam.exe").unwrap().id,
+            OTHER_CATEGORY_ID
+        );
+    }
+
+    #[test]
+    fn test_assign_and_remove_wildcard_app_category() {
+        let db = Database::open_in_memory().unwrap();
+        let entertainment = db
+            .get_categories()
+            .unwrap()
+            .into_iter()
+            .find(|c| c.name == "Entertainment")
+            .unwrap();
+
+        db.set_app_category("*Twitch*", entertainment.id).unwrap();
+        assert_eq!(
+            db.get_category_for_app("Twitch Studio.exe").unwrap().id,
+            entertainment.id
+        );
+
+        assert!(db.remove_app_category("*Twitch*").unwrap());
+        assert!(!db.remove_app_category("*Twitch*").unwrap());
+        assert_eq!(
+            db.get_category_for_app("Twitch Studio.exe").unwrap().id,
+            OTHER_CATEGORY_ID
+        );
+    }
+
+    #[test]
+    fn test_get_category_apps() {
+        let db = Database::open_in_memory().unwrap();
+        db.set_app_category("Obsidian.exe", 2).unwrap();
+
+        let categories = db.get_category_apps().unwrap();
+        assert_eq!(categories.len(), db.get_categories().unwrap().len());
+
+        let apps_for = |name: &str| {
+            categories
+                .iter()
+                .find(|c| c.category.name == name)
+                .map(|c| c.apps.clone())
+                .unwrap()
+        };
+        let work = apps_for("Work");
+        assert!(work.contains(&"code.exe".to_string()));
+        assert!(work.contains(&"Obsidian.exe".to_string()));
+        assert!(!work.contains(&"chrome.exe".to_string()));
+        assert!(apps_for("Browser").contains(&"chrome.exe".to_string()));
+        assert!(apps_for("Other").is_empty());
+    }
+
+    #[test]
+    fn test_query_limits_from_config() {
+        let db = Database::open_in_memory().unwrap();
+        assert_eq!(db.query_limits(), QueryLimits::default());
+
+        let start = Utc::now() - chrono::Duration::hours(6);
+        for i in 0..5 {
+            let s = start + chrono::Duration::minutes(i * 10);
+            let e = s + chrono::Duration::minutes(5);
+            db.save_session(
+                &test_session("code.exe", s, e, 1),
+                e,
+                SessionIntegrity::default(),
+            )
+            .unwrap();
+        }
+
+        // Configured default is used when no limit is requested
+        db.set_config("default_session_limit", "3").unwrap();
+        let limit = db.query_limits().resolve(None);
+        assert_eq!(limit, 3);
+        let (sessions, total) = db
+            .query_sessions_flexible(None, None, None, None, None, None, limit, 0, true)
+            .unwrap();
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(total, 5);
+
+        // The max cap clamps both requested limits and direct queries
+        db.set_config("max_session_limit", "2").unwrap();
+        assert_eq!(db.query_limits().resolve(Some(100)), 2);
+        assert_eq!(db.query_limits().resolve(None), 2);
+        let (sessions, _) = db
+            .query_sessions_flexible(None, None, None, None, None, None, 100, 0, true)
+            .unwrap();
+        assert_eq!(sessions.len(), 2);
+
+        // Full-day reads page past the cap
+        let date = start.format("%Y-%m-%d").to_string();
+        let day_count = db
+            .query_sessions_flexible(Some(&date), None, None, None, None, None, 1, 0, false)
+            .unwrap()
+            .1;
+        assert_eq!(
+            db.get_all_sessions_for_date(&date).unwrap().len() as i64,
+            day_count
+        );
+    }
+
+    #[test]
+    fn test_redaction_patterns() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(db.get_redaction_patterns().unwrap().is_empty());
+
+        let entry = db.add_redaction_pattern(r"\d{4}").unwrap();
+        // Adding the same pattern again returns the existing entry
+        assert_eq!(db.add_redaction_pattern(r"\d{4}").unwrap(), entry);
+        assert_eq!(db.get_redaction_patterns().unwrap(), vec![entry.clone()]);
+
+        assert!(db.remove_redaction_pattern(entry.id).unwrap());
+        assert!(!db.remove_redaction_pattern(entry.id).unwrap());
+    }
+
+    #[test]
+    fn test_top_media_counts_paused_tracks_once() {
+        use chrono::TimeZone;
+
+        let db = Database::open_in_memory().unwrap();
+        let at = |h: u32, m: u32| {
+            chrono::Local
+                .with_ymd_and_hms(2025, 1, 10, h, m, 0)
+                .single()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let play = |title: &str, artist: &str, start, mins| {
+            db.save_media(
+                title,
+                artist,
+                "Album",
+                "Spotify.exe",
+                start,
+                start + chrono::Duration::minutes(mins),
+                None,
+            )
+            .unwrap();
+        };
+
+        // Paused for two minutes, then resumed: one play
+        play("Song A", "Band", at(9, 0), 2);
+        play("Song A", "Band", at(9, 4), 2);
+        // Saved twice: still one play
+        play("Song B", "Band", at(9, 10), 3);
+        play("Song B", "Band", at(9, 10), 3);
+        // Played again an hour later: a second play
+        play("Song A", "Band", at(10, 30), 4);
+        play("Solo", "Singer", at(11, 0), 20);
+        play("Podcast", "", at(12, 0), 30);
+
+        let tracks = db.get_top_tracks("2025-01-10", "2025-01-10", 10).unwrap();
+        assert_eq!(tracks[0].title, "Podcast");
+        assert_eq!(tracks[1].title, "Solo");
+        let song_a = tracks.iter().find(|t| t.title == "Song A").unwrap();
+        assert_eq!(song_a.play_count, 2);
+        assert_eq!(song_a.listen_secs, 8 * 60);
+        let song_b = tracks.iter().find(|t| t.title == "Song B").unwrap();
+        assert_eq!(song_b.play_count, 1);
+        assert_eq!(song_b.listen_secs, 3 * 60);
+
+        let artists = db.get_top_artists("2025-01-10", "2025-01-10", 10).unwrap();
+        assert_eq!(artists.len(), 2);
+        assert_eq!(artists[0].artist, "Singer");
+        assert_eq!(artists[1].artist, "Band");
+        assert_eq!(artists[1].play_count, 3);
+        assert_eq!(artists[1].track_count, 2);
+        assert_eq!(artists[1].listen_secs, 11 * 60);
+
+        assert_eq!(
+            db.get_top_tracks("2025-01-10", "2025-01-10", 1)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(db
+            .get_top_artists("2025-01-11", "2025-01-17", 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_top_tracks_group_normalized_titles() {
+        let db = Database::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::hours(2);
+        let play = |title: &str, start: DateTime<Utc>| {
+            db.save_media(
+                title,
+                "Band",
+                "Album",
+                "Spotify.exe",
+                start,
+                start + chrono::Duration::minutes(3),
+                None,
+            )
+            .unwrap();
+        };
+        play("Song A", start);
+        // Resumed under the remastered title
+        play(
+            "Song A - Remastered 2011",
+            start + chrono::Duration::minutes(4),
+        );
+        play("Song A (Live)", start + chrono::Duration::minutes(60));
+        let today = crate::store::local_date_string();
+        let day = start
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d")
+            .to_string();
+
+        // Off by default: each title is its own track
+        assert_eq!(db.get_top_tracks(&day, &today, 10).unwrap().len(), 3);
+
+        db.set_config("normalize_media_titles", "true").unwrap();
+        let tracks = db.get_top_tracks(&day, &today, 10).unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].title, "Song A");
+        assert_eq!(tracks[0].play_count, 2);
+        assert_eq!(tracks[0].listen_secs, 9 * 60);
+    }
+
+    #[test]
+    fn test_export_records_merge_by_start_time() {
+        let db = Database::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::hours(1);
+        for i in 0..3 {
+            let at = start + chrono::Duration::minutes(i * 10);
+            let id = db
+                .save_session(
+                    &test_session("code.exe", at, at + chrono::Duration::minutes(5), 10),
+                    at + chrono::Duration::minutes(5),
+                    SessionIntegrity {
+                        hash: Some("abc"),
+                        signature: Some("sig"),
+                        prev_hash: None,
+                    },
+                )
+                .unwrap();
+            if i == 0 {
+                db.add_session_tag(id, "review").unwrap();
+                db.add_session_tag(id, "deep-work").unwrap();
+            }
+            let at = at + chrono::Duration::minutes(5);
+            db.save_media(
+                "Song",
+                "Artist",
+                "Album",
+                "Spotify.exe",
+                at,
+                at + chrono::Duration::minutes(3),
+                None,
+            )
+            .unwrap();
+        }
+
+        // Read two records per page until the stream ends
+        let mut lines = Vec::new();
+        let mut cursor = ExportCursor::default();
+        while let Some(next) = db
+            .for_each_export_record(&cursor, 2, |record| {
+                lines.push(serde_json::to_value(record).unwrap());
+            })
+            .unwrap()
+        {
+            cursor = next;
+        }
+
+        let types: Vec<_> = lines.iter().map(|l| l["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            types,
+            ["session", "media", "session", "media", "session", "media"]
+        );
+        assert_eq!(lines[0]["hash"], "abc");
+        assert_eq!(lines[0]["signature"], "sig");
+        assert_eq!(lines[0]["tags"], serde_json::json!(["deep-work", "review"]));
+        assert_eq!(lines[2]["tags"], serde_json::json!([]));
+        assert_eq!(lines[1]["title"], "Song");
+    }
+
+    #[test]
+    fn test_switch_count_and_focus_streaks() {
+        let db = Database::open_in_memory().unwrap();
+        // Start at local noon so every session falls on the same date
+        let policy = timezone_policy();
+        let date = policy.date_of(Utc::now());
+        let mut at = policy.day_start(date) + chrono::Duration::hours(12);
+        let date = date.to_string();
+        for (minutes, is_idle) in [(5, false), (30, false), (20, true), (12, false)] {
+            let end = at + chrono::Duration::minutes(minutes);
+            let mut session = test_session("code.exe", at, end, 10);
+            session.is_idle = is_idle;
+            db.save_session(&session, end, SessionIntegrity::default())
+                .unwrap();
+            at = end;
+        }
+
+        assert_eq!(db.get_switch_count(&date).unwrap(), 3);
+
+        let streaks = db.get_focus_streaks(&date, 2).unwrap();
+        let durations: Vec<_> = streaks.iter().map(|s| s.duration_secs).collect();
+        assert_eq!(durations, [30 * 60, 12 * 60]);
+    }
+
+    #[test]
+    fn test_prune_older_than() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+        let old = now - chrono::Duration::days(40);
+        for start in [old, now - chrono::Duration::minutes(30)] {
+            db.save_session(
+                &test_session("code.exe", start, start + chrono::Duration::minutes(5), 10),
+                start + chrono::Duration::minutes(5),
+                SessionIntegrity::default(),
+            )
+            .unwrap();
+            db.save_media(
+                "Song",
+                "Artist",
+                "Album",
+                "Spotify.exe",
+                start,
+                start + chrono::Duration::minutes(3),
+                None,
+            )
+            .unwrap();
+        }
+
+        // 0 keeps everything
+        assert_eq!(db.prune_sessions_older_than(0).unwrap(), 0);
+        assert_eq!(db.prune_media_older_than(0).unwrap(), 0);
+
+        assert_eq!(db.prune_sessions_older_than(30).unwrap(), 1);
+        assert_eq!(db.prune_media_older_than(30).unwrap(), 1);
+        db.vacuum().unwrap();
+
+        let stats = db.get_storage_stats().unwrap();
+        assert_eq!(stats.sessions, 1);
+        assert_eq!(stats.media, 1);
+    }
+
+    #[test]
+    fn test_storage_stats_row_counts() {
+        let db = Database::open_in_memory().unwrap();
+
+        let empty = db.get_storage_stats().unwrap();
+        assert_eq!(empty.sessions, 0);
+        assert_eq!(empty.media, 0);
+        assert_eq!(empty.oldest_record, None);
+        assert_eq!(empty.db_path, None);
+
+        let start = Utc::now() - chrono::Duration::days(10);
+        for i in 0..3 {
+            let s = start + chrono::Duration::hours(i);
+            let e = s + chrono::Duration::minutes(30);
+            db.save_session(
+                &test_session("code.exe", s, e, 10),
+                e,
+                SessionIntegrity::default(),
+            )
+            .unwrap();
+        }
+        let media_start = start - chrono::Duration::days(1);
+        db.save_media(
+            "Song",
+            "Artist",
+            "Album",
+            "Spotify.exe",
+            media_start,
+            media_start + chrono::Duration::minutes(3),
+            None,
+        )
+        .unwrap();
+
+        let stats = db.get_storage_stats().unwrap();
+        assert_eq!(stats.sessions, 3);
+        assert_eq!(stats.media, 1);
+        assert_eq!(stats.oldest_record, Some(media_start.to_rfc3339()));
+        assert!(stats.data_size_bytes > 0);
+        assert!(stats.bytes_per_day.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_migrates_v1_database_forward() {
+        // The original (v1) schema, as shipped before versioning
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                process_name TEXT NOT NULL,
+                window_title TEXT,
+                start_time TEXT NOT NULL,
+                end_time TEXT,
+                keystrokes INTEGER DEFAULT 0,
+                clicks INTEGER DEFAULT 0,
+                scrolls INTEGER DEFAULT 0,
+                is_idle BOOLEAN DEFAULT 0,
+                hash TEXT,
+                signature TEXT,
+                prev_hash TEXT
+            );
+            CREATE TABLE media (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                title TEXT NOT NULL,
+                artist TEXT,
+                album TEXT,
+                source_app TEXT,
+                start_time TEXT NOT NULL,
+                end_time TEXT,
+                duration_secs INTEGER DEFAULT 0
+            );
+            CREATE TABLE daily_integrity (
+                date TEXT PRIMARY KEY,
+                merkle_root TEXT NOT NULL,
+                prev_day_root TEXT,
+                session_count INTEGER NOT NULL,
+                signature TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+            INSERT INTO sessions (process_name, window_title, start_time, end_time, keystrokes)
+            VALUES ('code.exe', 'main.rs', '2025-01-15T12:00:00+00:00', '2025-01-15T12:30:00+00:00', 42);
+            PRAGMA user_version = 1;
+            "#,
+        )
+        .unwrap();
+
+        let db = Database {
+            conn: Arc::new(Mutex::new(conn)),
+        };
+        db.init_schema().unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+
+        // Old rows read back with defaults for the new columns
+        let sessions = db.get_all_sessions_for_date("2025-01-15").unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].keystrokes, 42);
+        assert_eq!(sessions[0].mouse_distance_px, 0);
+        assert_eq!(sessions[0].url, None);
+        assert_eq!(sessions[0].process_path, None);
+        assert_eq!(sessions[0].monitor, None);
+        assert_eq!(sessions[0].idle_reason, None);
+
+        // New columns are writable
+        let start = Utc::now() - chrono::Duration::hours(1);
+        let mut session = test_session("chrome.exe", start, start, 5);
+        session.url = Some("example.com".to_string());
+        session.process_path = Some(r"C:\Apps\chrome.exe".to_string());
+        session.monitor = Some(r"\\.\DISPLAY2".to_string());
+        session.idle_reason = Some(IdleReason::Locked);
+        session.double_clicks = 2;
+        db.save_session(&session, Utc::now(), SessionIntegrity::default())
+            .unwrap();
+
+        // Re-running is a no-op
+        db.init_schema().unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_weekly_stats_group_by_monday() {
+        use chrono::Datelike;
+
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+        for (days_ago, keys) in [(14, 10), (21, 20), (70, 999)] {
+            let start = now - chrono::Duration::days(days_ago);
+            let end = start + chrono::Duration::minutes(30);
+            db.save_session(
+                &test_session("code.exe", start, end, keys),
+                end,
+                SessionIntegrity::default(),
+            )
+            .unwrap();
+        }
+
+        let weeks = db.get_weekly_stats(8).unwrap();
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[0].keystrokes, 20);
+        assert_eq!(weeks[1].keystrokes, 10);
+        assert_eq!(weeks[1].focus_secs, 1800);
+
+        let first = NaiveDate::parse_from_str(&weeks[0].week_start, "%Y-%m-%d").unwrap();
+        let second = NaiveDate::parse_from_str(&weeks[1].week_start, "%Y-%m-%d").unwrap();
+        assert_eq!(first.weekday(), chrono::Weekday::Mon);
+        assert_eq!((second - first).num_days(), 7);
+
+        assert_eq!(db.get_weekly_stats(12).unwrap().len(), 3);
+        assert!(db.get_weekly_stats(i32::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_stats_by_weekday_buckets() {
+        use chrono::TimeZone;
+
+        let db = Database::open_in_memory().unwrap();
+        let local = |d: u32, h: u32, m: u32| {
+            chrono::Local
+                .with_ymd_and_hms(2025, 1, d, h, m, 0)
+                .single()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let seed = |start, end, keys| {
+            db.save_session(
+                &test_session("code.exe", start, end, keys),
+                end,
+                SessionIntegrity::default(),
+            )
+            .unwrap();
+        };
+
+        // Monday Jan 13, 2025
+        seed(local(13, 10, 0), local(13, 11, 0), 100);
+        // Wednesdays Jan 15 and Jan 22
+        seed(local(15, 9, 0), local(15, 9, 30), 20);
+        seed(local(22, 9, 0), local(22, 10, 0), 30);
+        // Late Sunday evening (local) Jan 19
+        seed(local(19, 23, 30), local(19, 23, 45), 5);
+        // Outside the range
+        seed(local(27, 10, 0), local(27, 11, 0), 999);
+
+        let buckets = db.stats_by_weekday("2025-01-13", "2025-01-26").unwrap();
+        assert_eq!(buckets.len(), 7);
+
+        assert_eq!(buckets[0].name, "Sunday");
+        assert_eq!(buckets[0].sessions, 1);
+        assert_eq!(buckets[0].keystrokes, 5);
+        assert_eq!(buckets[0].focus_secs, 900);
+
+        assert_eq!(buckets[1].name, "Monday");
+        assert_eq!(buckets[1].keystrokes, 100);
+        assert_eq!(buckets[1].focus_secs, 3600);
+
+        assert_eq!(buckets[3].name, "Wednesday");
+        assert_eq!(buckets[3].sessions, 2);
+        assert_eq!(buckets[3].keystrokes, 50);
+        assert_eq!(buckets[3].focus_secs, 5400);
+
+        for i in [2, 4, 5, 6] {
+            assert_eq!(buckets[i].sessions, 0);
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_wal_truncates_log() {
+        let dir = std::env::temp_dir().join(format!("ownmon-wal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let conn = Connection::open(dir.join("activity.db")).unwrap();
+        conn.pragma_update(None, "journal_mode", "WAL").unwrap();
+        let db = Database {
+            conn: Arc::new(Mutex::new(conn)),
+        };
+        db.init_schema().unwrap();
+
+        let start = Utc::now();
+        let end = start + chrono::Duration::minutes(5);
+        db.save_session(
+            &test_session("code.exe", start, end, 1),
+            end,
+            SessionIntegrity::default(),
+        )
+        .unwrap();
+        assert!(db.wal_size_bytes() > 0);
+
+        assert!(db.checkpoint_wal().unwrap());
+        assert_eq!(db.wal_size_bytes(), 0);
+        assert_eq!(db.get_storage_stats().unwrap().sessions, 1);
+
+        drop(db);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_typing_samples_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+        let minute = |m: i64| {
+            DateTime::parse_from_rfc3339("2025-01-15T10:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc)
+                + chrono::Duration::minutes(m)
+        };
+        let sample = |m, keystrokes| TypingSample {
+            minute: minute(m),
+            keystrokes,
+        };
+
+        db.save_typing_samples(&[sample(0, 30), sample(1, 45), sample(5, 12)])
+            .unwrap();
+        // A minute split by a restart adds up
+        db.save_typing_samples(&[sample(1, 5)]).unwrap();
+
+        assert_eq!(
+            db.get_typing_samples(minute(0), minute(5)).unwrap(),
+            vec![sample(0, 30), sample(1, 50)]
+        );
+        assert_eq!(
+            db.get_typing_samples(minute(5), minute(60)).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_app_history_queries() {
+        use chrono::TimeZone;
+
+        let db = Database::open_in_memory().unwrap();
+        let local = |d: u32, h: u32| {
+            chrono::Local
+                .with_ymd_and_hms(2025, 1, d, h, 0, 0)
+                .single()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let seed = |app, d, h, mins| {
+            let start = local(d, h);
+            let end = start + chrono::Duration::minutes(mins);
+            db.save_session(
+                &test_session(app, start, end, 0),
+                end,
+                SessionIntegrity::default(),
+            )
+            .unwrap();
+        };
+
+        seed("Duolingo.exe", 10, 8, 10);
+        seed("duolingo.exe", 10, 20, 5);
+        seed("Duolingo.exe", 12, 8, 15);
+        seed("code.exe", 11, 9, 60);
+
+        assert_eq!(
+            db.get_app_first_seen("DUOLINGO.EXE").unwrap(),
+            Some(local(10, 8).to_rfc3339())
+        );
+        assert_eq!(db.get_app_first_seen("missing.exe").unwrap(), None);
+
+        let usage = db
+            .get_app_daily_usage("Duolingo.exe", "2025-01-01", "2025-01-11")
+            .unwrap();
+        assert_eq!(
+            usage,
+            vec![AppDailyUsage {
+                date: "2025-01-10".to_string(),
+                focus_secs: 900,
+                sessions: 2,
+            }]
+        );
+
+        assert_eq!(
+            db.get_app_usage_dates("Duolingo.exe").unwrap(),
+            vec!["2025-01-12".to_string(), "2025-01-10".to_string()]
+        );
+        assert_eq!(
+            db.get_app_lifetime_totals("Duolingo.exe").unwrap(),
+            (1800, 3)
+        );
+
+        let apps = db.get_distinct_apps(None, None).unwrap();
+        let names: Vec<&str> = apps.iter().map(|a| a.process_name.as_str()).collect();
+        assert_eq!(names, vec!["code.exe", "Duolingo.exe", "duolingo.exe"]);
+        assert_eq!(apps[1].session_count, 2);
+        assert_eq!(
+            apps[1].last_seen,
+            (local(12, 8) + chrono::Duration::minutes(15)).to_rfc3339()
+        );
+
+        let apps = db
+            .get_distinct_apps(Some("2025-01-11"), Some("2025-01-11"))
+            .unwrap();
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].process_name, "code.exe");
+        assert_eq!(
+            db.get_distinct_apps(Some("2025-01-12"), None).unwrap()[0].session_count,
+            1
+        );
+    }
+
+    #[test]
+    fn test_pace_over_seeded_history() {
+        use chrono::TimeZone;
+
+        let db = Database::open_in_memory().unwrap();
+        let local = |d: u32, h: u32, m: u32| {
+            chrono::Local
+                .with_ymd_and_hms(2025, 1, d, h, m, 0)
+                .single()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let seed = |start, end, keys| {
+            db.save_session(
+                &test_session("code.exe", start, end, keys),
+                end,
+                SessionIntegrity::default(),
+            )
+            .unwrap();
+        };
+
+        // Day 1: one full hour, one session clipped at noon, one after noon
+        seed(local(13, 9, 0), local(13, 10, 0), 100);
+        seed(local(13, 11, 30), local(13, 12, 30), 50);
+        seed(local(13, 14, 0), local(13, 15, 0), 999);
+        // Day 2: one full hour
+        seed(local(14, 10, 0), local(14, 11, 0), 200);
+        // Today: 2.5 hours before noon
+        seed(local(15, 9, 0), local(15, 11, 30), 350);
+
+        let cutoff = 12 * 3600;
+        let history = db
+            .get_totals_until_time_of_day("2025-01-01", "2025-01-14", cutoff)
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].focus_secs, 5400);
+        assert_eq!(history[0].keystrokes, 150);
+        assert_eq!(history[1].focus_secs, 3600);
+        assert_eq!(history[1].keystrokes, 200);
+
+        let today = db
+            .get_totals_until_time_of_day("2025-01-15", "2025-01-15", cutoff)
+            .unwrap();
+        assert_eq!(today.len(), 1);
+
+        let pace = crate::store::compute_pace(today[0].focus_secs, today[0].keystrokes, &history);
+        assert_eq!(pace.avg_focus_secs, 4500.0);
+        assert_eq!(pace.focus_ratio, Some(2.0));
+        assert_eq!(pace.keystrokes_ratio, Some(2.0));
+    }
+
+    #[test]
+    fn test_run_summary_persisted_and_fetchable() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(db.get_last_run_summary().unwrap().is_none());
+
+        let mut store = crate::store::ActivityStore::new();
+        store.switch_session(1, 100, "code.exe", "main.rs");
+        store.add_input_counts(42, 7, 0);
+
+        let summary = store.get_run_summary(5);
+        db.save_run_summary(&summary).unwrap();
+
+        let loaded = db.get_last_run_summary().unwrap().unwrap();
+        assert_eq!(loaded.ended_at, summary.ended_at);
+        assert_eq!(loaded.summary.total_keystrokes, 42);
+        assert_eq!(loaded.summary.session_count, 1);
+        assert_eq!(loaded.top_apps[0].process_name, "code.exe");
+    }
+
+    #[test]
+    fn test_blacklist_add_get_remove() {
+        let db = Database::open_in_memory().unwrap();
+
+        let id = db.add_to_blacklist("Steam*.exe", Some("Games")).unwrap();
+        let entry = db.get_blacklist_entry("Steam*.exe").unwrap().unwrap();
+        assert_eq!(entry.id, id);
+        assert_eq!(entry.description.as_deref(), Some("Games"));
+        assert!(db.is_blacklisted("steamwebhelper.exe"));
+
+        assert!(db.remove_from_blacklist("Steam*.exe").unwrap());
+        assert!(!db.remove_from_blacklist("Steam*.exe").unwrap());
+        assert!(db.get_blacklist_entry("Steam*.exe").unwrap().is_none());
+        assert!(!db.is_blacklisted("steamwebhelper.exe"));
+    }
+
+    #[test]
+    fn test_idle_threshold_for_app() {
+        let db = Database::open_in_memory().unwrap();
+        assert_eq!(db.get_idle_threshold_for_app("vlc.exe"), 300);
+
+        db.set_config("afk_threshold_secs", "120").unwrap();
+        db.set_idle_override("*player*", 3600).unwrap();
+        db.set_idle_override("VLC.exe", 86400).unwrap();
+
+        // Exact match wins over the wildcard, case-insensitively
+        assert_eq!(db.get_idle_threshold_for_app("vlc.exe"), 86400);
+        assert_eq!(db.get_idle_threshold_for_app("mpc-player.exe"), 3600);
+        assert_eq!(db.get_idle_threshold_for_app("code.exe"), 120);
+
+        assert!(db.remove_idle_override("VLC.exe").unwrap());
+        assert!(!db.remove_idle_override("VLC.exe").unwrap());
+        assert_eq!(db.get_idle_threshold_for_app("vlc.exe"), 120);
+        assert_eq!(db.get_idle_overrides().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_goals() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(db.get_goals().unwrap().is_empty());
+
+        let goal = db.add_goal("keystrokes", 10000, "daily").unwrap();
+        db.add_goal("focus_time_secs", 4 * 3600, "daily").unwrap();
+        assert_eq!(db.get_goals().unwrap()[0], goal);
+
+        assert!(db.delete_goal(goal.id).unwrap());
+        assert!(!db.delete_goal(goal.id).unwrap());
+        assert_eq!(db.get_goals().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_is_privacy_app() {
+        let db = Database::open_in_memory().unwrap();
+
+        // Off by default
+        assert!(!db.is_privacy_app("KeePassXC.exe"));
+
+        db.set_config("privacy_patterns", "KeePass*, 1Password.exe")
+            .unwrap();
+        assert!(db.is_privacy_app("KeePassXC.exe"));
+        assert!(db.is_privacy_app("1password.exe"));
+        assert!(!db.is_privacy_app("chrome.exe"));
+    }
+
+    #[test]
+    fn test_is_dashboard_window() {
+        let db = Database::open_in_memory().unwrap();
+
+        assert!(db.is_dashboard_window("chrome.exe", "OwnMon - Google Chrome"));
+        assert!(
+            db.is_dashboard_window("firefox.exe", "localhost:13234/api/stats — Mozilla Firefox")
+        );
+        // Other pages, and the dashboard URL outside a browser, don't match
+        assert!(!db.is_dashboard_window("chrome.exe", "Inbox - Google Chrome"));
+        assert!(!db.is_dashboard_window("WindowsTerminal.exe", "curl localhost:13234/api/stats"));
+
+        db.set_config("dashboard_title_patterns", "My Stats*")
+            .unwrap();
+        assert!(db.is_dashboard_window("msedge.exe", "My Stats - Microsoft Edge"));
+        assert!(!db.is_dashboard_window("msedge.exe", "OwnMon - Microsoft Edge"));
+    }
+
+    #[test]
+    fn test_wildcard_to_like_escaping() {
+        assert_eq!(
+            wildcard_to_like("*YouTube*"),
+            ("%YouTube%".to_string(), '\\')
+        );
+        assert_eq!(wildcard_to_like("app?.exe").0, "app_.exe");
+        assert_eq!(wildcard_to_like("100%*").0, "100\\%%");
+        assert_eq!(wildcard_to_like("my_app*").0, "my\\_app%");
+        assert_eq!(wildcard_to_like("O'Brien*").0, "O''Brien%");
+        assert_eq!(wildcard_to_like("a\\b*").0, "a\\\\b%");
+    }
+
+    #[test]
+    fn test_query_sessions_with_special_characters() {
+        let db = Database::open_in_memory().unwrap();
+        let start = Utc::now();
+        let end = start + chrono::Duration::seconds(60);
+
+        for name in [
+            "100%.exe",
+            "1000.exe",
+            "my_app.exe",
+            "myxapp.exe",
+            "O'Brien.exe",
+        ] {
+            db.save_session(
+                &test_session(name, start, end, 0),
+                end,
+                SessionIntegrity::default(),
+            )
+            .unwrap();
+        }
+
+        let query = |app: &str| {
+            let (sessions, total) = db
+                .query_sessions_flexible(None, None, None, Some(app), None, None, 100, 0, false)
+                .unwrap();
+            assert_eq!(sessions.len() as i64, total);
+            sessions
+                .into_iter()
+                .map(|s| s.process_name)
+                .collect::<Vec<_>>()
+        };
+
+        // `%` and `_` in user input are literal, not wildcards
+        assert_eq!(query("100%*"), vec!["100%.exe"]);
+        assert_eq!(query("my_app*"), vec!["my_app.exe"]);
+
+        // Single quotes don't break the query (exact and wildcard)
+        assert_eq!(query("O'Brien.exe"), vec!["O'Brien.exe"]);
+        assert_eq!(query("O'B*"), vec!["O'Brien.exe"]);
+
+        // `?` still matches a single character
+        assert_eq!(query("my?app.exe").len(), 2);
     }
 }
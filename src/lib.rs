@@ -1,8 +1,14 @@
+pub mod archive;
+pub mod autostart;
+pub mod backup;
 pub mod crypto;
 pub mod database;
 pub mod media;
 pub mod monitor;
+pub mod report;
+pub mod rules;
 pub mod server;
 pub mod store;
 pub mod tray;
+pub mod verify;
 pub mod winapi_utils;
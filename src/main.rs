@@ -6,7 +6,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use ownmon::monitor::*;
-use ownmon::store::ACTIVITY_STORE;
+use ownmon::store::{format_count, format_duration, ACTIVITY_STORE};
 use ownmon::tray::setup_tray;
 use ownmon::winapi_utils::*;
 use std::path::PathBuf;
@@ -16,15 +16,82 @@ use std::thread;
 use std::time::Duration;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // --data-dir applies to every mode below, like OWNMON_DATA_DIR
+    let args = match ownmon::database::take_data_dir_arg(std::env::args().skip(1)) {
+        Ok((data_dir, rest)) => {
+            if let Some(dir) = data_dir {
+                std::env::set_var(ownmon::database::DATA_DIR_ENV, dir);
+            }
+            rest
+        }
+        Err(e) => {
+            attach_parent_console();
+            eprintln!("Error: {}", e);
+            eprintln!("Usage: ownmon [--data-dir <path>]");
+            std::process::exit(2);
+        }
+    };
+
+    // Command-line modes run before the single-instance lock and tray setup
+    match ownmon::verify::VerifyOptions::parse(args.clone()) {
+        Ok(Some(options)) => {
+            attach_parent_console();
+            let code = match ownmon::verify::run(&options) {
+                Ok(true) => 0,
+                Ok(false) => 1,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    2
+                }
+            };
+            std::process::exit(code);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            attach_parent_console();
+            eprintln!("Error: {}", e);
+            eprintln!("Usage: ownmon --verify [--db <path>] [--pubkey <path>]");
+            std::process::exit(2);
+        }
+    }
+
+    // Stage a backup to replace the database on the next start
+    match ownmon::backup::parse_restore_arg(args) {
+        Ok(Some(path)) => {
+            attach_parent_console();
+            let data_dir = ownmon::database::Database::data_dir();
+            match ownmon::backup::stage_restore(&path, &data_dir) {
+                Ok(_) => {
+                    println!(
+                        "Backup {} will be restored the next time OwnMon starts.",
+                        path.display()
+                    );
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(2);
+                }
+            }
+        }
+        Ok(None) => {}
+        Err(e) => {
+            attach_parent_console();
+            eprintln!("Error: {}", e);
+            eprintln!("Usage: ownmon --restore <backup.db>");
+            std::process::exit(2);
+        }
+    }
+
     // Check for single instance using lock file with PID validation
     use std::fs::{self, OpenOptions};
     use std::io::{Read, Write};
 
-    // Create lock file path in APPDATA
-    let lock_path: PathBuf = match std::env::var("APPDATA") {
-        Ok(appdata) => PathBuf::from(appdata).join("ownmon").join("ownmon.lock"),
-        Err(_) => PathBuf::from(".").join("ownmon.lock"),
-    };
+    // Lock file lives in the data directory, so each data directory can
+    // have its own running instance
+    let data_dir = ownmon::database::Database::data_dir();
+    fs::create_dir_all(&data_dir)?;
+    let lock_path: PathBuf = data_dir.join("ownmon.lock");
 
     // Check if lock file exists and validate PID
     if lock_path.exists() {
@@ -70,6 +137,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     run_application(_lock_guard)
 }
 
+/// Attaches to the console of the shell that started us, so CLI output is
+/// visible from release builds (which use the windows subsystem).
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
 /// Check if a process with given PID is running
 fn is_process_running(pid: u32) -> bool {
     use windows::Win32::Foundation::CloseHandle;
@@ -109,23 +185,56 @@ fn run_application(_lock: LockFileGuard) -> Result<(), Box<dyn std::error::Error
     println!("╚════════════════════════════════════════════════════════════╝");
     println!();
 
+    // Swap in a backup staged with --restore (before the database is opened)
+    let data_dir = ownmon::database::Database::data_dir();
+    match ownmon::backup::apply_pending_restore(
+        &data_dir,
+        &ownmon::database::Database::default_path(),
+        &ownmon::backup::backups_dir(),
+    ) {
+        Ok(true) => println!("   ✓ Database restored from backup"),
+        Ok(false) => {}
+        Err(e) => tracing::error!(error = %e, "Failed to restore database backup"),
+    }
+
+    // Record the start time reported by /health
+    let _ = &*ownmon::store::STARTED_AT;
+
     // Initialize database early
     println!("🔧 Initializing database...");
     let _ = &*ownmon::store::DATABASE; // Trigger lazy init
     println!("   ✓ Database ready");
 
+    // Group remastered/live variants of a track, if enabled
+    if let Ok(mut store) = ACTIVITY_STORE.write() {
+        store.set_media_title_normalizer(ownmon::media::load_title_normalizer());
+
+        // Idle detection starts from the user's real last input, not from now
+        if let Some(idle) =
+            get_idle_duration().and_then(|idle| chrono::Duration::from_std(idle).ok())
+        {
+            store.seed_last_input(chrono::Utc::now() - idle);
+        }
+    }
+
+    // Restore counts of a session interrupted by a crash
+    ownmon::store::recover_session_checkpoint();
+
     // Check and compute any missing daily integrity records from previous days
     ownmon::store::check_and_compute_missing_integrity();
 
+    // Merge shared category/blacklist rules, if a rules file is present
+    ownmon::rules::import_startup_rules();
+
     // Start HTTP server
     println!("🔧 Starting HTTP server...");
-    let broadcast_tx = ownmon::server::start_server();
+    let (broadcast_tx, server_addr) = ownmon::server::start_server();
     // Store broadcast sender globally for poller to use
     let _ = ownmon::store::BROADCAST_TX.set(broadcast_tx);
-    println!(
-        "   ✓ HTTP server listening on http://127.0.0.1:{}",
-        ownmon::server::DEFAULT_PORT
-    );
+    match server_addr {
+        Some(addr) => println!("   ✓ HTTP server listening on http://{}", addr),
+        None => println!("   ⚠ HTTP server could not bind a port (see log)"),
+    }
 
     // Shutdown signal
     let shutdown = Arc::new(AtomicBool::new(false));
@@ -155,14 +264,56 @@ fn run_application(_lock: LockFileGuard) -> Result<(), Box<dyn std::error::Error
     // Start polling thread
     println!("🔧 Starting window polling...");
     let shutdown_poller = Arc::clone(&shutdown);
-    let polling_handle = spawn_polling_thread(shutdown_poller, PollerConfig::default());
+    let polling_handle =
+        spawn_polling_thread(WinApiWindowSource, shutdown_poller, PollerConfig::load());
     println!("   ✓ Polling thread started");
 
-    // Install hooks
+    // Install hooks (only one process may hold them, or input is double counted)
     println!("🔧 Installing input hooks...");
-    let _keyboard_hook = HookGuard::install_keyboard_hook(Some(keyboard_hook_proc))?;
-    let _mouse_hook = HookGuard::install_mouse_hook(Some(mouse_hook_proc))?;
-    println!("   ✓ Keyboard and mouse hooks installed");
+    let hook_mutex = match NamedMutexGuard::acquire_input_hooks() {
+        Ok(Some(guard)) => Some(guard),
+        Ok(None) => {
+            tracing::warn!("Input hooks are already installed by another OwnMon process");
+            None
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to create the input hook mutex");
+            None
+        }
+    };
+    // Without hooks (e.g. on locked-down machines, or while another instance
+    // counts input) windows and media are still tracked; only input counting
+    // is lost
+    let (keyboard_hook, mouse_hook) = if hook_mutex.is_some() {
+        let keyboard_hook = HookGuard::install_keyboard_hook(Some(keyboard_hook_proc))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to install keyboard hook"))
+            .ok();
+        load_double_click_settings();
+        let mouse_hook = HookGuard::install_mouse_hook(Some(mouse_hook_proc))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to install mouse hook"))
+            .ok();
+        (keyboard_hook, mouse_hook)
+    } else {
+        (None, None)
+    };
+    if keyboard_hook.is_some() && mouse_hook.is_some() {
+        ownmon::store::INPUT_HOOKS_ACTIVE.store(true, Ordering::SeqCst);
+        println!("   ✓ Keyboard and mouse hooks installed");
+    } else {
+        tracing::warn!("Running without input hooks; keystrokes and clicks won't be counted");
+        println!("   ⚠ Input hooks unavailable; keystrokes and clicks won't be counted");
+        ownmon::tray::show_balloon(
+            "OwnMon",
+            "Input hooks could not be installed. Windows and media are still tracked, but keystrokes and clicks won't be counted.",
+        );
+    }
+
+    // Locked time is recorded as its own kind of idle
+    let _session_notifications = SessionNotificationGuard::register(handle_session_change)
+        .inspect_err(
+            |e| tracing::warn!(error = %e, "Failed to register session lock notifications"),
+        )
+        .ok();
 
     println!();
     println!("════════════════════════════════════════════════════════════════");
@@ -170,13 +321,12 @@ fn run_application(_lock: LockFileGuard) -> Result<(), Box<dyn std::error::Error
     println!("   • Right-click the tray icon for options");
     println!("   • Select 'Exit' or press Ctrl+C to quit");
     println!();
-    println!(
-        "🌐 API available at http://127.0.0.1:{}",
-        ownmon::server::DEFAULT_PORT
-    );
-    println!("   • GET /api/stats    - Today's statistics");
-    println!("   • GET /api/sessions - Recent sessions");
-    println!("   • WS  /ws           - Real-time updates");
+    if let Some(addr) = server_addr {
+        println!("🌐 API available at http://{}", addr);
+        println!("   • GET /api/stats    - Today's statistics");
+        println!("   • GET /api/sessions - Recent sessions");
+        println!("   • WS  /ws           - Real-time updates");
+    }
     println!("════════════════════════════════════════════════════════════════");
     println!();
 
@@ -201,6 +351,8 @@ fn run_application(_lock: LockFileGuard) -> Result<(), Box<dyn std::error::Error
                     last_count = count;
                 }
             }
+
+            ownmon::monitor::goals::check_goals();
         }
     });
 
@@ -211,7 +363,9 @@ fn run_application(_lock: LockFileGuard) -> Result<(), Box<dyn std::error::Error
     // Cleanup
     println!("\n⏳ Shutting down...");
     shutdown.store(true, Ordering::SeqCst);
-    polling_handle.join().expect("Polling thread panicked");
+    if !join_polling_thread(polling_handle, POLLER_SHUTDOWN_TIMEOUT) {
+        println!("   ⚠ Polling thread didn't stop cleanly, saving anyway");
+    }
 
     // Save all pending data to database
     println!("💾 Saving data to database...");
@@ -234,28 +388,33 @@ fn print_summary() {
         println!("════════════════════════════════════════════════════════════════");
         println!("   Sessions:      {}", summary.session_count);
         println!("   Unique Apps:   {}", summary.app_count);
-        println!("   Keystrokes:    {}", summary.total_keystrokes);
-        println!("   Mouse Clicks:  {}", summary.total_clicks);
-        println!("   Focus Time:    {}s", summary.total_focus_time_secs);
+        println!(
+            "   Keystrokes:    {}",
+            format_count(summary.total_keystrokes)
+        );
+        println!("   Mouse Clicks:  {}", format_count(summary.total_clicks));
+        println!(
+            "   Focus Time:    {}",
+            format_duration(summary.total_focus_time_secs as i64)
+        );
+        println!(
+            "   Active Time:   {}",
+            format_duration(summary.active_focus_time_secs as i64)
+        );
 
         if !store.completed_sessions.is_empty() {
             println!();
             println!("Top Applications:");
-            let stats = store.compute_application_stats();
-            let mut sorted: Vec<_> = stats.into_iter().collect();
-            sorted.sort_by(|a, b| {
-                b.1.total_focus_duration_secs
-                    .cmp(&a.1.total_focus_duration_secs)
-            });
-
-            for (i, (name, stat)) in sorted.iter().take(5).enumerate() {
+            let run_summary = store.get_run_summary(ownmon::store::RUN_SUMMARY_TOP_APPS);
+
+            for (i, stat) in run_summary.top_apps.iter().enumerate() {
                 println!(
-                    "   {}. {} - {}s, {} keys, {} clicks",
+                    "   {}. {} - {}, {} keys, {} clicks",
                     i + 1,
-                    name,
-                    stat.total_focus_duration_secs,
-                    stat.total_keystrokes,
-                    stat.total_clicks
+                    stat.process_name,
+                    format_duration(stat.total_focus_duration_secs as i64),
+                    format_count(stat.total_keystrokes),
+                    format_count(stat.total_clicks)
                 );
             }
         }
@@ -265,7 +424,7 @@ fn print_summary() {
         if media_time > 0 || store.current_media.is_some() || !store.media_history.is_empty() {
             println!();
             println!("🎵 Media Listened:");
-            println!("   Total Time:    {}s", media_time);
+            println!("   Total Time:    {}", format_duration(media_time));
             println!(
                 "   Tracks:        {}",
                 store.media_history.len() + if store.current_media.is_some() { 1 } else { 0 }
@@ -291,11 +450,11 @@ fn print_summary() {
                 println!("   Recent Tracks:");
                 for (i, media) in recent_media.iter().take(5).enumerate() {
                     println!(
-                        "      {}. {} - {} ({}s)",
+                        "      {}. {} - {} ({})",
                         i + 1,
                         media.media_info.title,
                         media.media_info.artist,
-                        media.duration_secs()
+                        format_duration(media.duration_secs())
                     );
                 }
             }
@@ -5,7 +5,11 @@
 //! media controls (Spotify, browsers, VLC, etc.).
 
 use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use windows::Media::Control::{
     GlobalSystemMediaTransportControlsSession, GlobalSystemMediaTransportControlsSessionManager,
     GlobalSystemMediaTransportControlsSessionPlaybackStatus,
@@ -51,6 +55,14 @@ pub struct MediaInfo {
     /// Current playback status.
     pub playback_status: PlaybackStatus,
 
+    /// Playback position in seconds (None if the player has no timeline).
+    #[serde(default)]
+    pub position_secs: Option<i64>,
+
+    /// Track length in seconds (None if the player has no timeline).
+    #[serde(default)]
+    pub track_duration_secs: Option<i64>,
+
     /// When this media info was captured.
     pub timestamp: DateTime<Utc>,
 }
@@ -70,10 +82,23 @@ impl MediaInfo {
             album,
             source_app_id,
             playback_status,
+            position_secs: None,
+            track_duration_secs: None,
             timestamp: Utc::now(),
         }
     }
 
+    /// Sets the playback position and track length.
+    pub fn with_timeline(
+        mut self,
+        position_secs: Option<i64>,
+        track_duration_secs: Option<i64>,
+    ) -> Self {
+        self.position_secs = position_secs;
+        self.track_duration_secs = track_duration_secs;
+        self
+    }
+
     /// Returns true if this represents actual playing media.
     pub fn is_playing(&self) -> bool {
         self.playback_status == PlaybackStatus::Playing && !self.title.is_empty()
@@ -91,23 +116,56 @@ pub struct MediaSession {
 
     /// When playback ended (None if still playing).
     pub end_time: Option<DateTime<Utc>>,
+
+    /// When the player last reported this media as playing.
+    #[serde(default = "Utc::now")]
+    pub last_seen: DateTime<Utc>,
+
+    /// Track length in seconds, if the player reported one.
+    #[serde(default)]
+    pub track_duration_secs: Option<i64>,
+
+    /// Last playback position reported by the player, in seconds.
+    #[serde(default)]
+    pub last_position_secs: Option<i64>,
 }
 
 impl MediaSession {
     /// Creates a new media session starting now.
     pub fn new(media_info: MediaInfo) -> Self {
+        let now = Utc::now();
         Self {
-            start_time: Utc::now(),
+            start_time: now,
             end_time: None,
+            last_seen: now,
+            track_duration_secs: media_info.track_duration_secs,
+            last_position_secs: media_info.position_secs,
             media_info,
         }
     }
 
+    /// Records the timeline from a later report of the same media.
+    ///
+    /// Fields the player didn't report keep their previous values.
+    pub fn update_timeline(&mut self, info: &MediaInfo) {
+        if info.track_duration_secs.is_some() {
+            self.track_duration_secs = info.track_duration_secs;
+        }
+        if info.position_secs.is_some() {
+            self.last_position_secs = info.position_secs;
+        }
+    }
+
     /// Finalizes the session.
     pub fn finalize(&mut self) {
         self.end_time = Some(Utc::now());
     }
 
+    /// Finalizes the session at the given time.
+    pub fn finalize_at(&mut self, end_time: DateTime<Utc>) {
+        self.end_time = Some(end_time.max(self.start_time));
+    }
+
     /// Returns the duration in seconds.
     pub fn duration_secs(&self) -> i64 {
         match self.end_time {
@@ -118,16 +176,200 @@ impl MediaSession {
 
     /// Returns true if this is the same media (by title and artist).
     pub fn is_same_media(&self, other: &MediaInfo) -> bool {
-        self.media_info.title == other.title && self.media_info.artist == other.artist
+        self.is_same_media_with(other, None)
+    }
+
+    /// Like [`is_same_media`](Self::is_same_media), but compares titles after
+    /// stripping version tags when a normalizer is given.
+    pub fn is_same_media_with(
+        &self,
+        other: &MediaInfo,
+        normalizer: Option<&TitleNormalizer>,
+    ) -> bool {
+        if self.media_info.artist != other.artist {
+            return false;
+        }
+        match normalizer {
+            Some(n) => n.normalize(&self.media_info.title) == n.normalize(&other.title),
+            None => self.media_info.title == other.title,
+        }
+    }
+}
+
+/// Tags stripped from titles by default, e.g. "Song (Remastered 2011)" or "Song - Live".
+pub const DEFAULT_TITLE_TAG_PATTERNS: &[&str] = &[
+    r"(?i)\s*[(\[][^()\[\]]*\b(remaster(ed)?|live|mono|stereo|deluxe|edition|version|explicit|acoustic)\b[^()\[\]]*[)\]]",
+    r"(?i)\s+-\s+[^-]*\b(remaster(ed)?|live|mono|stereo|version|edit|acoustic)\b.*$",
+];
+
+/// Strips version tags from media titles so variants of a track compare equal.
+///
+/// Only used for grouping; the raw title is what gets stored.
+#[derive(Debug, Clone)]
+pub struct TitleNormalizer {
+    patterns: Vec<Regex>,
+}
+
+impl TitleNormalizer {
+    /// Compiles the given patterns, skipping (and logging) invalid ones.
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        let patterns = patterns
+            .iter()
+            .filter_map(|p| match Regex::new(p.as_ref()) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    tracing::warn!(pattern = p.as_ref(), error = %e, "Invalid media title pattern");
+                    None
+                }
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Returns the title with all matching tags removed.
+    pub fn normalize(&self, title: &str) -> String {
+        let mut out = title.to_string();
+        for re in &self.patterns {
+            out = re.replace_all(&out, "").into_owned();
+        }
+        out.trim().to_string()
+    }
+}
+
+impl Default for TitleNormalizer {
+    fn default() -> Self {
+        Self::new(DEFAULT_TITLE_TAG_PATTERNS)
     }
 }
 
-/// Gets the current media session manager.
+/// Builds the title normalizer from config, if `normalize_media_titles` is on.
+///
+/// `media_title_tags` may hold a JSON array of regexes to use instead of the defaults.
+pub fn load_title_normalizer() -> Option<TitleNormalizer> {
+    crate::store::with_db(title_normalizer_from).flatten()
+}
+
+/// Builds the title normalizer from the settings in `db`, see
+/// [`load_title_normalizer`].
+pub fn title_normalizer_from(db: &crate::database::Database) -> Option<TitleNormalizer> {
+    let enabled = db
+        .get_config("normalize_media_titles")
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(false);
+    if !enabled {
+        return None;
+    }
+
+    let custom = db
+        .get_config("media_title_tags")
+        .ok()
+        .flatten()
+        .filter(|v| !v.trim().is_empty());
+    match custom {
+        Some(json) => match serde_json::from_str::<Vec<String>>(&json) {
+            Ok(patterns) => Some(TitleNormalizer::new(&patterns)),
+            Err(e) => {
+                tracing::warn!(error = %e, "media_title_tags is not a JSON array, using defaults");
+                Some(TitleNormalizer::default())
+            }
+        },
+        None => Some(TitleNormalizer::default()),
+    }
+}
+
+/// Number of `TimeSpan` ticks (100 ns) per second.
+const TICKS_PER_SEC: i64 = 10_000_000;
+
+/// Converts GSMTC timeline ticks into `(position_secs, track_duration_secs)`.
+///
+/// Players that don't expose a timeline report an empty range, which yields
+/// `(None, None)`. The position is clamped to the track.
+pub fn timeline_secs(start: i64, end: i64, position: i64) -> (Option<i64>, Option<i64>) {
+    let length = (end - start) / TICKS_PER_SEC;
+    if length <= 0 {
+        return (None, None);
+    }
+    let position = ((position - start) / TICKS_PER_SEC).clamp(0, length);
+    (Some(position), Some(length))
+}
+
+/// Delay before the first retry after the session manager can't be obtained.
+const MANAGER_RETRY_BASE: Duration = Duration::from_secs(1);
+
+/// Longest delay between session manager requests.
+const MANAGER_RETRY_MAX: Duration = Duration::from_secs(300);
+
+/// Cached session manager plus the retry state for obtaining it.
+#[derive(Default)]
+struct ManagerCache {
+    manager: Option<GlobalSystemMediaTransportControlsSessionManager>,
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+static SESSION_MANAGER: Lazy<Mutex<ManagerCache>> =
+    Lazy::new(|| Mutex::new(ManagerCache::default()));
+
+/// Returns how long to wait after the given number of consecutive failures.
+///
+/// Doubles from [`MANAGER_RETRY_BASE`] and is capped at [`MANAGER_RETRY_MAX`].
+pub fn manager_retry_delay(failures: u32) -> Duration {
+    let exponent = failures.saturating_sub(1).min(16);
+    MANAGER_RETRY_BASE
+        .saturating_mul(1 << exponent)
+        .min(MANAGER_RETRY_MAX)
+}
+
+/// Requests a new media session manager from Windows.
 pub fn get_session_manager(
 ) -> windows::core::Result<GlobalSystemMediaTransportControlsSessionManager> {
     GlobalSystemMediaTransportControlsSessionManager::RequestAsync()?.get()
 }
 
+/// Returns the cached session manager, requesting it if needed.
+///
+/// The manager is requested once and reused. While GSMTC is unavailable,
+/// requests back off exponentially instead of repeating every poll.
+fn cached_session_manager() -> Option<GlobalSystemMediaTransportControlsSessionManager> {
+    let mut cache = SESSION_MANAGER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(manager) = &cache.manager {
+        return Some(manager.clone());
+    }
+    if cache.retry_at.is_some_and(|at| Instant::now() < at) {
+        return None;
+    }
+
+    match get_session_manager() {
+        Ok(manager) => {
+            if cache.failures > 0 {
+                tracing::info!(failures = cache.failures, "Media session manager available");
+            }
+            cache.manager = Some(manager.clone());
+            cache.failures = 0;
+            cache.retry_at = None;
+            Some(manager)
+        }
+        Err(e) => {
+            cache.failures += 1;
+            let delay = manager_retry_delay(cache.failures);
+            cache.retry_at = Some(Instant::now() + delay);
+            if cache.failures == 1 {
+                tracing::warn!(error = %e, "Media session manager unavailable, retrying with backoff");
+            } else {
+                tracing::debug!(
+                    error = %e,
+                    failures = cache.failures,
+                    retry_in_secs = delay.as_secs(),
+                    "Media session manager still unavailable"
+                );
+            }
+            None
+        }
+    }
+}
+
 /// Gets the currently active media session.
 pub fn get_current_session(
     manager: &GlobalSystemMediaTransportControlsSessionManager,
@@ -137,7 +379,7 @@ pub fn get_current_session(
 
 /// Fetches media info from the current session.
 pub fn fetch_current_media() -> Option<MediaInfo> {
-    let manager = get_session_manager().ok()?;
+    let manager = cached_session_manager()?;
     let session = get_current_session(&manager)?;
 
     // Get playback info
@@ -154,19 +396,85 @@ pub fn fetch_current_media() -> Option<MediaInfo> {
     // Get source app ID
     let source_app_id = session.SourceAppUserModelId().ok()?.to_string();
 
-    Some(MediaInfo::new(
-        title,
-        artist,
-        album,
-        source_app_id,
-        playback_status,
-    ))
+    // Timeline is optional; not every player reports one
+    let (position_secs, track_duration_secs) = session
+        .GetTimelineProperties()
+        .ok()
+        .and_then(|timeline| {
+            Some(timeline_secs(
+                timeline.StartTime().ok()?.Duration,
+                timeline.EndTime().ok()?.Duration,
+                timeline.Position().ok()?.Duration,
+            ))
+        })
+        .unwrap_or((None, None));
+
+    Some(
+        MediaInfo::new(title, artist, album, source_app_id, playback_status)
+            .with_timeline(position_secs, track_duration_secs),
+    )
+}
+
+/// Source of the media playing now.
+///
+/// The window poller reads media through this trait so it can be driven
+/// without a real player, using `MockMediaSource` in tests.
+pub trait MediaSource {
+    /// Returns the current media, or `None` if no player reports any.
+    fn current_media(&self) -> Option<MediaInfo>;
+}
+
+/// [`MediaSource`] backed by the system media transport controls.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemMediaSource;
+
+impl MediaSource for SystemMediaSource {
+    fn current_media(&self) -> Option<MediaInfo> {
+        fetch_current_media()
+    }
+}
+
+/// Scripted [`MediaSource`] for tests: what is playing is set by the caller.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MockMediaSource {
+    playing: Mutex<Option<MediaInfo>>,
+}
+
+#[cfg(test)]
+impl MockMediaSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the media reported (`None` = nothing playing).
+    pub fn play(&self, media: Option<MediaInfo>) {
+        if let Ok(mut playing) = self.playing.lock() {
+            *playing = media;
+        }
+    }
+}
+
+#[cfg(test)]
+impl MediaSource for MockMediaSource {
+    fn current_media(&self) -> Option<MediaInfo> {
+        self.playing.lock().ok().and_then(|m| m.clone())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_manager_retry_delay_backs_off() {
+        assert_eq!(manager_retry_delay(1), Duration::from_secs(1));
+        assert_eq!(manager_retry_delay(2), Duration::from_secs(2));
+        assert_eq!(manager_retry_delay(5), Duration::from_secs(16));
+        assert_eq!(manager_retry_delay(10), MANAGER_RETRY_MAX);
+        assert_eq!(manager_retry_delay(u32::MAX), MANAGER_RETRY_MAX);
+    }
+
     #[test]
     fn test_media_info_is_playing() {
         let playing = MediaInfo::new(
@@ -227,6 +535,65 @@ mod tests {
         assert!(!session.is_same_media(&info3));
     }
 
+    #[test]
+    fn test_normalized_title_comparison() {
+        let song = |title: &str| {
+            MediaInfo::new(
+                title.to_string(),
+                "Artist X".to_string(),
+                "Album".to_string(),
+                "app".to_string(),
+                PlaybackStatus::Playing,
+            )
+        };
+        let normalizer = TitleNormalizer::default();
+        let session = MediaSession::new(song("Song"));
+
+        assert!(session.is_same_media_with(&song("Song (Remastered)"), Some(&normalizer)));
+        assert!(session.is_same_media_with(&song("Song (Remastered 2011)"), Some(&normalizer)));
+        assert!(session.is_same_media_with(&song("Song - Live"), Some(&normalizer)));
+        assert!(!session.is_same_media_with(&song("Other"), Some(&normalizer)));
+
+        // Without normalization the variants stay distinct
+        assert!(!session.is_same_media(&song("Song (Remastered)")));
+
+        // Custom patterns replace the defaults
+        let custom = TitleNormalizer::new(&[r"\s*\[.*\]"]);
+        assert_eq!(custom.normalize("Song [Official Video]"), "Song");
+        assert_eq!(custom.normalize("Song (Remastered)"), "Song (Remastered)");
+    }
+
+    #[test]
+    fn test_timeline_secs() {
+        let secs = |s: i64| s * TICKS_PER_SEC;
+        assert_eq!(timeline_secs(0, secs(225), secs(90)), (Some(90), Some(225)));
+        // Position is relative to the start and clamped to the track
+        assert_eq!(
+            timeline_secs(secs(10), secs(70), secs(100)),
+            (Some(60), Some(60))
+        );
+        // No timeline reported
+        assert_eq!(timeline_secs(0, 0, 0), (None, None));
+
+        let song = MediaInfo::new(
+            "Song".to_string(),
+            "Artist".to_string(),
+            "Album".to_string(),
+            "app".to_string(),
+            PlaybackStatus::Playing,
+        );
+        let mut session = MediaSession::new(song.clone().with_timeline(Some(5), Some(225)));
+        assert_eq!(session.track_duration_secs, Some(225));
+
+        session.update_timeline(&song.clone().with_timeline(Some(90), Some(225)));
+        assert_eq!(session.last_position_secs, Some(90));
+
+        // A report without a timeline keeps what we had
+        session.update_timeline(&song);
+        assert_eq!(session.last_position_secs, Some(90));
+        assert_eq!(session.track_duration_secs, Some(225));
+    }
+
     #[test]
     fn test_playback_status_serialization() {
         let status = PlaybackStatus::Playing;
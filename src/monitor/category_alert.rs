@@ -0,0 +1,217 @@
+//! Alerts when focus moves into a distracting category during work hours.
+//!
+//! Configured with `category_alerts` (comma-separated category names, empty =
+//! off), `work_hours` (`HH:MM-HH:MM`, local time) and
+//! `category_alert_debounce_secs`.
+
+use chrono::{DateTime, Local, NaiveTime, Utc};
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+/// Alert settings read from config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryAlertConfig {
+    /// Lowercased names of the categories that trigger an alert.
+    pub categories: Vec<String>,
+    pub work_start: NaiveTime,
+    pub work_end: NaiveTime,
+    /// Minimum time between two alerts.
+    pub debounce_secs: i64,
+}
+
+impl CategoryAlertConfig {
+    /// Builds a config from raw setting values. Returns `None` when no alert
+    /// categories are set.
+    pub fn parse(categories: &str, work_hours: &str, debounce_secs: i64) -> Option<Self> {
+        let categories: Vec<String> = categories
+            .split(',')
+            .map(|c| c.trim().to_lowercase())
+            .filter(|c| !c.is_empty())
+            .collect();
+        if categories.is_empty() {
+            return None;
+        }
+
+        let (work_start, work_end) = parse_work_hours(work_hours).unwrap_or_else(|| {
+            tracing::warn!(work_hours, "Invalid work_hours, using 09:00-17:00");
+            (
+                NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default(),
+                NaiveTime::from_hms_opt(17, 0, 0).unwrap_or_default(),
+            )
+        });
+
+        Some(Self {
+            categories,
+            work_start,
+            work_end,
+            debounce_secs: debounce_secs.max(0),
+        })
+    }
+
+    /// Returns true if `time` falls within work hours (end exclusive).
+    ///
+    /// A start later than the end spans midnight (e.g. `22:00-06:00`).
+    pub fn in_work_hours(&self, time: NaiveTime) -> bool {
+        if self.work_start <= self.work_end {
+            time >= self.work_start && time < self.work_end
+        } else {
+            time >= self.work_start || time < self.work_end
+        }
+    }
+
+    fn is_alert_category(&self, category: &str) -> bool {
+        let category = category.to_lowercase();
+        self.categories.contains(&category)
+    }
+}
+
+/// Parses `HH:MM-HH:MM`.
+fn parse_work_hours(value: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = value.split_once('-')?;
+    Some((
+        NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?,
+        NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?,
+    ))
+}
+
+/// Tracks the last focused category and the last alert, for transition
+/// detection and debouncing.
+#[derive(Debug, Default)]
+pub struct CategoryAlertState {
+    last_category: Option<String>,
+    last_alert: Option<DateTime<Utc>>,
+}
+
+impl CategoryAlertState {
+    /// Records a focus change into `category` and returns true if an alert should fire.
+    ///
+    /// Alerts fire only on a transition from a different category into an
+    /// alert category, within work hours, and not within the debounce window
+    /// of the previous alert.
+    pub fn on_focus(
+        &mut self,
+        category: &str,
+        now: DateTime<Utc>,
+        local_time: NaiveTime,
+        config: &CategoryAlertConfig,
+    ) -> bool {
+        let is_transition = !matches!(
+            self.last_category.as_deref(),
+            Some(last) if last.eq_ignore_ascii_case(category)
+        );
+        self.last_category = Some(category.to_string());
+
+        if !is_transition
+            || !config.is_alert_category(category)
+            || !config.in_work_hours(local_time)
+        {
+            return false;
+        }
+
+        let debounced = self
+            .last_alert
+            .is_some_and(|last| (now - last).num_seconds() < config.debounce_secs);
+        if debounced {
+            return false;
+        }
+
+        self.last_alert = Some(now);
+        true
+    }
+}
+
+static ALERT_STATE: Lazy<Mutex<CategoryAlertState>> =
+    Lazy::new(|| Mutex::new(CategoryAlertState::default()));
+
+/// Reads the alert config. Returns `None` when alerts are off.
+fn load_config() -> Option<CategoryAlertConfig> {
+    crate::store::with_db(|db| {
+        let get = |key: &str| db.get_config(key).ok().flatten().unwrap_or_default();
+
+        CategoryAlertConfig::parse(
+            &get("category_alerts"),
+            &get("work_hours"),
+            get("category_alert_debounce_secs").parse().unwrap_or(300),
+        )
+    })
+    .flatten()
+}
+
+/// Checks a focus change to `process_name` and alerts if it enters an alert category.
+pub fn check_category_alert(process_name: &str) {
+    let Some(config) = load_config() else {
+        return;
+    };
+
+    let Some(category) =
+        crate::store::with_db(|d| d.get_category_for_app(process_name).ok()).flatten()
+    else {
+        return;
+    };
+
+    let fire = match ALERT_STATE.lock() {
+        Ok(mut state) => state.on_focus(&category.name, Utc::now(), Local::now().time(), &config),
+        Err(_) => false,
+    };
+    if !fire {
+        return;
+    }
+
+    tracing::info!(process = process_name, category = %category.name, "Category alert");
+    crate::store::broadcast_update(
+        "category_alert",
+        &serde_json::json!({
+            "process_name": process_name,
+            "category": category,
+        }),
+    );
+    crate::tray::show_balloon(
+        "OwnMon",
+        &format!("{} is in {} during work hours", process_name, category.name),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_category_alert_transitions_and_debounce() {
+        let config = CategoryAlertConfig::parse("Entertainment", "09:00-17:00", 300).unwrap();
+        let mut state = CategoryAlertState::default();
+        let t0 = Utc::now();
+        let secs = |s: i64| t0 + chrono::Duration::seconds(s);
+
+        assert!(!state.on_focus("Work", secs(0), at(10, 0), &config));
+        // Work -> Entertainment during work hours fires
+        assert!(state.on_focus("Entertainment", secs(10), at(10, 0), &config));
+        // Staying in the same category is not a transition
+        assert!(!state.on_focus("entertainment", secs(20), at(10, 0), &config));
+        // Rapid switch back and forth is debounced
+        assert!(!state.on_focus("Work", secs(30), at(10, 1), &config));
+        assert!(!state.on_focus("Entertainment", secs(40), at(10, 1), &config));
+        // After the debounce window it fires again
+        assert!(!state.on_focus("Work", secs(400), at(10, 7), &config));
+        assert!(state.on_focus("Entertainment", secs(410), at(10, 7), &config));
+        // Outside work hours nothing fires
+        assert!(!state.on_focus("Work", secs(2000), at(18, 0), &config));
+        assert!(!state.on_focus("Entertainment", secs(2010), at(18, 0), &config));
+    }
+
+    #[test]
+    fn test_category_alert_config_parse() {
+        assert!(CategoryAlertConfig::parse("", "09:00-17:00", 300).is_none());
+
+        let overnight =
+            CategoryAlertConfig::parse("Games, Entertainment", "22:00-06:00", 60).unwrap();
+        assert_eq!(overnight.categories, vec!["games", "entertainment"]);
+        assert!(overnight.in_work_hours(at(23, 0)));
+        assert!(overnight.in_work_hours(at(5, 59)));
+        assert!(!overnight.in_work_hours(at(6, 0)));
+        assert!(!overnight.in_work_hours(at(12, 0)));
+    }
+}
@@ -0,0 +1,123 @@
+//! Focus mode: nudges when a distracting app is opened.
+//!
+//! Enabled with `focus_mode`; categories opt in through their `distracting`
+//! flag. Notifications for the same app are at most one per
+//! `focus_mode_debounce_secs`.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Tracks when each app last triggered a notification.
+#[derive(Debug, Default)]
+pub struct FocusModeState {
+    last_alert: HashMap<String, DateTime<Utc>>,
+}
+
+impl FocusModeState {
+    /// Records focus moving to a distracting `process_name` and returns true
+    /// if a notification should fire.
+    ///
+    /// Apps are debounced independently, so switching between two
+    /// distracting apps notifies once for each.
+    pub fn on_distracting_focus(
+        &mut self,
+        process_name: &str,
+        now: DateTime<Utc>,
+        debounce_secs: i64,
+    ) -> bool {
+        let key = process_name.to_lowercase();
+        let debounced = self
+            .last_alert
+            .get(&key)
+            .is_some_and(|last| (now - *last).num_seconds() < debounce_secs);
+        if debounced {
+            return false;
+        }
+
+        self.last_alert.insert(key, now);
+        true
+    }
+}
+
+static FOCUS_MODE_STATE: Lazy<Mutex<FocusModeState>> =
+    Lazy::new(|| Mutex::new(FocusModeState::default()));
+
+/// Reads the debounce window if focus mode is on, `None` when it is off.
+fn load_debounce_secs() -> Option<i64> {
+    crate::store::with_db(|db| {
+        let get = |key: &str| db.get_config(key).ok().flatten();
+        let enabled = get("focus_mode").is_some_and(|v| v == "true");
+        enabled.then(|| {
+            get("focus_mode_debounce_secs")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300i64)
+                .max(0)
+        })
+    })
+    .flatten()
+}
+
+/// Checks a focus change to `process_name` and notifies if focus mode is on
+/// and its category is marked distracting.
+pub fn check_focus_mode(process_name: &str) {
+    if crate::store::is_monitoring_paused() {
+        return;
+    }
+    let Some(debounce_secs) = load_debounce_secs() else {
+        return;
+    };
+
+    let Some(category) =
+        crate::store::with_db(|db| db.get_category_for_app(process_name).ok()).flatten()
+    else {
+        return;
+    };
+    if !category.distracting {
+        return;
+    }
+
+    let fire = FOCUS_MODE_STATE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .on_distracting_focus(process_name, Utc::now(), debounce_secs);
+    if !fire {
+        return;
+    }
+
+    let app = process_name
+        .strip_suffix(".exe")
+        .or_else(|| process_name.strip_suffix(".EXE"))
+        .unwrap_or(process_name);
+    tracing::info!(process = process_name, category = %category.name, "Focus mode alert");
+    crate::store::broadcast_update(
+        "focus_mode_alert",
+        &serde_json::json!({
+            "process_name": process_name,
+            "category": category,
+        }),
+    );
+    crate::tray::show_balloon("OwnMon", &format!("You opened {} during focus time", app));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_focus_mode_debounces_per_app() {
+        let mut state = FocusModeState::default();
+        let t0 = Utc::now();
+        let secs = |s: i64| t0 + chrono::Duration::seconds(s);
+
+        assert!(state.on_distracting_focus("Discord.exe", secs(0), 300));
+        // Another distracting app has its own window
+        assert!(state.on_distracting_focus("steam.exe", secs(10), 300));
+        // Reopening within the window is quiet, regardless of case
+        assert!(!state.on_distracting_focus("discord.exe", secs(60), 300));
+        assert!(state.on_distracting_focus("Discord.exe", secs(300), 300));
+        // A zero window notifies every time
+        assert!(state.on_distracting_focus("steam.exe", secs(11), 0));
+    }
+}
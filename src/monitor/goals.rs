@@ -0,0 +1,180 @@
+//! Notifications when a daily goal is reached.
+//!
+//! Goals live in the `goals` table. Today's totals are checked periodically
+//! and each goal notifies at most once per (local) day.
+
+use crate::database::Goal;
+use crate::store::{format_count, format_duration, FocusTotals};
+use chrono::{NaiveDate, Utc};
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// The only goal period supported so far.
+pub const DAILY: &str = "daily";
+
+/// A total a goal can be set on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalMetric {
+    Keystrokes,
+    Clicks,
+    FocusTimeSecs,
+}
+
+impl GoalMetric {
+    /// Parses a metric name as stored in the `goals` table.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "keystrokes" => Some(Self::Keystrokes),
+            "clicks" => Some(Self::Clicks),
+            "focus_time_secs" => Some(Self::FocusTimeSecs),
+            _ => None,
+        }
+    }
+
+    /// Reads this metric from the day's totals.
+    pub fn value(self, totals: &FocusTotals) -> i64 {
+        match self {
+            Self::Keystrokes => totals.keystrokes as i64,
+            Self::Clicks => totals.clicks as i64,
+            Self::FocusTimeSecs => totals.focus_time_secs,
+        }
+    }
+
+    /// Describes reaching `threshold`, e.g. "10,000 keystrokes".
+    pub fn describe(self, threshold: i64) -> String {
+        match self {
+            Self::Keystrokes => format!("{} keystrokes", format_count(threshold.max(0) as u64)),
+            Self::Clicks => format!("{} clicks", format_count(threshold.max(0) as u64)),
+            Self::FocusTimeSecs => format!("{} of focus time", format_duration(threshold)),
+        }
+    }
+}
+
+/// Remembers which goals already notified today.
+#[derive(Debug, Default)]
+pub struct GoalTracker {
+    day: Option<NaiveDate>,
+    fired: HashSet<i64>,
+}
+
+impl GoalTracker {
+    /// Returns the goals reached for the first time on `today`.
+    pub fn check<'a>(
+        &mut self,
+        goals: &'a [Goal],
+        totals: &FocusTotals,
+        today: NaiveDate,
+    ) -> Vec<&'a Goal> {
+        if self.day != Some(today) {
+            self.day = Some(today);
+            self.fired.clear();
+        }
+
+        goals
+            .iter()
+            .filter(|goal| goal.period == DAILY)
+            .filter(|goal| {
+                GoalMetric::parse(&goal.metric)
+                    .is_some_and(|metric| metric.value(totals) >= goal.threshold)
+            })
+            .filter(|goal| self.fired.insert(goal.id))
+            .collect()
+    }
+}
+
+static TRACKER: Lazy<Mutex<GoalTracker>> = Lazy::new(|| Mutex::new(GoalTracker::default()));
+
+/// Checks today's totals against the goals and notifies for newly reached ones.
+///
+/// Does nothing while monitoring is paused.
+pub fn check_goals() {
+    if crate::store::is_monitoring_paused() {
+        return;
+    }
+
+    let today = crate::store::timezone_policy().date_of(Utc::now());
+    let count_idle = crate::store::count_idle_in_focus();
+    let Some((goals, sessions)) = crate::store::with_db(|d| {
+        (
+            d.get_goals().unwrap_or_default(),
+            d.get_all_sessions_for_date(&today.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+        )
+    }) else {
+        return;
+    };
+    if goals.is_empty() {
+        return;
+    }
+
+    let Ok(store) = crate::store::ACTIVITY_STORE.read() else {
+        return;
+    };
+    let totals =
+        crate::store::compute_focus_totals(&sessions, store.current_session.as_ref(), count_idle);
+    drop(store);
+
+    let reached: Vec<Goal> = match TRACKER.lock() {
+        Ok(mut tracker) => tracker
+            .check(&goals, &totals, today)
+            .into_iter()
+            .cloned()
+            .collect(),
+        Err(_) => return,
+    };
+
+    for goal in reached {
+        let Some(metric) = GoalMetric::parse(&goal.metric) else {
+            continue;
+        };
+        tracing::info!(id = goal.id, metric = %goal.metric, threshold = goal.threshold, "Goal reached");
+        crate::store::broadcast_update("goal_reached", &goal);
+        crate::tray::show_balloon(
+            "OwnMon",
+            &format!("Daily goal reached: {}", metric.describe(goal.threshold)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal(id: i64, metric: &str, threshold: i64) -> Goal {
+        Goal {
+            id,
+            metric: metric.to_string(),
+            threshold,
+            period: DAILY.to_string(),
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_goal_fires_once_per_day() {
+        let goals = vec![
+            goal(1, "keystrokes", 1000),
+            goal(2, "focus_time_secs", 3600),
+            goal(3, "unknown", 0),
+        ];
+        let mut tracker = GoalTracker::default();
+        let day = NaiveDate::from_ymd_opt(2025, 12, 13).unwrap();
+        let mut totals = FocusTotals {
+            keystrokes: 999,
+            focus_time_secs: 7200,
+            ..Default::default()
+        };
+
+        let ids = |reached: Vec<&Goal>| reached.iter().map(|g| g.id).collect::<Vec<_>>();
+        assert_eq!(ids(tracker.check(&goals, &totals, day)), vec![2]);
+
+        totals.keystrokes = 1000;
+        assert_eq!(ids(tracker.check(&goals, &totals, day)), vec![1]);
+        assert!(tracker.check(&goals, &totals, day).is_empty());
+
+        // A new day starts over
+        let next = day.succ_opt().unwrap();
+        assert_eq!(ids(tracker.check(&goals, &totals, next)), vec![1, 2]);
+    }
+}
@@ -14,11 +14,22 @@
 //! - Never perform I/O
 //! - Always call `CallNextHookEx`
 
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::store::KeyBreakdown;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetDoubleClickTime, VK_0, VK_9, VK_A, VK_CAPITAL, VK_CONTROL, VK_DOWN, VK_F1, VK_F24,
+    VK_LBUTTON, VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_LWIN, VK_MBUTTON, VK_MENU, VK_NUMPAD0,
+    VK_NUMPAD9, VK_PRIOR, VK_RBUTTON, VK_RCONTROL, VK_RETURN, VK_RMENU, VK_RWIN, VK_SHIFT,
+    VK_SPACE, VK_TAB, VK_Z,
+};
 use windows::Win32::UI::WindowsAndMessaging::{
-    CallNextHookEx, HC_ACTION, WM_KEYDOWN, WM_LBUTTONDOWN, WM_MBUTTONDOWN, WM_MOUSEWHEEL,
-    WM_RBUTTONDOWN, WM_SYSKEYDOWN,
+    CallNextHookEx, GetSystemMetrics, HC_ACTION, KBDLLHOOKSTRUCT, MSLLHOOKSTRUCT, SM_CXDOUBLECLK,
+    SM_CYDOUBLECLK, WM_KEYDOWN, WM_KEYUP, WM_LBUTTONDBLCLK, WM_LBUTTONDOWN, WM_MBUTTONDOWN,
+    WM_MOUSEHWHEEL, WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_RBUTTONDBLCLK, WM_RBUTTONDOWN, WM_SYSKEYDOWN,
+    WM_SYSKEYUP,
 };
 
 // ============================================================================
@@ -40,14 +51,471 @@ pub static MIDDLE_CLICK_COUNT: AtomicU64 = AtomicU64::new(0);
 /// Mouse scroll event count since last flush.
 pub static SCROLL_COUNT: AtomicU64 = AtomicU64::new(0);
 
+/// Wheel-up (away from the user) event count since last flush.
+pub static SCROLL_UP_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Wheel-down (toward the user) event count since last flush.
+pub static SCROLL_DOWN_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Horizontal wheel left event count since last flush.
+pub static SCROLL_LEFT_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Horizontal wheel right event count since last flush.
+pub static SCROLL_RIGHT_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Keystroke counts since last flush, indexed by `KeyClass`.
+static KEY_CLASS_COUNTS: [AtomicU64; KEY_CLASS_COUNT] = [
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+];
+
+/// Keyboard shortcut (Ctrl/Alt/Win + key) count since last flush.
+pub static SHORTCUT_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Double-click count (left or right button) since last flush.
+///
+/// The second press of a double-click is also counted as a click.
+pub static DOUBLE_CLICK_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// System double-click time in milliseconds (see `load_double_click_settings`).
+static DOUBLE_CLICK_TIME_MS: AtomicU32 = AtomicU32::new(500);
+
+/// System double-click rectangle size in pixels, packed by `pack_point`.
+static DOUBLE_CLICK_RECT: AtomicU64 = AtomicU64::new(4 << 32 | 4);
+
+/// Last left/right button press (time in ms, packed point), for double-click detection.
+static LAST_LEFT_DOWN: AtomicU64 = AtomicU64::new(NO_MOUSE_POS);
+static LAST_LEFT_DOWN_POS: AtomicU64 = AtomicU64::new(NO_MOUSE_POS);
+static LAST_RIGHT_DOWN: AtomicU64 = AtomicU64::new(NO_MOUSE_POS);
+static LAST_RIGHT_DOWN_POS: AtomicU64 = AtomicU64::new(NO_MOUSE_POS);
+
+/// Mouse travel since last flush, in 1/`MOUSE_DISTANCE_SCALE` pixels.
+pub static MOUSE_DISTANCE: AtomicU64 = AtomicU64::new(0);
+
+/// Fixed-point scale of `MOUSE_DISTANCE` (hundredths of a pixel).
+pub const MOUSE_DISTANCE_SCALE: u64 = 100;
+
+/// Moves longer than this between two events are treated as cursor jumps
+/// (monitor layout change, remote desktop, `SetCursorPos`) and not counted.
+pub const MAX_MOUSE_STEP_PX: f64 = 1000.0;
+
+/// Last cursor position packed by `pack_point`, or `NO_MOUSE_POS`.
+static LAST_MOUSE_POS: AtomicU64 = AtomicU64::new(NO_MOUSE_POS);
+
+/// Marks `LAST_MOUSE_POS` as unknown. A real cursor at (-1, -1) packs to
+/// the same value and just skips one step.
+const NO_MOUSE_POS: u64 = u64::MAX;
+
+/// Bitmask of modifier keys currently held down (see `modifier_bit`).
+static MODIFIER_STATE: AtomicU32 = AtomicU32::new(0);
+
+// ============================================================================
+// Keystroke Privacy Mode
+// ============================================================================
+
+/// Whether the keyboard hook only counts keystrokes (`privacy_mode` config).
+static KEY_PRIVACY_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Turns keystroke privacy mode on or off.
+///
+/// While on, the keyboard hook never looks at which key was pressed: it only
+/// increments the keystroke count, so key classes and shortcuts aren't
+/// counted and the debug event log stays off whatever its own setting.
+/// Turning it on also clears the events the log already holds.
+pub fn set_privacy_mode(enabled: bool) {
+    KEY_PRIVACY_MODE.store(enabled, Ordering::SeqCst);
+    if enabled {
+        DEBUG_EVENT_LOG.store(false, Ordering::SeqCst);
+        clear_debug_events();
+        MODIFIER_STATE.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Returns true if keystroke privacy mode is on.
+pub fn privacy_mode_enabled() -> bool {
+    KEY_PRIVACY_MODE.load(Ordering::SeqCst)
+}
+
+// ============================================================================
+// Debug Event Log
+// ============================================================================
+
+/// Number of raw events kept by the debug event log.
+pub const DEBUG_EVENT_CAPACITY: usize = 1024;
+
+/// Whether the hooks record raw events (`debug_event_log` config, off by default).
+static DEBUG_EVENT_LOG: AtomicBool = AtomicBool::new(false);
+
+/// Event time per slot, in milliseconds since the Unix epoch.
+static DEBUG_EVENT_TIMES: [AtomicU64; DEBUG_EVENT_CAPACITY] =
+    [const { AtomicU64::new(0) }; DEBUG_EVENT_CAPACITY];
+
+/// Event per slot, packed by `pack_debug_event`; 0 marks an empty slot.
+static DEBUG_EVENTS: [AtomicU64; DEBUG_EVENT_CAPACITY] =
+    [const { AtomicU64::new(0) }; DEBUG_EVENT_CAPACITY];
+
+/// Total events recorded; the next slot is this modulo the capacity.
+static DEBUG_EVENT_NEXT: AtomicUsize = AtomicUsize::new(0);
+
+/// Kind of a raw input event in the debug event log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum DebugEventKind {
+    KeyDown = 1,
+    KeyUp = 2,
+    MouseDown = 3,
+    Wheel = 4,
+    HWheel = 5,
+}
+
+impl DebugEventKind {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::KeyDown),
+            2 => Some(Self::KeyUp),
+            3 => Some(Self::MouseDown),
+            4 => Some(Self::Wheel),
+            5 => Some(Self::HWheel),
+            _ => None,
+        }
+    }
+}
+
+/// A raw input event read back from the debug event log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DebugEvent {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub event: DebugEventKind,
+    /// Virtual key for key and button events, wheel delta for wheel events.
+    pub code: i32,
+}
+
+/// Turns recording of raw events on or off.
+///
+/// Stays off while privacy mode is on. Turning it off clears the recorded
+/// events so no keystroke log outlives the setting.
+pub fn set_debug_event_log(enabled: bool) {
+    let enabled = enabled && !privacy_mode_enabled();
+    DEBUG_EVENT_LOG.store(enabled, Ordering::SeqCst);
+    if !enabled {
+        clear_debug_events();
+    }
+}
+
+/// Returns true if the hooks are recording raw events.
+pub fn debug_event_log_enabled() -> bool {
+    DEBUG_EVENT_LOG.load(Ordering::Relaxed)
+}
+
+#[inline]
+fn pack_debug_event(kind: DebugEventKind, code: i32) -> u64 {
+    ((kind as u64) << 32) | (code as u32 as u64)
+}
+
+/// Writes one event into the next slot, overwriting the oldest.
+///
+/// Callers check `DEBUG_EVENT_LOG` first so the disabled path is a single
+/// atomic load. Nothing is recorded while tracking or monitoring is paused,
+/// so keys typed into privacy apps or during a user pause never reach the log.
+#[cold]
+fn record_debug_event(kind: DebugEventKind, code: i32) {
+    if crate::store::TRACKING_PAUSED.load(Ordering::Relaxed) || crate::store::is_monitoring_paused()
+    {
+        return;
+    }
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    push_debug_event(kind, code, timestamp_ms);
+}
+
+/// Stores an event in the ring buffer slot after the last one written.
+#[inline]
+fn push_debug_event(kind: DebugEventKind, code: i32, timestamp_ms: u64) {
+    let slot = DEBUG_EVENT_NEXT.fetch_add(1, Ordering::Relaxed) % DEBUG_EVENT_CAPACITY;
+    DEBUG_EVENTS[slot].store(0, Ordering::Release);
+    DEBUG_EVENT_TIMES[slot].store(timestamp_ms, Ordering::Release);
+    DEBUG_EVENTS[slot].store(pack_debug_event(kind, code), Ordering::Release);
+}
+
+/// Empties every slot of the ring buffer. The total recorded is kept.
+fn clear_debug_events() {
+    for (event, time) in DEBUG_EVENTS.iter().zip(&DEBUG_EVENT_TIMES) {
+        event.store(0, Ordering::Release);
+        time.store(0, Ordering::Release);
+    }
+}
+
+/// Returns the recorded events, oldest first, and the total ever recorded.
+///
+/// Slots being overwritten during the read are skipped.
+pub fn debug_events() -> (Vec<DebugEvent>, usize) {
+    let total = DEBUG_EVENT_NEXT.load(Ordering::Acquire);
+    let start = total.saturating_sub(DEBUG_EVENT_CAPACITY);
+    let events = (start..total)
+        .filter_map(|i| {
+            let slot = i % DEBUG_EVENT_CAPACITY;
+            let timestamp_ms = DEBUG_EVENT_TIMES[slot].load(Ordering::Acquire);
+            let packed = DEBUG_EVENTS[slot].load(Ordering::Acquire);
+            Some(DebugEvent {
+                timestamp_ms,
+                event: DebugEventKind::from_u8((packed >> 32) as u8)?,
+                code: packed as u32 as i32,
+            })
+        })
+        .collect();
+    (events, total)
+}
+
+// ============================================================================
+// Shortcut Detection
+// ============================================================================
+
+/// Returns the state bit for a Ctrl/Alt/Win virtual key, or 0 for other keys.
+///
+/// Left and right variants get separate bits so releasing one side
+/// doesn't clear the other.
+#[inline]
+fn modifier_bit(vk: u32) -> u32 {
+    const CONTROL: u32 = VK_CONTROL.0 as u32;
+    const LCONTROL: u32 = VK_LCONTROL.0 as u32;
+    const RCONTROL: u32 = VK_RCONTROL.0 as u32;
+    const MENU: u32 = VK_MENU.0 as u32;
+    const LMENU: u32 = VK_LMENU.0 as u32;
+    const RMENU: u32 = VK_RMENU.0 as u32;
+    const LWIN: u32 = VK_LWIN.0 as u32;
+    const RWIN: u32 = VK_RWIN.0 as u32;
+
+    match vk {
+        CONTROL => 1 << 0,
+        LCONTROL => 1 << 1,
+        RCONTROL => 1 << 2,
+        MENU => 1 << 3,
+        LMENU => 1 << 4,
+        RMENU => 1 << 5,
+        LWIN => 1 << 6,
+        RWIN => 1 << 7,
+        _ => 0,
+    }
+}
+
+/// Applies a key event to the modifier state.
+///
+/// Returns the new modifier state and whether the event is a shortcut:
+/// a non-modifier key going down while a modifier is held.
+#[inline]
+pub fn apply_chord_event(state: u32, vk: u32, is_down: bool) -> (u32, bool) {
+    let bit = modifier_bit(vk);
+    if bit != 0 {
+        let state = if is_down { state | bit } else { state & !bit };
+        (state, false)
+    } else {
+        (state, is_down && state != 0)
+    }
+}
+
+// ============================================================================
+// Key Classification
+// ============================================================================
+
+/// Kind of key, as counted in a [`KeyBreakdown`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyClass {
+    Alphanumeric = 0,
+    Whitespace = 1,
+    Navigation = 2,
+    Modifier = 3,
+    Other = 4,
+}
+
+/// Number of `KeyClass` variants.
+const KEY_CLASS_COUNT: usize = 5;
+
+/// Classifies a virtual key code.
+///
+/// Virtual key codes group each kind of key into a few contiguous ranges,
+/// so this is a handful of range comparisons rather than a per-key table.
+#[inline]
+pub fn classify_key(vk: u32) -> KeyClass {
+    const DIGIT_0: u32 = VK_0.0 as u32;
+    const DIGIT_9: u32 = VK_9.0 as u32;
+    const LETTER_A: u32 = VK_A.0 as u32;
+    const LETTER_Z: u32 = VK_Z.0 as u32;
+    const NUMPAD_0: u32 = VK_NUMPAD0.0 as u32;
+    const NUMPAD_9: u32 = VK_NUMPAD9.0 as u32;
+    const TAB: u32 = VK_TAB.0 as u32;
+    const RETURN: u32 = VK_RETURN.0 as u32;
+    const SPACE: u32 = VK_SPACE.0 as u32;
+    // Page Up, Page Down, End, Home, Left, Up, Right, Down
+    const NAV_FIRST: u32 = VK_PRIOR.0 as u32;
+    const NAV_LAST: u32 = VK_DOWN.0 as u32;
+    // Shift, Ctrl, Alt
+    const SHIFT: u32 = VK_SHIFT.0 as u32;
+    const MENU: u32 = VK_MENU.0 as u32;
+    const CAPITAL: u32 = VK_CAPITAL.0 as u32;
+    const LWIN: u32 = VK_LWIN.0 as u32;
+    const RWIN: u32 = VK_RWIN.0 as u32;
+    const F1: u32 = VK_F1.0 as u32;
+    const F24: u32 = VK_F24.0 as u32;
+    // Left/right Shift, Ctrl, Alt
+    const LSHIFT: u32 = VK_LSHIFT.0 as u32;
+    const RMENU: u32 = VK_RMENU.0 as u32;
+
+    match vk {
+        DIGIT_0..=DIGIT_9 | LETTER_A..=LETTER_Z | NUMPAD_0..=NUMPAD_9 => KeyClass::Alphanumeric,
+        TAB | RETURN | SPACE => KeyClass::Whitespace,
+        NAV_FIRST..=NAV_LAST => KeyClass::Navigation,
+        SHIFT..=MENU | CAPITAL | LWIN..=RWIN | F1..=F24 | LSHIFT..=RMENU => KeyClass::Modifier,
+        _ => KeyClass::Other,
+    }
+}
+
+// ============================================================================
+// Scroll Direction
+// ============================================================================
+
+/// Direction of a mouse wheel event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Classifies a wheel message by the sign of its delta.
+///
+/// The delta is the signed high word of `MSLLHOOKSTRUCT.mouseData`: positive
+/// is up (away from the user) for `WM_MOUSEWHEEL` and right for
+/// `WM_MOUSEHWHEEL`. Returns `None` for other messages or a zero delta.
+#[inline]
+pub fn classify_scroll(msg: u32, mouse_data: u32) -> Option<ScrollDirection> {
+    match (msg, wheel_delta(mouse_data).signum()) {
+        (_, 0) => None,
+        (WM_MOUSEWHEEL, 1) => Some(ScrollDirection::Up),
+        (WM_MOUSEWHEEL, _) => Some(ScrollDirection::Down),
+        (WM_MOUSEHWHEEL, 1) => Some(ScrollDirection::Right),
+        (WM_MOUSEHWHEEL, _) => Some(ScrollDirection::Left),
+        _ => None,
+    }
+}
+
+/// Signed wheel rotation from the high word of `mouseData`.
+#[inline]
+fn wheel_delta(mouse_data: u32) -> i16 {
+    (mouse_data >> 16) as u16 as i16
+}
+
+/// Packs a screen point into a single atomic-friendly value.
+#[inline]
+fn pack_point(x: i32, y: i32) -> u64 {
+    ((x as u32 as u64) << 32) | (y as u32 as u64)
+}
+
+/// Reverses `pack_point`; `None` for `NO_MOUSE_POS`.
+#[inline]
+fn unpack_point(packed: u64) -> Option<(i32, i32)> {
+    (packed != NO_MOUSE_POS).then_some(((packed >> 32) as u32 as i32, packed as u32 as i32))
+}
+
+/// Distance between two cursor positions in 1/`MOUSE_DISTANCE_SCALE` pixels.
+///
+/// Returns 0 when there is no previous position or the step is a jump
+/// longer than `MAX_MOUSE_STEP_PX`. Coordinates may be negative on
+/// multi-monitor setups.
+#[inline]
+pub fn mouse_step_distance(last: Option<(i32, i32)>, x: i32, y: i32) -> u64 {
+    let Some((last_x, last_y)) = last else {
+        return 0;
+    };
+    let dx = f64::from(x) - f64::from(last_x);
+    let dy = f64::from(y) - f64::from(last_y);
+    let distance = (dx * dx + dy * dy).sqrt();
+    if distance > MAX_MOUSE_STEP_PX {
+        return 0;
+    }
+    (distance * MOUSE_DISTANCE_SCALE as f64).round() as u64
+}
+
+/// Whether a button press completes a double-click.
+///
+/// Low-level hooks never receive `WM_*BUTTONDBLCLK` (Windows synthesizes
+/// those per window), so double-clicks are detected the way Windows does:
+/// a second press within `max_ms` and inside a `rect` (width, height)
+/// centered on the first press.
+#[inline]
+pub fn is_double_click(
+    last: Option<(u32, (i32, i32))>,
+    time: u32,
+    point: (i32, i32),
+    max_ms: u32,
+    rect: (i32, i32),
+) -> bool {
+    let Some((last_time, (last_x, last_y))) = last else {
+        return false;
+    };
+    time.wrapping_sub(last_time) <= max_ms
+        && (point.0 - last_x).abs() <= rect.0 / 2
+        && (point.1 - last_y).abs() <= rect.1 / 2
+}
+
+/// Records a left/right button press and counts it as a double-click if it
+/// completes one. A completed double-click resets the state, so a triple
+/// click counts once.
+#[inline]
+fn record_button_press(last_time: &AtomicU64, last_pos: &AtomicU64, time: u32, x: i32, y: i32) {
+    let last = match last_time.load(Ordering::Relaxed) {
+        NO_MOUSE_POS => None,
+        t => unpack_point(last_pos.load(Ordering::Relaxed)).map(|pos| (t as u32, pos)),
+    };
+    let rect = unpack_point(DOUBLE_CLICK_RECT.load(Ordering::Relaxed)).unwrap_or((4, 4));
+    let max_ms = DOUBLE_CLICK_TIME_MS.load(Ordering::Relaxed);
+
+    if is_double_click(last, time, (x, y), max_ms, rect) {
+        DOUBLE_CLICK_COUNT.fetch_add(1, Ordering::Relaxed);
+        last_time.store(NO_MOUSE_POS, Ordering::Relaxed);
+    } else {
+        last_time.store(time as u64, Ordering::Relaxed);
+        last_pos.store(pack_point(x, y), Ordering::Relaxed);
+    }
+}
+
+/// Reads the user's double-click time and rectangle from the system.
+///
+/// Call before installing the mouse hook; the hook itself only reads the
+/// cached values.
+pub fn load_double_click_settings() {
+    let (time_ms, cx, cy) = unsafe {
+        (
+            GetDoubleClickTime(),
+            GetSystemMetrics(SM_CXDOUBLECLK),
+            GetSystemMetrics(SM_CYDOUBLECLK),
+        )
+    };
+    if time_ms > 0 {
+        DOUBLE_CLICK_TIME_MS.store(time_ms, Ordering::Relaxed);
+    }
+    if cx > 0 && cy > 0 {
+        DOUBLE_CLICK_RECT.store(pack_point(cx, cy), Ordering::Relaxed);
+    }
+}
+
 // ============================================================================
 // Hook Callbacks
 // ============================================================================
 
 /// Low-level keyboard hook callback.
 ///
-/// Counts WM_KEYDOWN and WM_SYSKEYDOWN events (key presses).
-/// WM_KEYUP events are ignored to avoid double-counting.
+/// Counts WM_KEYDOWN and WM_SYSKEYDOWN events (key presses), in total and
+/// by `KeyClass`.
+/// WM_KEYUP events are not counted, only used to track held modifiers
+/// for shortcut detection.
 ///
 /// # Safety
 /// This function is called by Windows from the message pump thread.
@@ -60,12 +528,45 @@ pub unsafe extern "system" fn keyboard_hook_proc(
     if code == HC_ACTION as i32 {
         let msg = wparam.0 as u32;
 
-        // Only count key-down events
-        if msg == WM_KEYDOWN || msg == WM_SYSKEYDOWN {
-            // Optional: access the key info if needed in the future
-            // let _kb_struct = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        let is_down = msg == WM_KEYDOWN || msg == WM_SYSKEYDOWN;
+        let is_up = msg == WM_KEYUP || msg == WM_SYSKEYUP;
+
+        if is_down || is_up {
+            // Privacy mode: count the keystroke without reading which key it was
+            if KEY_PRIVACY_MODE.load(Ordering::Relaxed) {
+                if is_down {
+                    KEYSTROKE_COUNT.fetch_add(1, Ordering::Relaxed);
+                }
+                return CallNextHookEx(None, code, wparam, lparam);
+            }
+
+            let kb_struct = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+
+            if DEBUG_EVENT_LOG.load(Ordering::Relaxed) {
+                let kind = if is_down {
+                    DebugEventKind::KeyDown
+                } else {
+                    DebugEventKind::KeyUp
+                };
+                record_debug_event(kind, kb_struct.vkCode as i32);
+            }
+
+            // Only count key-down events
+            if is_down {
+                KEYSTROKE_COUNT.fetch_add(1, Ordering::Relaxed);
+                KEY_CLASS_COUNTS[classify_key(kb_struct.vkCode) as usize]
+                    .fetch_add(1, Ordering::Relaxed);
+            }
+
+            let state = MODIFIER_STATE.load(Ordering::Relaxed);
+            let (new_state, is_shortcut) = apply_chord_event(state, kb_struct.vkCode, is_down);
 
-            KEYSTROKE_COUNT.fetch_add(1, Ordering::Relaxed);
+            if new_state != state {
+                MODIFIER_STATE.store(new_state, Ordering::Relaxed);
+            }
+            if is_shortcut {
+                SHORTCUT_COUNT.fetch_add(1, Ordering::Relaxed);
+            }
         }
     }
 
@@ -73,10 +574,28 @@ pub unsafe extern "system" fn keyboard_hook_proc(
     CallNextHookEx(None, code, wparam, lparam)
 }
 
+/// Records a mouse button press or wheel event in the debug event log.
+///
+/// Movement is left out; it would push everything else out of the buffer.
+#[cold]
+fn record_mouse_debug_event(msg: u32, info: &MSLLHOOKSTRUCT) {
+    let (kind, code) = match msg {
+        WM_LBUTTONDOWN | WM_LBUTTONDBLCLK => (DebugEventKind::MouseDown, VK_LBUTTON.0 as i32),
+        WM_RBUTTONDOWN | WM_RBUTTONDBLCLK => (DebugEventKind::MouseDown, VK_RBUTTON.0 as i32),
+        WM_MBUTTONDOWN => (DebugEventKind::MouseDown, VK_MBUTTON.0 as i32),
+        WM_MOUSEWHEEL => (DebugEventKind::Wheel, wheel_delta(info.mouseData) as i32),
+        WM_MOUSEHWHEEL => (DebugEventKind::HWheel, wheel_delta(info.mouseData) as i32),
+        _ => return,
+    };
+    record_debug_event(kind, code);
+}
+
 /// Low-level mouse hook callback.
 ///
-/// Counts mouse button clicks (left, right, middle) and scroll events.
-/// Mouse movement events are ignored for performance.
+/// Counts mouse button clicks (left, right, middle), left/right
+/// double-clicks and scroll events, including the direction of vertical
+/// and horizontal wheel events, and accumulates cursor travel distance
+/// from movement events.
 ///
 /// # Safety
 /// This function is called by Windows from the message pump thread.
@@ -89,21 +608,74 @@ pub unsafe extern "system" fn mouse_hook_proc(
     if code == HC_ACTION as i32 {
         let msg = wparam.0 as u32;
 
+        if DEBUG_EVENT_LOG.load(Ordering::Relaxed) {
+            record_mouse_debug_event(msg, &*(lparam.0 as *const MSLLHOOKSTRUCT));
+        }
+
         match msg {
+            WM_MOUSEMOVE => {
+                let point = (*(lparam.0 as *const MSLLHOOKSTRUCT)).pt;
+                let last = LAST_MOUSE_POS.swap(pack_point(point.x, point.y), Ordering::Relaxed);
+                let step = mouse_step_distance(unpack_point(last), point.x, point.y);
+                if step > 0 {
+                    MOUSE_DISTANCE.fetch_add(step, Ordering::Relaxed);
+                }
+            }
             WM_LBUTTONDOWN => {
                 LEFT_CLICK_COUNT.fetch_add(1, Ordering::Relaxed);
+                let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+                record_button_press(
+                    &LAST_LEFT_DOWN,
+                    &LAST_LEFT_DOWN_POS,
+                    info.time,
+                    info.pt.x,
+                    info.pt.y,
+                );
             }
             WM_RBUTTONDOWN => {
                 RIGHT_CLICK_COUNT.fetch_add(1, Ordering::Relaxed);
+                let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+                record_button_press(
+                    &LAST_RIGHT_DOWN,
+                    &LAST_RIGHT_DOWN_POS,
+                    info.time,
+                    info.pt.x,
+                    info.pt.y,
+                );
+            }
+            WM_LBUTTONDBLCLK | WM_RBUTTONDBLCLK => {
+                // Not sent to low-level hooks by Windows, but if another hook
+                // forwards one it replaces the second press of the pair.
+                let counter = if msg == WM_LBUTTONDBLCLK {
+                    &LEFT_CLICK_COUNT
+                } else {
+                    &RIGHT_CLICK_COUNT
+                };
+                counter.fetch_add(1, Ordering::Relaxed);
+                DOUBLE_CLICK_COUNT.fetch_add(1, Ordering::Relaxed);
             }
             WM_MBUTTONDOWN => {
                 MIDDLE_CLICK_COUNT.fetch_add(1, Ordering::Relaxed);
             }
-            WM_MOUSEWHEEL => {
-                SCROLL_COUNT.fetch_add(1, Ordering::Relaxed);
+            WM_MOUSEWHEEL | WM_MOUSEHWHEEL => {
+                if msg == WM_MOUSEWHEEL {
+                    SCROLL_COUNT.fetch_add(1, Ordering::Relaxed);
+                }
+
+                let mouse_struct = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+                let counter = match classify_scroll(msg, mouse_struct.mouseData) {
+                    Some(ScrollDirection::Up) => Some(&SCROLL_UP_COUNT),
+                    Some(ScrollDirection::Down) => Some(&SCROLL_DOWN_COUNT),
+                    Some(ScrollDirection::Left) => Some(&SCROLL_LEFT_COUNT),
+                    Some(ScrollDirection::Right) => Some(&SCROLL_RIGHT_COUNT),
+                    None => None,
+                };
+                if let Some(counter) = counter {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
             }
             _ => {
-                // Ignore mouse movement and other events
+                // Ignore other events
             }
         }
     }
@@ -142,6 +714,57 @@ pub fn flush_scroll_count() -> u64 {
     SCROLL_COUNT.swap(0, Ordering::Relaxed)
 }
 
+/// Atomically reads and resets the scroll direction counters.
+///
+/// Returns (up, down, left, right).
+#[inline]
+pub fn flush_scroll_directions() -> (u64, u64, u64, u64) {
+    (
+        SCROLL_UP_COUNT.swap(0, Ordering::Relaxed),
+        SCROLL_DOWN_COUNT.swap(0, Ordering::Relaxed),
+        SCROLL_LEFT_COUNT.swap(0, Ordering::Relaxed),
+        SCROLL_RIGHT_COUNT.swap(0, Ordering::Relaxed),
+    )
+}
+
+/// Atomically reads and resets the shortcut counter.
+#[inline]
+pub fn flush_shortcut_count() -> u64 {
+    SHORTCUT_COUNT.swap(0, Ordering::Relaxed)
+}
+
+/// Atomically reads and resets the per-class keystroke counters.
+#[inline]
+pub fn flush_key_breakdown() -> KeyBreakdown {
+    let take = |class: KeyClass| KEY_CLASS_COUNTS[class as usize].swap(0, Ordering::Relaxed);
+    KeyBreakdown {
+        alphanumeric: take(KeyClass::Alphanumeric),
+        whitespace: take(KeyClass::Whitespace),
+        navigation: take(KeyClass::Navigation),
+        modifier: take(KeyClass::Modifier),
+        other: take(KeyClass::Other),
+    }
+}
+
+/// Atomically reads and resets the double-click counter.
+#[inline]
+pub fn flush_double_clicks() -> u64 {
+    DOUBLE_CLICK_COUNT.swap(0, Ordering::Relaxed)
+}
+
+/// Reads and resets the mouse travel distance.
+///
+/// Returns whole pixels; the sub-pixel remainder is kept for the next flush.
+#[inline]
+pub fn flush_mouse_distance() -> u64 {
+    let scaled = MOUSE_DISTANCE.swap(0, Ordering::Relaxed);
+    let remainder = scaled % MOUSE_DISTANCE_SCALE;
+    if remainder > 0 {
+        MOUSE_DISTANCE.fetch_add(remainder, Ordering::Relaxed);
+    }
+    scaled / MOUSE_DISTANCE_SCALE
+}
+
 /// Reads current counter values without resetting them.
 ///
 /// Useful for debugging or status display.
@@ -163,6 +786,19 @@ pub fn reset_all_counts() {
     RIGHT_CLICK_COUNT.store(0, Ordering::Relaxed);
     MIDDLE_CLICK_COUNT.store(0, Ordering::Relaxed);
     SCROLL_COUNT.store(0, Ordering::Relaxed);
+    SCROLL_UP_COUNT.store(0, Ordering::Relaxed);
+    SCROLL_DOWN_COUNT.store(0, Ordering::Relaxed);
+    SCROLL_LEFT_COUNT.store(0, Ordering::Relaxed);
+    SCROLL_RIGHT_COUNT.store(0, Ordering::Relaxed);
+    SHORTCUT_COUNT.store(0, Ordering::Relaxed);
+    for counter in &KEY_CLASS_COUNTS {
+        counter.store(0, Ordering::Relaxed);
+    }
+    DOUBLE_CLICK_COUNT.store(0, Ordering::Relaxed);
+    LAST_LEFT_DOWN.store(NO_MOUSE_POS, Ordering::Relaxed);
+    LAST_RIGHT_DOWN.store(NO_MOUSE_POS, Ordering::Relaxed);
+    MOUSE_DISTANCE.store(0, Ordering::Relaxed);
+    LAST_MOUSE_POS.store(NO_MOUSE_POS, Ordering::Relaxed);
 }
 
 #[cfg(test)]
@@ -213,4 +849,215 @@ mod tests {
         assert_eq!(RIGHT_CLICK_COUNT.load(Ordering::Relaxed), 0);
         assert_eq!(MIDDLE_CLICK_COUNT.load(Ordering::Relaxed), 0);
     }
+
+    #[test]
+    fn test_classify_scroll_delta_sign() {
+        // WHEEL_DELTA (120) and multiples in the high word; low word is reserved
+        let data = |delta: i16| ((delta as u16 as u32) << 16) | 0x1234;
+
+        assert_eq!(
+            classify_scroll(WM_MOUSEWHEEL, data(120)),
+            Some(ScrollDirection::Up)
+        );
+        assert_eq!(
+            classify_scroll(WM_MOUSEWHEEL, data(-120)),
+            Some(ScrollDirection::Down)
+        );
+        assert_eq!(
+            classify_scroll(WM_MOUSEWHEEL, data(-360)),
+            Some(ScrollDirection::Down)
+        );
+        assert_eq!(
+            classify_scroll(WM_MOUSEWHEEL, data(1)),
+            Some(ScrollDirection::Up)
+        );
+        assert_eq!(
+            classify_scroll(WM_MOUSEHWHEEL, data(120)),
+            Some(ScrollDirection::Right)
+        );
+        assert_eq!(
+            classify_scroll(WM_MOUSEHWHEEL, data(-120)),
+            Some(ScrollDirection::Left)
+        );
+        assert_eq!(classify_scroll(WM_MOUSEWHEEL, data(0)), None);
+        assert_eq!(classify_scroll(WM_LBUTTONDOWN, data(120)), None);
+    }
+
+    #[test]
+    fn test_mouse_step_distance() {
+        // First event has nothing to measure from
+        assert_eq!(mouse_step_distance(None, 10, 10), 0);
+        // 3-4-5 triangle, in hundredths of a pixel
+        assert_eq!(mouse_step_distance(Some((0, 0)), 3, 4), 500);
+        // Negative coordinates (monitor left of the primary one)
+        assert_eq!(mouse_step_distance(Some((-10, 0)), -7, -4), 500);
+        // Jumps across the virtual screen are ignored
+        assert_eq!(mouse_step_distance(Some((0, 0)), 3000, 0), 0);
+
+        assert_eq!(unpack_point(pack_point(-1920, 1080)), Some((-1920, 1080)));
+        assert_eq!(unpack_point(NO_MOUSE_POS), None);
+    }
+
+    #[test]
+    fn test_is_double_click() {
+        let first = Some((1_000, (100, 100)));
+        assert!(is_double_click(first, 1_300, (101, 99), 500, (4, 4)));
+        // Too slow
+        assert!(!is_double_click(first, 1_501, (100, 100), 500, (4, 4)));
+        // Moved outside the double-click rectangle
+        assert!(!is_double_click(first, 1_100, (103, 100), 500, (4, 4)));
+        // No earlier press
+        assert!(!is_double_click(None, 1_100, (100, 100), 500, (4, 4)));
+        // Tick count wraparound
+        assert!(is_double_click(
+            Some((u32::MAX - 100, (0, 0))),
+            100,
+            (0, 0),
+            500,
+            (4, 4)
+        ));
+    }
+
+    #[test]
+    fn test_classify_key_ranges() {
+        let cases = [
+            (0x41, KeyClass::Alphanumeric), // A
+            (0x5A, KeyClass::Alphanumeric), // Z
+            (0x30, KeyClass::Alphanumeric), // 0
+            (VK_NUMPAD9.0 as u32, KeyClass::Alphanumeric),
+            (VK_SPACE.0 as u32, KeyClass::Whitespace),
+            (VK_RETURN.0 as u32, KeyClass::Whitespace),
+            (VK_TAB.0 as u32, KeyClass::Whitespace),
+            (VK_PRIOR.0 as u32, KeyClass::Navigation),
+            (VK_DOWN.0 as u32, KeyClass::Navigation),
+            (VK_LSHIFT.0 as u32, KeyClass::Modifier),
+            (VK_CONTROL.0 as u32, KeyClass::Modifier),
+            (VK_RWIN.0 as u32, KeyClass::Modifier),
+            (VK_F1.0 as u32, KeyClass::Modifier),
+            (VK_F24.0 as u32, KeyClass::Modifier),
+            (0x08, KeyClass::Other), // Backspace
+            (0x1B, KeyClass::Other), // Escape
+            (0x2E, KeyClass::Other), // Delete
+            (0xBE, KeyClass::Other), // Period
+        ];
+        for (vk, expected) in cases {
+            assert_eq!(classify_key(vk), expected, "vk={:#x}", vk);
+        }
+    }
+
+    #[test]
+    fn test_flush_key_breakdown() {
+        reset_all_counts();
+
+        KEY_CLASS_COUNTS[KeyClass::Alphanumeric as usize].store(7, Ordering::Relaxed);
+        KEY_CLASS_COUNTS[KeyClass::Navigation as usize].store(2, Ordering::Relaxed);
+        let breakdown = flush_key_breakdown();
+
+        assert_eq!(breakdown.alphanumeric, 7);
+        assert_eq!(breakdown.navigation, 2);
+        assert_eq!(breakdown.total(), 9);
+        assert_eq!(flush_key_breakdown(), KeyBreakdown::default());
+    }
+
+    #[test]
+    fn test_chord_detection_sequence() {
+        const KEY_C: u32 = 0x43;
+        const KEY_V: u32 = 0x56;
+        let lctrl = VK_LCONTROL.0 as u32;
+        let rctrl = VK_RCONTROL.0 as u32;
+        let lwin = VK_LWIN.0 as u32;
+
+        // (vk, is_down, expected shortcut)
+        let events = [
+            (KEY_C, true, false),  // plain "c"
+            (KEY_C, false, false), //
+            (lctrl, true, false),  // Ctrl down
+            (lctrl, true, false),  // Ctrl auto-repeat
+            (KEY_C, true, true),   // Ctrl+C
+            (KEY_C, false, false), //
+            (KEY_V, true, true),   // Ctrl+V (Ctrl still held)
+            (rctrl, true, false),  // both Ctrls held
+            (lctrl, false, false), // left released, right still held
+            (KEY_V, true, true),   // RCtrl+V
+            (rctrl, false, false), // all modifiers released
+            (KEY_V, true, false),  // plain "v"
+            (lwin, true, false),   // Win down
+            (KEY_C, true, true),   // Win+C
+        ];
+
+        let mut state = 0;
+        let mut shortcuts = 0;
+        for (vk, is_down, expected) in events {
+            let (next, is_shortcut) = apply_chord_event(state, vk, is_down);
+            assert_eq!(is_shortcut, expected, "vk={:#x} down={}", vk, is_down);
+            state = next;
+            shortcuts += is_shortcut as u32;
+        }
+        assert_eq!(shortcuts, 4);
+    }
+
+    /// Serializes tests that touch the debug event log.
+    static DEBUG_LOG_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn test_debug_event_log_wraps_around() {
+        let _guard = DEBUG_LOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let before = debug_events().1;
+        let extra = 5;
+        for i in 0..DEBUG_EVENT_CAPACITY + extra {
+            push_debug_event(DebugEventKind::KeyDown, i as i32, 1_000 + i as u64);
+        }
+        push_debug_event(DebugEventKind::Wheel, -120, 9_999);
+
+        let (events, total) = debug_events();
+        assert_eq!(total, before + DEBUG_EVENT_CAPACITY + extra + 1);
+        assert_eq!(events.len(), DEBUG_EVENT_CAPACITY);
+
+        // The oldest entries were overwritten; order is oldest first
+        assert_eq!(events[0].code, extra as i32 + 1);
+        assert_eq!(events[0].timestamp_ms, 1_000 + extra as u64 + 1);
+        assert_eq!(
+            events.last(),
+            Some(&DebugEvent {
+                timestamp_ms: 9_999,
+                event: DebugEventKind::Wheel,
+                code: -120,
+            })
+        );
+    }
+
+    #[test]
+    fn test_privacy_mode_overrides_debug_event_log() {
+        let _guard = DEBUG_LOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        set_debug_event_log(true);
+        push_debug_event(DebugEventKind::KeyDown, 0x41, 1_000);
+        assert!(!debug_events().0.is_empty());
+
+        // Enabling privacy mode stops the log and drops recorded keys
+        set_privacy_mode(true);
+        assert!(privacy_mode_enabled());
+        assert!(!debug_event_log_enabled());
+        assert!(debug_events().0.is_empty());
+
+        // The log's own setting can't turn it back on
+        set_debug_event_log(true);
+        assert!(!debug_event_log_enabled());
+
+        set_privacy_mode(false);
+        set_debug_event_log(true);
+        assert!(debug_event_log_enabled());
+        set_debug_event_log(false);
+    }
+
+    #[test]
+    fn test_disabling_debug_event_log_clears_events() {
+        let _guard = DEBUG_LOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        set_debug_event_log(true);
+        push_debug_event(DebugEventKind::KeyDown, 0x41, 1_000);
+        assert!(!debug_events().0.is_empty());
+
+        set_debug_event_log(false);
+        assert!(!debug_event_log_enabled());
+        assert!(debug_events().0.is_empty());
+    }
 }
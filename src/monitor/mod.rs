@@ -3,8 +3,14 @@
 //! This module contains the input hook handlers and window polling logic
 //! for tracking user activity.
 
+pub mod category_alert;
+pub mod focus_mode;
+pub mod goals;
 pub mod input_hooks;
+pub mod title_redaction;
 pub mod window_poller;
+pub mod window_source;
 
 pub use input_hooks::*;
 pub use window_poller::*;
+pub use window_source::*;
@@ -0,0 +1,197 @@
+//! Redacts window titles before they are stored or broadcast.
+//!
+//! `title_redaction` selects the mode (`none`, `hash` or `strip`) and the
+//! `redaction_patterns` table holds the regexes that mark sensitive text.
+//! With no patterns configured, the whole title is treated as sensitive.
+
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+
+/// Replacement for matched text in `strip` mode.
+pub const STRIP_REPLACEMENT: &str = "***";
+
+/// Number of hex characters kept from the title hash in `hash` mode.
+const HASH_PREFIX_LEN: usize = 16;
+
+/// How window titles are redacted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactionMode {
+    /// Titles are kept as-is.
+    None,
+    /// Sensitive titles are replaced by a SHA256 prefix of the full title.
+    Hash,
+    /// Sensitive text is replaced with `***`.
+    Strip,
+}
+
+impl RedactionMode {
+    /// Parses a `title_redaction` value.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "none" => Some(Self::None),
+            "hash" => Some(Self::Hash),
+            "strip" => Some(Self::Strip),
+            _ => None,
+        }
+    }
+}
+
+/// Applies the configured redaction mode to window titles.
+#[derive(Debug, Clone)]
+pub struct TitleRedactor {
+    mode: RedactionMode,
+    patterns: Vec<Regex>,
+}
+
+impl TitleRedactor {
+    /// Compiles the given patterns, skipping (and logging) invalid ones.
+    pub fn new<S: AsRef<str>>(mode: RedactionMode, patterns: &[S]) -> Self {
+        let patterns = patterns
+            .iter()
+            .filter_map(|p| match Regex::new(p.as_ref()) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    tracing::warn!(pattern = p.as_ref(), error = %e, "Invalid redaction pattern");
+                    None
+                }
+            })
+            .collect();
+        Self { mode, patterns }
+    }
+
+    /// Returns the title as it may be stored.
+    ///
+    /// `strip` replaces each match with `***`; `hash` replaces a title that
+    /// matches any pattern with `sha256:<prefix>`. Empty titles are kept.
+    pub fn redact<'a>(&self, title: &'a str) -> Cow<'a, str> {
+        if title.is_empty() {
+            return Cow::Borrowed(title);
+        }
+        match self.mode {
+            RedactionMode::None => Cow::Borrowed(title),
+            RedactionMode::Strip if self.patterns.is_empty() => {
+                Cow::Owned(STRIP_REPLACEMENT.to_string())
+            }
+            RedactionMode::Strip => {
+                let mut out = Cow::Borrowed(title);
+                for re in &self.patterns {
+                    if let Cow::Owned(replaced) = re.replace_all(&out, STRIP_REPLACEMENT) {
+                        out = Cow::Owned(replaced);
+                    }
+                }
+                out
+            }
+            RedactionMode::Hash => {
+                if self.patterns.is_empty() || self.patterns.iter().any(|re| re.is_match(title)) {
+                    Cow::Owned(hash_title(title))
+                } else {
+                    Cow::Borrowed(title)
+                }
+            }
+        }
+    }
+}
+
+impl Default for TitleRedactor {
+    fn default() -> Self {
+        Self::new::<&str>(RedactionMode::None, &[])
+    }
+}
+
+/// Hashes a title to `sha256:` plus a prefix of its hex digest.
+///
+/// Equal titles hash equally, so time per title can still be grouped.
+pub fn hash_title(title: &str) -> String {
+    let digest = hex::encode(Sha256::digest(title.as_bytes()));
+    format!("sha256:{}", &digest[..HASH_PREFIX_LEN])
+}
+
+/// Builds the title redactor from `title_redaction` and `redaction_patterns`.
+///
+/// Unknown modes fall back to `none`.
+pub fn load_title_redactor() -> TitleRedactor {
+    crate::store::with_db(title_redactor_from).unwrap_or_default()
+}
+
+/// Builds the title redactor from the settings in `db`.
+fn title_redactor_from(db: &crate::database::Database) -> TitleRedactor {
+    let value = db
+        .get_config("title_redaction")
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let mode = match RedactionMode::parse(&value) {
+        Some(mode) => mode,
+        None => {
+            if !value.is_empty() {
+                tracing::warn!(value = %value, "Invalid title_redaction, titles are not redacted");
+            }
+            RedactionMode::None
+        }
+    };
+    if mode == RedactionMode::None {
+        return TitleRedactor::default();
+    }
+
+    let patterns: Vec<String> = db
+        .get_redaction_patterns()
+        .map(|entries| entries.into_iter().map(|e| e.pattern).collect())
+        .unwrap_or_default();
+    TitleRedactor::new(mode, &patterns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(RedactionMode::parse("none"), Some(RedactionMode::None));
+        assert_eq!(RedactionMode::parse(" Hash "), Some(RedactionMode::Hash));
+        assert_eq!(RedactionMode::parse("strip"), Some(RedactionMode::Strip));
+        assert_eq!(RedactionMode::parse("mask"), None);
+    }
+
+    #[test]
+    fn test_strip_replaces_matches() {
+        let redactor =
+            TitleRedactor::new(RedactionMode::Strip, &[r"[\w.]+@[\w.]+", r"\d{4}-\d{4}"]);
+        assert_eq!(
+            redactor.redact("Inbox - alice@example.com - Mail"),
+            "Inbox - *** - Mail"
+        );
+        assert_eq!(redactor.redact("Card 1234-5678"), "Card ***");
+        assert_eq!(redactor.redact("Notepad"), "Notepad");
+        assert_eq!(redactor.redact(""), "");
+    }
+
+    #[test]
+    fn test_strip_without_patterns_hides_title() {
+        let redactor = TitleRedactor::new::<&str>(RedactionMode::Strip, &[]);
+        assert_eq!(redactor.redact("Secret plans.docx"), STRIP_REPLACEMENT);
+    }
+
+    #[test]
+    fn test_hash_replaces_matching_titles() {
+        let redactor = TitleRedactor::new(RedactionMode::Hash, &["(?i)bank"]);
+        let hashed = redactor.redact("My Bank - Account");
+        assert_eq!(hashed, hash_title("My Bank - Account"));
+        assert!(hashed.starts_with("sha256:"));
+        assert_eq!(hashed.len(), "sha256:".len() + HASH_PREFIX_LEN);
+        assert_eq!(redactor.redact("Notepad"), "Notepad");
+
+        let all = TitleRedactor::new::<&str>(RedactionMode::Hash, &[]);
+        assert_eq!(all.redact("Notepad"), hash_title("Notepad"));
+    }
+
+    #[test]
+    fn test_none_and_invalid_patterns() {
+        let redactor = TitleRedactor::new(RedactionMode::None, &["secret"]);
+        assert_eq!(redactor.redact("secret"), "secret");
+
+        // Invalid patterns are skipped rather than hiding every title
+        let redactor = TitleRedactor::new(RedactionMode::Strip, &["(", "secret"]);
+        assert_eq!(redactor.redact("my secret doc"), "my *** doc");
+    }
+}
@@ -3,16 +3,20 @@
 //! This module provides the polling loop that monitors the foreground window
 //! and detects when focus changes between applications.
 
-use crate::media::fetch_current_media;
-use crate::monitor::input_hooks::{flush_click_counts, flush_keystroke_count, flush_scroll_count};
-use crate::store::ACTIVITY_STORE;
-use crate::winapi_utils::{
-    get_foreground_window, get_process_name, get_window_text, get_window_thread_process_id,
+use crate::media::{MediaSource, SystemMediaSource};
+use crate::monitor::category_alert::check_category_alert;
+use crate::monitor::focus_mode::check_focus_mode;
+use crate::monitor::input_hooks::{
+    flush_click_counts, flush_double_clicks, flush_key_breakdown, flush_keystroke_count,
+    flush_mouse_distance, flush_scroll_count, flush_scroll_directions, flush_shortcut_count,
 };
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use crate::monitor::window_source::WindowSource;
+use crate::store::{ActivityStore, IdleStateChange, KeyBreakdown, ACTIVITY_STORE, TRACKING_PAUSED};
+use crate::winapi_utils::SessionChange;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Configuration for the window poller.
 #[derive(Debug, Clone)]
@@ -22,6 +26,13 @@ pub struct PollerConfig {
 
     /// Whether to track window title changes within the same process.
     pub track_title_changes: bool,
+
+    /// Whether the input hooks record raw events for `/api/debug/events`.
+    pub debug_event_log: bool,
+
+    /// Whether the keyboard hook only counts keystrokes, overriding
+    /// `debug_event_log`.
+    pub privacy_mode: bool,
 }
 
 impl Default for PollerConfig {
@@ -29,7 +40,144 @@ impl Default for PollerConfig {
         Self {
             poll_interval: Duration::from_millis(100),
             track_title_changes: false,
+            debug_event_log: false,
+            privacy_mode: false,
+        }
+    }
+}
+
+impl PollerConfig {
+    /// Reads `poll_interval_ms`, `track_title_changes`, `debug_event_log`
+    /// and `privacy_mode` from config, falling back to the defaults.
+    pub fn load() -> Self {
+        let default = Self::default();
+        crate::store::with_db(|db| {
+            let get = |key: &str| db.get_config(key).ok().flatten();
+            Self {
+                poll_interval: get("poll_interval_ms")
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&ms| ms > 0)
+                    .map(Duration::from_millis)
+                    .unwrap_or(default.poll_interval),
+                track_title_changes: get("track_title_changes")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(default.track_title_changes),
+                debug_event_log: get("debug_event_log")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(default.debug_event_log),
+                privacy_mode: get("privacy_mode")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(default.privacy_mode),
+            }
+        })
+        .unwrap_or(default)
+    }
+}
+
+/// Current polling interval in milliseconds, read by the polling thread every cycle.
+static POLL_INTERVAL_MS: AtomicU64 = AtomicU64::new(100);
+
+/// Changes the polling interval of the running polling thread.
+pub fn set_poll_interval(interval: Duration) {
+    POLL_INTERVAL_MS.store(interval.as_millis().max(1) as u64, Ordering::SeqCst);
+}
+
+/// How often pending sessions are saved to the database.
+///
+/// Measured in wall-clock time, so the cadence holds when the poll interval changes.
+const DB_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the polling thread re-reads its config from the database.
+const CONFIG_RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Shortest time the window focused at startup must hold focus (or get
+/// input) before it starts a session, so a window that only flashes while
+/// OwnMon starts doesn't leave a zero-length session.
+const STARTUP_FOCUS_GRACE: Duration = Duration::from_secs(2);
+
+/// Longest the polling thread sleeps between checks of the shutdown flag,
+/// so it exits promptly even with a long poll interval.
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long [`join_polling_thread`] waits at shutdown before giving up.
+pub const POLLER_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Minimum gap between `session_change` broadcasts. Changes arriving sooner
+/// are coalesced and only the latest is sent once the gap has passed.
+const SESSION_CHANGE_GAP: Duration = Duration::from_millis(500);
+
+/// Holds back `session_change` broadcasts that follow each other too quickly.
+#[derive(Debug)]
+struct BroadcastCoalescer {
+    last_sent: Option<Instant>,
+    pending: Option<serde_json::Value>,
+}
+
+impl BroadcastCoalescer {
+    const fn new() -> Self {
+        Self {
+            last_sent: None,
+            pending: None,
+        }
+    }
+
+    /// Queues `data`, returning it if it can be sent right away.
+    fn offer(&mut self, data: serde_json::Value, now: Instant) -> Option<serde_json::Value> {
+        self.pending = Some(data);
+        self.flush(now)
+    }
+
+    /// Returns the latest held payload once the gap since the last send has passed.
+    fn flush(&mut self, now: Instant) -> Option<serde_json::Value> {
+        let ready = self
+            .last_sent
+            .is_none_or(|sent| now.duration_since(sent) >= SESSION_CHANGE_GAP);
+        if !ready {
+            return None;
         }
+        let data = self.pending.take()?;
+        self.last_sent = Some(now);
+        Some(data)
+    }
+}
+
+/// Pending `session_change` broadcasts (see [`SESSION_CHANGE_GAP`]).
+static SESSION_CHANGES: Mutex<BroadcastCoalescer> = Mutex::new(BroadcastCoalescer::new());
+
+/// Media details of the last `media_update` sent, without the position, so
+/// unchanged media isn't re-broadcast.
+static LAST_MEDIA_BROADCAST: Mutex<Option<serde_json::Value>> = Mutex::new(None);
+
+/// Broadcasts a `session_change`, coalescing it with others sent just before.
+fn broadcast_session_change(data: serde_json::Value) {
+    let ready = SESSION_CHANGES
+        .lock()
+        .ok()
+        .and_then(|mut changes| changes.offer(data, Instant::now()));
+    if let Some(data) = ready {
+        crate::store::broadcast_update("session_change", &data);
+    }
+}
+
+/// Sends a coalesced `session_change` whose gap has passed.
+fn flush_session_change() {
+    let ready = SESSION_CHANGES
+        .lock()
+        .ok()
+        .and_then(|mut changes| changes.flush(Instant::now()));
+    if let Some(data) = ready {
+        crate::store::broadcast_update("session_change", &data);
+    }
+}
+
+/// Records a lock or unlock reported by the message loop.
+///
+/// The next poll cycle then splits off the idle time, marked as locked.
+pub fn handle_session_change(change: SessionChange) {
+    let locked = change == SessionChange::Locked;
+    tracing::info!(locked, "Workstation lock state changed");
+    if let Ok(mut store) = ACTIVITY_STORE.write() {
+        store.set_screen_locked(locked);
     }
 }
 
@@ -41,6 +189,7 @@ impl Default for PollerConfig {
 /// 3. Flushes input counters to the current session
 ///
 /// # Arguments
+/// * `source` - Where foreground window information is read from
 /// * `shutdown` - Atomic flag to signal thread termination
 /// * `config` - Polling configuration
 ///
@@ -50,106 +199,334 @@ impl Default for PollerConfig {
 /// # Example
 /// ```ignore
 /// let shutdown = Arc::new(AtomicBool::new(false));
-/// let handle = spawn_polling_thread(Arc::clone(&shutdown), PollerConfig::default());
+/// let handle = spawn_polling_thread(
+///     WinApiWindowSource,
+///     Arc::clone(&shutdown),
+///     PollerConfig::default(),
+/// );
 ///
 /// // ... run message loop ...
 ///
 /// shutdown.store(true, Ordering::SeqCst);
-/// handle.join().unwrap();
+/// join_polling_thread(handle, POLLER_SHUTDOWN_TIMEOUT);
 /// ```
-pub fn spawn_polling_thread(shutdown: Arc<AtomicBool>, config: PollerConfig) -> JoinHandle<()> {
+pub fn spawn_polling_thread(
+    source: impl WindowSource + Send + 'static,
+    shutdown: Arc<AtomicBool>,
+    mut config: PollerConfig,
+) -> JoinHandle<()> {
+    set_poll_interval(config.poll_interval);
+    crate::monitor::set_privacy_mode(config.privacy_mode);
+    crate::monitor::set_debug_event_log(config.debug_event_log);
+
     thread::spawn(move || {
         tracing::info!(
             interval_ms = config.poll_interval.as_millis(),
             "Window polling thread started"
         );
 
-        let mut last_hwnd: Option<isize> = None;
-        let mut last_title: String = String::new();
-        let mut db_save_counter: u32 = 0;
-        const DB_SAVE_INTERVAL: u32 = 50; // Every 50 cycles (~5 seconds at 100ms)
+        let mut state = PollState::default();
+        let mut last_save = Instant::now();
+        let mut last_checkpoint = Instant::now();
+        let mut last_prune = Instant::now();
+        let mut last_wal_checkpoint = Instant::now();
+        let mut last_config_reload = Instant::now();
+        let started = Instant::now();
 
         loop {
             // Check for idle and split session if needed
-            if let Ok(mut store) = ACTIVITY_STORE.write() {
-                (*store).check_and_split_on_idle();
+            let screensaver_running = source.screensaver_running();
+            let idle_change = match ACTIVITY_STORE.write() {
+                Ok(mut store) => {
+                    store.screensaver_running = screensaver_running;
+                    let change = store.check_and_split_on_idle();
+                    store.split_at_day_boundary(chrono::Utc::now());
+                    change
+                }
+                Err(_) => None,
+            };
+            if let Some(change) = idle_change {
+                crate::store::broadcast_update("idle_state", &change);
             }
 
             if shutdown.load(Ordering::SeqCst) {
                 break;
             }
 
-            poll_cycle(&mut last_hwnd, &mut last_title, config.track_title_changes); // Periodic database save for crash safety
-            db_save_counter += 1;
-            if db_save_counter >= DB_SAVE_INTERVAL {
-                db_save_counter = 0;
+            // Adopt config changes made while running
+            if last_config_reload.elapsed() >= CONFIG_RELOAD_INTERVAL {
+                last_config_reload = Instant::now();
+                let reloaded = PollerConfig::load();
+                if reloaded.poll_interval != config.poll_interval
+                    || reloaded.track_title_changes != config.track_title_changes
+                {
+                    tracing::info!(
+                        interval_ms = reloaded.poll_interval.as_millis(),
+                        track_title_changes = reloaded.track_title_changes,
+                        "Poller config reloaded"
+                    );
+                    set_poll_interval(reloaded.poll_interval);
+                }
+                crate::monitor::set_privacy_mode(reloaded.privacy_mode);
+                crate::monitor::set_debug_event_log(reloaded.debug_event_log);
+                config = reloaded;
+            }
+
+            // Without hooks, follow the system's last input time for idle detection
+            if !crate::store::input_hooks_active() {
+                track_input_without_hooks();
+            }
+
+            poll_cycle(
+                &source,
+                &SystemMediaSource,
+                &ACTIVITY_STORE,
+                &mut state,
+                config.track_title_changes,
+                started.elapsed() < STARTUP_FOCUS_GRACE,
+            );
+            flush_session_change();
+
+            // Periodic database save for crash safety
+            if last_save.elapsed() >= DB_SAVE_INTERVAL {
+                last_save = Instant::now();
+
+                // Close media sessions the player stopped reporting or that ran too long
+                let max_media_secs = crate::store::max_media_session_secs();
+                if let Ok(mut store) = ACTIVITY_STORE.write() {
+                    store.finalize_stale_media(chrono::Utc::now(), max_media_secs);
+                }
+
                 crate::store::save_pending_to_db();
+
+                // Checkpoint the current session's running counts for crash recovery
+                let interval = current_save_interval();
+                if !interval.is_zero() && last_checkpoint.elapsed() >= interval {
+                    last_checkpoint = Instant::now();
+                    crate::store::checkpoint_current_session();
+                }
+
+                // Apply the retention policy to memory and the database
+                let interval = prune_interval();
+                if !interval.is_zero() && last_prune.elapsed() >= interval {
+                    last_prune = Instant::now();
+                    crate::store::prune_old_data();
+                }
+
+                // Keep the write-ahead log from growing without bound
+                let interval = wal_checkpoint_interval();
+                if !interval.is_zero() && last_wal_checkpoint.elapsed() >= interval {
+                    last_wal_checkpoint = Instant::now();
+                    crate::store::checkpoint_wal();
+                }
             }
 
-            thread::sleep(config.poll_interval);
+            sleep_unless_shutdown(
+                &shutdown,
+                Duration::from_millis(POLL_INTERVAL_MS.load(Ordering::SeqCst)),
+            );
         }
 
         tracing::info!("Window polling thread shutting down");
 
-        // Final flush before exit
-        flush_counters_to_store();
+        // Final flush before exit, waiting for the lock so nothing is left over
+        flush_counters_to_store_with(&ACTIVITY_STORE, true);
     })
 }
 
+/// Sleeps for `duration`, waking early once `shutdown` is set.
+fn sleep_unless_shutdown(shutdown: &AtomicBool, duration: Duration) {
+    let deadline = Instant::now() + duration;
+    while !shutdown.load(Ordering::SeqCst) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        thread::sleep(remaining.min(SHUTDOWN_CHECK_INTERVAL));
+    }
+}
+
+/// Waits up to `timeout` for the polling thread to exit after `shutdown` is set.
+///
+/// Returns `false` if it is still running (e.g. stuck reading a hung
+/// process) or panicked, so shutdown can save what it has instead of
+/// blocking forever. A thread still running is left detached.
+pub fn join_polling_thread(handle: JoinHandle<()>, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while !handle.is_finished() {
+        if Instant::now() >= deadline {
+            tracing::warn!(
+                timeout_secs = timeout.as_secs_f64(),
+                "Polling thread didn't stop in time, saving without it"
+            );
+            return false;
+        }
+        thread::sleep(SHUTDOWN_CHECK_INTERVAL);
+    }
+    match handle.join() {
+        Ok(()) => true,
+        Err(_) => {
+            tracing::error!("Polling thread panicked");
+            false
+        }
+    }
+}
+
+/// Process name of the placeholder session while the desktop is focused.
+const DESKTOP_SESSION: &str = "[Desktop]";
+
+/// Process name of the placeholder session while no window has focus.
+const NO_WINDOW_SESSION: &str = "[No window]";
+
+/// Process name of the session recorded while the dashboard is focused (`dashboard_mode = tag`).
+const DASHBOARD_SESSION: &str = "[Dashboard]";
+
+/// How time spent viewing the OwnMon dashboard is tracked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DashboardMode {
+    /// Tracked as normal browser time.
+    Off,
+    /// Recorded as a `[Dashboard]` session.
+    Tag,
+    /// Not recorded; tracking pauses as for a privacy app.
+    Exclude,
+}
+
+/// Window handle recorded for the no-window placeholder session.
+const NO_WINDOW_HWND: isize = 0;
+
+/// A newly focused window that has not yet held focus for the grace period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PendingFocus {
+    hwnd: isize,
+    since: Instant,
+}
+
+/// Focus state carried from one poll cycle to the next.
+#[derive(Debug, Default)]
+struct PollState {
+    last_hwnd: Option<isize>,
+    /// Raw (unredacted) title of `last_hwnd`, for change detection.
+    last_title: String,
+    pending_focus: Option<PendingFocus>,
+}
+
 /// Performs a single poll cycle.
 ///
-/// Checks the current foreground window and updates the store if needed.
-fn poll_cycle(last_hwnd: &mut Option<isize>, last_title: &mut String, track_title_changes: bool) {
+/// Checks the current foreground window and media, and updates `store` if
+/// needed. While `starting_up`, focus changes wait at least
+/// [`STARTUP_FOCUS_GRACE`].
+fn poll_cycle(
+    source: &impl WindowSource,
+    media: &impl MediaSource,
+    store: &RwLock<ActivityStore>,
+    state: &mut PollState,
+    track_title_changes: bool,
+    starting_up: bool,
+) {
     // Always flush counters, even if window hasn't changed
-    flush_counters_to_store();
+    let had_input = flush_counters_to_store(store);
+
+    // Record nothing while the user has paused monitoring. Forgetting the
+    // last window makes the focused one start a fresh session on resume.
+    if crate::store::is_monitoring_paused() {
+        if state.last_hwnd.take().is_some() {
+            if let Ok(mut store) = store.write() {
+                store.end_current_session();
+            }
+        }
+        state.last_title.clear();
+        state.pending_focus = None;
+        return;
+    }
 
     // Poll for media changes
-    poll_media();
+    poll_media(store, media);
 
     // Get current foreground window
-    let hwnd = match get_foreground_window() {
+    let hwnd_value = match source.foreground_window() {
         Some(h) => h,
         None => {
-            // No foreground window (e.g., desktop focused, lock screen)
+            // No foreground window (e.g., lock screen, mid-switch)
+            if state.last_hwnd != Some(NO_WINDOW_HWND) && track_desktop_time() {
+                enter_windowless_state(store, state, NO_WINDOW_HWND, NO_WINDOW_SESSION);
+            }
             return;
         }
     };
 
-    let hwnd_value = hwnd.0 as isize;
-    let window_changed = last_hwnd.is_none_or(|last| last != hwnd_value);
+    let window_changed = state.last_hwnd.is_none_or(|last| last != hwnd_value);
+    if !window_changed {
+        // Focus came back before a pending window settled: it was a flicker
+        state.pending_focus = None;
+    }
+
+    if window_changed && source.is_desktop_window(hwnd_value) && track_desktop_time() {
+        enter_windowless_state(store, state, hwnd_value, DESKTOP_SESSION);
+        return;
+    }
 
     // Get window info
-    let current_title = get_window_text(hwnd);
-    let title_changed = !window_changed && track_title_changes && *last_title != current_title;
+    let current_title = source.window_text(hwnd_value);
+    let title_changed = !window_changed
+        && state.last_title != current_title
+        && (track_title_changes || dashboard_title_changed(&state.last_title, &current_title));
 
     if window_changed || title_changed {
-        let (_, pid) = get_window_thread_process_id(hwnd);
-        let raw_process_name = get_process_name(pid).unwrap_or_else(|| "Unknown".to_string());
+        let pid = source.window_process_id(hwnd_value);
+        let raw_process_name = source
+            .process_name(pid)
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        let dashboard_mode = dashboard_mode();
+        let is_dashboard = dashboard_mode != DashboardMode::Off
+            && crate::store::with_db(|d| d.is_dashboard_window(&raw_process_name, &current_title))
+                .unwrap_or(false);
+        let exclude_dashboard = is_dashboard && dashboard_mode == DashboardMode::Exclude;
+
+        // Pause tracking while a privacy app (e.g., password manager) is focused
+        let is_privacy_app = exclude_dashboard
+            || crate::store::with_db(|d| d.is_privacy_app(&raw_process_name)).unwrap_or(false);
+
+        let was_paused = crate::store::is_tracking_paused();
+        let paused = match store.write() {
+            Ok(mut store) => update_privacy_pause(&mut store, is_privacy_app),
+            Err(_) => was_paused,
+        };
+
+        if paused {
+            if !was_paused {
+                let reason = if exclude_dashboard {
+                    "dashboard"
+                } else {
+                    "privacy"
+                };
+                tracing::info!(reason, "Tracking paused");
+                crate::store::broadcast_update(
+                    "tracking_paused",
+                    &serde_json::json!({ "paused": true, "reason": reason }),
+                );
+            }
+            state.last_hwnd = Some(hwnd_value);
+            state.last_title = current_title;
+            return;
+        } else if was_paused {
+            tracing::info!("Tracking resumed");
+        }
 
         // Check if process is blacklisted
-        let is_blacklisted = crate::store::DATABASE
-            .as_ref()
-            .map(|db| {
-                db.lock()
-                    .ok()
-                    .map(|d| d.is_blacklisted(&raw_process_name))
-                    .unwrap_or(false)
-            })
-            .unwrap_or(false);
+        let is_blacklisted =
+            crate::store::with_db(|d| d.is_blacklisted(&raw_process_name)).unwrap_or(false);
 
         if is_blacklisted {
-            *last_hwnd = Some(hwnd_value);
-            *last_title = current_title;
+            state.last_hwnd = Some(hwnd_value);
+            state.last_title = current_title;
             return;
         }
-        let process_name = if raw_process_name == "ApplicationFrameHost.exe" {
-            // Extract app name from window title (e.g., "Calculator" from "Calculator")
-            // or use a sanitized version
-            if !current_title.is_empty() {
-                format!("[UWP] {}", extract_app_name(&current_title))
-            } else {
-                "UWP App".to_string()
-            }
+        let process_name = if is_dashboard {
+            DASHBOARD_SESSION.to_string()
+        } else if raw_process_name == "ApplicationFrameHost.exe" {
+            uwp_process_name(source.uwp_app_id(hwnd_value).as_deref(), &current_title)
         } else if raw_process_name == "Unknown" && !current_title.is_empty() {
             // Fallback for elevated processes - use window title
             format!("[Elevated] {}", extract_app_name(&current_title))
@@ -157,75 +534,572 @@ fn poll_cycle(last_hwnd: &mut Option<isize>, last_title: &mut String, track_titl
             raw_process_name
         };
 
+        // Wait until the new window has held focus past the grace period
+        let grace = if starting_up {
+            focus_grace().max(STARTUP_FOCUS_GRACE)
+        } else {
+            focus_grace()
+        };
+        if window_changed
+            && !settle_focus(
+                &mut state.pending_focus,
+                hwnd_value,
+                grace,
+                had_input,
+                Instant::now(),
+            )
+        {
+            return;
+        }
+
+        // Redact before the title is stored, broadcast or logged. The raw
+        // title is only kept in `last_title` for change detection.
+        let redactor = super::title_redaction::load_title_redactor();
+        let stored_title = redactor.redact(&current_title);
+
+        // Browser URLs follow the same redaction settings as titles
+        let url = if is_dashboard || !track_browser_urls() {
+            None
+        } else {
+            source
+                .browser_url(hwnd_value, &process_name)
+                .map(|url| redactor.redact(&url).into_owned())
+        };
+        let process_path = if is_dashboard {
+            None
+        } else {
+            source.process_path(pid)
+        };
+        let monitor = source.window_monitor(hwnd_value);
+
         // Update store
-        if let Ok(mut store) = ACTIVITY_STORE.write() {
-            store.switch_session(hwnd_value, pid, &process_name, &current_title);
+        if let Ok(mut store) = store.write() {
+            store.switch_session(hwnd_value, pid, &process_name, &stored_title);
+            if let Some(session) = &mut store.current_session {
+                session.url = url.clone();
+                session.process_path = process_path;
+                session.monitor = monitor;
+            }
         }
 
         // Broadcast session update to WebSocket clients
         let session_data = serde_json::json!({
             "process_name": process_name,
-            "window_title": current_title,
+            "window_title": stored_title,
+            "url": url,
         });
-        crate::store::broadcast_update("session_change", &session_data);
+        broadcast_session_change(session_data);
 
         if window_changed {
+            check_category_alert(&process_name);
+            check_focus_mode(&process_name);
+
+            // Extract the app icon once per process for the dashboard
+            if !is_dashboard && !crate::winapi_utils::has_app_icon(&process_name) {
+                if let Some(png) = source.window_icon(hwnd_value) {
+                    crate::winapi_utils::cache_app_icon(&process_name, png);
+                }
+            }
+
             tracing::debug!(
                 pid = pid,
                 process = %process_name,
-                title = %current_title,
+                title = %stored_title,
                 "Window focus changed"
             );
         } else {
             tracing::trace!(
-                title = %current_title,
+                title = %stored_title,
                 "Window title changed"
             );
         }
 
-        *last_hwnd = Some(hwnd_value);
-        *last_title = current_title;
+        state.last_hwnd = Some(hwnd_value);
+        state.last_title = current_title;
+    }
+
+    let fullscreen = source.is_fullscreen_window(hwnd_value);
+    if let Ok(mut store) = store.try_write() {
+        store.set_foreground_fullscreen(fullscreen);
+    }
+}
+
+/// Reads whether desktop/no-window time gets its own placeholder session.
+fn track_desktop_time() -> bool {
+    crate::store::with_db(|d| d.get_config("track_desktop_time").ok().flatten())
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(false)
+}
+
+/// Reads whether the address bar URL is recorded for browser sessions.
+fn track_browser_urls() -> bool {
+    crate::store::with_db(|d| d.get_config("track_browser_urls").ok().flatten())
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(true)
+}
+
+/// Attributes time to a placeholder session while the desktop or no window
+/// has focus, instead of padding the last app's session.
+fn enter_windowless_state(
+    store: &RwLock<ActivityStore>,
+    state: &mut PollState,
+    hwnd_value: isize,
+    placeholder: &str,
+) {
+    state.pending_focus = None;
+
+    let started = match store.write() {
+        Ok(mut store) => {
+            update_privacy_pause(&mut store, false);
+            start_placeholder_session(&mut store, hwnd_value, placeholder)
+        }
+        Err(_) => false,
+    };
+
+    if started {
+        tracing::debug!(session = placeholder, "No app window focused");
+        broadcast_session_change(serde_json::json!({
+            "process_name": placeholder,
+            "window_title": "",
+        }));
+    }
+
+    state.last_hwnd = Some(hwnd_value);
+    state.last_title.clear();
+}
+
+/// Ends the current session and starts a placeholder session, unless the
+/// placeholder is already active. Returns true if a new session was started.
+fn start_placeholder_session(
+    store: &mut ActivityStore,
+    hwnd_value: isize,
+    placeholder: &str,
+) -> bool {
+    if store
+        .current_session
+        .as_ref()
+        .is_some_and(|s| s.process_name == placeholder)
+    {
+        return false;
     }
+
+    store.switch_session(hwnd_value, 0, placeholder, "");
+    true
 }
 
-/// Flushes atomic input counters to the activity store.
+/// Reads how dashboard time is tracked from config (default: off).
+fn dashboard_mode() -> DashboardMode {
+    let mode = crate::store::with_db(|d| d.get_config("dashboard_mode").ok().flatten()).flatten();
+    match mode.as_deref() {
+        Some("tag") => DashboardMode::Tag,
+        Some("exclude") => DashboardMode::Exclude,
+        _ => DashboardMode::Off,
+    }
+}
+
+/// Returns true if a title change moves onto or off the dashboard (e.g., a
+/// browser tab switch), so it is handled like a focus change.
+fn dashboard_title_changed(old_title: &str, new_title: &str) -> bool {
+    if dashboard_mode() == DashboardMode::Off {
+        return false;
+    }
+    crate::store::with_db(|d| d.is_dashboard_title(old_title) != d.is_dashboard_title(new_title))
+        .unwrap_or(false)
+}
+
+/// Reads the current session checkpoint interval from config (default: 30s, 0 = off).
+fn current_save_interval() -> Duration {
+    let secs = crate::store::with_db(|d| d.get_config("current_save_interval_secs").ok().flatten())
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30);
+    Duration::from_secs(secs)
+}
+
+/// Reads how often old data is pruned from config (default: 1h, 0 = off).
+fn prune_interval() -> Duration {
+    let secs = crate::store::with_db(|d| d.get_config("prune_interval_secs").ok().flatten())
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3600);
+    Duration::from_secs(secs)
+}
+
+/// Reads how often the WAL is checkpointed from config (default: 10m, 0 = off).
+fn wal_checkpoint_interval() -> Duration {
+    let secs = crate::store::with_db(|d| d.get_config("wal_checkpoint_secs").ok().flatten())
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(600);
+    Duration::from_secs(secs)
+}
+
+/// Reads the focus grace period from config (default: 0, disabled).
+fn focus_grace() -> Duration {
+    let grace_ms = crate::store::with_db(|d| d.get_config("focus_grace_ms").ok().flatten())
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    Duration::from_millis(grace_ms)
+}
+
+/// Decides whether a focus change to `hwnd` should start a session now.
+///
+/// The first sighting of a window only marks it as pending; it is committed
+/// once it has held focus for `grace`, or earlier if the user gives it input
+/// (a splash screen or notification doesn't get typed into).
+fn settle_focus(
+    pending: &mut Option<PendingFocus>,
+    hwnd: isize,
+    grace: Duration,
+    had_input: bool,
+    now: Instant,
+) -> bool {
+    if grace.is_zero() {
+        *pending = None;
+        return true;
+    }
+
+    match pending {
+        Some(p) if p.hwnd == hwnd => {
+            if had_input || now.duration_since(p.since) >= grace {
+                *pending = None;
+                true
+            } else {
+                false
+            }
+        }
+        _ => {
+            *pending = Some(PendingFocus { hwnd, since: now });
+            false
+        }
+    }
+}
+
+/// Updates the privacy pause state for a newly focused app.
+///
+/// Focusing a privacy app ends the current session and pauses tracking
+/// until focus moves to another app. Returns `true` while paused.
+fn update_privacy_pause(store: &mut ActivityStore, is_privacy_app: bool) -> bool {
+    if is_privacy_app {
+        if !TRACKING_PAUSED.swap(true, Ordering::SeqCst) {
+            store.end_current_session();
+        }
+        true
+    } else {
+        TRACKING_PAUSED.store(false, Ordering::SeqCst);
+        false
+    }
+}
+
+/// Consecutive contended flushes after which the poller waits for the
+/// store lock instead of carrying the counts over again.
+const MAX_DEFERRED_FLUSHES: u32 = 5;
+
+/// Flushes whose counts were carried over because the store was locked.
+static DEFERRED_FLUSHES: AtomicU64 = AtomicU64::new(0);
+
+/// Flushes that had to wait for the store lock after [`MAX_DEFERRED_FLUSHES`].
+static FORCED_FLUSHES: AtomicU64 = AtomicU64::new(0);
+
+/// Input counted since the last flush that couldn't be added to the store yet.
+static PENDING_INPUT: Mutex<PendingInput> = Mutex::new(PendingInput {
+    delta: InputDelta::ZERO,
+    deferrals: 0,
+});
+
+/// Input counts read from the hooks, waiting to be added to the store.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct InputDelta {
+    keystrokes: u64,
+    clicks: u64,
+    scrolls: u64,
+    shortcuts: u64,
+    key_breakdown: KeyBreakdown,
+    double_clicks: u64,
+    scroll_up: u64,
+    scroll_down: u64,
+    scroll_left: u64,
+    scroll_right: u64,
+    mouse_distance: u64,
+}
+
+impl InputDelta {
+    const ZERO: Self = Self {
+        keystrokes: 0,
+        clicks: 0,
+        scrolls: 0,
+        shortcuts: 0,
+        key_breakdown: KeyBreakdown {
+            alphanumeric: 0,
+            whitespace: 0,
+            navigation: 0,
+            modifier: 0,
+            other: 0,
+        },
+        double_clicks: 0,
+        scroll_up: 0,
+        scroll_down: 0,
+        scroll_left: 0,
+        scroll_right: 0,
+        mouse_distance: 0,
+    };
+
+    /// Atomically reads and resets the hook counters.
+    fn take() -> Self {
+        let (left, right, middle) = flush_click_counts();
+        let (scroll_up, scroll_down, scroll_left, scroll_right) = flush_scroll_directions();
+        Self {
+            keystrokes: flush_keystroke_count(),
+            clicks: left + right + middle,
+            scrolls: flush_scroll_count(),
+            shortcuts: flush_shortcut_count(),
+            key_breakdown: flush_key_breakdown(),
+            double_clicks: flush_double_clicks(),
+            scroll_up,
+            scroll_down,
+            scroll_left,
+            scroll_right,
+            mouse_distance: flush_mouse_distance(),
+        }
+    }
+
+    /// Whether any of the counts is input for idle detection. Mouse
+    /// movement alone is recorded but doesn't count.
+    fn has_input(&self) -> bool {
+        self.keystrokes > 0
+            || self.clicks > 0
+            || self.scrolls > 0
+            || self.scroll_left + self.scroll_right > 0
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.keystrokes += other.keystrokes;
+        self.clicks += other.clicks;
+        self.scrolls += other.scrolls;
+        self.shortcuts += other.shortcuts;
+        self.key_breakdown.add(&other.key_breakdown);
+        self.double_clicks += other.double_clicks;
+        self.scroll_up += other.scroll_up;
+        self.scroll_down += other.scroll_down;
+        self.scroll_left += other.scroll_left;
+        self.scroll_right += other.scroll_right;
+        self.mouse_distance += other.mouse_distance;
+    }
+
+    /// Adds the counts to the store's current session.
+    fn apply(&self, store: &mut ActivityStore) -> Option<IdleStateChange> {
+        let mut idle_change = None;
+        if self.has_input() {
+            idle_change = store.add_input_counts(self.keystrokes, self.clicks, self.scrolls);
+            store.add_shortcut_count(self.shortcuts);
+            store.add_key_breakdown(&self.key_breakdown);
+            store.add_double_clicks(self.double_clicks);
+            store.add_scroll_directions(
+                self.scroll_up,
+                self.scroll_down,
+                self.scroll_left,
+                self.scroll_right,
+            );
+        }
+        store.add_mouse_distance(self.mouse_distance);
+        idle_change
+    }
+}
+
+/// Counts carried over from contended flushes.
+struct PendingInput {
+    delta: InputDelta,
+    /// Consecutive flushes that found the store locked.
+    deferrals: u32,
+}
+
+/// How often counter flushes found the store locked.
+#[derive(Debug, Clone, Copy, Default, serde::Serialize)]
+pub struct CounterFlushStats {
+    /// Flushes whose counts were carried over to the next poll.
+    pub deferred: u64,
+    /// Flushes that waited for the lock after several deferrals in a row.
+    pub forced: u64,
+}
+
+/// Gets the counter flush contention totals since start.
+pub fn counter_flush_stats() -> CounterFlushStats {
+    CounterFlushStats {
+        deferred: DEFERRED_FLUSHES.load(Ordering::Relaxed),
+        forced: FORCED_FLUSHES.load(Ordering::Relaxed),
+    }
+}
+
+/// Non-blocking [`flush_counters_to_store_with`], used on every poll.
+fn flush_counters_to_store(store: &RwLock<ActivityStore>) -> bool {
+    flush_counters_to_store_with(store, false)
+}
+
+/// Flushes atomic input counters to `store`.
 ///
 /// This atomically reads and resets the counters, then adds the values
-/// to the current session in the store.
-fn flush_counters_to_store() {
-    let keystrokes = flush_keystroke_count();
-    let (left, right, middle) = flush_click_counts();
-    let scrolls = flush_scroll_count();
-
-    let total_clicks = left + right + middle;
-
-    // Only acquire lock if we have something to add
-    if keystrokes > 0 || total_clicks > 0 || scrolls > 0 {
-        if let Ok(mut store) = ACTIVITY_STORE.try_write() {
-            store.add_input_counts(keystrokes, total_clicks, scrolls);
-        } else {
-            // Lock contention - counts will be added next cycle
-            // This is rare but acceptable for monitoring purposes
-            tracing::trace!("Store lock contention, deferring counter flush");
+/// to the current session in the store. Counts are discarded while
+/// tracking is paused. Returns true if any input was seen.
+///
+/// The store lock is only tried, so API reads don't stall polling. When it
+/// is held, the counts are kept and added by a later flush; after
+/// [`MAX_DEFERRED_FLUSHES`] contended flushes in a row, or with `block`, the
+/// flush waits for the lock, so input is never dropped.
+fn flush_counters_to_store_with(store: &RwLock<ActivityStore>, block: bool) -> bool {
+    let delta = InputDelta::take();
+    let had_input = delta.has_input();
+
+    let mut pending = PENDING_INPUT.lock().unwrap_or_else(|e| e.into_inner());
+    if crate::store::is_tracking_paused() || crate::store::is_monitoring_paused() {
+        // Carried-over counts belong to the time before the pause
+        pending.delta = InputDelta::ZERO;
+        pending.deferrals = 0;
+        return had_input;
+    }
+
+    let idle_change = apply_input_delta(store, &mut pending, delta, block);
+
+    // Broadcast after the store lock is released
+    if let Some(change) = idle_change {
+        crate::store::broadcast_update("idle_state", &change);
+    }
+
+    had_input
+}
+
+/// Adds `delta` and any carried-over counts to `store`, or carries them
+/// over if the lock is held (see [`flush_counters_to_store_with`]).
+fn apply_input_delta(
+    store: &RwLock<ActivityStore>,
+    pending: &mut PendingInput,
+    delta: InputDelta,
+    block: bool,
+) -> Option<IdleStateChange> {
+    pending.delta.add(&delta);
+    // Only acquire the lock if there is something to add
+    if pending.delta == InputDelta::ZERO {
+        return None;
+    }
+
+    let guard = match store.try_write() {
+        Ok(guard) => Some(guard),
+        Err(std::sync::TryLockError::Poisoned(_)) => None,
+        Err(std::sync::TryLockError::WouldBlock) => {
+            if block || pending.deferrals + 1 >= MAX_DEFERRED_FLUSHES {
+                FORCED_FLUSHES.fetch_add(1, Ordering::Relaxed);
+                store.write().ok()
+            } else {
+                DEFERRED_FLUSHES.fetch_add(1, Ordering::Relaxed);
+                pending.deferrals += 1;
+                tracing::trace!(
+                    deferrals = pending.deferrals,
+                    "Store lock contention, deferring counter flush"
+                );
+                return None;
+            }
         }
+    };
+
+    let delta = std::mem::take(&mut pending.delta);
+    pending.deferrals = 0;
+    guard.and_then(|mut store| delta.apply(&mut store))
+}
+
+/// Feeds the system's last input time to the store when the input hooks
+/// aren't installed, so sessions aren't all recorded as idle.
+fn track_input_without_hooks() {
+    if crate::store::is_tracking_paused() || crate::store::is_monitoring_paused() {
+        return;
+    }
+    let Some(idle) =
+        crate::winapi_utils::get_idle_duration().and_then(|d| chrono::Duration::from_std(d).ok())
+    else {
+        return;
+    };
+    let change = ACTIVITY_STORE
+        .write()
+        .ok()
+        .and_then(|mut store| store.record_uncounted_input(chrono::Utc::now() - idle));
+    if let Some(change) = change {
+        crate::store::broadcast_update("idle_state", &change);
     }
 }
 
-/// Polls for current media and updates the store.
-fn poll_media() {
-    if let Some(media_info) = fetch_current_media() {
-        // Broadcast media update
-        let media_data = serde_json::json!({
-            "title": media_info.title,
-            "artist": media_info.artist,
-            "album": media_info.album,
-            "is_playing": media_info.is_playing(),
-        });
-        crate::store::broadcast_update("media_update", &media_data);
+/// Reads whether currently playing media is tracked.
+fn media_tracking_enabled() -> bool {
+    crate::store::with_db(|d| d.get_config("media_tracking_enabled").ok().flatten())
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(true)
+}
 
-        if let Ok(mut store) = ACTIVITY_STORE.try_write() {
-            store.update_media(media_info);
+/// Polls `media` for what is playing and updates `store`.
+///
+/// Skipped entirely when `media_tracking_enabled` is off; any session in
+/// progress is then closed as stale. `media_update` is only broadcast when
+/// the media details differ from the last broadcast.
+fn poll_media(store: &RwLock<ActivityStore>, media: &impl MediaSource) {
+    if !media_tracking_enabled() {
+        return;
+    }
+    let Some(media_info) = media.current_media() else {
+        // Broadcast again once playback resumes, even if nothing changed
+        if let Ok(mut last) = LAST_MEDIA_BROADCAST.lock() {
+            *last = None;
         }
+        return;
+    };
+
+    // The position moves on every poll while playing, so it isn't compared
+    let details = serde_json::json!({
+        "title": media_info.title,
+        "artist": media_info.artist,
+        "album": media_info.album,
+        "is_playing": media_info.is_playing(),
+        "track_duration_secs": media_info.track_duration_secs,
+    });
+    let changed = LAST_MEDIA_BROADCAST
+        .lock()
+        .map(|mut last| {
+            let changed = last.as_ref() != Some(&details);
+            if changed {
+                *last = Some(details.clone());
+            }
+            changed
+        })
+        .unwrap_or(true);
+    if changed {
+        let mut media_data = details;
+        media_data["position_secs"] = serde_json::json!(media_info.position_secs);
+        crate::store::broadcast_update("media_update", &media_data);
+    }
+
+    if let Ok(mut store) = store.try_write() {
+        store.update_media(media_info);
+    }
+}
+
+/// Names a session in a UWP frame window (`ApplicationFrameHost.exe`).
+///
+/// Uses the package name from the hosted app's AppUserModelID, e.g.
+/// `[UWP] Microsoft.WindowsCalculator` for
+/// `Microsoft.WindowsCalculator_8wekyb3d8bbwe!App`, so an app's sessions
+/// group together whatever its title says. Falls back to the window title
+/// when the ID can't be read.
+fn uwp_process_name(app_id: Option<&str>, title: &str) -> String {
+    // The package family name is `<name>_<publisher id>`
+    let package = app_id
+        .and_then(|id| id.split('!').next())
+        .map(|family| family.rsplit_once('_').map_or(family, |(name, _)| name))
+        .filter(|name| !name.is_empty());
+
+    match package {
+        Some(name) => format!("[UWP] {}", name),
+        None if !title.is_empty() => format!("[UWP] {}", extract_app_name(title)),
+        None => "UWP App".to_string(),
     }
 }
 
@@ -259,6 +1133,68 @@ fn extract_app_name(title: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::database::{Database, SessionIntegrity};
+    use crate::media::{MediaInfo, MockMediaSource, PlaybackStatus};
+    use crate::monitor::window_source::{MockWindow, MockWindowSource};
+    use crate::server::routes::sessions::{build_sessions_response, SessionsQuery};
+
+    /// Serializes tests that drive `poll_cycle`, which still shares the
+    /// pause flags and carried-over input counts between threads.
+    static POLL_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    /// Runs one poll cycle against `store` with no media playing.
+    fn poll(
+        source: &MockWindowSource,
+        store: &RwLock<ActivityStore>,
+        state: &mut PollState,
+        starting_up: bool,
+    ) {
+        poll_cycle(
+            source,
+            &MockMediaSource::new(),
+            store,
+            state,
+            false,
+            starting_up,
+        );
+    }
+
+    /// Runs one poll cycle with fresh poller state focused on `hwnd` and
+    /// returns the current session's process name.
+    fn poll_focus(
+        source: &MockWindowSource,
+        store: &RwLock<ActivityStore>,
+        hwnd: isize,
+    ) -> Option<String> {
+        let mut state = PollState::default();
+        source.focus(Some(hwnd));
+        poll(source, store, &mut state, false);
+        assert_eq!(state.last_hwnd, Some(hwnd));
+        let store = store.read().unwrap();
+        store
+            .current_session
+            .as_ref()
+            .map(|s| s.process_name.clone())
+    }
+
+    #[test]
+    fn test_sleep_and_join_stop_at_shutdown() {
+        let shutdown = Arc::new(AtomicBool::new(true));
+        let start = Instant::now();
+        sleep_unless_shutdown(&shutdown, Duration::from_secs(10));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // A thread that notices the flag is joined
+        let flag = Arc::clone(&shutdown);
+        let prompt = thread::spawn(move || sleep_unless_shutdown(&flag, Duration::from_secs(10)));
+        assert!(join_polling_thread(prompt, Duration::from_secs(5)));
+
+        // A stuck thread is given up on after the timeout
+        let stuck = thread::spawn(|| thread::sleep(Duration::from_secs(2)));
+        let start = Instant::now();
+        assert!(!join_polling_thread(stuck, Duration::from_millis(100)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
 
     #[test]
     fn test_poller_config_default() {
@@ -267,9 +1203,505 @@ mod tests {
         assert!(!config.track_title_changes);
     }
 
+    #[test]
+    fn test_session_changes_coalesce_within_gap() {
+        let mut changes = BroadcastCoalescer::new();
+        let start = Instant::now();
+        let msg = |n: i32| serde_json::json!({ "n": n });
+
+        // The first change goes out immediately
+        assert_eq!(changes.offer(msg(1), start), Some(msg(1)));
+
+        // Quick follow-ups are held, keeping only the latest
+        let soon = start + Duration::from_millis(100);
+        assert_eq!(changes.offer(msg(2), soon), None);
+        assert_eq!(changes.offer(msg(3), soon), None);
+        assert_eq!(changes.flush(soon), None);
+
+        let later = start + SESSION_CHANGE_GAP;
+        assert_eq!(changes.flush(later), Some(msg(3)));
+        assert_eq!(changes.flush(later + SESSION_CHANGE_GAP), None);
+    }
+
     #[test]
     fn test_flush_counters_no_panic_on_empty() {
         // Should not panic when counters are zero
-        flush_counters_to_store();
+        flush_counters_to_store(&RwLock::new(ActivityStore::new()));
+    }
+
+    #[test]
+    fn test_contended_flush_keeps_counts() {
+        let store = RwLock::new(ActivityStore::new());
+        store
+            .write()
+            .unwrap()
+            .switch_session(1, 1, "code.exe", "main.rs");
+        let mut pending = PendingInput {
+            delta: InputDelta::ZERO,
+            deferrals: 0,
+        };
+        let typed = |keystrokes| InputDelta {
+            keystrokes,
+            ..InputDelta::ZERO
+        };
+        let keystrokes = |store: &RwLock<ActivityStore>| {
+            store
+                .read()
+                .unwrap()
+                .current_session
+                .as_ref()
+                .unwrap()
+                .keystrokes
+        };
+
+        // An API read holds the lock: the counts are carried over, not dropped
+        {
+            let _reader = store.read().unwrap();
+            apply_input_delta(&store, &mut pending, typed(3), false);
+            apply_input_delta(&store, &mut pending, typed(4), false);
+            assert_eq!(pending.delta.keystrokes, 7);
+            assert_eq!(pending.deferrals, 2);
+        }
+
+        apply_input_delta(&store, &mut pending, typed(1), false);
+        assert_eq!(keystrokes(&store), 8);
+        assert_eq!(pending.delta, InputDelta::ZERO);
+        assert_eq!(pending.deferrals, 0);
+
+        // Long contention makes the flush wait for the reader to finish
+        let (tx, rx) = std::sync::mpsc::channel();
+        thread::scope(|scope| {
+            let reader = store.read().unwrap();
+            scope.spawn(|| {
+                for _ in 0..MAX_DEFERRED_FLUSHES {
+                    apply_input_delta(&store, &mut pending, typed(1), false);
+                }
+                tx.send(()).unwrap();
+            });
+            // The last flush is waiting for the lock
+            assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+            drop(reader);
+            rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        });
+        assert_eq!(keystrokes(&store), 8 + MAX_DEFERRED_FLUSHES as u64);
+    }
+
+    #[test]
+    fn test_focus_grace_ignores_flicker_but_commits_settled_switch() {
+        let grace = Duration::from_millis(500);
+        let start = Instant::now();
+        let mut pending = None;
+
+        // A splash screen grabs focus briefly, then focus returns
+        assert!(!settle_focus(&mut pending, 2, grace, false, start));
+        assert!(!settle_focus(
+            &mut pending,
+            2,
+            grace,
+            false,
+            start + Duration::from_millis(200)
+        ));
+        pending = None; // poll_cycle clears it when the previous window is back
+
+        // A real switch is committed once it holds focus past the grace period
+        assert!(!settle_focus(&mut pending, 3, grace, false, start));
+        assert!(settle_focus(
+            &mut pending,
+            3,
+            grace,
+            false,
+            start + Duration::from_millis(600)
+        ));
+        assert!(pending.is_none());
+
+        // A quick switch the user types into is committed right away
+        assert!(!settle_focus(&mut pending, 4, grace, false, start));
+        assert!(settle_focus(
+            &mut pending,
+            4,
+            grace,
+            true,
+            start + Duration::from_millis(100)
+        ));
+
+        // Without a grace period every switch commits immediately
+        assert!(settle_focus(&mut pending, 5, Duration::ZERO, false, start));
+    }
+
+    #[test]
+    fn test_desktop_focus_starts_placeholder_session() {
+        let mut store = ActivityStore::new();
+        store.switch_session(1, 100, "chrome.exe", "Google");
+
+        // Focusing the desktop ends the app session and starts a placeholder
+        assert!(start_placeholder_session(&mut store, 2, DESKTOP_SESSION));
+        let session = store.current_session.as_ref().unwrap();
+        assert_eq!(session.process_name, DESKTOP_SESSION);
+        assert_eq!(session.window_handle, 2);
+
+        // Staying on the desktop keeps the same placeholder session
+        let start = session.start_time;
+        assert!(!start_placeholder_session(&mut store, 2, DESKTOP_SESSION));
+        assert_eq!(store.current_session.as_ref().unwrap().start_time, start);
+
+        // Losing all focus switches to the no-window placeholder
+        assert!(start_placeholder_session(
+            &mut store,
+            NO_WINDOW_HWND,
+            NO_WINDOW_SESSION
+        ));
+        assert_eq!(
+            store.current_session.as_ref().unwrap().process_name,
+            NO_WINDOW_SESSION
+        );
+    }
+
+    #[test]
+    fn test_privacy_app_pauses_and_resumes_tracking() {
+        // poll_cycle also clears the global pause flag
+        let _guard = POLL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut store = ActivityStore::new();
+        store.switch_session(1, 100, "chrome.exe", "Google");
+
+        // Focusing a privacy app ends the session and pauses tracking
+        assert!(update_privacy_pause(&mut store, true));
+        assert!(crate::store::is_tracking_paused());
+        assert!(store.current_session.is_none());
+
+        // Staying on the privacy app keeps tracking paused
+        assert!(update_privacy_pause(&mut store, true));
+        assert!(crate::store::is_tracking_paused());
+
+        // Switching away resumes tracking
+        assert!(!update_privacy_pause(&mut store, false));
+        assert!(!crate::store::is_tracking_paused());
+    }
+
+    #[test]
+    fn test_focus_switch_flows_to_sessions_api() {
+        let _guard = POLL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let source = MockWindowSource::new();
+        let store = RwLock::new(ActivityStore::new());
+        source.add_window(MockWindow::new(
+            0x7101,
+            71001,
+            "harness-editor.exe",
+            "harness.rs - Editor",
+        ));
+        source.add_window(MockWindow::new(
+            0x7102,
+            71002,
+            "harness-browser.exe",
+            "Docs - Browser",
+        ));
+        source.focus(Some(0x7101));
+        let mut state = PollState::default();
+
+        // Focus the editor and give it some input
+        poll(&source, &store, &mut state, false);
+        {
+            let mut store = store.write().unwrap();
+            let session = store.current_session.as_mut().unwrap();
+            assert_eq!(session.process_name, "harness-editor.exe");
+            // Backdate so the session clears the minimum duration
+            session.start_time -= chrono::Duration::minutes(1);
+            store.add_input_counts(42, 3, 0);
+        }
+
+        // Switching away completes the editor session
+        source.focus(Some(0x7102));
+        poll(&source, &store, &mut state, false);
+
+        let db = Database::open_in_memory().unwrap();
+        let sessions = store.write().unwrap().drain_pending_sessions();
+        for session in sessions
+            .iter()
+            .filter(|s| s.process_name == "harness-editor.exe")
+        {
+            db.save_session(
+                session,
+                session.end_time.unwrap(),
+                SessionIntegrity::default(),
+            )
+            .unwrap();
+        }
+
+        let response = build_sessions_response(
+            &db,
+            &SessionsQuery {
+                from: Some((chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339()),
+                app: Some("harness-editor.exe".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(response.total, 1);
+        let session = &response.sessions[0].session;
+        assert_eq!(session.window_title.as_deref(), Some("harness.rs - Editor"));
+        assert_eq!(session.keystrokes, 42);
+        assert_eq!(session.clicks, 3);
+    }
+
+    #[test]
+    fn test_uwp_host_window_named_from_title() {
+        let _guard = POLL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let source = MockWindowSource::new();
+        let store = RwLock::new(ActivityStore::new());
+        source.add_window(MockWindow::new(
+            0x7201,
+            72001,
+            "ApplicationFrameHost.exe",
+            "Calculator",
+        ));
+        source.add_window(MockWindow::new(
+            0x7202,
+            72002,
+            "ApplicationFrameHost.exe",
+            "",
+        ));
+
+        assert_eq!(
+            poll_focus(&source, &store, 0x7201).as_deref(),
+            Some("[UWP] Calculator")
+        );
+        assert_eq!(
+            poll_focus(&source, &store, 0x7202).as_deref(),
+            Some("UWP App")
+        );
+
+        // The hosted app's AppUserModelID wins over a noisy title
+        source.add_window(MockWindow {
+            uwp_app_id: Some(
+                "microsoft.windowscommunicationsapps_8wekyb3d8bbwe!microsoft.windowslive.mail"
+                    .into(),
+            ),
+            ..MockWindow::new(
+                0x7203,
+                72003,
+                "ApplicationFrameHost.exe",
+                "Inbox - user@example.com - Mail",
+            )
+        });
+        assert_eq!(
+            poll_focus(&source, &store, 0x7203).as_deref(),
+            Some("[UWP] microsoft.windowscommunicationsapps")
+        );
+    }
+
+    #[test]
+    fn test_uwp_process_name_prefers_app_id() {
+        assert_eq!(
+            uwp_process_name(
+                Some("Microsoft.WindowsCalculator_8wekyb3d8bbwe!App"),
+                "Calculator"
+            ),
+            "[UWP] Microsoft.WindowsCalculator"
+        );
+        // A family name without a publisher ID is used as is
+        assert_eq!(
+            uwp_process_name(Some("Contoso.App!App"), ""),
+            "[UWP] Contoso.App"
+        );
+        assert_eq!(
+            uwp_process_name(None, "Mail - user@example.com"),
+            "[UWP] Mail"
+        );
+        assert_eq!(uwp_process_name(Some(""), ""), "UWP App");
+    }
+
+    #[test]
+    fn test_browser_url_recorded_on_session() {
+        let _guard = POLL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let source = MockWindowSource::new();
+        let store = RwLock::new(ActivityStore::new());
+        source.add_window(MockWindow {
+            url: Some("github.com/nquangit/ownmon".to_string()),
+            ..MockWindow::new(0x7401, 74001, "harness-browser.exe", "ownmon - GitHub")
+        });
+        source.add_window(MockWindow::new(0x7402, 74002, "harness-notes.exe", "Notes"));
+
+        poll_focus(&source, &store, 0x7401);
+        let url = store
+            .read()
+            .unwrap()
+            .current_session
+            .as_ref()
+            .and_then(|s| s.url.clone());
+        assert_eq!(url.as_deref(), Some("github.com/nquangit/ownmon"));
+
+        poll_focus(&source, &store, 0x7402);
+        let store = store.read().unwrap();
+        assert_eq!(store.current_session.as_ref().unwrap().url, None);
+    }
+
+    #[test]
+    fn test_process_path_recorded_on_session() {
+        let _guard = POLL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let source = MockWindowSource::new();
+        let store = RwLock::new(ActivityStore::new());
+        source.add_window(MockWindow {
+            process_path: Some(r"D:\Tools\Python\harness-python.exe".to_string()),
+            ..MockWindow::new(0x7501, 75001, "harness-python.exe", "REPL")
+        });
+        // Elevated processes can't be opened, so no path is known
+        source.add_window(MockWindow::new(0x7502, 75002, "harness-admin.exe", "Admin"));
+
+        poll_focus(&source, &store, 0x7501);
+        let path = store
+            .read()
+            .unwrap()
+            .current_session
+            .as_ref()
+            .and_then(|s| s.process_path.clone());
+        assert_eq!(path.as_deref(), Some(r"D:\Tools\Python\harness-python.exe"));
+
+        poll_focus(&source, &store, 0x7502);
+        let store = store.read().unwrap();
+        assert_eq!(store.current_session.as_ref().unwrap().process_path, None);
+    }
+
+    #[test]
+    fn test_monitor_recorded_on_session() {
+        let _guard = POLL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let source = MockWindowSource::new();
+        let store = RwLock::new(ActivityStore::new());
+        source.add_window(MockWindow {
+            monitor: Some(r"\\.\DISPLAY2".to_string()),
+            ..MockWindow::new(0x7601, 76001, "harness-slack.exe", "Slack")
+        });
+
+        poll_focus(&source, &store, 0x7601);
+        let store = store.read().unwrap();
+        assert_eq!(
+            store.current_session.as_ref().unwrap().monitor.as_deref(),
+            Some(r"\\.\DISPLAY2")
+        );
+    }
+
+    #[test]
+    fn test_elevated_window_named_from_title() {
+        let _guard = POLL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let source = MockWindowSource::new();
+        let store = RwLock::new(ActivityStore::new());
+        // The process can't be opened, so its name is unknown
+        source.add_window(MockWindow {
+            hwnd: 0x7301,
+            pid: 73001,
+            process_name: None,
+            title: "Registry Editor - HKLM".to_string(),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            poll_focus(&source, &store, 0x7301).as_deref(),
+            Some("[Elevated] Registry Editor")
+        );
+    }
+
+    #[test]
+    fn test_startup_window_waits_for_grace() {
+        let _guard = POLL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let source = MockWindowSource::new();
+        let store = RwLock::new(ActivityStore::new());
+        source.add_window(MockWindow::new(0x7601, 76001, "launcher.exe", "Starting"));
+        source.focus(Some(0x7601));
+
+        // During startup the focused window is only marked pending
+        let mut state = PollState::default();
+        poll(&source, &store, &mut state, true);
+        assert!(store.read().unwrap().current_session.is_none());
+        assert_eq!(state.pending_focus.map(|p| p.hwnd), Some(0x7601));
+
+        // Afterwards the configured grace applies (none by default)
+        poll(&source, &store, &mut state, false);
+        let store = store.read().unwrap();
+        assert_eq!(
+            store
+                .current_session
+                .as_ref()
+                .map(|s| s.process_name.as_str()),
+            Some("launcher.exe")
+        );
+    }
+
+    #[test]
+    fn test_monitoring_pause_ends_session_and_skips_recording() {
+        let _guard = POLL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let source = MockWindowSource::new();
+        let store = RwLock::new(ActivityStore::new());
+        source.add_window(MockWindow::new(0x7501, 75001, "notepad.exe", "notes.txt"));
+        source.focus(Some(0x7501));
+
+        let mut state = PollState::default();
+        poll(&source, &store, &mut state, false);
+        assert!(store.read().unwrap().current_session.is_some());
+
+        assert!(crate::store::set_monitoring_paused(true));
+        assert!(!crate::store::set_monitoring_paused(true));
+
+        // The first paused cycle ends the session, and none record anything
+        poll(&source, &store, &mut state, false);
+        assert!(store.read().unwrap().current_session.is_none());
+        poll(&source, &store, &mut state, false);
+        assert!(store.read().unwrap().current_session.is_none());
+        assert_eq!(state.last_hwnd, None);
+
+        // Resuming starts a fresh session for the focused window
+        assert!(crate::store::set_monitoring_paused(false));
+        poll(&source, &store, &mut state, false);
+        let store = store.read().unwrap();
+        assert_eq!(
+            store
+                .current_session
+                .as_ref()
+                .map(|s| s.process_name.as_str()),
+            Some("notepad.exe")
+        );
+    }
+
+    #[test]
+    fn test_media_source_feeds_store() {
+        let _guard = POLL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let store = RwLock::new(ActivityStore::new());
+        let media = MockMediaSource::new();
+        let title = |store: &RwLock<ActivityStore>| {
+            store
+                .read()
+                .unwrap()
+                .current_media
+                .as_ref()
+                .map(|m| m.media_info.title.clone())
+        };
+
+        poll_media(&store, &media);
+        assert_eq!(title(&store), None);
+
+        media.play(Some(MediaInfo::new(
+            "Song".to_string(),
+            "Band".to_string(),
+            "Album".to_string(),
+            "Spotify.exe".to_string(),
+            PlaybackStatus::Playing,
+        )));
+        poll_media(&store, &media);
+        assert_eq!(title(&store).as_deref(), Some("Song"));
+    }
+
+    #[test]
+    fn test_blacklisted_window_keeps_current_session() {
+        let _guard = POLL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let source = MockWindowSource::new();
+        let store = RwLock::new(ActivityStore::new());
+        source.add_window(MockWindow::new(0x7401, 74001, "notepad.exe", "notes.txt"));
+        // ownmon.exe is on the default blacklist
+        source.add_window(MockWindow::new(0x7402, 74002, "ownmon.exe", "OwnMon"));
+
+        assert_eq!(
+            poll_focus(&source, &store, 0x7401).as_deref(),
+            Some("notepad.exe")
+        );
+        assert_eq!(
+            poll_focus(&source, &store, 0x7402).as_deref(),
+            Some("notepad.exe")
+        );
     }
 }
@@ -0,0 +1,275 @@
+//! Foreground window access used by the window poller.
+//!
+//! The poller reads window state through the [`WindowSource`] trait so the
+//! poll → store → database path can be driven without real windows, using
+//! `MockWindowSource` in tests.
+
+use crate::winapi_utils::{
+    get_browser_url, get_foreground_window, get_process_name, get_process_path, get_uwp_app_id,
+    get_window_icon, get_window_monitor, get_window_text, get_window_thread_process_id,
+    is_desktop_window, is_fullscreen_window, is_screensaver_running,
+};
+#[cfg(test)]
+use std::sync::Mutex;
+use windows::Win32::Foundation::HWND;
+
+/// Source of foreground window information.
+///
+/// Window handles are passed as `isize`, the same form stored on sessions.
+pub trait WindowSource {
+    /// Returns the focused window, or `None` if no window has focus.
+    fn foreground_window(&self) -> Option<isize>;
+
+    /// Returns the window's title, or an empty string.
+    fn window_text(&self, hwnd: isize) -> String;
+
+    /// Returns the ID of the process that owns the window.
+    fn window_process_id(&self, hwnd: isize) -> u32;
+
+    /// Returns the executable name of a process.
+    fn process_name(&self, pid: u32) -> Option<String>;
+
+    /// Returns the full executable path of a process.
+    fn process_path(&self, _pid: u32) -> Option<String> {
+        None
+    }
+
+    /// Returns true if the window is the desktop or shell window.
+    fn is_desktop_window(&self, hwnd: isize) -> bool;
+
+    /// Returns true if the window covers its whole monitor.
+    fn is_fullscreen_window(&self, hwnd: isize) -> bool;
+
+    /// Returns the device name of the monitor showing most of the window.
+    fn window_monitor(&self, _hwnd: isize) -> Option<String> {
+        None
+    }
+
+    /// Returns true while the screensaver is running.
+    fn screensaver_running(&self) -> bool {
+        false
+    }
+
+    /// Returns the AppUserModelID of the app in a UWP frame window.
+    fn uwp_app_id(&self, _hwnd: isize) -> Option<String> {
+        None
+    }
+
+    /// Returns the window's icon as PNG bytes, if it can be read.
+    fn window_icon(&self, _hwnd: isize) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Returns the address bar URL if the window belongs to a known browser.
+    fn browser_url(&self, _hwnd: isize, _process_name: &str) -> Option<String> {
+        None
+    }
+}
+
+/// [`WindowSource`] backed by the Windows API.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WinApiWindowSource;
+
+fn to_hwnd(hwnd: isize) -> HWND {
+    HWND(hwnd as *mut std::ffi::c_void)
+}
+
+impl WindowSource for WinApiWindowSource {
+    fn foreground_window(&self) -> Option<isize> {
+        get_foreground_window().map(|hwnd| hwnd.0 as isize)
+    }
+
+    fn window_text(&self, hwnd: isize) -> String {
+        get_window_text(to_hwnd(hwnd))
+    }
+
+    fn window_process_id(&self, hwnd: isize) -> u32 {
+        get_window_thread_process_id(to_hwnd(hwnd)).1
+    }
+
+    fn process_name(&self, pid: u32) -> Option<String> {
+        get_process_name(pid)
+    }
+
+    fn process_path(&self, pid: u32) -> Option<String> {
+        get_process_path(pid)
+    }
+
+    fn is_desktop_window(&self, hwnd: isize) -> bool {
+        is_desktop_window(to_hwnd(hwnd))
+    }
+
+    fn is_fullscreen_window(&self, hwnd: isize) -> bool {
+        is_fullscreen_window(to_hwnd(hwnd))
+    }
+
+    fn window_monitor(&self, hwnd: isize) -> Option<String> {
+        get_window_monitor(to_hwnd(hwnd))
+    }
+
+    fn screensaver_running(&self) -> bool {
+        is_screensaver_running()
+    }
+
+    fn uwp_app_id(&self, hwnd: isize) -> Option<String> {
+        get_uwp_app_id(to_hwnd(hwnd))
+    }
+
+    fn window_icon(&self, hwnd: isize) -> Option<Vec<u8>> {
+        get_window_icon(to_hwnd(hwnd))
+    }
+
+    fn browser_url(&self, hwnd: isize, process_name: &str) -> Option<String> {
+        get_browser_url(to_hwnd(hwnd), process_name)
+    }
+}
+
+/// A window known to [`MockWindowSource`].
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub struct MockWindow {
+    pub hwnd: isize,
+    pub pid: u32,
+    /// `None` simulates a process that can't be opened (e.g., elevated).
+    pub process_name: Option<String>,
+    /// Full executable path; `None` like an elevated process.
+    pub process_path: Option<String>,
+    pub title: String,
+    pub is_desktop: bool,
+    pub is_fullscreen: bool,
+    /// Monitor device name, e.g. `\\.\DISPLAY1`.
+    pub monitor: Option<String>,
+    /// Address bar URL reported for browser windows.
+    pub url: Option<String>,
+    /// AppUserModelID of the hosted app, for UWP frame windows.
+    pub uwp_app_id: Option<String>,
+}
+
+#[cfg(test)]
+impl MockWindow {
+    /// Creates a normal app window.
+    pub fn new(hwnd: isize, pid: u32, process_name: &str, title: &str) -> Self {
+        Self {
+            hwnd,
+            pid,
+            process_name: Some(process_name.to_string()),
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Scripted [`WindowSource`] for tests: windows and focus are set by the caller.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MockWindowSource {
+    windows: Mutex<Vec<MockWindow>>,
+    focused: Mutex<Option<isize>>,
+}
+
+#[cfg(test)]
+impl MockWindowSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a window, replacing any existing window with the same handle.
+    pub fn add_window(&self, window: MockWindow) {
+        if let Ok(mut windows) = self.windows.lock() {
+            windows.retain(|w| w.hwnd != window.hwnd);
+            windows.push(window);
+        }
+    }
+
+    /// Sets the focused window (`None` = no window has focus).
+    pub fn focus(&self, hwnd: Option<isize>) {
+        if let Ok(mut focused) = self.focused.lock() {
+            *focused = hwnd;
+        }
+    }
+
+    fn with_window<T>(&self, hwnd: isize, f: impl FnOnce(&MockWindow) -> T) -> Option<T> {
+        let windows = self.windows.lock().ok()?;
+        windows.iter().find(|w| w.hwnd == hwnd).map(f)
+    }
+}
+
+#[cfg(test)]
+impl WindowSource for MockWindowSource {
+    fn foreground_window(&self) -> Option<isize> {
+        self.focused.lock().ok().and_then(|f| *f)
+    }
+
+    fn window_text(&self, hwnd: isize) -> String {
+        self.with_window(hwnd, |w| w.title.clone())
+            .unwrap_or_default()
+    }
+
+    fn window_process_id(&self, hwnd: isize) -> u32 {
+        self.with_window(hwnd, |w| w.pid).unwrap_or(0)
+    }
+
+    fn process_name(&self, pid: u32) -> Option<String> {
+        let windows = self.windows.lock().ok()?;
+        windows
+            .iter()
+            .find(|w| w.pid == pid)
+            .and_then(|w| w.process_name.clone())
+    }
+
+    fn process_path(&self, pid: u32) -> Option<String> {
+        let windows = self.windows.lock().ok()?;
+        windows
+            .iter()
+            .find(|w| w.pid == pid)
+            .and_then(|w| w.process_path.clone())
+    }
+
+    fn is_desktop_window(&self, hwnd: isize) -> bool {
+        self.with_window(hwnd, |w| w.is_desktop).unwrap_or(false)
+    }
+
+    fn is_fullscreen_window(&self, hwnd: isize) -> bool {
+        self.with_window(hwnd, |w| w.is_fullscreen).unwrap_or(false)
+    }
+
+    fn window_monitor(&self, hwnd: isize) -> Option<String> {
+        self.with_window(hwnd, |w| w.monitor.clone()).flatten()
+    }
+
+    fn browser_url(&self, hwnd: isize, _process_name: &str) -> Option<String> {
+        self.with_window(hwnd, |w| w.url.clone()).flatten()
+    }
+
+    fn uwp_app_id(&self, hwnd: isize) -> Option<String> {
+        self.with_window(hwnd, |w| w.uwp_app_id.clone()).flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_window_source_follows_focus() {
+        let source = MockWindowSource::new();
+        assert_eq!(source.foreground_window(), None);
+
+        source.add_window(MockWindow::new(1, 10, "app.exe", "App"));
+        source.add_window(MockWindow {
+            hwnd: 2,
+            pid: 20,
+            ..Default::default()
+        });
+        source.focus(Some(1));
+
+        assert_eq!(source.foreground_window(), Some(1));
+        assert_eq!(source.window_text(1), "App");
+        assert_eq!(source.window_process_id(1), 10);
+        assert_eq!(source.process_name(10).as_deref(), Some("app.exe"));
+        // Unreadable process and unknown window
+        assert_eq!(source.process_name(20), None);
+        assert_eq!(source.window_text(3), "");
+        assert_eq!(source.window_process_id(3), 0);
+    }
+}
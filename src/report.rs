@@ -0,0 +1,268 @@
+//! Signed report bundles for sharing a day's activity.
+//!
+//! A bundle carries a sealed day's signed sessions, its daily integrity
+//! record and OwnMon's public key, plus a signature over all of it, so a
+//! third party can check it with [`verify_report_bundle`] without access to
+//! the database. The bundle only proves it was signed by the key it carries;
+//! compare `public_key` with a copy obtained separately (for example from
+//! `GET /api/integrity/pubkey`) to know whose key that is.
+
+use ed25519_dalek::SigningKey;
+use rusqlite::Result as SqlResult;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::{
+    build_merkle_root, hash_session_data, key_fingerprint, sign_hash, verify_signature,
+};
+use crate::database::{DailyIntegrityRecord, Database, SignedSessionRecord};
+use crate::store::{verify_daily_integrity_record, SessionVerifyIssue};
+
+/// Current report bundle format version.
+pub const REPORT_BUNDLE_VERSION: u32 = 1;
+
+/// The signed content of a report bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub version: u32,
+    pub date: String,
+    pub generated_at: String,
+    /// Base64-encoded Ed25519 public key that signed the bundle.
+    pub public_key: String,
+    /// The day's sealed record, including its Merkle root and signature.
+    pub daily: DailyIntegrityRecord,
+    /// Signed sessions in Merkle leaf order.
+    pub sessions: Vec<SignedSessionRecord>,
+}
+
+/// A report together with the hash and signature covering it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportBundle {
+    pub report: Report,
+    /// Hex SHA-256 of the report serialized as compact JSON.
+    pub bundle_hash: String,
+    /// Signature over `bundle_hash`, made with `sign_hash`.
+    pub signature: String,
+}
+
+/// Result of checking a report bundle.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportVerification {
+    pub date: String,
+    /// Fingerprint of the bundle's public key, to compare against a trusted copy.
+    pub key_fingerprint: String,
+    /// `bundle_hash` matches the report and its signature verifies.
+    pub bundle_signature_valid: bool,
+    pub sessions_checked: usize,
+    /// Sessions whose hash, signature or link to the previous session fails.
+    pub invalid_sessions: Vec<SessionVerifyIssue>,
+    /// The daily Merkle root matches one rebuilt from the session hashes.
+    pub merkle_root_valid: bool,
+    /// The daily record's signature verifies.
+    pub daily_signature_valid: bool,
+    /// Everything above checked out.
+    pub valid: bool,
+}
+
+/// Hashes a report the way its bundle signature covers it.
+pub fn report_hash(report: &Report) -> String {
+    let json = serde_json::to_vec(report).expect("report fields always serialize");
+    hex::encode(Sha256::digest(json))
+}
+
+/// Builds a signed bundle for a sealed day.
+///
+/// Returns `None` if the day has no daily integrity record yet.
+pub fn build_report_bundle(
+    db: &Database,
+    key: &SigningKey,
+    date: &str,
+) -> SqlResult<Option<ReportBundle>> {
+    use base64::Engine;
+
+    let Some(daily) = db.get_daily_integrity(date)? else {
+        return Ok(None);
+    };
+    let report = Report {
+        version: REPORT_BUNDLE_VERSION,
+        date: date.to_string(),
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        public_key: base64::engine::general_purpose::STANDARD
+            .encode(key.verifying_key().as_bytes()),
+        daily,
+        sessions: db.get_signed_sessions_for_date(date)?,
+    };
+    let bundle_hash = report_hash(&report);
+    let signature = sign_hash(&bundle_hash, key);
+
+    Ok(Some(ReportBundle {
+        report,
+        bundle_hash,
+        signature,
+    }))
+}
+
+/// Checks a report bundle using only what it contains.
+///
+/// Verifies the bundle signature, each session's hash, signature and link to
+/// the session before it in the bundle, and the daily Merkle root and
+/// signature. The first session's link to the previous day can't be checked
+/// from the bundle alone. Fails only if the public key can't be decoded.
+pub fn verify_report_bundle(bundle: &ReportBundle) -> Result<ReportVerification, String> {
+    let report = &bundle.report;
+    let key = crate::verify::decode_public_key(&report.public_key)?;
+
+    let bundle_signature_valid = report_hash(report) == bundle.bundle_hash
+        && verify_signature(&bundle.bundle_hash, &bundle.signature, &key);
+
+    let mut invalid_sessions = Vec::new();
+    let mut expected_prev = report.sessions.first().and_then(|s| s.prev_hash.clone());
+    for session in &report.sessions {
+        let recomputed = hash_session_data(
+            &session.process_name,
+            session.window_title.as_deref().unwrap_or_default(),
+            &session.start_time,
+            session.end_time.as_deref().unwrap_or_default(),
+            session.keystrokes as u64,
+            session.clicks as u64,
+            session.scrolls as u64,
+            session.prev_hash.as_deref(),
+        );
+        let hash_valid = recomputed == session.hash;
+        let signature_valid = session
+            .signature
+            .as_deref()
+            .is_some_and(|sig| verify_signature(&session.hash, sig, &key));
+        let chain_valid = session.prev_hash == expected_prev;
+
+        if !(hash_valid && signature_valid && chain_valid) {
+            invalid_sessions.push(SessionVerifyIssue {
+                id: session.id,
+                process_name: session.process_name.clone(),
+                start_time: session.start_time.clone(),
+                hash_valid,
+                signature_valid,
+                chain_valid,
+            });
+        }
+        expected_prev = Some(session.hash.clone());
+    }
+
+    let hashes: Vec<String> = report.sessions.iter().map(|s| s.hash.clone()).collect();
+    let merkle_root_valid = report.daily.date == report.date
+        && build_merkle_root(&hashes).is_some_and(|root| root == report.daily.merkle_root);
+    let daily_signature_valid = verify_daily_integrity_record(&report.daily, &key);
+
+    let valid = bundle_signature_valid
+        && invalid_sessions.is_empty()
+        && merkle_root_valid
+        && daily_signature_valid;
+
+    Ok(ReportVerification {
+        date: report.date.clone(),
+        key_fingerprint: key_fingerprint(&key),
+        bundle_signature_valid,
+        sessions_checked: report.sessions.len(),
+        invalid_sessions,
+        merkle_root_valid,
+        daily_signature_valid,
+        valid,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hash_and_sign_session;
+    use crate::database::SessionIntegrity;
+    use crate::store::WindowSession;
+    use rand::rngs::OsRng;
+
+    /// Seeds a signed, chained and sealed day.
+    fn sealed_day(db: &Database, key: &SigningKey) {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-15T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let mut prev_hash: Option<String> = None;
+        for i in 0..3 {
+            let mut session = WindowSession::new(0, 0, "code.exe".into(), "main.rs".into());
+            session.start_time = start + chrono::Duration::minutes(i * 10);
+            session.keystrokes = 10 + i as u64;
+            let end = session.start_time + chrono::Duration::minutes(5);
+            let (hash, signature) = hash_and_sign_session(
+                key,
+                &session.process_name,
+                &session.window_title,
+                &session.start_time.to_rfc3339(),
+                &end.to_rfc3339(),
+                session.keystrokes,
+                session.mouse_clicks,
+                session.mouse_scrolls,
+                prev_hash.as_deref(),
+            );
+            let integrity = SessionIntegrity {
+                hash: Some(&hash),
+                signature: Some(&signature),
+                prev_hash: prev_hash.as_deref(),
+            };
+            db.save_session(&session, end, integrity).unwrap();
+            prev_hash = Some(hash);
+        }
+        crate::store::compute_daily_integrity_with(db, key, "2025-01-15", true).unwrap();
+    }
+
+    #[test]
+    fn test_report_bundle_round_trip_and_tampering() {
+        let db = Database::open_in_memory().unwrap();
+        let key = SigningKey::generate(&mut OsRng);
+        sealed_day(&db, &key);
+
+        // Unsealed days have no report
+        assert!(build_report_bundle(&db, &key, "2025-01-16")
+            .unwrap()
+            .is_none());
+
+        let bundle = build_report_bundle(&db, &key, "2025-01-15")
+            .unwrap()
+            .unwrap();
+        let json = serde_json::to_string(&bundle).unwrap();
+        let parsed: ReportBundle = serde_json::from_str(&json).unwrap();
+        let result = verify_report_bundle(&parsed).unwrap();
+        assert!(result.valid, "{:?}", result);
+        assert_eq!(result.sessions_checked, 3);
+        assert_eq!(
+            result.key_fingerprint,
+            key_fingerprint(&key.verifying_key())
+        );
+
+        // Editing a session breaks its hash and the bundle signature
+        let mut tampered = parsed.clone();
+        tampered.report.sessions[1].keystrokes = 9999;
+        let result = verify_report_bundle(&tampered).unwrap();
+        assert!(!result.bundle_signature_valid);
+        assert_eq!(result.invalid_sessions.len(), 1);
+        assert!(!result.invalid_sessions[0].hash_valid);
+        assert!(!result.valid);
+
+        // Dropping a session breaks the chain and the Merkle root
+        let mut dropped = parsed.clone();
+        dropped.report.sessions.remove(1);
+        let result = verify_report_bundle(&dropped).unwrap();
+        assert!(!result.merkle_root_valid);
+        assert!(!result.invalid_sessions[0].chain_valid);
+
+        // Re-signing with another key doesn't match the day's signatures
+        let other = SigningKey::generate(&mut OsRng);
+        let mut resigned = parsed;
+        resigned.report.public_key = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            other.verifying_key().as_bytes(),
+        );
+        resigned.bundle_hash = report_hash(&resigned.report);
+        resigned.signature = sign_hash(&resigned.bundle_hash, &other);
+        let result = verify_report_bundle(&resigned).unwrap();
+        assert!(result.bundle_signature_valid);
+        assert!(!result.daily_signature_valid);
+        assert!(!result.valid);
+    }
+}
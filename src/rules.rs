@@ -0,0 +1,186 @@
+//! Shareable rule packs for categories and blacklist entries.
+//!
+//! An optional `rules.json` next to the database (path configurable via the
+//! `rules_path` config) is merged into the database at startup, so teams can
+//! distribute a standard categorization.
+//!
+//! ```json
+//! {
+//!   "force": false,
+//!   "categories": [{ "name": "Design", "color": "#EC4899", "icon": "🎨" }],
+//!   "app_categories": [{ "pattern": "figma*.exe", "category": "Design" }],
+//!   "blacklist": [{ "pattern": "KeePass*.exe", "description": "Password manager" }]
+//! }
+//! ```
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::database::Database;
+
+/// A rule pack loaded from JSON.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RulePack {
+    /// Overwrite existing categories, mappings and blacklist descriptions.
+    #[serde(default)]
+    pub force: bool,
+
+    #[serde(default)]
+    pub categories: Vec<CategoryRule>,
+
+    #[serde(default)]
+    pub app_categories: Vec<AppCategoryRule>,
+
+    #[serde(default)]
+    pub blacklist: Vec<BlacklistRule>,
+}
+
+/// A category to create (matched by name).
+#[derive(Debug, Clone, Deserialize)]
+pub struct CategoryRule {
+    pub name: String,
+    pub color: String,
+    pub icon: Option<String>,
+}
+
+/// An app pattern to map to a category (by category name).
+#[derive(Debug, Clone, Deserialize)]
+pub struct AppCategoryRule {
+    pub pattern: String,
+    pub category: String,
+}
+
+/// A blacklist pattern.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlacklistRule {
+    pub pattern: String,
+    pub description: Option<String>,
+}
+
+/// Counts of rows added or updated by an import.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuleImportSummary {
+    pub categories: usize,
+    pub app_categories: usize,
+    pub blacklist: usize,
+}
+
+/// Returns the rules file path from the `rules_path` config, or the default
+/// `rules.json` in the data directory.
+pub fn rules_path(db: &Database) -> PathBuf {
+    db.get_config("rules_path")
+        .ok()
+        .flatten()
+        .filter(|p| !p.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Database::data_dir().join("rules.json"))
+}
+
+/// Reads and parses a rule pack file.
+pub fn load_rule_pack(path: &Path) -> Result<RulePack, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+/// Imports the rules file into the database if one exists.
+/// Call this on application startup.
+pub fn import_startup_rules() {
+    crate::store::with_db(import_rules_file);
+}
+
+/// Imports the rules file next to `db`, if there is one.
+fn import_rules_file(db: &Database) {
+    let path = rules_path(db);
+    if !path.exists() {
+        tracing::debug!(path = ?path, "No rules file found");
+        return;
+    }
+
+    let rules = match load_rule_pack(&path) {
+        Ok(rules) => rules,
+        Err(e) => {
+            tracing::warn!(path = ?path, error = %e, "Failed to read rules file");
+            return;
+        }
+    };
+
+    match db.import_rules(&rules) {
+        Ok(summary) => tracing::info!(
+            path = ?path,
+            categories = summary.categories,
+            app_categories = summary.app_categories,
+            blacklist = summary.blacklist,
+            "Rules file imported"
+        ),
+        Err(e) => tracing::warn!(path = ?path, ?e, "Failed to import rules file"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RULES: &str = r##"{
+        "categories": [
+            { "name": "Design", "color": "#EC4899", "icon": "🎨" },
+            { "name": "Work", "color": "#000000" }
+        ],
+        "app_categories": [
+            { "pattern": "figma.exe", "category": "Design" },
+            { "pattern": "code.exe", "category": "Design" },
+            { "pattern": "unknown.exe", "category": "Missing" }
+        ],
+        "blacklist": [
+            { "pattern": "secret*.exe", "description": "Private" }
+        ]
+    }"##;
+
+    #[test]
+    fn test_import_sample_rules_file() {
+        let path = std::env::temp_dir().join(format!("ownmon-rules-{}.json", std::process::id()));
+        std::fs::write(&path, SAMPLE_RULES).unwrap();
+        let rules = load_rule_pack(&path);
+        std::fs::remove_file(&path).ok();
+        let rules = rules.unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        let summary = db.import_rules(&rules).unwrap();
+
+        // New category and mapping added; existing "Work" and "code.exe" kept
+        assert_eq!(summary.categories, 1);
+        assert_eq!(summary.app_categories, 1);
+        assert_eq!(summary.blacklist, 1);
+
+        assert_eq!(db.get_category_for_app("figma.exe").unwrap().name, "Design");
+        assert_eq!(db.get_category_for_app("code.exe").unwrap().name, "Work");
+        assert!(db.is_blacklisted("SecretTool.exe"));
+
+        let work = db
+            .get_categories()
+            .unwrap()
+            .into_iter()
+            .find(|c| c.name == "Work")
+            .unwrap();
+        assert_eq!(work.color, "#3B82F6");
+    }
+
+    #[test]
+    fn test_import_rules_force_overwrites() {
+        let mut rules: RulePack = serde_json::from_str(SAMPLE_RULES).unwrap();
+        rules.force = true;
+
+        let db = Database::open_in_memory().unwrap();
+        let summary = db.import_rules(&rules).unwrap();
+        assert_eq!(summary.categories, 2);
+        assert_eq!(summary.app_categories, 2);
+
+        assert_eq!(db.get_category_for_app("code.exe").unwrap().name, "Design");
+        let work = db
+            .get_categories()
+            .unwrap()
+            .into_iter()
+            .find(|c| c.name == "Work")
+            .unwrap();
+        assert_eq!(work.color, "#000000");
+    }
+}
@@ -0,0 +1,99 @@
+//! Optional bearer-token authentication for the API.
+//!
+//! When the `api_token` config key is set, every `/api/*` and `/ws` request
+//! must carry `Authorization: Bearer <token>`. `/health` stays open so
+//! monitoring keeps working.
+
+use axum::{
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::store::with_db;
+
+/// Reads the configured API token.
+///
+/// `Ok(None)` means no token is set. An error means the setting couldn't be
+/// read (no database, or the query failed); callers must refuse the request
+/// rather than treat that as "no token".
+fn configured_token() -> Result<Option<String>, String> {
+    let value = with_db(|db| db.get_config("api_token"))
+        .ok_or_else(|| "Database not available".to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok(value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty()))
+}
+
+/// Whether a request path is protected by the token.
+fn is_protected(path: &str) -> bool {
+    path == "/ws" || path == "/api" || path.starts_with("/api/")
+}
+
+/// Compares two strings without stopping at the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Checks an `Authorization` header value against the expected token.
+pub fn is_authorized(header_value: Option<&str>, token: &str) -> bool {
+    header_value
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|provided| constant_time_eq(provided.trim().as_bytes(), token.as_bytes()))
+}
+
+/// Middleware rejecting unauthenticated requests with 401 when a token is set.
+///
+/// If the token can't be read, protected requests get 503 instead of being
+/// let through unchecked.
+pub async fn require_token(request: Request, next: Next) -> Response {
+    if !is_protected(request.uri().path()) {
+        return next.run(request).await;
+    }
+    let token = match configured_token() {
+        Ok(Some(token)) => token,
+        Ok(None) => return next.run(request).await,
+        Err(e) => {
+            tracing::warn!(error = %e, "Could not read API token, refusing request");
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "API token could not be verified",
+            )
+                .into_response();
+        }
+    };
+
+    let header_value = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok());
+    if !is_authorized(header_value, &token) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "Missing or invalid API token",
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_checks() {
+        assert!(is_authorized(Some("Bearer s3cret"), "s3cret"));
+        assert!(!is_authorized(Some("Bearer wrong"), "s3cret"));
+        assert!(!is_authorized(Some("s3cret"), "s3cret"));
+        assert!(!is_authorized(None, "s3cret"));
+
+        assert!(is_protected("/api/stats"));
+        assert!(is_protected("/ws"));
+        assert!(!is_protected("/health"));
+        assert!(!is_protected("/apikeys"));
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Provides a REST API and WebSocket for real-time updates to frontends.
 
+pub mod auth;
 pub mod routes;
 pub mod state;
 pub mod ws;
@@ -10,8 +11,14 @@ use crate::server::routes::{health, media, sessions, stats};
 use crate::server::state::AppState;
 use crate::server::ws::ws_handler;
 
-use axum::{routing::get, Router};
-use std::net::SocketAddr;
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, post, put},
+    Router,
+};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicU16, Ordering};
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tower_http::cors::{Any, CorsLayer};
@@ -19,26 +26,134 @@ use tower_http::cors::{Any, CorsLayer};
 /// Default server port.
 pub const DEFAULT_PORT: u16 = 13234;
 
+/// Default bind address (loopback only).
+pub const DEFAULT_BIND_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+/// How many consecutive ports to try when the configured one is in use.
+const PORT_ATTEMPTS: u16 = 10;
+
+/// Default number of broadcasts buffered per WebSocket client.
+pub const DEFAULT_WS_CHANNEL_CAPACITY: usize = 1024;
+
+/// Largest accepted `ws_channel_capacity`.
+const MAX_WS_CHANNEL_CAPACITY: usize = 65536;
+
+/// Address and channel settings for the HTTP server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerConfig {
+    pub bind_address: IpAddr,
+    pub port: u16,
+    /// Broadcasts a slow client can fall behind by before it lags.
+    pub channel_capacity: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: DEFAULT_BIND_ADDRESS,
+            port: DEFAULT_PORT,
+            channel_capacity: DEFAULT_WS_CHANNEL_CAPACITY,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Reads `bind_address`, `http_port` and `ws_channel_capacity` from
+    /// config, falling back to the defaults when absent or unparseable.
+    pub fn load() -> Self {
+        let default = Self::default();
+        crate::store::with_db(|db| {
+            let get = |key: &str| db.get_config(key).ok().flatten();
+
+            Self {
+                bind_address: get("bind_address")
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(default.bind_address),
+                port: get("http_port")
+                    .and_then(|v| v.trim().parse().ok())
+                    .filter(|&port| port > 0)
+                    .unwrap_or(default.port),
+                channel_capacity: get("ws_channel_capacity")
+                    .and_then(|v| v.trim().parse::<usize>().ok())
+                    .filter(|&capacity| capacity > 0)
+                    .map(|capacity| capacity.min(MAX_WS_CHANNEL_CAPACITY))
+                    .unwrap_or(default.channel_capacity),
+            }
+        })
+        .unwrap_or(default)
+    }
+}
+
+/// Binds the first free port starting at the configured one.
+///
+/// Only "address in use" moves on to the next port; any other error
+/// (e.g. an address not present on this machine) is returned as is.
+fn bind_listener(config: ServerConfig) -> std::io::Result<std::net::TcpListener> {
+    let mut last_err = None;
+    for port in (config.port..=u16::MAX).take(PORT_ATTEMPTS as usize) {
+        let addr = SocketAddr::new(config.bind_address, port);
+        match std::net::TcpListener::bind(addr) {
+            Ok(listener) => return Ok(listener),
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+                tracing::error!(%addr, "HTTP port already in use, trying the next one");
+                last_err = Some(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| std::io::ErrorKind::AddrInUse.into()))
+}
+
+/// Port the HTTP server actually bound (0 until bound).
+static BOUND_PORT: AtomicU16 = AtomicU16::new(0);
+
+/// Returns the port the HTTP server is listening on, if it has bound one.
+pub fn bound_port() -> Option<u16> {
+    match BOUND_PORT.load(Ordering::Relaxed) {
+        0 => None,
+        port => Some(port),
+    }
+}
+
 /// Starts the HTTP server on a background thread.
 ///
-/// Returns a handle to the broadcast sender for pushing updates.
-pub fn start_server() -> broadcast::Sender<String> {
-    let (tx, _) = broadcast::channel::<String>(100);
+/// Returns a handle to the broadcast sender for pushing updates, and the
+/// address actually bound (`None` if no port could be bound).
+pub fn start_server() -> (broadcast::Sender<String>, Option<SocketAddr>) {
+    let config = ServerConfig::load();
+    let (tx, _) = broadcast::channel::<String>(config.channel_capacity);
     let tx_clone = tx.clone();
 
+    let listener = match bind_listener(config) {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!(
+                ?e,
+                address = %config.bind_address,
+                port = config.port,
+                "Failed to bind HTTP server"
+            );
+            return (tx, None);
+        }
+    };
+    let addr = listener.local_addr().ok();
+    if let Some(addr) = addr {
+        BOUND_PORT.store(addr.port(), Ordering::Relaxed);
+    }
+
     std::thread::spawn(move || {
         let rt = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
         rt.block_on(async {
-            run_server(tx_clone).await;
+            run_server(listener, tx_clone).await;
         });
     });
 
-    tracing::info!(port = DEFAULT_PORT, "HTTP server starting");
-    tx
+    tracing::info!(?addr, "HTTP server starting");
+    (tx, addr)
 }
 
-/// Runs the axum server.
-async fn run_server(broadcast_tx: broadcast::Sender<String>) {
+/// Runs the axum server on an already bound listener.
+async fn run_server(listener: std::net::TcpListener, broadcast_tx: broadcast::Sender<String>) {
     let state = Arc::new(AppState::new(broadcast_tx));
 
     // CORS layer for frontend
@@ -54,27 +169,181 @@ async fn run_server(broadcast_tx: broadcast::Sender<String>) {
         .route("/api/stats", get(stats::get_stats))
         .route("/api/stats/daily", get(stats::get_daily_stats))
         .route("/api/stats/hourly", get(stats::get_hourly_stats))
+        .route("/api/stats/heatmap", get(stats::get_heatmap))
         .route("/api/stats/timeline", get(stats::get_timeline))
+        .route("/api/stats/weekly", get(stats::get_weekly_stats))
+        .route("/api/stats/focus-streaks", get(stats::get_focus_streaks))
+        .route("/api/stats/pace", get(stats::get_pace))
+        .route("/api/stats/score", get(stats::get_score))
+        .route("/api/stats/weekday", get(stats::get_weekday_stats))
+        .route("/api/stats/categories", get(stats::get_category_stats))
+        .route("/api/stats/monitors", get(stats::get_monitor_stats))
+        .route("/api/stats/typing", get(stats::get_typing))
+        .route("/api/summary/last", get(routes::summary::get_last_summary))
         // Data API
         .route("/api/sessions", get(sessions::get_sessions))
+        .route(
+            "/api/sessions/:id",
+            get(sessions::get_session).delete(sessions::delete_session),
+        )
+        .route("/api/sessions/:id/tags", post(sessions::add_session_tag))
+        .route(
+            "/api/sessions/:id/tags/:tag",
+            delete(sessions::remove_session_tag),
+        )
         .route("/api/media", get(media::get_media))
+        .route("/api/media/top-artists", get(media::get_top_artists))
+        .route("/api/media/top-tracks", get(media::get_top_tracks))
         .route("/api/apps", get(stats::get_top_apps))
+        .route("/api/apps/list", get(routes::apps::get_app_list))
         // Categories API
-        .route("/api/categories", get(routes::categories::get_categories))
+        .route(
+            "/api/categories",
+            get(routes::categories::get_categories).post(routes::categories::create_category),
+        )
+        .route(
+            "/api/categories/:id",
+            put(routes::categories::update_category).delete(routes::categories::delete_category),
+        )
+        .route(
+            "/api/categories/full",
+            get(routes::categories::get_categories_full),
+        )
+        .route("/api/apps/:name/icon", get(routes::apps::get_app_icon))
+        .route(
+            "/api/apps/:name/history",
+            get(routes::apps::get_app_history),
+        )
         .route(
             "/api/apps/:name/category",
-            get(routes::categories::get_app_category),
+            get(routes::categories::get_app_category)
+                .post(routes::categories::assign_app_category)
+                .delete(routes::categories::remove_app_category),
+        )
+        .route(
+            "/api/current/category",
+            post(routes::categories::set_current_category),
+        )
+        // Blacklist API
+        .route(
+            "/api/blacklist",
+            get(routes::blacklist::get_blacklist).post(routes::blacklist::add_blacklist),
+        )
+        .route(
+            "/api/blacklist/:pattern",
+            delete(routes::blacklist::remove_blacklist),
+        )
+        // Autostart API
+        .route(
+            "/api/autostart",
+            get(routes::autostart::get_autostart).put(routes::autostart::put_autostart),
         )
+        // Goals API
+        .route(
+            "/api/goals",
+            get(routes::goals::get_goals).post(routes::goals::create_goal),
+        )
+        .route("/api/goals/:id", delete(routes::goals::delete_goal))
         // Config API
         .route("/api/config", get(routes::config::get_config))
+        .route("/api/config/:key", put(routes::config::put_config))
+        .route(
+            "/api/config/idle_overrides",
+            get(routes::config::get_idle_overrides),
+        )
+        .route(
+            "/api/config/idle_overrides/:pattern",
+            put(routes::config::put_idle_override).delete(routes::config::delete_idle_override),
+        )
+        .route(
+            "/api/config/redaction_patterns",
+            get(routes::config::get_redaction_patterns).post(routes::config::add_redaction_pattern),
+        )
+        .route(
+            "/api/config/redaction_patterns/:id",
+            delete(routes::config::delete_redaction_pattern),
+        )
+        // Integrity API
+        .route(
+            "/api/integrity/compute",
+            post(routes::integrity::compute_integrity),
+        )
+        .route(
+            "/api/integrity/recompute",
+            post(routes::integrity::recompute_integrity),
+        )
+        .route("/api/integrity/verify", get(routes::integrity::verify_day))
+        .route(
+            "/api/integrity/status",
+            get(routes::integrity::get_integrity_status),
+        )
+        .route("/api/integrity/proof", get(routes::integrity::get_proof))
+        .route(
+            "/api/integrity/pubkey",
+            get(routes::integrity::get_public_key),
+        )
+        // Export API
+        .route(
+            "/api/export/sessions.csv",
+            get(routes::export::get_sessions_csv),
+        )
+        .route("/api/export/all.jsonl", get(routes::export::get_all_jsonl))
+        .route("/api/export/archive", get(routes::export::get_archive))
+        .route("/api/export/report", get(routes::export::get_report))
+        // Backup API
+        .route("/api/backup", post(routes::backup::create))
+        .route("/api/backups", get(routes::backup::list))
+        .route(
+            "/api/import/archive",
+            post(routes::export::import_archive)
+                .layer(DefaultBodyLimit::max(routes::export::MAX_ARCHIVE_BYTES)),
+        )
+        // Monitoring API
+        .route(
+            "/api/monitoring/status",
+            get(routes::monitoring::get_status),
+        )
+        .route("/api/monitoring/pause", post(routes::monitoring::pause))
+        .route("/api/monitoring/resume", post(routes::monitoring::resume))
+        // Diagnostics API
+        .route("/api/diag/storage", get(routes::diag::get_storage))
+        .route("/api/debug/events", get(routes::diag::get_debug_events))
         // WebSocket
         .route("/ws", get(ws_handler))
+        // Optional API token (CORS stays outermost so preflights pass)
+        .layer(middleware::from_fn(auth::require_token))
         .layer(cors)
         .with_state(state);
 
-    let addr = SocketAddr::from(([127, 0, 0, 1], DEFAULT_PORT));
-    tracing::info!("HTTP server listening on http://{}", addr);
+    listener
+        .set_nonblocking(true)
+        .expect("Failed to make HTTP listener non-blocking");
+    let listener =
+        tokio::net::TcpListener::from_std(listener).expect("Failed to register HTTP listener");
+    if let Ok(addr) = listener.local_addr() {
+        tracing::info!("HTTP server listening on http://{}", addr);
+    }
 
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
     axum::serve(listener, app).await.unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_listener_skips_port_in_use() {
+        let taken = std::net::TcpListener::bind((DEFAULT_BIND_ADDRESS, 0)).unwrap();
+        let taken_port = taken.local_addr().unwrap().port();
+
+        let listener = bind_listener(ServerConfig {
+            bind_address: DEFAULT_BIND_ADDRESS,
+            port: taken_port,
+            ..ServerConfig::default()
+        })
+        .unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert_ne!(port, taken_port);
+        assert!(port > taken_port && port < taken_port + PORT_ATTEMPTS);
+    }
+}
@@ -0,0 +1,174 @@
+//! Application metadata endpoints.
+
+use axum::{
+    extract::{Path, Query},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::database::{AppDailyUsage, AppSeen};
+use crate::server::routes::db_or_503;
+use crate::store::usage_streak;
+
+/// Days of history returned by `/api/apps/:name/history` without `from`.
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+
+/// GET /api/apps/:name/icon - The app's icon as PNG.
+///
+/// Icons are captured when an app's window gains focus, so apps not seen
+/// since startup (or whose icon can't be read, e.g. elevated or UWP
+/// windows) return 404.
+pub async fn get_app_icon(Path(name): Path<String>) -> impl IntoResponse {
+    match crate::winapi_utils::get_app_icon(&name) {
+        Some(png) => Ok((
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                (header::CACHE_CONTROL, "max-age=3600"),
+            ],
+            png,
+        )),
+        None => Err((StatusCode::NOT_FOUND, format!("No icon for {}", name))),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AppHistoryQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AppHistoryResponse {
+    pub process_name: String,
+    /// Start of the app's earliest session.
+    pub first_seen: String,
+    pub from: String,
+    pub to: String,
+    /// Focus time per day in the range; days without use are omitted.
+    pub days: Vec<AppDailyUsage>,
+    /// Consecutive days of use up to today (or yesterday, if not used yet today).
+    pub current_streak_days: u32,
+    pub total_focus_secs: i64,
+    pub total_sessions: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AppListQuery {
+    /// First day (YYYY-MM-DD, default: no lower bound)
+    pub from: Option<String>,
+    /// Last day (YYYY-MM-DD, default: no upper bound)
+    pub to: Option<String>,
+    /// `name` (default) or `recent`
+    pub sort: Option<String>,
+}
+
+/// GET /api/apps/list?from&to&sort - Distinct apps with sessions in a range.
+///
+/// Meant for filter dropdowns and autocomplete, so it only counts sessions
+/// instead of computing full stats. Sorted by name, or most recently seen
+/// first with `sort=recent`.
+pub async fn get_app_list(
+    Query(query): Query<AppListQuery>,
+) -> Result<Json<Vec<AppSeen>>, (StatusCode, String)> {
+    let from = parse_day(query.from.as_deref(), "from")?;
+    let to = parse_day(query.to.as_deref(), "to")?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "from must not be after to".to_string(),
+            ));
+        }
+    }
+    let by_recency = match query.sort.as_deref() {
+        None | Some("name") => false,
+        Some("recent") => true,
+        Some(other) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid sort '{}', expected name or recent", other),
+            ))
+        }
+    };
+    let format = |day: NaiveDate| day.format("%Y-%m-%d").to_string();
+    let (from, to) = (from.map(format), to.map(format));
+
+    let db = db_or_503()?;
+
+    let mut apps = db
+        .get_distinct_apps(from.as_deref(), to.as_deref())
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if by_recency {
+        apps.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+    }
+
+    Ok(Json(apps))
+}
+
+/// Parses an optional `YYYY-MM-DD` query value.
+pub fn parse_day(
+    value: Option<&str>,
+    name: &str,
+) -> Result<Option<NaiveDate>, (StatusCode, String)> {
+    value
+        .map(|v| {
+            NaiveDate::parse_from_str(v, "%Y-%m-%d").map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid {} '{}', expected YYYY-MM-DD", name, v),
+                )
+            })
+        })
+        .transpose()
+}
+
+/// GET /api/apps/:name/history - Daily focus time, streak and lifetime
+/// totals for one app.
+///
+/// Defaults to the last 30 days (including today).
+pub async fn get_app_history(
+    Path(name): Path<String>,
+    Query(query): Query<AppHistoryQuery>,
+) -> Result<Json<AppHistoryResponse>, (StatusCode, String)> {
+    let today = crate::store::timezone_policy().date_of(chrono::Utc::now());
+    let to = parse_day(query.to.as_deref(), "to")?.unwrap_or(today);
+    let from = parse_day(query.from.as_deref(), "from")?
+        .unwrap_or(to - chrono::Duration::days(DEFAULT_HISTORY_DAYS - 1));
+    if from > to {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "from must not be after to".to_string(),
+        ));
+    }
+    let (from, to) = (
+        from.format("%Y-%m-%d").to_string(),
+        to.format("%Y-%m-%d").to_string(),
+    );
+
+    let db = db_or_503()?;
+    let internal = |e: rusqlite::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+    let first_seen = db
+        .get_app_first_seen(&name)
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, format!("No sessions for {}", name)))?;
+    let days = db
+        .get_app_daily_usage(&name, &from, &to)
+        .map_err(internal)?;
+    let usage_dates = db.get_app_usage_dates(&name).map_err(internal)?;
+    let (total_focus_secs, total_sessions) = db.get_app_lifetime_totals(&name).map_err(internal)?;
+
+    Ok(Json(AppHistoryResponse {
+        process_name: name,
+        first_seen,
+        from,
+        to,
+        days,
+        current_streak_days: usage_streak(&usage_dates, today),
+        total_focus_secs,
+        total_sessions,
+    }))
+}
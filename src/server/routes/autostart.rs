@@ -0,0 +1,38 @@
+//! Start-with-Windows endpoints.
+
+use axum::{http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::autostart::{disable_autostart, enable_autostart, is_autostart_enabled};
+
+/// Whether OwnMon starts at login.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutostartStatus {
+    pub enabled: bool,
+}
+
+/// GET /api/autostart - Whether OwnMon is registered to start at login.
+pub async fn get_autostart() -> Json<AutostartStatus> {
+    Json(AutostartStatus {
+        enabled: is_autostart_enabled(),
+    })
+}
+
+/// PUT /api/autostart - Register or unregister OwnMon to start at login.
+pub async fn put_autostart(
+    Json(request): Json<AutostartStatus>,
+) -> Result<Json<AutostartStatus>, (StatusCode, String)> {
+    let result = if request.enabled {
+        enable_autostart()
+    } else {
+        disable_autostart()
+    };
+    result.map_err(|e| {
+        tracing::warn!(error = %e, enabled = request.enabled, "Failed to update autostart");
+        (StatusCode::INTERNAL_SERVER_ERROR, e.message())
+    })?;
+
+    Ok(Json(AutostartStatus {
+        enabled: is_autostart_enabled(),
+    }))
+}
@@ -0,0 +1,23 @@
+//! Database backup endpoints.
+
+use axum::{http::StatusCode, Json};
+
+use crate::backup::{backups_dir, create_backup, list_backups, BackupInfo};
+use crate::server::routes::db_or_503;
+
+/// POST /api/backup - Copy the live database into the backups folder.
+pub async fn create() -> Result<(StatusCode, Json<BackupInfo>), (StatusCode, String)> {
+    let db = db_or_503()?;
+
+    let backup = create_backup(&db, &backups_dir()).map_err(|e| {
+        tracing::warn!(error = %e, "Database backup failed");
+        (StatusCode::INTERNAL_SERVER_ERROR, e)
+    })?;
+
+    Ok((StatusCode::CREATED, Json(backup)))
+}
+
+/// GET /api/backups - List backups, newest first.
+pub async fn list() -> Json<Vec<BackupInfo>> {
+    Json(list_backups(&backups_dir()))
+}
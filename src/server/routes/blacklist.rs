@@ -0,0 +1,83 @@
+//! Blacklist management endpoints.
+//!
+//! The poller checks the blacklist on every focus change, so edits apply
+//! from the next window switch.
+
+use axum::{extract::Path, http::StatusCode, Json};
+use serde::Deserialize;
+
+use crate::database::BlacklistEntry;
+use crate::server::routes::db_or_503;
+use crate::store::lock_db;
+
+/// Request body for adding a blacklist pattern.
+#[derive(Debug, Deserialize)]
+pub struct AddBlacklistRequest {
+    /// Process name pattern (supports * and ? wildcards)
+    pub pattern: String,
+    pub description: Option<String>,
+}
+
+/// GET /api/blacklist - List all blacklist patterns.
+pub async fn get_blacklist() -> Json<Vec<BlacklistEntry>> {
+    let Some(db) = lock_db() else {
+        return Json(vec![]);
+    };
+
+    Json(db.get_blacklist().unwrap_or_default())
+}
+
+/// POST /api/blacklist - Add a pattern and return the created entry.
+pub async fn add_blacklist(
+    Json(request): Json<AddBlacklistRequest>,
+) -> Result<(StatusCode, Json<BlacklistEntry>), (StatusCode, String)> {
+    let pattern = request.pattern.trim();
+    if pattern.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Pattern is empty".to_string()));
+    }
+
+    let db = db_or_503()?;
+
+    let existing = db
+        .get_blacklist_entry(pattern)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if existing.is_some() {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Pattern already blacklisted: {}", pattern),
+        ));
+    }
+
+    db.add_to_blacklist(pattern, request.description.as_deref())
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let entry = db
+        .get_blacklist_entry(pattern)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Blacklist entry was not saved".to_string(),
+        ))?;
+    tracing::info!(pattern = %entry.pattern, id = entry.id, "Blacklist pattern added");
+
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+/// DELETE /api/blacklist/:pattern - Remove a pattern.
+pub async fn remove_blacklist(
+    Path(pattern): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let db = db_or_503()?;
+
+    let removed = db
+        .remove_from_blacklist(&pattern)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !removed {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Pattern not blacklisted: {}", pattern),
+        ));
+    }
+    tracing::info!(pattern = %pattern, "Blacklist pattern removed");
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,41 +1,286 @@
-//! Categories endpoint.
+//! Categories endpoints.
 
-use axum::{extract::Path, Json};
-use serde::Serialize;
+use axum::{extract::Path, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
 
-use crate::database::Category;
-use crate::store::DATABASE;
+pub use crate::database::CategoryWithApps;
 
+use crate::database::{Category, OTHER_CATEGORY_ID};
+use crate::server::routes::db_or_503;
+use crate::store::{lock_db, ACTIVITY_STORE};
+
+/// Request body for setting the current session's category.
+#[derive(Deserialize)]
+pub struct CurrentCategoryRequest {
+    /// Category ID to use, or `null` to go back to the app's mapping.
+    pub category_id: Option<i64>,
+}
+
+/// The current session with its resolved category.
 #[derive(Serialize)]
-pub struct CategoryWithApps {
-    #[serde(flatten)]
+pub struct CurrentCategoryResponse {
+    pub process_name: String,
     pub category: Category,
-    pub apps: Vec<String>,
+    pub is_override: bool,
 }
 
 /// GET /api/categories - List all categories.
 pub async fn get_categories() -> Json<Vec<Category>> {
-    let Some(db_arc) = DATABASE.as_ref() else {
+    let Some(db) = lock_db() else {
         return Json(vec![]);
     };
 
-    let Ok(db) = db_arc.lock() else {
+    match db.get_categories() {
+        Ok(categories) => Json(categories),
+        Err(_) => Json(vec![]),
+    }
+}
+
+/// Request body for creating or updating a category.
+#[derive(Deserialize)]
+pub struct CategoryRequest {
+    pub name: String,
+    /// Display color (e.g. "#3B82F6")
+    pub color: String,
+    pub icon: Option<String>,
+    /// Mark as distracting for focus mode (unchanged on update if omitted)
+    pub distracting: Option<bool>,
+}
+
+/// Returns true if a database error is a UNIQUE constraint violation.
+fn is_duplicate(e: &rusqlite::Error) -> bool {
+    matches!(
+        e,
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation
+    )
+}
+
+/// Maps a create/update error to a response, with 409 for a taken name.
+fn category_write_error(e: rusqlite::Error, name: &str) -> (StatusCode, String) {
+    if is_duplicate(&e) {
+        (
+            StatusCode::CONFLICT,
+            format!("Category already exists: {}", name),
+        )
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+/// POST /api/categories - Create a category.
+pub async fn create_category(
+    Json(request): Json<CategoryRequest>,
+) -> Result<(StatusCode, Json<Category>), (StatusCode, String)> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Name is empty".to_string()));
+    }
+
+    let db = db_or_503()?;
+
+    let id = db
+        .create_category(name, &request.color, request.icon.as_deref())
+        .map_err(|e| category_write_error(e, name))?;
+    let distracting = request.distracting.unwrap_or(false);
+    if distracting {
+        db.set_category_distracting(id, true)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    tracing::info!(id, name, "Category created");
+
+    Ok((
+        StatusCode::CREATED,
+        Json(Category {
+            id,
+            name: name.to_string(),
+            color: request.color,
+            icon: request.icon,
+            distracting,
+        }),
+    ))
+}
+
+/// PUT /api/categories/:id - Rename, recolor or change the icon of a category.
+pub async fn update_category(
+    Path(id): Path<i64>,
+    Json(request): Json<CategoryRequest>,
+) -> Result<Json<Category>, (StatusCode, String)> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Name is empty".to_string()));
+    }
+
+    let db = db_or_503()?;
+
+    let updated = db
+        .update_category(id, name, &request.color, request.icon.as_deref())
+        .map_err(|e| category_write_error(e, name))?;
+    if !updated {
+        return Err((StatusCode::NOT_FOUND, format!("No category with id {}", id)));
+    }
+    if let Some(distracting) = request.distracting {
+        db.set_category_distracting(id, distracting)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+
+    let category = db
+        .get_category(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, format!("No category with id {}", id)))?;
+    Ok(Json(category))
+}
+
+/// DELETE /api/categories/:id - Delete a category.
+///
+/// Apps mapped to it move to "Other", which itself can't be deleted.
+pub async fn delete_category(Path(id): Path<i64>) -> Result<StatusCode, (StatusCode, String)> {
+    if id == OTHER_CATEGORY_ID {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The Other category can't be deleted".to_string(),
+        ));
+    }
+
+    let db = db_or_503()?;
+
+    let deleted = db
+        .delete_category(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, format!("No category with id {}", id)));
+    }
+    tracing::info!(id, "Category deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/categories/full - List all categories with their app patterns.
+pub async fn get_categories_full() -> Json<Vec<CategoryWithApps>> {
+    let Some(db) = lock_db() else {
         return Json(vec![]);
     };
 
-    match db.get_categories() {
+    match db.get_category_apps() {
         Ok(categories) => Json(categories),
         Err(_) => Json(vec![]),
     }
 }
 
-/// GET /api/apps/:name/category - Get category for an app.
-pub async fn get_app_category(Path(name): Path<String>) -> Json<Option<Category>> {
-    let Some(db_arc) = DATABASE.as_ref() else {
-        return Json(None);
+/// POST /api/current/category - Override the category of the current session only.
+pub async fn set_current_category(
+    Json(request): Json<CurrentCategoryRequest>,
+) -> Result<Json<CurrentCategoryResponse>, (StatusCode, String)> {
+    let db = db_or_503()?;
+
+    let override_category = match request.category_id {
+        Some(id) => Some(
+            db.get_category(id)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .ok_or((StatusCode::NOT_FOUND, format!("No category with id {}", id)))?,
+        ),
+        None => None,
     };
 
-    let Ok(db) = db_arc.lock() else {
+    let process_name = {
+        let mut store = ACTIVITY_STORE.write().map_err(|_| {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Activity store not available".to_string(),
+            )
+        })?;
+        if !store.set_current_category(request.category_id) {
+            return Err((StatusCode::CONFLICT, "No active session".to_string()));
+        }
+        store
+            .current_session
+            .as_ref()
+            .map(|s| s.process_name.clone())
+            .unwrap_or_default()
+    };
+
+    let is_override = override_category.is_some();
+    let category = match override_category {
+        Some(category) => category,
+        None => db
+            .get_category_for_app(&process_name)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+    };
+
+    Ok(Json(CurrentCategoryResponse {
+        process_name,
+        category,
+        is_override,
+    }))
+}
+
+/// Request body for assigning an app pattern to a category.
+#[derive(Deserialize)]
+pub struct AppCategoryRequest {
+    pub category_id: i64,
+}
+
+/// An app pattern with its assigned category.
+#[derive(Serialize)]
+pub struct AppCategoryResponse {
+    pub pattern: String,
+    pub category: Category,
+}
+
+/// POST /api/apps/:name/category - Map an app (or wildcard pattern) to a category.
+///
+/// Patterns like `*YouTube*` may be sent URL-encoded (`%2AYouTube%2A`).
+pub async fn assign_app_category(
+    Path(name): Path<String>,
+    Json(request): Json<AppCategoryRequest>,
+) -> Result<Json<AppCategoryResponse>, (StatusCode, String)> {
+    let pattern = name.trim();
+    if pattern.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "App name is empty".to_string()));
+    }
+
+    let db = db_or_503()?;
+
+    let category = db
+        .get_category(request.category_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("No category with id {}", request.category_id),
+        ))?;
+
+    db.set_app_category(pattern, category.id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    tracing::info!(pattern, category = %category.name, "App category assigned");
+
+    Ok(Json(AppCategoryResponse {
+        pattern: pattern.to_string(),
+        category,
+    }))
+}
+
+/// DELETE /api/apps/:name/category - Remove an app pattern's category mapping.
+pub async fn remove_app_category(
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let db = db_or_503()?;
+
+    let removed = db
+        .remove_app_category(name.trim())
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !removed {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("No category mapping for {}", name),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/apps/:name/category - Get category for an app.
+pub async fn get_app_category(Path(name): Path<String>) -> Json<Option<Category>> {
+    let Some(db) = lock_db() else {
         return Json(None);
     };
 
@@ -1,9 +1,12 @@
-//! Configuration endpoint.
+//! Configuration endpoints.
 
-use axum::{http::StatusCode, Json};
-use serde::Serialize;
+use axum::{extract::Path, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
 
-use crate::store::DATABASE;
+use crate::database::{IdleOverride, RedactionPattern};
+use crate::monitor::title_redaction::RedactionMode;
+use crate::server::routes::db_or_503;
+use crate::store::{lock_db, with_db, TimezonePolicy};
 
 #[derive(Debug, Serialize)]
 pub struct ConfigResponse {
@@ -19,11 +22,7 @@ pub struct ConfigSetting {
 
 /// GET /api/config - Get all configuration settings
 pub async fn get_config() -> Result<Json<ConfigResponse>, StatusCode> {
-    let db = DATABASE
-        .as_ref()
-        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?
-        .lock()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let db = lock_db().ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
 
     match db.get_all_config() {
         Ok(config) => {
@@ -44,3 +43,247 @@ pub async fn get_config() -> Result<Json<ConfigResponse>, StatusCode> {
         }
     }
 }
+
+/// Request body for updating a setting.
+#[derive(Debug, Deserialize)]
+pub struct UpdateConfigRequest {
+    pub value: String,
+}
+
+/// Checks a new value against the type of the current one.
+///
+/// Settings holding a boolean or a number only accept the same type, so a
+/// typo can't silently turn into the default. `poll_interval_ms` must be
+/// positive.
+pub fn validate_config_value(key: &str, current: &str, value: &str) -> Result<(), String> {
+    if key == "title_redaction" && RedactionMode::parse(value).is_none() {
+        return Err(format!("{} must be none, hash or strip", key));
+    }
+    if key == "timezone" && TimezonePolicy::parse(value).is_none() {
+        return Err(format!(
+            "{} must be local, utc or an offset like +07:00",
+            key
+        ));
+    }
+    if current.parse::<bool>().is_ok() {
+        value
+            .parse::<bool>()
+            .map_err(|_| format!("{} must be true or false", key))?;
+    } else if current.parse::<i64>().is_ok() {
+        let n = value
+            .parse::<i64>()
+            .map_err(|_| format!("{} must be an integer", key))?;
+        if n < 0 || (key == "poll_interval_ms" && n == 0) {
+            return Err(format!("{} is out of range", key));
+        }
+    } else if current.parse::<f64>().is_ok() {
+        value
+            .parse::<f64>()
+            .map_err(|_| format!("{} must be a number", key))?;
+    }
+    Ok(())
+}
+
+/// PUT /api/config/:key - Update an existing setting.
+///
+/// Changing `poll_interval_ms`, `debug_event_log` or `privacy_mode` takes
+/// effect on the running poller and input hooks immediately. While
+/// `privacy_mode` is on, `debug_event_log` can't be turned on.
+pub async fn put_config(
+    Path(key): Path<String>,
+    Json(request): Json<UpdateConfigRequest>,
+) -> Result<Json<ConfigSetting>, (StatusCode, String)> {
+    let db = db_or_503()?;
+
+    let current = db
+        .get_config(&key)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, format!("Unknown setting: {}", key)))?;
+
+    let value = request.value.trim();
+    validate_config_value(&key, &current, value).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let config_enabled = |key: &str| db.get_config(key).ok().flatten().as_deref() == Some("true");
+    if key == "debug_event_log" && value == "true" && config_enabled("privacy_mode") {
+        return Err((
+            StatusCode::CONFLICT,
+            "debug_event_log can't be enabled while privacy_mode is on".to_string(),
+        ));
+    }
+
+    db.set_config(&key, value)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if key == "api_token" {
+        tracing::info!(key = %key, "Config updated");
+    } else {
+        tracing::info!(key = %key, value = %value, "Config updated");
+    }
+
+    if key == "poll_interval_ms" {
+        if let Ok(ms) = value.parse() {
+            crate::monitor::set_poll_interval(std::time::Duration::from_millis(ms));
+        }
+    }
+    if key == "debug_event_log" {
+        crate::monitor::set_debug_event_log(value == "true");
+    }
+    if key == "privacy_mode" {
+        crate::monitor::set_privacy_mode(value == "true");
+        crate::monitor::set_debug_event_log(config_enabled("debug_event_log"));
+    }
+    if key == "timezone" {
+        crate::store::set_timezone_policy(TimezonePolicy::from_config(Some(value)));
+    }
+
+    let description = db
+        .get_all_config()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .find(|(k, _, _)| *k == key)
+        .and_then(|(_, _, description)| description);
+
+    Ok(Json(ConfigSetting {
+        key,
+        value: value.to_string(),
+        description,
+    }))
+}
+
+/// Request body for setting an app's idle threshold.
+#[derive(Debug, Deserialize)]
+pub struct IdleOverrideRequest {
+    pub threshold_secs: i64,
+}
+
+/// GET /api/config/idle_overrides - List per-app idle thresholds.
+pub async fn get_idle_overrides() -> Json<Vec<IdleOverride>> {
+    let overrides = with_db(|db| db.get_idle_overrides().ok())
+        .flatten()
+        .unwrap_or_default();
+    Json(overrides)
+}
+
+/// PUT /api/config/idle_overrides/:pattern - Set an app's idle threshold.
+///
+/// `pattern` is a process name or wildcard pattern. A very high threshold
+/// (e.g. 86400) effectively means "never idle" for media players.
+pub async fn put_idle_override(
+    Path(pattern): Path<String>,
+    Json(request): Json<IdleOverrideRequest>,
+) -> Result<Json<IdleOverride>, (StatusCode, String)> {
+    if request.threshold_secs <= 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "threshold_secs must be positive".to_string(),
+        ));
+    }
+
+    let db = db_or_503()?;
+
+    db.set_idle_override(&pattern, request.threshold_secs)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    tracing::info!(pattern = %pattern, secs = request.threshold_secs, "Idle threshold set");
+
+    Ok(Json(IdleOverride {
+        process_pattern: pattern,
+        threshold_secs: request.threshold_secs,
+    }))
+}
+
+/// DELETE /api/config/idle_overrides/:pattern - Remove an app's idle threshold.
+pub async fn delete_idle_override(
+    Path(pattern): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let db = db_or_503()?;
+
+    let removed = db
+        .remove_idle_override(&pattern)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !removed {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("No idle threshold for: {}", pattern),
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Request body for adding a title redaction pattern.
+#[derive(Debug, Deserialize)]
+pub struct RedactionPatternRequest {
+    pub pattern: String,
+}
+
+/// GET /api/config/redaction_patterns - List title redaction patterns.
+pub async fn get_redaction_patterns() -> Json<Vec<RedactionPattern>> {
+    let patterns = with_db(|db| db.get_redaction_patterns().ok())
+        .flatten()
+        .unwrap_or_default();
+    Json(patterns)
+}
+
+/// POST /api/config/redaction_patterns - Add a title redaction pattern.
+///
+/// Takes effect on the next focus or title change.
+pub async fn add_redaction_pattern(
+    Json(request): Json<RedactionPatternRequest>,
+) -> Result<(StatusCode, Json<RedactionPattern>), (StatusCode, String)> {
+    let pattern = request.pattern.trim();
+    if pattern.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "pattern is required".to_string()));
+    }
+    regex::Regex::new(pattern)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid pattern: {}", e)))?;
+
+    let db = db_or_503()?;
+
+    let entry = db
+        .add_redaction_pattern(pattern)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    tracing::info!(id = entry.id, "Redaction pattern added");
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+/// DELETE /api/config/redaction_patterns/:id - Remove a title redaction pattern.
+pub async fn delete_redaction_pattern(
+    Path(id): Path<i64>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let db = db_or_503()?;
+
+    let removed = db
+        .remove_redaction_pattern(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !removed {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("No redaction pattern with id {}", id),
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_config_value_by_current_type() {
+        assert!(validate_config_value("poll_interval_ms", "100", "250").is_ok());
+        assert!(validate_config_value("poll_interval_ms", "100", "fast").is_err());
+        assert!(validate_config_value("poll_interval_ms", "100", "0").is_err());
+        assert!(validate_config_value("afk_threshold_secs", "300", "-5").is_err());
+        assert!(validate_config_value("afk_threshold_secs", "300", "0").is_ok());
+
+        assert!(validate_config_value("track_title_changes", "false", "true").is_ok());
+        assert!(validate_config_value("track_title_changes", "false", "yes").is_err());
+
+        assert!(validate_config_value("title_redaction", "none", "hash").is_ok());
+        assert!(validate_config_value("title_redaction", "none", "blur").is_err());
+        assert!(validate_config_value("timezone", "local", "+07:00").is_ok());
+        assert!(validate_config_value("timezone", "local", "Europe/Paris").is_err());
+
+        // Free-form settings accept any text
+        assert!(validate_config_value("privacy_patterns", "", "KeePass*").is_ok());
+        assert!(validate_config_value("work_hours", "09:00-17:00", "08:00-16:00").is_ok());
+    }
+}
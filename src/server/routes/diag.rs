@@ -0,0 +1,36 @@
+//! Diagnostics endpoints.
+
+use axum::{http::StatusCode, Json};
+use serde::Serialize;
+
+use crate::database::StorageStats;
+use crate::monitor::{DebugEvent, DEBUG_EVENT_CAPACITY};
+use crate::server::routes::db_or_503;
+
+/// GET /api/diag/storage - Database size on disk and row counts
+pub async fn get_storage() -> Result<Json<StorageStats>, (StatusCode, String)> {
+    let db = db_or_503()?;
+
+    db.get_storage_stats()
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+#[derive(Debug, Serialize)]
+pub struct DebugEventsResponse {
+    pub enabled: bool,
+    pub capacity: usize,
+    pub total_recorded: usize,
+    pub events: Vec<DebugEvent>,
+}
+
+/// GET /api/debug/events - Raw input events kept by the debug event log
+pub async fn get_debug_events() -> Json<DebugEventsResponse> {
+    let (events, total_recorded) = crate::monitor::debug_events();
+    Json(DebugEventsResponse {
+        enabled: crate::monitor::debug_event_log_enabled(),
+        capacity: DEBUG_EVENT_CAPACITY,
+        total_recorded,
+        events,
+    })
+}
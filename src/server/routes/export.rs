@@ -0,0 +1,349 @@
+//! Export and import endpoints.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::Query,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use crate::archive::{decode_archive, encode_archive, ArchiveImportSummary};
+use crate::database::{ExportCursor, SessionWithDuration};
+use crate::report::build_report_bundle;
+use crate::server::routes::db_or_503;
+use crate::server::routes::integrity::parse_date;
+use crate::server::routes::sessions::SessionsQuery;
+use crate::store::{with_db, KEY_MANAGER};
+
+/// Largest archive accepted by the import endpoint.
+pub const MAX_ARCHIVE_BYTES: usize = 256 * 1024 * 1024;
+
+/// GET /api/export/archive - Download all sessions and media as a binary archive.
+pub async fn get_archive() -> Result<impl IntoResponse, (StatusCode, String)> {
+    let db = db_or_503()?;
+
+    let archive = db
+        .export_archive()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let bytes =
+        encode_archive(&archive).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let filename = format!(
+        "attachment; filename=\"ownmon-{}.omarc\"",
+        crate::store::local_date_string()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (header::CONTENT_DISPOSITION, filename),
+        ],
+        bytes,
+    ))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ReportQuery {
+    pub date: Option<String>,
+    pub format: Option<String>,
+}
+
+/// GET /api/export/report - Download a sealed day as a signed report bundle.
+///
+/// `format=signed` is the only format (and the default). The day must have
+/// a daily integrity record.
+pub async fn get_report(
+    Query(query): Query<ReportQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let date = parse_date(query.date)?;
+    if let Some(format) = query.format.as_deref().filter(|f| *f != "signed") {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unsupported report format '{}', expected signed", format),
+        ));
+    }
+
+    let key_manager = KEY_MANAGER.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Key manager not available".to_string(),
+    ))?;
+    let db = db_or_503()?;
+
+    let bundle = build_report_bundle(&db, key_manager.signing_key(), &date)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("No sealed integrity record for {}", date),
+        ))?;
+
+    let filename = format!("attachment; filename=\"ownmon-report-{}.json\"", date);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::CONTENT_DISPOSITION, filename),
+        ],
+        Json(bundle),
+    ))
+}
+
+/// POST /api/import/archive - Import an archive produced by `/api/export/archive`.
+pub async fn import_archive(
+    body: Bytes,
+) -> Result<Json<ArchiveImportSummary>, (StatusCode, String)> {
+    let archive = decode_archive(&body).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let db = db_or_503()?;
+
+    let summary = db
+        .import_archive(&archive)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    tracing::info!(
+        sessions = summary.sessions,
+        media = summary.media,
+        skipped = summary.skipped,
+        "Archive imported"
+    );
+
+    Ok(Json(summary))
+}
+
+/// Header row of the sessions CSV export.
+pub const SESSIONS_CSV_HEADER: &str =
+    "id,process_name,window_title,start_time,end_time,duration_secs,keystrokes,clicks,scrolls,is_idle,category";
+
+/// Quotes a CSV field if it contains a comma, quote or line break.
+///
+/// Values starting with `=`, `+`, `-` or `@` get a leading `'` so spreadsheet
+/// apps show them as text instead of running them as formulas.
+pub fn csv_field(value: &str) -> Cow<'_, str> {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        Cow::Owned(format!("'{}", value))
+    } else {
+        Cow::Borrowed(value)
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        value
+    }
+}
+
+/// Formats one session as a CSV line (with trailing CRLF).
+fn session_csv_row(session: &SessionWithDuration, category: &str) -> String {
+    format!(
+        "{},{},{},{},{},{},{},{},{},{},{}\r\n",
+        session.id,
+        csv_field(&session.process_name),
+        csv_field(session.window_title.as_deref().unwrap_or_default()),
+        session.start_time,
+        session.end_time.as_deref().unwrap_or_default(),
+        session.duration_secs,
+        session.keystrokes,
+        session.clicks,
+        session.scrolls,
+        session.is_idle,
+        csv_field(category),
+    )
+}
+
+/// Reads the next page of sessions for a CSV export as CSV text.
+///
+/// Returns `Ok(None)` once all pages have been read.
+fn sessions_csv_page(
+    query: &SessionsQuery,
+    date: Option<&str>,
+    offset: usize,
+) -> Result<Option<(String, usize)>, String> {
+    with_db(|db| {
+        let page_size = db.query_limits().max.max(1);
+        let (sessions, _) = db
+            .query_sessions_flexible(
+                date,
+                query.from.as_deref(),
+                query.to.as_deref(),
+                query.app.as_deref(),
+                query.category,
+                query.tag.as_deref(),
+                page_size,
+                offset,
+                false,
+            )
+            .map_err(|e| e.to_string())?;
+        if sessions.is_empty() {
+            return Ok(None);
+        }
+
+        let mut chunk = String::new();
+        for session in &sessions {
+            let category = db
+                .get_category_for_session(session)
+                .map(|c| c.name)
+                .unwrap_or_default();
+            chunk.push_str(&session_csv_row(session, &category));
+        }
+        Ok(Some((chunk, offset + sessions.len())))
+    })
+    .unwrap_or_else(|| Err("Database not available".to_string()))
+}
+
+/// Runs one page read of a streamed export on the blocking thread pool.
+///
+/// A failed read becomes a stream error, which aborts the response instead
+/// of ending it as if the download were complete.
+async fn read_export_page<T, F>(read: F) -> Result<Option<T>, std::io::Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<Option<T>, String> + Send + 'static,
+{
+    tokio::task::spawn_blocking(read)
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|page| page)
+        .map_err(|e| {
+            tracing::warn!(error = %e, "Export stopped by an error");
+            std::io::Error::other(e)
+        })
+}
+
+/// GET /api/export/sessions.csv - Download sessions as CSV.
+///
+/// Takes the same `date`/`from`/`to`/`app`/`category` filters as
+/// `/api/sessions` (today by default) but has no row limit. Rows are read
+/// and sent one page at a time, oldest first.
+pub async fn get_sessions_csv(Query(query): Query<SessionsQuery>) -> impl IntoResponse {
+    let date = if query.date.is_none() && query.from.is_none() && query.to.is_none() {
+        Some(crate::store::local_date_string())
+    } else {
+        query.date.clone()
+    };
+    let filename = format!(
+        "attachment; filename=\"ownmon-sessions-{}.csv\"",
+        date.clone().unwrap_or_else(crate::store::local_date_string)
+    );
+
+    let header_row = futures::stream::once(async {
+        Ok::<_, std::io::Error>(format!("{}\r\n", SESSIONS_CSV_HEADER))
+    });
+    let query = Arc::new(query);
+    let rows = futures::stream::unfold(Some(0), move |offset| {
+        let query = Arc::clone(&query);
+        let date = date.clone();
+        async move {
+            let offset = offset?;
+            let page =
+                read_export_page(move || sessions_csv_page(&query, date.as_deref(), offset)).await;
+            match page {
+                Ok(Some((chunk, next))) => Some((Ok(chunk), Some(next))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), None)),
+            }
+        }
+    });
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, filename),
+        ],
+        Body::from_stream(futures::StreamExt::chain(header_row, rows)),
+    )
+}
+
+/// Reads the next page of the JSON Lines export.
+///
+/// Returns `Ok(None)` once every record has been read. A record that fails
+/// to serialize fails the page rather than being left out of the backup.
+fn jsonl_page(cursor: &ExportCursor) -> Result<Option<(String, ExportCursor)>, String> {
+    let mut chunk = String::new();
+    let mut serialize_error = None;
+    let next = with_db(|db| {
+        let page_size = db.query_limits().max;
+        db.for_each_export_record(cursor, page_size, |record| {
+            if serialize_error.is_some() {
+                return;
+            }
+            match serde_json::to_string(record) {
+                Ok(line) => {
+                    chunk.push_str(&line);
+                    chunk.push('\n');
+                }
+                Err(e) => serialize_error = Some(e.to_string()),
+            }
+        })
+    })
+    .ok_or_else(|| "Database not available".to_string())?
+    .map_err(|e| e.to_string())?;
+
+    if let Some(e) = serialize_error {
+        return Err(e);
+    }
+    Ok(next.map(|next| (chunk, next)))
+}
+
+/// GET /api/export/all.jsonl - Download every session and media record as
+/// JSON Lines.
+///
+/// One object per line, tagged with `type` and ordered by start time.
+/// Sessions keep their integrity fields so the dump can be verified against
+/// the public key. Records are read and sent one page at a time.
+pub async fn get_all_jsonl() -> impl IntoResponse {
+    let filename = format!(
+        "attachment; filename=\"ownmon-{}.jsonl\"",
+        crate::store::local_date_string()
+    );
+    let lines = futures::stream::unfold(Some(ExportCursor::default()), |cursor| async move {
+        let cursor = cursor?;
+        match read_export_page(move || jsonl_page(&cursor)).await {
+            Ok(Some((chunk, next))) => Some((Ok(chunk), Some(next))),
+            Ok(None) => None,
+            Err(e) => Some((Err(e), None)),
+        }
+    });
+
+    (
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (header::CONTENT_DISPOSITION, filename),
+        ],
+        Body::from_stream(lines),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("chrome.exe"), "chrome.exe");
+        assert_eq!(csv_field("Inbox, 3 unread"), "\"Inbox, 3 unread\"");
+        assert_eq!(
+            csv_field("Say \"hi\" - Notepad"),
+            "\"Say \"\"hi\"\" - Notepad\""
+        );
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+
+        // Formula-like values are kept as text
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("+1 555"), "'+1 555");
+        assert_eq!(csv_field("-rf"), "'-rf");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+
+        let session = SessionWithDuration {
+            id: 7,
+            process_name: "code.exe".to_string(),
+            window_title: Some("a, \"b\"".to_string()),
+            start_time: "2025-01-15T10:00:00+00:00".to_string(),
+            end_time: Some("2025-01-15T10:05:00+00:00".to_string()),
+            duration_secs: 300,
+            keystrokes: 12,
+            ..Default::default()
+        };
+        assert_eq!(
+            session_csv_row(&session, "Work"),
+            "7,code.exe,\"a, \"\"b\"\"\",2025-01-15T10:00:00+00:00,2025-01-15T10:05:00+00:00,300,12,0,0,false,Work\r\n"
+        );
+    }
+}
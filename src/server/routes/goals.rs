@@ -0,0 +1,77 @@
+//! Goal endpoints.
+
+use axum::{extract::Path, http::StatusCode, Json};
+use serde::Deserialize;
+
+use crate::database::Goal;
+use crate::monitor::goals::{GoalMetric, DAILY};
+use crate::server::routes::db_or_503;
+use crate::store::with_db;
+
+#[derive(Debug, Deserialize)]
+pub struct GoalRequest {
+    pub metric: String,
+    pub threshold: i64,
+    /// Defaults to `daily`, the only period supported.
+    pub period: Option<String>,
+}
+
+/// GET /api/goals - List goals.
+pub async fn get_goals() -> Json<Vec<Goal>> {
+    let goals = with_db(|d| d.get_goals().ok())
+        .flatten()
+        .unwrap_or_default();
+    Json(goals)
+}
+
+/// POST /api/goals - Add a goal, e.g. `{"metric": "keystrokes", "threshold": 10000}`.
+pub async fn create_goal(
+    Json(request): Json<GoalRequest>,
+) -> Result<(StatusCode, Json<Goal>), (StatusCode, String)> {
+    if GoalMetric::parse(&request.metric).is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown metric '{}', expected keystrokes, clicks or focus_time_secs",
+                request.metric
+            ),
+        ));
+    }
+    if request.threshold <= 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "threshold must be positive".to_string(),
+        ));
+    }
+    let period = request.period.as_deref().unwrap_or(DAILY);
+    if period != DAILY {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unsupported period '{}', expected daily", period),
+        ));
+    }
+
+    let db = db_or_503()?;
+
+    let goal = db
+        .add_goal(&request.metric, request.threshold, period)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    tracing::info!(id = goal.id, metric = %goal.metric, threshold = goal.threshold, "Goal added");
+
+    Ok((StatusCode::CREATED, Json(goal)))
+}
+
+/// DELETE /api/goals/:id - Remove a goal.
+pub async fn delete_goal(Path(id): Path<i64>) -> Result<StatusCode, (StatusCode, String)> {
+    let db = db_or_503()?;
+
+    let deleted = db
+        .delete_goal(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, format!("No goal with id {}", id)));
+    }
+    tracing::info!(id, "Goal deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,18 +1,62 @@
 //! Health check endpoint.
 
+use crate::store::{
+    input_hooks_active, is_monitoring_paused, is_tracking_paused, ACTIVITY_STORE, DATABASE,
+    KEY_MANAGER, STARTED_AT,
+};
 use axum::Json;
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 
 #[derive(Serialize)]
 pub struct HealthResponse {
     pub status: &'static str,
     pub version: &'static str,
+    /// When monitoring started.
+    pub started_at: DateTime<Utc>,
+    pub uptime_secs: i64,
+    /// Whether sessions are being persisted.
+    pub database: bool,
+    /// Whether sessions are being signed.
+    pub integrity: bool,
+    /// Whether keystrokes and clicks are being counted.
+    pub input_hooks_active: bool,
+    /// Sessions recorded since start, including the active one.
+    pub session_count: u32,
+    pub monitoring_paused: bool,
+    pub tracking_paused: bool,
+    /// Size of the database's write-ahead log on disk (`None` without a database).
+    pub wal_size_bytes: Option<u64>,
+    /// Whether keystroke privacy mode is in effect.
+    pub privacy_mode: bool,
+    /// How often input counter flushes found the store locked.
+    pub counter_flush: crate::monitor::CounterFlushStats,
 }
 
 /// GET /health - Server health check.
+///
+/// Reports whether the monitor is actually collecting data, not just that
+/// the HTTP thread is alive.
 pub async fn health_check() -> Json<HealthResponse> {
+    let started_at = *STARTED_AT;
+    let session_count = ACTIVITY_STORE
+        .read()
+        .map(|store| store.get_daily_summary().session_count)
+        .unwrap_or(0);
+
     Json(HealthResponse {
         status: "ok",
         version: env!("CARGO_PKG_VERSION"),
+        started_at,
+        uptime_secs: (Utc::now() - started_at).num_seconds(),
+        database: DATABASE.is_some(),
+        integrity: KEY_MANAGER.is_some(),
+        input_hooks_active: input_hooks_active(),
+        session_count,
+        monitoring_paused: is_monitoring_paused(),
+        tracking_paused: is_tracking_paused(),
+        wal_size_bytes: crate::store::with_db(|db| db.wal_size_bytes()),
+        privacy_mode: crate::monitor::privacy_mode_enabled(),
+        counter_flush: crate::monitor::counter_flush_stats(),
     })
 }
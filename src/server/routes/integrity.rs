@@ -0,0 +1,273 @@
+//! Daily integrity endpoints.
+
+use axum::{extract::Query, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{build_merkle_root, key_fingerprint, merkle_proof};
+use crate::database::DailyIntegrityRecord;
+use crate::server::routes::apps::parse_day;
+use crate::server::routes::db_or_503;
+use crate::store::{
+    compute_daily_integrity, integrity_status_with, recompute_daily_integrity, save_pending_to_db,
+    verify_integrity, DayIntegrityStatus, IntegrityError, IntegrityRecompute, IntegrityReport,
+    KEY_MANAGER,
+};
+
+/// Days covered by `GET /api/integrity/status` when `from` is omitted.
+const DEFAULT_STATUS_DAYS: i64 = 30;
+
+#[derive(Debug, Deserialize)]
+pub struct ComputeQuery {
+    pub date: Option<String>,
+}
+
+/// Parses an optional `YYYY-MM-DD` date, defaulting to today (UTC).
+pub fn parse_date(date: Option<String>) -> Result<String, (StatusCode, String)> {
+    match date {
+        Some(date) => Ok(chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid date '{}', expected YYYY-MM-DD", date),
+                )
+            })?
+            .format("%Y-%m-%d")
+            .to_string()),
+        None => Ok(crate::store::local_date_string()),
+    }
+}
+
+/// Maps an integrity error to a response status.
+fn error_status(e: &IntegrityError) -> StatusCode {
+    match e {
+        IntegrityError::DatabaseUnavailable | IntegrityError::KeyManagerUnavailable => {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+        IntegrityError::Database(_) | IntegrityError::MerkleRoot => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// POST /api/integrity/compute - Seal a day's integrity record on demand
+pub async fn compute_integrity(
+    Query(query): Query<ComputeQuery>,
+) -> Result<Json<DailyIntegrityRecord>, (StatusCode, String)> {
+    let date = parse_date(query.date)?;
+
+    // Make sure everything recorded so far is part of the root
+    save_pending_to_db();
+
+    match compute_daily_integrity(&date) {
+        Ok(Some(record)) => Ok(Json(record)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            format!("No signed sessions for {}", date),
+        )),
+        Err(e) => {
+            tracing::warn!(date = %date, error = %e, "On-demand integrity computation failed");
+            let status = error_status(&e);
+            Err((status, e.to_string()))
+        }
+    }
+}
+
+/// POST /api/integrity/recompute - Re-seal a day after its sessions changed
+///
+/// Later sealed days are re-signed so the day-to-day chain still links.
+pub async fn recompute_integrity(
+    Query(query): Query<ComputeQuery>,
+) -> Result<Json<IntegrityRecompute>, (StatusCode, String)> {
+    let date = parse_date(query.date)?;
+    save_pending_to_db();
+
+    match recompute_daily_integrity(&date) {
+        Ok(Some(result)) => {
+            if result.old_merkle_root.as_deref() != Some(result.new_merkle_root.as_str()) {
+                tracing::warn!(
+                    date = %date,
+                    old_root = ?result.old_merkle_root,
+                    new_root = %result.new_merkle_root,
+                    "Sealed Merkle root replaced"
+                );
+            }
+            Ok(Json(result))
+        }
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            format!("No signed sessions for {}", date),
+        )),
+        Err(e) => {
+            tracing::warn!(date = %date, error = %e, "Integrity recomputation failed");
+            Err((error_status(&e), e.to_string()))
+        }
+    }
+}
+
+/// GET /api/integrity/verify - Check a day's session hashes, signatures and daily record
+pub async fn verify_day(
+    Query(query): Query<ComputeQuery>,
+) -> Result<Json<IntegrityReport>, (StatusCode, String)> {
+    let date = parse_date(query.date)?;
+
+    match verify_integrity(&date) {
+        Ok(report) => {
+            if !report.valid {
+                tracing::warn!(
+                    date = %date,
+                    invalid_sessions = report.invalid_sessions.len(),
+                    "Integrity verification failed"
+                );
+            }
+            Ok(Json(report))
+        }
+        Err(e) => Err((error_status(&e), e.to_string())),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProofQuery {
+    pub date: Option<String>,
+    pub session_id: i64,
+}
+
+/// One step of a Merkle inclusion proof.
+#[derive(Debug, Serialize)]
+pub struct ProofStep {
+    /// Sibling hash at this level.
+    pub hash: String,
+    /// True if the sibling is the left node (hash it first).
+    pub is_left: bool,
+}
+
+/// Proof that one session is part of a sealed day.
+#[derive(Debug, Serialize)]
+pub struct ProofResponse {
+    pub session_id: i64,
+    /// Position of the session among the day's signed sessions.
+    pub leaf_index: usize,
+    pub leaf_hash: String,
+    /// Sibling hashes from the leaf up to the root.
+    pub proof: Vec<ProofStep>,
+    /// The signed daily record whose `merkle_root` the proof leads to.
+    pub daily: DailyIntegrityRecord,
+}
+
+/// GET /api/integrity/proof?date=YYYY-MM-DD&session_id=N - Merkle inclusion proof for a session
+pub async fn get_proof(
+    Query(query): Query<ProofQuery>,
+) -> Result<Json<ProofResponse>, (StatusCode, String)> {
+    let date = parse_date(query.date)?;
+
+    let db = db_or_503()?;
+
+    let internal = |e: rusqlite::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    let daily = db.get_daily_integrity(&date).map_err(internal)?.ok_or((
+        StatusCode::NOT_FOUND,
+        format!("{} has not been sealed", date),
+    ))?;
+    let sessions = db.get_signed_sessions_for_date(&date).map_err(internal)?;
+
+    let leaf_index = sessions
+        .iter()
+        .position(|s| s.id == query.session_id)
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("No signed session {} on {}", query.session_id, date),
+        ))?;
+
+    // Sessions saved after sealing (or removed since) change the root
+    let hashes: Vec<String> = sessions.into_iter().map(|s| s.hash).collect();
+    if build_merkle_root(&hashes).as_deref() != Some(daily.merkle_root.as_str()) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Sessions on {} no longer match the sealed root; recompute integrity first",
+                date
+            ),
+        ));
+    }
+
+    let proof = merkle_proof(&hashes, leaf_index)
+        .into_iter()
+        .map(|(hash, is_left)| ProofStep { hash, is_left })
+        .collect();
+
+    Ok(Json(ProofResponse {
+        session_id: query.session_id,
+        leaf_index,
+        leaf_hash: hashes[leaf_index].clone(),
+        proof,
+        daily,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityStatusResponse {
+    pub from: String,
+    pub to: String,
+    /// Most recent sealed day overall, which may be outside the range.
+    pub latest_sealed_date: Option<String>,
+    pub days: Vec<DayIntegrityStatus>,
+}
+
+/// GET /api/integrity/status - Seal status of each day in a range
+///
+/// Defaults to the last 30 days (including today).
+pub async fn get_integrity_status(
+    Query(query): Query<StatusQuery>,
+) -> Result<Json<IntegrityStatusResponse>, (StatusCode, String)> {
+    let today = crate::store::timezone_policy().date_of(chrono::Utc::now());
+    let to = parse_day(query.to.as_deref(), "to")?.unwrap_or(today);
+    let from = parse_day(query.from.as_deref(), "from")?
+        .unwrap_or(to - chrono::Duration::days(DEFAULT_STATUS_DAYS - 1));
+    if from > to {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "from must not be after to".to_string(),
+        ));
+    }
+
+    let db = db_or_503()?;
+    let internal = |e: rusqlite::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+    let days = integrity_status_with(&db, from, to).map_err(internal)?;
+    let latest_sealed_date = db.get_latest_integrity_date().map_err(internal)?;
+
+    Ok(Json(IntegrityStatusResponse {
+        from: from.format("%Y-%m-%d").to_string(),
+        to: to.format("%Y-%m-%d").to_string(),
+        latest_sealed_date,
+        days,
+    }))
+}
+
+/// OwnMon's integrity signing public key.
+#[derive(Debug, Serialize)]
+pub struct PublicKeyResponse {
+    /// Base64-encoded Ed25519 public key.
+    pub public_key: String,
+    pub algorithm: &'static str,
+    /// Hex SHA-256 of the key, as stored with daily integrity records.
+    pub fingerprint: String,
+}
+
+/// GET /api/integrity/pubkey - Public key for verifying signatures elsewhere.
+pub async fn get_public_key() -> Result<Json<PublicKeyResponse>, (StatusCode, String)> {
+    let key_manager = KEY_MANAGER.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Key manager not available".to_string(),
+    ))?;
+
+    Ok(Json(PublicKeyResponse {
+        public_key: key_manager.public_key_base64(),
+        algorithm: "ed25519",
+        fingerprint: key_fingerprint(key_manager.verifying_key()),
+    }))
+}
@@ -1,10 +1,19 @@
 //! Media endpoint with flexible filtering.
 
-use axum::{extract::Query, Json};
+use axum::{extract::Query, http::StatusCode, Json};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
-use crate::database::MediaRecord;
-use crate::store::{ACTIVITY_STORE, DATABASE};
+use crate::database::{ArtistListenStats, Database, MediaRecord, QueryLimits, TrackListenStats};
+use crate::server::routes::apps::parse_day;
+use crate::server::routes::db_or_503;
+use crate::store::{lock_db, read_store, ACTIVITY_STORE};
+
+/// Days covered by the top artists/tracks reports when `from` is omitted.
+const DEFAULT_TOP_DAYS: i64 = 7;
+
+/// Entries returned by the top artists/tracks reports when `limit` is omitted.
+const DEFAULT_TOP_LIMIT: usize = 10;
 
 /// Flexible query parameters for media.
 #[derive(Deserialize)]
@@ -19,7 +28,7 @@ pub struct MediaQuery {
     pub artist: Option<String>,
     /// Filter by source app (supports * wildcard)
     pub source_app: Option<String>,
-    /// Limit results (default: 1000)
+    /// Limit results (default: `default_session_limit` config)
     pub limit: Option<usize>,
     /// Offset for pagination
     pub offset: Option<usize>,
@@ -36,6 +45,10 @@ pub struct CurrentMedia {
     pub source_app: String,
     pub start_time: String,
     pub duration_secs: i64,
+    /// Track length reported by the player (None if unknown).
+    pub track_duration_secs: Option<i64>,
+    /// Last playback position reported by the player, in seconds.
+    pub position_secs: Option<i64>,
     pub is_playing: bool,
 }
 
@@ -59,38 +72,43 @@ pub struct MediaResponse {
 /// - `to`: Filter to end time (ISO 8601)  
 /// - `artist`: Filter by artist (supports * wildcard)
 /// - `source_app`: Filter by source app (supports * wildcard)
-/// - `limit`: Max results (default 1000)
+/// - `limit`: Max results (default `default_session_limit`, capped at `max_session_limit`)
 /// - `offset`: Pagination offset
 /// - `order`: "asc" or "desc" (default desc)
 pub async fn get_media(Query(query): Query<MediaQuery>) -> Json<MediaResponse> {
     // Performance safeguards: limit max results to prevent memory issues
-    let limit = query.limit.unwrap_or(500).min(2000);
+    let mut limit = QueryLimits::default().resolve(query.limit);
     let offset = query.offset.unwrap_or(0);
     let order_desc = query.order.as_deref().unwrap_or("desc") != "asc";
 
     // Default to today's date if no time filters provided (prevents full table scan)
     let default_date = if query.date.is_none() && query.from.is_none() && query.to.is_none() {
-        Some(chrono::Utc::now().format("%Y-%m-%d").to_string())
+        Some(crate::store::local_date_string())
     } else {
         query.date.clone()
     };
 
     // Get current playing media from memory store
     let current = {
-        let store = ACTIVITY_STORE.read().unwrap();
-        store.current_media.as_ref().map(|m| CurrentMedia {
-            title: m.media_info.title.clone(),
-            artist: m.media_info.artist.clone(),
-            album: m.media_info.album.clone(),
-            source_app: m.media_info.source_app_id.clone(),
-            start_time: m.start_time.to_rfc3339(),
-            duration_secs: m.duration_secs(),
-            is_playing: m.media_info.is_playing(),
-        })
+        let store = read_store(&ACTIVITY_STORE);
+        store
+            .as_ref()
+            .and_then(|s| s.current_media.as_ref())
+            .map(|m| CurrentMedia {
+                title: m.media_info.title.clone(),
+                artist: m.media_info.artist.clone(),
+                album: m.media_info.album.clone(),
+                source_app: m.media_info.source_app_id.clone(),
+                start_time: m.start_time.to_rfc3339(),
+                duration_secs: m.duration_secs(),
+                track_duration_secs: m.track_duration_secs,
+                position_secs: m.last_position_secs,
+                is_playing: m.media_info.is_playing(),
+            })
     };
 
     // Get historical media from database
-    let Some(db_arc) = DATABASE.as_ref() else {
+    let Some(db) = lock_db() else {
         return Json(MediaResponse {
             current,
             history: vec![],
@@ -100,15 +118,8 @@ pub async fn get_media(Query(query): Query<MediaQuery>) -> Json<MediaResponse> {
         });
     };
 
-    let Ok(db) = db_arc.lock() else {
-        return Json(MediaResponse {
-            current,
-            history: vec![],
-            total: 0,
-            limit,
-            offset,
-        });
-    };
+    // Apply configured limits
+    limit = db.query_limits().resolve(query.limit);
 
     let (history, total) = match db.query_media_flexible(
         default_date.as_deref(),
@@ -140,3 +151,76 @@ pub async fn get_media(Query(query): Query<MediaQuery>) -> Json<MediaResponse> {
         offset,
     })
 }
+
+#[derive(Deserialize)]
+pub struct TopMediaQuery {
+    /// First day (YYYY-MM-DD, default: 6 days before `to`)
+    pub from: Option<String>,
+    /// Last day (YYYY-MM-DD, default: today)
+    pub to: Option<String>,
+    /// Max entries (default 10, capped at `max_session_limit`)
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct TopArtistsResponse {
+    pub from: String,
+    pub to: String,
+    pub artists: Vec<ArtistListenStats>,
+}
+
+#[derive(Serialize)]
+pub struct TopTracksResponse {
+    pub from: String,
+    pub to: String,
+    pub tracks: Vec<TrackListenStats>,
+}
+
+/// Resolves a top artists/tracks query to its date range and runs `f` on it.
+fn with_top_media<T>(
+    query: &TopMediaQuery,
+    f: impl FnOnce(&Database, &str, &str, usize) -> rusqlite::Result<T>,
+) -> Result<(String, String, T), (StatusCode, String)> {
+    let today = crate::store::timezone_policy().date_of(chrono::Utc::now());
+    let to = parse_day(query.to.as_deref(), "to")?.unwrap_or(today);
+    let from = parse_day(query.from.as_deref(), "from")?
+        .unwrap_or(to - chrono::Duration::days(DEFAULT_TOP_DAYS - 1));
+    if from > to {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "from must not be after to".to_string(),
+        ));
+    }
+    let format = |day: NaiveDate| day.format("%Y-%m-%d").to_string();
+    let (from, to) = (format(from), format(to));
+
+    let db = db_or_503()?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_TOP_LIMIT)
+        .min(db.query_limits().max);
+
+    let result = f(&db, &from, &to, limit)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((from, to, result))
+}
+
+/// GET /api/media/top-artists?from&to&limit - Most listened artists.
+pub async fn get_top_artists(
+    Query(query): Query<TopMediaQuery>,
+) -> Result<Json<TopArtistsResponse>, (StatusCode, String)> {
+    let (from, to, artists) = with_top_media(&query, |db, from, to, limit| {
+        db.get_top_artists(from, to, limit)
+    })?;
+    Ok(Json(TopArtistsResponse { from, to, artists }))
+}
+
+/// GET /api/media/top-tracks?from&to&limit - Most listened tracks.
+pub async fn get_top_tracks(
+    Query(query): Query<TopMediaQuery>,
+) -> Result<Json<TopTracksResponse>, (StatusCode, String)> {
+    let (from, to, tracks) = with_top_media(&query, |db, from, to, limit| {
+        db.get_top_tracks(from, to, limit)
+    })?;
+    Ok(Json(TopTracksResponse { from, to, tracks }))
+}
@@ -1,8 +1,37 @@
 //! Route handlers module.
 
+pub mod apps;
+pub mod autostart;
+pub mod backup;
+pub mod blacklist;
 pub mod categories;
 pub mod config;
+pub mod diag;
+pub mod export;
+pub mod goals;
 pub mod health;
+pub mod integrity;
 pub mod media;
+pub mod monitoring;
 pub mod sessions;
 pub mod stats;
+pub mod summary;
+
+use axum::http::StatusCode;
+use std::sync::MutexGuard;
+
+use crate::database::Database;
+use crate::store::lock_db;
+
+/// Locks the database for a handler, or answers 503 if there is none.
+///
+/// A lock poisoned by an earlier panic is recovered, see
+/// [`with_db`](crate::store::with_db).
+pub fn db_or_503() -> Result<MutexGuard<'static, Database>, (StatusCode, String)> {
+    lock_db().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Database not available".to_string(),
+        )
+    })
+}
@@ -0,0 +1,37 @@
+//! Monitoring pause/resume endpoints.
+
+use axum::Json;
+use serde::Serialize;
+
+/// Current monitoring state.
+#[derive(Debug, Serialize)]
+pub struct MonitoringStatus {
+    /// True while the user has paused monitoring.
+    pub paused: bool,
+    /// True while tracking is suspended because a privacy app is focused.
+    pub privacy_paused: bool,
+}
+
+fn status() -> Json<MonitoringStatus> {
+    Json(MonitoringStatus {
+        paused: crate::store::is_monitoring_paused(),
+        privacy_paused: crate::store::is_tracking_paused(),
+    })
+}
+
+/// GET /api/monitoring/status - Whether monitoring is paused.
+pub async fn get_status() -> Json<MonitoringStatus> {
+    status()
+}
+
+/// POST /api/monitoring/pause - Stop recording until resumed.
+pub async fn pause() -> Json<MonitoringStatus> {
+    crate::store::set_monitoring_paused(true);
+    status()
+}
+
+/// POST /api/monitoring/resume - Resume recording.
+pub async fn resume() -> Json<MonitoringStatus> {
+    crate::store::set_monitoring_paused(false);
+    status()
+}
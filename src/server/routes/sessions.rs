@@ -1,13 +1,21 @@
 //! Sessions endpoint with flexible filtering.
 
-use axum::{extract::Query, Json};
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::database::{Category, SessionWithDuration};
-use crate::store::DATABASE;
+use crate::database::{
+    Category, Database, DeletedSession, QueryLimits, SessionDetail, SessionWithDuration,
+};
+use crate::server::routes::db_or_503;
+use crate::store::{lock_db, ACTIVITY_STORE};
 
 /// Flexible query parameters for sessions.
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 pub struct SessionsQuery {
     /// Filter by date (YYYY-MM-DD)
     pub date: Option<String>,
@@ -19,20 +27,49 @@ pub struct SessionsQuery {
     pub app: Option<String>,
     /// Filter by category ID
     pub category: Option<i64>,
-    /// Limit results (default: 1000)
+    /// Filter by tag (exact match)
+    pub tag: Option<String>,
+    /// Limit results (default: `default_session_limit` config)
     pub limit: Option<usize>,
     /// Offset for pagination
     pub offset: Option<usize>,
     /// Order by: "asc" or "desc" (default: desc)
     pub order: Option<String>,
+    /// Merge adjacent sessions of the same app (default: false)
+    pub merge: Option<bool>,
 }
 
+/// Longest tag accepted by `POST /api/sessions/:id/tags`, in characters.
+pub const MAX_TAG_LEN: usize = 64;
+
 /// Enhanced session response with category info.
 #[derive(Serialize)]
 pub struct SessionWithCategory {
     #[serde(flatten)]
     pub session: SessionWithDuration,
     pub category: Option<Category>,
+    pub tags: Vec<String>,
+}
+
+/// A single session with its category and integrity chain fields.
+#[derive(Serialize)]
+pub struct SessionDetailResponse {
+    #[serde(flatten)]
+    pub detail: SessionDetail,
+    pub category: Option<Category>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AddTagRequest {
+    pub tag: String,
+}
+
+/// A session's tags after adding or removing one.
+#[derive(Serialize)]
+pub struct SessionTagsResponse {
+    pub id: i64,
+    pub tags: Vec<String>,
 }
 
 /// Response wrapper with metadata.
@@ -52,84 +89,185 @@ pub struct SessionsResponse {
 /// - `to`: Filter to end time (ISO 8601)  
 /// - `app`: Filter by process name (supports * wildcard)
 /// - `category`: Filter by category ID
-/// - `limit`: Max results (default 1000)
+/// - `tag`: Filter by tag
+/// - `limit`: Max results (default `default_session_limit`, capped at `max_session_limit`)
 /// - `offset`: Pagination offset
 /// - `order`: "asc" or "desc" (default desc)
+/// - `merge`: Join adjacent sessions of the same app less than `merge_gap_secs` apart
 pub async fn get_sessions(Query(query): Query<SessionsQuery>) -> Json<SessionsResponse> {
+    let empty = |limit| {
+        Json(SessionsResponse {
+            sessions: vec![],
+            total: 0,
+            limit,
+            offset: query.offset.unwrap_or(0),
+        })
+    };
+
+    let Some(db) = lock_db() else {
+        return empty(QueryLimits::default().resolve(query.limit));
+    };
+
+    Json(build_sessions_response(&db, &query))
+}
+
+/// Runs a sessions query against `db` and attaches each session's category.
+pub fn build_sessions_response(db: &Database, query: &SessionsQuery) -> SessionsResponse {
     // Performance safeguards: limit max results to prevent memory issues
-    let limit = query.limit.unwrap_or(500).min(2000);
+    let limit = db.query_limits().resolve(query.limit);
     let offset = query.offset.unwrap_or(0);
     let order_desc = query.order.as_deref().unwrap_or("desc") != "asc";
 
     // Default to today's date if no time filters provided (prevents full table scan)
     let default_date = if query.date.is_none() && query.from.is_none() && query.to.is_none() {
-        Some(chrono::Utc::now().format("%Y-%m-%d").to_string())
+        Some(crate::store::local_date_string())
     } else {
         query.date.clone()
     };
 
-    let Some(db_arc) = DATABASE.as_ref() else {
-        return Json(SessionsResponse {
-            sessions: vec![],
-            total: 0,
-            limit,
-            offset,
-        });
-    };
-
-    let Ok(db) = db_arc.lock() else {
-        return Json(SessionsResponse {
-            sessions: vec![],
-            total: 0,
-            limit,
-            offset,
-        });
-    };
-
     // Query sessions using the flexible method
     let (sessions, total) = match db.query_sessions_flexible(
         default_date.as_deref(),
         query.from.as_deref(),
         query.to.as_deref(),
         query.app.as_deref(),
+        query.category,
+        query.tag.as_deref(),
         limit,
         offset,
         order_desc,
     ) {
         Ok(result) => result,
         Err(_) => {
-            return Json(SessionsResponse {
+            return SessionsResponse {
                 sessions: vec![],
                 total: 0,
                 limit,
                 offset,
-            });
+            };
         }
     };
 
-    // Get category for each session
-    let sessions_with_categories: Vec<SessionWithCategory> = sessions
+    // Merging happens after pagination, so a page may hold fewer than `limit`
+    let sessions = if query.merge.unwrap_or(false) {
+        let read = |key: &str| db.get_config(key).ok().flatten();
+        let max_gap_secs = read("merge_gap_secs")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10);
+        let match_titles = read("track_title_changes").is_some_and(|v| v == "true");
+        crate::store::merge_adjacent_sessions(sessions, max_gap_secs, match_titles)
+    } else {
+        sessions
+    };
+
+    // Get category for each session (category filtering is done in the query)
+    let sessions: Vec<SessionWithCategory> = sessions
         .into_iter()
         .map(|session| {
-            let category = db.get_category_for_app(&session.process_name).ok();
-            SessionWithCategory { session, category }
+            let category = db.get_category_for_session(&session).ok();
+            let tags = db.get_session_tags(session.id).unwrap_or_default();
+            SessionWithCategory {
+                session,
+                category,
+                tags,
+            }
         })
         .collect();
 
-    // Filter by category if specified
-    let sessions = if let Some(cat_id) = query.category {
-        sessions_with_categories
-            .into_iter()
-            .filter(|s| s.category.as_ref().map(|c| c.id) == Some(cat_id))
-            .collect()
-    } else {
-        sessions_with_categories
-    };
-
-    Json(SessionsResponse {
+    SessionsResponse {
         sessions,
         total,
         limit,
         offset,
-    })
+    }
+}
+
+/// GET /api/sessions/:id - A single saved session.
+pub async fn get_session(
+    Path(id): Path<i64>,
+) -> Result<Json<SessionDetailResponse>, (StatusCode, String)> {
+    let db = db_or_503()?;
+    let detail = db
+        .get_session(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, format!("No session with id {}", id)))?;
+    let category = db.get_category_for_session(&detail.session).ok();
+    let tags = db.get_session_tags(id).unwrap_or_default();
+
+    Ok(Json(SessionDetailResponse {
+        detail,
+        category,
+        tags,
+    }))
+}
+
+/// DELETE /api/sessions/:id - Remove a saved session.
+///
+/// Returns the session's date so the client can offer to re-seal it with
+/// `POST /api/integrity/recompute`.
+pub async fn delete_session(
+    Path(id): Path<i64>,
+) -> Result<Json<DeletedSession>, (StatusCode, String)> {
+    let deleted = db_or_503()?
+        .delete_session(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, format!("No session with id {}", id)))?;
+    tracing::info!(id, date = %deleted.date, "Session deleted");
+
+    // Stats for today are built from the in-memory copy too
+    if let Ok(start_time) = DateTime::parse_from_rfc3339(&deleted.start_time) {
+        if let Ok(mut store) = ACTIVITY_STORE.write() {
+            store.remove_completed_session(start_time.with_timezone(&Utc), &deleted.process_name);
+        }
+    }
+
+    Ok(Json(deleted))
+}
+
+/// POST /api/sessions/:id/tags - Tag a saved session.
+///
+/// The tag is trimmed; empty tags and tags longer than [`MAX_TAG_LEN`]
+/// characters are rejected.
+pub async fn add_session_tag(
+    Path(id): Path<i64>,
+    Json(request): Json<AddTagRequest>,
+) -> Result<Json<SessionTagsResponse>, (StatusCode, String)> {
+    let tag = request.tag.trim();
+    if tag.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Tag must not be empty".to_string()));
+    }
+    if tag.chars().count() > MAX_TAG_LEN {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Tag must be at most {} characters", MAX_TAG_LEN),
+        ));
+    }
+
+    let db = db_or_503()?;
+    let internal = |e: rusqlite::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+    if !db.add_session_tag(id, tag).map_err(internal)? {
+        return Err((StatusCode::NOT_FOUND, format!("No session with id {}", id)));
+    }
+    let tags = db.get_session_tags(id).map_err(internal)?;
+
+    Ok(Json(SessionTagsResponse { id, tags }))
+}
+
+/// DELETE /api/sessions/:id/tags/:tag - Remove a tag from a session.
+pub async fn remove_session_tag(
+    Path((id, tag)): Path<(i64, String)>,
+) -> Result<Json<SessionTagsResponse>, (StatusCode, String)> {
+    let db = db_or_503()?;
+    let internal = |e: rusqlite::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+    if !db.remove_session_tag(id, &tag).map_err(internal)? {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Session {} has no tag '{}'", id, tag),
+        ));
+    }
+    let tags = db.get_session_tags(id).map_err(internal)?;
+
+    Ok(Json(SessionTagsResponse { id, tags }))
 }
@@ -1,9 +1,19 @@
 //! Statistics endpoints.
 
-use axum::{extract::Query, Json};
+use axum::{extract::Query, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::store::{ACTIVITY_STORE, DATABASE};
+use crate::database::{AppTotals, Category, SessionWithDuration, OTHER_CATEGORY_ID};
+use crate::server::routes::db_or_503;
+use crate::server::routes::integrity::parse_date;
+use crate::store::{
+    category_weights, compute_focus_totals, compute_pace, compute_productivity_score,
+    compute_typing_cadence, count_idle_in_focus, format_count, format_duration, read_store,
+    with_db, ActivityStore, KeyBreakdown, PaceComparison, ProductivityScore, TypingCadence,
+    TypingSample, ACTIVITY_STORE,
+};
+use std::sync::RwLock;
 
 #[derive(Serialize)]
 pub struct StatsResponse {
@@ -11,108 +21,151 @@ pub struct StatsResponse {
     pub unique_apps: u32,
     pub keystrokes: u64,
     pub clicks: u64,
+    pub mouse_distance_px: u64,
+    /// Keystrokes by kind of key (alphanumeric, whitespace, navigation, ...)
+    pub key_breakdown: KeyBreakdown,
     pub focus_time_secs: u64,
+    pub idle_time_secs: u64,
+    /// Focus time excluding idle sessions, even with `count_idle_in_focus`
+    pub active_focus_time_secs: u64,
+    /// Number of focus changes (non-idle sessions)
+    pub context_switches: u32,
+    /// Longest uninterrupted non-idle session
+    pub longest_focus_streak_secs: u64,
     pub media_time_secs: i64,
+    /// Human-readable values, included with `?human=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<FormattedStats>,
+}
+
+/// Display strings for [`StatsResponse`].
+#[derive(Serialize)]
+pub struct FormattedStats {
+    pub keystrokes: String,
+    pub clicks: String,
+    pub focus_time: String,
+    pub idle_time: String,
+    pub media_time: String,
+}
+
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    /// Add a `formatted` block with human-readable values
+    #[serde(default)]
+    pub human: bool,
+    /// Include idle sessions in focus time (default: `count_idle_in_focus` config)
+    pub count_idle_in_focus: Option<bool>,
 }
 
 #[derive(Serialize)]
 pub struct AppStats {
     pub process_name: String,
+    /// Category the app is mapped to ("Other" if unmapped)
+    pub category: Category,
     pub focus_time_secs: u64,
     pub keystrokes: u64,
     pub clicks: u64,
     pub session_count: u32,
 }
 
+impl From<AppTotals> for AppStats {
+    fn from(totals: AppTotals) -> Self {
+        Self {
+            process_name: totals.process_name,
+            category: totals.category,
+            focus_time_secs: totals.focus_secs.max(0) as u64,
+            keystrokes: totals.keystrokes.max(0) as u64,
+            clicks: totals.clicks.max(0) as u64,
+            session_count: totals.sessions as u32,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct DailyQuery {
     pub date: Option<String>,
 }
 
 /// GET /api/stats - Today's summary statistics.
-pub async fn get_stats() -> Json<StatsResponse> {
-    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-
-    // Query all of today's sessions from database using flexible query
-    let (sessions, _total) = DATABASE
-        .as_ref()
-        .and_then(|db| db.lock().ok())
-        .and_then(|d| {
-            d.query_sessions_flexible(
-                Some(&today), // date filter
-                None,         // from
-                None,         // to
-                None,         // process
-                10000,        // limit (large number to get all)
-                0,            // offset
-                false,        // order_desc
-            )
-            .ok()
-        })
-        .unwrap_or((vec![], 0));
-
-    // Compute stats from database sessions
-    let mut total_keystrokes = 0u64;
-    let mut total_clicks = 0u64;
-    let mut total_duration = 0i64;
-    let mut unique_apps = std::collections::HashSet::new();
-
-    for session in &sessions {
-        total_keystrokes += session.keystrokes as u64;
-        total_clicks += session.clicks as u64;
-        total_duration += session.duration_secs;
-        unique_apps.insert(session.process_name.clone());
-    }
+pub async fn get_stats(Query(query): Query<StatsQuery>) -> Json<StatsResponse> {
+    let today = crate::store::local_date_string();
+    let count_idle = query
+        .count_idle_in_focus
+        .unwrap_or_else(count_idle_in_focus);
 
-    // Add current session if active (not yet in database)
-    let store = ACTIVITY_STORE.read().unwrap();
-    if let Some(current) = &store.current_session {
-        total_keystrokes += current.keystrokes;
-        total_clicks += current.mouse_clicks;
-        total_duration += current.duration_secs();
-        unique_apps.insert(current.process_name.clone());
-    }
+    // Query all of today's sessions from database
+    let sessions = with_db(|d| d.get_all_sessions_for_date(&today).ok())
+        .flatten()
+        .unwrap_or_default();
+
+    Json(stats_response(
+        &ACTIVITY_STORE,
+        &sessions,
+        count_idle,
+        query.human,
+    ))
+}
+
+/// Builds the `/api/stats` body from saved sessions plus the store.
+///
+/// A poisoned store contributes no current session and no media time.
+fn stats_response(
+    store: &RwLock<ActivityStore>,
+    sessions: &[SessionWithDuration],
+    count_idle: bool,
+    human: bool,
+) -> StatsResponse {
+    // Include the current session (not yet in database)
+    let store = read_store(store);
+    let current = store.as_ref().and_then(|s| s.current_session.as_ref());
+    let totals = compute_focus_totals(sessions, current, count_idle);
 
-    let media_time = store.total_media_time_secs();
-
-    Json(StatsResponse {
-        sessions: sessions.len() as u32
-            + if store.current_session.is_some() {
-                1
-            } else {
-                0
-            },
-        unique_apps: unique_apps.len() as u32,
-        keystrokes: total_keystrokes,
-        clicks: total_clicks,
-        focus_time_secs: total_duration.max(0) as u64,
+    let media_time = store.as_ref().map_or(0, |s| s.total_media_time_secs());
+
+    let formatted = human.then(|| FormattedStats {
+        keystrokes: format_count(totals.keystrokes),
+        clicks: format_count(totals.clicks),
+        focus_time: format_duration(totals.focus_time_secs),
+        idle_time: format_duration(totals.idle_time_secs),
+        media_time: format_duration(media_time),
+    });
+
+    StatsResponse {
+        sessions: totals.sessions as u32,
+        unique_apps: totals.unique_apps as u32,
+        keystrokes: totals.keystrokes,
+        clicks: totals.clicks,
+        mouse_distance_px: totals.mouse_distance_px,
+        key_breakdown: totals.key_breakdown,
+        focus_time_secs: totals.focus_time_secs.max(0) as u64,
+        idle_time_secs: totals.idle_time_secs.max(0) as u64,
+        active_focus_time_secs: totals.active_time_secs.max(0) as u64,
+        context_switches: totals.context_switches as u32,
+        longest_focus_streak_secs: totals.longest_focus_secs.max(0) as u64,
         media_time_secs: media_time,
-    })
+        formatted,
+    }
 }
 
 /// GET /api/stats/daily?date=YYYY-MM-DD - Stats for a specific date.
 pub async fn get_daily_stats(Query(query): Query<DailyQuery>) -> Json<Option<DailyStatsResponse>> {
-    let date = query
-        .date
-        .unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%d").to_string());
-
-    let Some(db_arc) = DATABASE.as_ref() else {
-        return Json(None);
-    };
-
-    let Ok(db) = db_arc.lock() else {
-        return Json(None);
-    };
+    let date = query.date.unwrap_or_else(crate::store::local_date_string);
 
     // Query aggregated stats from sessions table
-    match db.get_stats_for_date(&date) {
-        Ok((keystrokes, clicks, focus_secs)) => Json(Some(DailyStatsResponse {
-            date,
-            keystrokes,
-            clicks,
-            focus_secs,
-        })),
-        Err(_) => Json(None),
+    let totals = with_db(|db| {
+        Ok::<_, rusqlite::Error>((db.get_stats_for_date(&date)?, db.get_switch_count(&date)?))
+    });
+    match totals {
+        Some(Ok(((keystrokes, clicks, focus_secs), context_switches))) => {
+            Json(Some(DailyStatsResponse {
+                date,
+                keystrokes,
+                clicks,
+                focus_secs,
+                context_switches,
+            }))
+        }
+        _ => Json(None),
     }
 }
 
@@ -122,28 +175,222 @@ pub struct DailyStatsResponse {
     pub keystrokes: i64,
     pub clicks: i64,
     pub focus_secs: i64,
+    /// Number of focus changes (non-idle sessions started that day)
+    pub context_switches: i64,
+}
+
+/// Number of sessions listed by `/api/stats/focus-streaks`.
+const FOCUS_STREAK_LIMIT: usize = 10;
+
+/// GET /api/stats/focus-streaks?date=YYYY-MM-DD - Longest uninterrupted
+/// non-idle sessions of a day, longest first.
+pub async fn get_focus_streaks(
+    Query(query): Query<DailyQuery>,
+) -> Json<Vec<crate::database::FocusStreak>> {
+    let date = query.date.unwrap_or_else(crate::store::local_date_string);
+
+    match with_db(|db| db.get_focus_streaks(&date, FOCUS_STREAK_LIMIT)) {
+        Some(Ok(streaks)) => Json(streaks),
+        _ => Json(vec![]),
+    }
+}
+
+#[derive(Serialize)]
+pub struct CategoryStatsResponse {
+    pub date: String,
+    pub categories: Vec<crate::database::CategoryStats>,
+}
+
+/// GET /api/stats/categories?date=YYYY-MM-DD - Focus time and input per
+/// category, longest first.
+pub async fn get_category_stats(Query(query): Query<DailyQuery>) -> Json<CategoryStatsResponse> {
+    let date = query.date.unwrap_or_else(crate::store::local_date_string);
+
+    let categories = match with_db(|db| db.get_category_stats(&date)) {
+        Some(Ok(categories)) => categories,
+        _ => vec![],
+    };
+    Json(CategoryStatsResponse { date, categories })
+}
+
+#[derive(Serialize)]
+pub struct MonitorStatsResponse {
+    pub date: String,
+    pub monitors: Vec<crate::database::MonitorStats>,
+}
+
+/// GET /api/stats/monitors?date=YYYY-MM-DD - Focus time and input per
+/// monitor, longest first.
+pub async fn get_monitor_stats(Query(query): Query<DailyQuery>) -> Json<MonitorStatsResponse> {
+    let date = query.date.unwrap_or_else(crate::store::local_date_string);
+
+    let monitors = match with_db(|db| db.get_monitor_stats(&date)) {
+        Some(Ok(monitors)) => monitors,
+        _ => vec![],
+    };
+    Json(MonitorStatsResponse { date, monitors })
+}
+
+#[derive(Deserialize)]
+pub struct TypingQuery {
+    pub date: Option<String>,
+    pub session_id: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct TypingResponse {
+    /// The day covered, when queried by date.
+    pub date: Option<String>,
+    /// The session covered, when queried by session.
+    pub session_id: Option<i64>,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    #[serde(flatten)]
+    pub cadence: TypingCadence,
+    /// Keystrokes per minute, oldest first; minutes without typing are omitted.
+    pub samples: Vec<TypingSample>,
+}
+
+/// GET /api/stats/typing?date=YYYY-MM-DD or ?session_id=N - Keystrokes per
+/// minute over a day or a session.
+pub async fn get_typing(
+    Query(query): Query<TypingQuery>,
+) -> Result<Json<TypingResponse>, (StatusCode, String)> {
+    // Minutes that already ended are saved with the pending sessions
+    crate::store::save_pending_to_db();
+
+    let (date, from, to) = match query.session_id {
+        Some(id) => {
+            let session = db_or_503()?
+                .get_session(id)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .ok_or((StatusCode::NOT_FOUND, format!("No session with id {}", id)))?
+                .session;
+            let parse = |t: &str| {
+                DateTime::parse_from_rfc3339(t)
+                    .map(|t| t.with_timezone(&Utc))
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            };
+            let start = parse(&session.start_time)?;
+            let end = match session.end_time.as_deref() {
+                Some(end) => parse(end)?,
+                None => Utc::now(),
+            };
+            // Include the minute the session started in
+            let from =
+                DateTime::from_timestamp(start.timestamp().div_euclid(60) * 60, 0).unwrap_or(start);
+            (None, from, end)
+        }
+        None => {
+            let date = parse_date(query.date)?;
+            let day = chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+            let (from, to) = crate::store::timezone_policy().day_range(day);
+            (Some(date), from, to)
+        }
+    };
+
+    let samples = db_or_503()?
+        .get_typing_samples(from, to)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(TypingResponse {
+        date,
+        session_id: query.session_id,
+        from,
+        to,
+        cadence: compute_typing_cadence(&samples),
+        samples,
+    }))
 }
 
 /// GET /api/apps - Top apps by focus time.
 pub async fn get_top_apps() -> Json<Vec<AppStats>> {
-    let store = ACTIVITY_STORE.read().unwrap();
-    let stats = store.compute_application_stats();
+    Json(top_apps())
+}
 
-    let mut apps: Vec<AppStats> = stats
-        .into_iter()
-        .map(|(name, stat)| AppStats {
-            process_name: name,
+/// Today's application totals with their categories, by focus time descending.
+///
+/// Totals come from today's saved non-idle sessions, so they survive a
+/// restart; the current session (not yet in database) is added on top.
+/// Without a database, this run's in-memory totals are used instead.
+pub fn top_apps() -> Vec<AppStats> {
+    let today = crate::store::local_date_string();
+    let current = ACTIVITY_STORE
+        .read()
+        .ok()
+        .and_then(|store| store.current_session.clone())
+        .filter(|session| !session.is_idle);
+
+    let apps = with_db(|db| {
+        let mut apps: Vec<AppStats> = db
+            .get_app_totals(&today)?
+            .into_iter()
+            .map(AppStats::from)
+            .collect();
+
+        if let Some(session) = current {
+            let focus = session.duration_secs().max(0) as u64;
+            match apps
+                .iter_mut()
+                .find(|app| app.process_name == session.process_name)
+            {
+                Some(app) => {
+                    app.focus_time_secs += focus;
+                    app.keystrokes += session.keystrokes;
+                    app.clicks += session.mouse_clicks;
+                }
+                // Don't count the current session until it's saved
+                None => apps.push(AppStats {
+                    category: db.get_category_for_app(&session.process_name)?,
+                    process_name: session.process_name,
+                    focus_time_secs: focus,
+                    keystrokes: session.keystrokes,
+                    clicks: session.mouse_clicks,
+                    session_count: 0,
+                }),
+            }
+        }
+
+        Ok::<_, rusqlite::Error>(apps)
+    });
+    let mut apps = match apps {
+        Some(apps) => apps.unwrap_or_default(),
+        None => in_memory_apps(&ACTIVITY_STORE),
+    };
+
+    // Sort by focus time descending
+    apps.sort_by(|a, b| b.focus_time_secs.cmp(&a.focus_time_secs));
+
+    apps
+}
+
+/// Application totals tracked by this run, all under "Other" since
+/// categories live in the database.
+fn in_memory_apps(store: &RwLock<ActivityStore>) -> Vec<AppStats> {
+    let Some(store) = read_store(store) else {
+        return Vec::new();
+    };
+    let other = Category {
+        id: OTHER_CATEGORY_ID,
+        name: "Other".to_string(),
+        color: "#9CA3AF".to_string(),
+        icon: Some("📁".to_string()),
+        distracting: false,
+    };
+
+    store
+        .compute_application_stats()
+        .into_values()
+        .map(|stat| AppStats {
+            process_name: stat.process_name,
+            category: other.clone(),
             focus_time_secs: stat.total_focus_duration_secs,
             keystrokes: stat.total_keystrokes,
             clicks: stat.total_clicks,
             session_count: stat.session_count,
         })
-        .collect();
-
-    // Sort by focus time descending
-    apps.sort_by(|a, b| b.focus_time_secs.cmp(&a.focus_time_secs));
-
-    Json(apps)
+        .collect()
 }
 
 #[derive(Deserialize)]
@@ -152,24 +399,102 @@ pub struct HourlyQuery {
 }
 
 /// GET /api/stats/hourly?date=YYYY-MM-DD - Hourly breakdown for charts.
+///
+/// Dates and hours follow the `timezone` config, like `/api/stats/heatmap`.
 pub async fn get_hourly_stats(
     Query(query): Query<HourlyQuery>,
 ) -> Json<Vec<crate::database::HourlyStats>> {
-    let date = query
-        .date
-        .unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%d").to_string());
+    let date = query.date.unwrap_or_else(crate::store::local_date_string);
 
-    let Some(db_arc) = DATABASE.as_ref() else {
-        return Json(vec![]);
-    };
+    match with_db(|db| db.get_hourly_stats(&date)) {
+        Some(Ok(stats)) => Json(stats),
+        _ => Json(vec![]),
+    }
+}
 
-    let Ok(db) = db_arc.lock() else {
-        return Json(vec![]);
-    };
+/// Longest range accepted by `/api/stats/heatmap`.
+const MAX_HEATMAP_DAYS: i64 = 366;
+
+#[derive(Deserialize)]
+pub struct HeatmapQuery {
+    /// Number of days to include, ending today (default 30)
+    pub days: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct HeatmapResponse {
+    /// The `timezone` config that dates and hours follow
+    pub timezone: String,
+    /// Current offset of that time zone from UTC in minutes
+    pub utc_offset_minutes: i32,
+    pub days: Vec<HeatmapDay>,
+}
+
+#[derive(Serialize)]
+pub struct HeatmapDay {
+    pub date: String,
+    /// Focus seconds for each local hour (index 0 = 00:00-01:00)
+    pub hours: [u64; 24],
+    pub focus_secs: u64,
+}
+
+/// GET /api/stats/heatmap?days=30 - Focus time per hour for each recent day.
+///
+/// Powers a contribution-style grid. Days without activity are included
+/// with zeros.
+pub async fn get_heatmap(Query(query): Query<HeatmapQuery>) -> Json<HeatmapResponse> {
+    let now = chrono::Utc::now();
+    let policy = crate::store::timezone_policy();
+    let days = query.days.unwrap_or(30).clamp(1, MAX_HEATMAP_DAYS);
+    let to = policy.date_of(now);
+    let from = to - chrono::Duration::days(days - 1);
+
+    let grid = with_db(|db| db.get_hourly_heatmap(from, to).ok())
+        .flatten()
+        .unwrap_or_default();
+
+    let days = grid
+        .into_iter()
+        .zip(from.iter_days())
+        .map(|(hours, date)| HeatmapDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            focus_secs: hours.iter().sum(),
+            hours,
+        })
+        .collect();
+
+    Json(HeatmapResponse {
+        timezone: policy.name(),
+        utc_offset_minutes: policy.offset_secs(now) / 60,
+        days,
+    })
+}
 
-    match db.get_hourly_stats(&date) {
-        Ok(stats) => Json(stats),
-        Err(_) => Json(vec![]),
+#[derive(Deserialize)]
+pub struct WeekdayQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// GET /api/stats/weekday?from=YYYY-MM-DD&to=YYYY-MM-DD - Totals by day of the week.
+///
+/// Defaults to the last 4 weeks (including today).
+pub async fn get_weekday_stats(
+    Query(query): Query<WeekdayQuery>,
+) -> Json<Vec<crate::database::WeekdayStats>> {
+    let today = crate::store::timezone_policy().date_of(chrono::Utc::now());
+    let to = query
+        .to
+        .unwrap_or_else(|| today.format("%Y-%m-%d").to_string());
+    let from = query.from.unwrap_or_else(|| {
+        (today - chrono::Duration::days(27))
+            .format("%Y-%m-%d")
+            .to_string()
+    });
+
+    match with_db(|db| db.stats_by_weekday(&from, &to)) {
+        Some(Ok(stats)) => Json(stats),
+        _ => Json(vec![]),
     }
 }
 
@@ -184,16 +509,167 @@ pub async fn get_timeline(
 ) -> Json<Vec<crate::database::DailyTimeline>> {
     let days = query.days.unwrap_or(7);
 
-    let Some(db_arc) = DATABASE.as_ref() else {
-        return Json(vec![]);
-    };
+    match with_db(|db| db.get_timeline(days)) {
+        Some(Ok(timeline)) => Json(timeline),
+        _ => Json(vec![]),
+    }
+}
 
-    let Ok(db) = db_arc.lock() else {
-        return Json(vec![]);
-    };
+/// Longest range accepted by `/api/stats/weekly`.
+const MAX_WEEKLY_WEEKS: i32 = 104;
+
+#[derive(Deserialize)]
+pub struct WeeklyQuery {
+    pub weeks: Option<i32>,
+}
+
+/// GET /api/stats/weekly?weeks=8 - Weekly totals for week-over-week comparison.
+pub async fn get_weekly_stats(
+    Query(query): Query<WeeklyQuery>,
+) -> Json<Vec<crate::database::WeeklyStats>> {
+    let weeks = query.weeks.unwrap_or(8).clamp(1, MAX_WEEKLY_WEEKS);
+
+    match with_db(|db| db.get_weekly_stats(weeks)) {
+        Some(Ok(weekly)) => Json(weekly),
+        _ => Json(vec![]),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ScoreQuery {
+    pub date: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ScoreResponse {
+    pub date: String,
+    #[serde(flatten)]
+    pub score: ProductivityScore,
+}
+
+/// GET /api/stats/score?date=YYYY-MM-DD - Focus score weighted by category.
+///
+/// Idle sessions are left out; defaults to today.
+pub async fn get_score(Query(query): Query<ScoreQuery>) -> Json<ScoreResponse> {
+    let date = query.date.unwrap_or_else(crate::store::local_date_string);
+
+    let categorized: Vec<(String, i64)> = with_db(|d| {
+        d.get_all_sessions_for_date(&date)
+            .unwrap_or_default()
+            .iter()
+            .filter(|s| !s.is_idle)
+            .map(|s| {
+                let category = d
+                    .get_category_for_session(s)
+                    .map(|c| c.name)
+                    .unwrap_or_else(|_| "Other".to_string());
+                (category, s.duration_secs)
+            })
+            .collect()
+    })
+    .unwrap_or_default();
+
+    Json(ScoreResponse {
+        date,
+        score: compute_productivity_score(&categorized, &category_weights()),
+    })
+}
+
+#[derive(Deserialize)]
+pub struct PaceQuery {
+    pub days: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct PaceResponse {
+    pub date: String,
+    pub time_of_day: String,
+    pub days: i64,
+    #[serde(flatten)]
+    pub pace: PaceComparison,
+}
+
+/// GET /api/stats/pace?days=14 - Today so far vs. the usual pace at this time of day.
+pub async fn get_pace(Query(query): Query<PaceQuery>) -> Json<PaceResponse> {
+    use chrono::Timelike;
+
+    let days = query.days.unwrap_or(14).clamp(1, 365);
+    let now = crate::store::timezone_policy().local_time(chrono::Utc::now());
+    let today = now.date();
+    let cutoff_secs = now.num_seconds_from_midnight() as i64;
+
+    let today_str = today.format("%Y-%m-%d").to_string();
+    let from = (today - chrono::Duration::days(days))
+        .format("%Y-%m-%d")
+        .to_string();
+    let yesterday = (today - chrono::Duration::days(1))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let (today_totals, history) = with_db(|d| {
+        (
+            d.get_totals_until_time_of_day(&today_str, &today_str, cutoff_secs)
+                .unwrap_or_default(),
+            d.get_totals_until_time_of_day(&from, &yesterday, cutoff_secs)
+                .unwrap_or_default(),
+        )
+    })
+    .unwrap_or_default();
+
+    let mut focus_secs: i64 = today_totals.iter().map(|t| t.focus_secs).sum();
+    let mut keystrokes: i64 = today_totals.iter().map(|t| t.keystrokes).sum();
+
+    // Add current session (not yet in database)
+    if let Ok(store) = ACTIVITY_STORE.read() {
+        if let Some(current) = &store.current_session {
+            focus_secs += current.duration_secs();
+            keystrokes += current.keystrokes as i64;
+        }
+    }
+
+    Json(PaceResponse {
+        date: today_str,
+        time_of_day: now.format("%H:%M:%S").to_string(),
+        days,
+        pace: compute_pace(focus_secs, keystrokes, &history),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_survive_poisoned_store() {
+        let store = RwLock::new(ActivityStore::new());
+        let _ = std::panic::catch_unwind(|| {
+            let _guard = store.write().unwrap();
+            panic!("poison the store");
+        });
+        assert!(store.is_poisoned());
+
+        let response = stats_response(&store, &[], true, true);
+        assert_eq!(response.sessions, 0);
+        assert_eq!(response.keystrokes, 0);
+        assert_eq!(response.focus_time_secs, 0);
+        assert_eq!(response.media_time_secs, 0);
+        assert!(response.formatted.is_some());
+    }
+
+    #[test]
+    fn test_in_memory_apps_without_database() {
+        let store = RwLock::new(ActivityStore::new());
+        {
+            let mut store = store.write().unwrap();
+            store.switch_session(1, 100, "code.exe", "main.rs");
+            store.add_input_counts(12, 3, 0);
+        }
 
-    match db.get_timeline(days) {
-        Ok(timeline) => Json(timeline),
-        Err(_) => Json(vec![]),
+        let apps = in_memory_apps(&store);
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].process_name, "code.exe");
+        assert_eq!(apps[0].keystrokes, 12);
+        assert_eq!(apps[0].clicks, 3);
+        assert_eq!(apps[0].category.id, OTHER_CATEGORY_ID);
     }
 }
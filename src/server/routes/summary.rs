@@ -0,0 +1,12 @@
+//! End-of-run summary endpoint.
+
+use axum::Json;
+
+use crate::store::{with_db, RunSummary};
+
+/// GET /api/summary/last - Summary saved when the app last shut down
+pub async fn get_last_summary() -> Json<Option<RunSummary>> {
+    let summary = with_db(|d| d.get_last_run_summary().ok().flatten()).flatten();
+
+    Json(summary)
+}
@@ -8,10 +8,25 @@ use axum::{
     response::Response,
 };
 use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
 use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
 
 use crate::server::state::AppState;
-use crate::store::ACTIVITY_STORE;
+use crate::store::{read_store, ACTIVITY_STORE, BROADCAST_HISTORY};
+
+/// Commands a client can send over the WebSocket.
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+enum ClientCommand {
+    /// Resend broadcasts from the last `since_secs` seconds (default 300).
+    Replay { since_secs: Option<i64> },
+    /// Resend the current session, media and today's stats.
+    GetStats,
+    /// Send application totals, by focus time descending.
+    GetApps,
+}
 
 /// WebSocket upgrade handler.
 pub async fn ws_handler(ws: WebSocketUpgrade, State(state): State<Arc<AppState>>) -> Response {
@@ -23,27 +38,53 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
     let (mut sender, mut receiver) = socket.split();
 
     // Send current activity state on connection
-    if let Some(initial_state) = get_current_state() {
+    if let Some(initial_state) = get_current_state("initial_state") {
         let _ = sender.send(Message::Text(initial_state)).await;
     }
 
     // Subscribe to broadcast channel
     let mut rx = state.subscribe();
 
+    // Replies to client commands, sent alongside broadcasts
+    let (reply_tx, mut reply_rx) = mpsc::unbounded_channel::<String>();
+
     // Spawn task to receive from broadcast and send to WebSocket
     let send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
+        loop {
+            let msg = tokio::select! {
+                msg = rx.recv() => match msg {
+                    Ok(msg) => msg,
+                    // Too slow to keep up: tell the client to re-request state
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::debug!(missed, "WebSocket client lagged");
+                        resync_message(missed)
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                Some(msg) = reply_rx.recv() => msg,
+            };
             if sender.send(Message::Text(msg)).await.is_err() {
                 break;
             }
         }
     });
 
-    // Handle incoming messages (for future use, e.g., commands)
+    // Handle incoming messages (commands)
     let recv_task = tokio::spawn(async move {
         while let Some(Ok(msg)) = receiver.next().await {
             match msg {
                 Message::Close(_) => break,
+                Message::Text(text) => {
+                    let replies = match serde_json::from_str::<ClientCommand>(&text) {
+                        Ok(command) => handle_command(command),
+                        Err(_) => vec![error_message("unknown command")],
+                    };
+                    for reply in replies {
+                        if reply_tx.send(reply).is_err() {
+                            break;
+                        }
+                    }
+                }
                 Message::Ping(data) => {
                     // Pong is handled automatically by axum
                     let _ = data;
@@ -62,11 +103,59 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
     tracing::debug!("WebSocket connection closed");
 }
 
-/// Gets the current activity state for initial WebSocket message.
-fn get_current_state() -> Option<String> {
-    let store = ACTIVITY_STORE.read().ok()?;
+/// Builds the messages sent back for a client command.
+fn handle_command(command: ClientCommand) -> Vec<String> {
+    match command {
+        ClientCommand::Replay { since_secs } => {
+            let now = chrono::Utc::now();
+            let mut messages = BROADCAST_HISTORY
+                .lock()
+                .map(|history| history.since(now, since_secs.unwrap_or(300)))
+                .unwrap_or_default();
+
+            let done = serde_json::json!({
+                "type": "replay_done",
+                "data": { "count": messages.len() },
+                "timestamp": now.to_rfc3339(),
+            });
+            messages.push(done.to_string());
+            messages
+        }
+        ClientCommand::GetStats => get_current_state("stats").into_iter().collect(),
+        ClientCommand::GetApps => {
+            let apps = serde_json::json!({
+                "type": "apps",
+                "data": crate::server::routes::stats::top_apps(),
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            });
+            vec![apps.to_string()]
+        }
+    }
+}
 
-    let current_session = store.current_session.as_ref().map(|s| {
+/// Builds the reply sent for a command that can't be handled.
+fn error_message(message: &str) -> String {
+    serde_json::json!({ "type": "error", "message": message }).to_string()
+}
+
+/// Builds the hint sent to a client that missed `missed` broadcasts.
+fn resync_message(missed: u64) -> String {
+    serde_json::json!({
+        "type": "resync",
+        "data": { "missed": missed },
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    })
+    .to_string()
+}
+
+/// Gets the current activity state, sent on connect and for `get_stats`.
+///
+/// A poisoned store is reported as no current session or media.
+fn get_current_state(message_type: &str) -> Option<String> {
+    let store = read_store(&ACTIVITY_STORE);
+    let current = store.as_ref().and_then(|s| s.current_session.as_ref());
+
+    let current_session = current.map(|s| {
         serde_json::json!({
             "process_name": s.process_name,
             "window_title": s.window_title,
@@ -74,59 +163,43 @@ fn get_current_state() -> Option<String> {
         })
     });
 
-    let current_media = store.current_media.as_ref().map(|m| {
-        serde_json::json!({
-            "title": m.media_info.title,
-            "artist": m.media_info.artist,
-            "album": m.media_info.album,
-            "is_playing": m.media_info.is_playing(),
-            "start_time": m.start_time.to_rfc3339(),
-        })
-    });
+    let current_media = store
+        .as_ref()
+        .and_then(|s| s.current_media.as_ref())
+        .map(|m| {
+            serde_json::json!({
+                "title": m.media_info.title,
+                "artist": m.media_info.artist,
+                "album": m.media_info.album,
+                "is_playing": m.media_info.is_playing(),
+                "position_secs": m.last_position_secs,
+                "track_duration_secs": m.track_duration_secs,
+                "start_time": m.start_time.to_rfc3339(),
+            })
+        });
 
     // Query database for today's stats (same as /api/stats)
-    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-    let (sessions, _) = crate::store::DATABASE
-        .as_ref()
-        .and_then(|db| db.lock().ok())
-        .and_then(|d| {
-            d.query_sessions_flexible(Some(&today), None, None, None, 10000, 0, false)
-                .ok()
-        })
-        .unwrap_or((vec![], 0));
-
-    // Compute stats from database
-    let mut total_keystrokes = 0u64;
-    let mut total_clicks = 0u64;
-    let mut total_duration = 0i64;
-    let mut unique_apps = std::collections::HashSet::new();
-
-    for session in &sessions {
-        total_keystrokes += session.keystrokes as u64;
-        total_clicks += session.clicks as u64;
-        total_duration += session.duration_secs;
-        unique_apps.insert(session.process_name.clone());
-    }
+    let today = crate::store::local_date_string();
+    let sessions = crate::store::with_db(|d| d.get_all_sessions_for_date(&today).ok())
+        .flatten()
+        .unwrap_or_default();
 
-    // Add current session
-    if let Some(current) = &store.current_session {
-        total_keystrokes += current.keystrokes;
-        total_clicks += current.mouse_clicks;
-        total_duration += current.duration_secs();
-        unique_apps.insert(current.process_name.clone());
-    }
+    // Compute stats from database plus the current session
+    let totals =
+        crate::store::compute_focus_totals(&sessions, current, crate::store::count_idle_in_focus());
 
     let message = serde_json::json!({
-        "type": "initial_state",
+        "type": message_type,
         "data": {
             "session": current_session,
             "media": current_media,
             "stats": {
-                "sessions": sessions.len() + if store.current_session.is_some() { 1 } else { 0 },
-                "unique_apps": unique_apps.len(),
-                "keystrokes": total_keystrokes,
-                "clicks": total_clicks,
-                "focus_time_secs": total_duration.max(0),
+                "sessions": totals.sessions,
+                "unique_apps": totals.unique_apps,
+                "keystrokes": totals.keystrokes,
+                "clicks": totals.clicks,
+                "focus_time_secs": totals.focus_time_secs.max(0),
+                "idle_time_secs": totals.idle_time_secs.max(0),
             }
         },
         "timestamp": chrono::Utc::now().to_rfc3339(),
@@ -134,3 +207,45 @@ fn get_current_state() -> Option<String> {
 
     serde_json::to_string(&message).ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_client_commands() {
+        assert!(matches!(
+            serde_json::from_str::<ClientCommand>(r#"{"cmd":"get_stats"}"#),
+            Ok(ClientCommand::GetStats)
+        ));
+        assert!(matches!(
+            serde_json::from_str::<ClientCommand>(r#"{"cmd":"get_apps"}"#),
+            Ok(ClientCommand::GetApps)
+        ));
+        assert!(serde_json::from_str::<ClientCommand>(r#"{"cmd":"reboot"}"#).is_err());
+        assert!(serde_json::from_str::<ClientCommand>("not json").is_err());
+
+        let error: serde_json::Value =
+            serde_json::from_str(&error_message("unknown command")).unwrap();
+        assert_eq!(error["type"], "error");
+        assert_eq!(error["message"], "unknown command");
+    }
+
+    #[tokio::test]
+    async fn test_lagged_receiver_gets_resync() {
+        let (tx, mut rx) = tokio::sync::broadcast::channel::<String>(2);
+        for i in 0..5 {
+            tx.send(i.to_string()).unwrap();
+        }
+
+        let Err(RecvError::Lagged(missed)) = rx.recv().await else {
+            panic!("expected the receiver to lag");
+        };
+        let resync: serde_json::Value = serde_json::from_str(&resync_message(missed)).unwrap();
+        assert_eq!(resync["type"], "resync");
+        assert_eq!(resync["data"]["missed"], 3);
+
+        // The receiver continues with the oldest message still buffered
+        assert_eq!(rx.recv().await.unwrap(), "3");
+    }
+}
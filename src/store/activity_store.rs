@@ -3,8 +3,11 @@
 //! Provides the main data store that holds current and completed sessions,
 //! along with methods for session management and aggregation.
 
-use super::types::{ApplicationStats, DailySummary, WindowSession};
-use crate::media::MediaSession;
+use super::types::{
+    ApplicationStats, DailySummary, IdleReason, IdleStateChange, KeyBreakdown, RunSummary,
+    TypingSample, WindowSession,
+};
+use crate::media::{MediaSession, TitleNormalizer};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
@@ -34,16 +37,38 @@ pub struct ActivityStore {
     /// History of played media sessions.
     pub media_history: Vec<MediaSession>,
 
+    /// Groups title variants ("Song (Remastered)") with the original when set.
+    media_title_normalizer: Option<TitleNormalizer>,
+
     // === AFK Tracking ===
     /// Timestamp of the last keyboard/mouse input
     pub last_input_time: DateTime<Utc>,
 
+    /// Whether the foreground window is currently full-screen.
+    pub foreground_fullscreen: bool,
+
+    /// Whether the workstation is locked (or switched to another user).
+    pub screen_locked: bool,
+
+    /// Whether the screensaver is running.
+    pub screensaver_running: bool,
+
+    /// Set by [`Self::seed_last_input`] so the first session keeps the
+    /// seeded input time instead of resetting it.
+    input_seeded: bool,
+
     // === Database Queue ===
     /// Sessions pending save to database (drained periodically).
     pending_sessions: Vec<WindowSession>,
 
     /// Media sessions pending save to database.
     pending_media: Vec<MediaSession>,
+
+    /// Keystrokes in the minute currently being typed.
+    typing_minute: Option<TypingSample>,
+
+    /// Completed typing minutes pending save to database.
+    pending_typing: Vec<TypingSample>,
 }
 
 impl ActivityStore {
@@ -75,17 +100,38 @@ impl ActivityStore {
         process_name: &str,
         window_title: &str,
     ) {
-        // 1. Finalize current session if exists
+        // 1-3. Finalize current session if exists
+        self.end_current_session();
+
+        // 4. Create new session
+        self.current_session = Some(WindowSession::new(
+            hwnd,
+            pid,
+            process_name.to_string(),
+            window_title.to_string(),
+        ));
+        // Reset last_input_time to now (start of new session), unless it was
+        // seeded at startup and no session has started since
+        if !std::mem::take(&mut self.input_seeded) {
+            self.last_input_time = Utc::now();
+        }
+        self.last_poll_time = Some(Utc::now());
+    }
+
+    /// Ends the current window session without starting a new one.
+    ///
+    /// The session is finalized and kept only if it meets the minimum
+    /// duration requirement.
+    pub fn end_current_session(&mut self) {
         if let Some(mut old_session) = self.current_session.take() {
             old_session.finalize();
 
             // Check minimum duration (get from DB or default to 3 seconds)
-            let min_duration = crate::store::DATABASE
-                .as_ref()
-                .and_then(|db| db.lock().ok())
-                .and_then(|d| d.get_config("min_session_duration_secs").ok().flatten())
-                .and_then(|v| v.parse::<i64>().ok())
-                .unwrap_or(3);
+            let min_duration =
+                crate::store::with_db(|d| d.get_config("min_session_duration_secs").ok().flatten())
+                    .flatten()
+                    .and_then(|v| v.parse::<i64>().ok())
+                    .unwrap_or(3);
 
             let duration = old_session.duration_secs();
             if duration >= min_duration {
@@ -106,17 +152,6 @@ impl ActivityStore {
                 );
             }
         }
-
-        // 4. Create new session
-        self.current_session = Some(WindowSession::new(
-            hwnd,
-            pid,
-            process_name.to_string(),
-            window_title.to_string(),
-        ));
-        // Reset last_input_time to now (start of new session)
-        self.last_input_time = Utc::now();
-        self.last_poll_time = Some(Utc::now());
     }
 
     /// Updates the aggregated stats with a completed session.
@@ -140,48 +175,250 @@ impl ActivityStore {
     ///
     /// This is more efficient than calling increment methods repeatedly,
     /// especially when flushing atomic counters from hooks.
-    pub fn add_input_counts(&mut self, keystrokes: u64, clicks: u64, scrolls: u64) {
-        // Check if user is resuming from idle (was inactive > threshold, now active)
-        if keystrokes > 0 || clicks > 0 || scrolls > 0 {
-            let now = Utc::now();
-            let time_since_last_input = (now - self.last_input_time).num_seconds();
-
-            // Get AFK threshold from config (default 300 seconds = 5 minutes)
-            let afk_threshold = crate::store::DATABASE
-                .as_ref()
-                .and_then(|db| db.lock().ok())
-                .and_then(|d| d.get_config("afk_threshold_secs").ok().flatten())
-                .and_then(|v| v.parse::<i64>().ok())
-                .unwrap_or(300);
-
-            // If user was idle > threshold and now returning
-            if time_since_last_input > afk_threshold {
-                if let Some(session) = &self.current_session {
-                    // Only split if session is not already marked as idle
-                    if !session.is_idle {
-                        self.split_on_resume_from_idle();
-                    }
+    ///
+    /// Returns the change to active if this input ends an idle period, for
+    /// the caller to broadcast after releasing the store lock.
+    pub fn add_input_counts(
+        &mut self,
+        keystrokes: u64,
+        clicks: u64,
+        scrolls: u64,
+    ) -> Option<IdleStateChange> {
+        let now = Utc::now();
+        let resumed = if keystrokes > 0 || clicks > 0 || scrolls > 0 {
+            self.register_input(now)
+        } else {
+            None
+        };
+
+        if let Some(session) = &mut self.current_session {
+            session.keystrokes += keystrokes;
+            session.mouse_clicks += clicks;
+            session.mouse_scrolls += scrolls;
+            self.record_typing(now, keystrokes);
+        }
+
+        resumed
+    }
+
+    /// Adds keystrokes to the per-minute typing sample for `at`.
+    ///
+    /// When `at` falls in a later minute, the previous minute is queued
+    /// for saving and a new one starts.
+    pub fn record_typing(&mut self, at: DateTime<Utc>, keystrokes: u64) {
+        if keystrokes == 0 {
+            return;
+        }
+        let minute = typing_minute_start(at);
+        match &mut self.typing_minute {
+            Some(sample) if sample.minute == minute => sample.keystrokes += keystrokes,
+            current => {
+                if let Some(done) = current.replace(TypingSample { minute, keystrokes }) {
+                    self.pending_typing.push(done);
                 }
             }
+        }
+    }
+
+    /// Drains the typing samples of minutes that ended before `now`.
+    ///
+    /// The minute containing `now` stays open so later keystrokes join it.
+    pub fn drain_typing_samples(&mut self, now: DateTime<Utc>) -> Vec<TypingSample> {
+        if self
+            .typing_minute
+            .is_some_and(|sample| sample.minute < typing_minute_start(now))
+        {
+            self.pending_typing.extend(self.typing_minute.take());
+        }
+        std::mem::take(&mut self.pending_typing)
+    }
+
+    /// Records input reported by the system rather than the hooks.
+    ///
+    /// Used when the input hooks couldn't be installed: nothing is counted,
+    /// but idle detection still follows the user's real last input time
+    /// (`GetLastInputInfo`). Returns the change to active, like
+    /// [`add_input_counts`](Self::add_input_counts).
+    pub fn record_uncounted_input(&mut self, at: DateTime<Utc>) -> Option<IdleStateChange> {
+        if at <= self.last_input_time {
+            return None;
+        }
+        self.register_input(at.min(Utc::now()))
+    }
+
+    /// Marks input at `now`, ending an idle period if there was one.
+    fn register_input(&mut self, now: DateTime<Utc>) -> Option<IdleStateChange> {
+        let mut resumed = None;
+
+        // Check if user is resuming from idle (was inactive > threshold, now active)
+        let time_since_last_input = (now - self.last_input_time).num_seconds();
+        let afk_threshold = self.idle_threshold();
+
+        match &self.current_session {
+            // Already split off by `check_and_split_on_idle`
+            Some(session) if session.is_idle => {
+                self.resume_from_idle_session(now);
+                resumed = Some(now);
+            }
+            // Idle > threshold but not yet noticed by the idle check
+            Some(_) if time_since_last_input > afk_threshold => {
+                self.split_on_resume_from_idle();
+                resumed = Some(now);
+            }
+            _ => {}
+        }
+
+        self.last_input_time = now;
+        resumed.map(|since| IdleStateChange {
+            idle: false,
+            since,
+            reason: None,
+        })
+    }
+
+    /// Ends the current idle session at `now` and continues the same window
+    /// in a new active session.
+    fn resume_from_idle_session(&mut self, now: DateTime<Utc>) {
+        let Some(mut idle_session) = self.current_session.take() else {
+            return;
+        };
+        idle_session.end_time = Some(now);
 
-            self.last_input_time = now;
+        let mut active_session = WindowSession::new(
+            idle_session.window_handle,
+            idle_session.process_id,
+            idle_session.process_name.clone(),
+            idle_session.window_title.clone(),
+        );
+        active_session.start_time = now;
+        active_session.url = idle_session.url.clone();
+        active_session.process_path = idle_session.process_path.clone();
+        active_session.monitor = idle_session.monitor.clone();
+
+        tracing::info!(
+            process = %idle_session.process_name,
+            "User resumed from idle"
+        );
+        self.save_session_if_valid(idle_session);
+        self.current_session = Some(active_session);
+    }
+
+    /// Adds keyboard shortcut counts to the current session.
+    ///
+    /// Shortcuts are already part of the keystroke count, so this doesn't
+    /// affect idle detection.
+    pub fn add_shortcut_count(&mut self, shortcuts: u64) {
+        if let Some(session) = &mut self.current_session {
+            session.shortcuts += shortcuts;
         }
+    }
 
+    /// Adds double-click counts to the current session.
+    ///
+    /// Both presses are already part of the click count, so this doesn't
+    /// affect idle detection.
+    pub fn add_double_clicks(&mut self, double_clicks: u64) {
         if let Some(session) = &mut self.current_session {
-            session.keystrokes += keystrokes;
-            session.mouse_clicks += clicks;
-            session.mouse_scrolls += scrolls;
+            session.double_clicks += double_clicks;
+        }
+    }
+
+    /// Adds keystrokes by kind of key to the current session.
+    ///
+    /// These are already part of the keystroke count, so this doesn't
+    /// affect idle detection.
+    pub fn add_key_breakdown(&mut self, breakdown: &KeyBreakdown) {
+        if let Some(session) = &mut self.current_session {
+            session.key_breakdown.add(breakdown);
+        }
+    }
+
+    /// Adds mouse travel distance (pixels) to the current session.
+    pub fn add_mouse_distance(&mut self, distance_px: u64) {
+        if let Some(session) = &mut self.current_session {
+            session.mouse_distance_px += distance_px;
+        }
+    }
+
+    /// Adds wheel events by direction to the current session.
+    pub fn add_scroll_directions(&mut self, up: u64, down: u64, left: u64, right: u64) {
+        if let Some(session) = &mut self.current_session {
+            session.scroll_up += up;
+            session.scroll_down += down;
+            session.scroll_left += left;
+            session.scroll_right += right;
+        }
+    }
+
+    /// Seeds the last input time at startup, before any session exists.
+    ///
+    /// The first session then starts with the user's real idle time (input
+    /// before OwnMon started counts), so restarting while the user is away
+    /// records that time as idle rather than active.
+    pub fn seed_last_input(&mut self, last_input: DateTime<Utc>) {
+        if self.current_session.is_some() {
+            return;
+        }
+        self.last_input_time = last_input.min(Utc::now());
+        self.input_seeded = true;
+    }
+
+    /// Queues a session recovered after a crash for saving.
+    pub fn restore_session(&mut self, session: WindowSession) {
+        self.save_session_if_valid(session);
+    }
+
+    /// Overrides the category of the current session (`None` clears it).
+    ///
+    /// Returns false if there is no current session.
+    pub fn set_current_category(&mut self, category_id: Option<i64>) -> bool {
+        match &mut self.current_session {
+            Some(session) => {
+                session.category_override = category_id;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Records whether the workstation is locked.
+    ///
+    /// While locked, the user counts as idle from their last input without
+    /// waiting for the AFK threshold.
+    pub fn set_screen_locked(&mut self, locked: bool) {
+        self.screen_locked = locked;
+    }
+
+    /// Gets the reason to record for idle time starting now.
+    fn idle_reason(&self) -> IdleReason {
+        if self.screen_locked {
+            IdleReason::Locked
+        } else if self.screensaver_running {
+            IdleReason::Screensaver
+        } else {
+            IdleReason::Away
+        }
+    }
+
+    /// Records whether the foreground window is full-screen.
+    ///
+    /// The current session is flagged if it goes full-screen at any point.
+    pub fn set_foreground_fullscreen(&mut self, fullscreen: bool) {
+        self.foreground_fullscreen = fullscreen;
+        if fullscreen {
+            if let Some(session) = &mut self.current_session {
+                session.is_fullscreen = true;
+            }
         }
     }
 
     /// Helper to save session if it meets minimum duration requirement.
     fn save_session_if_valid(&mut self, session: WindowSession) {
-        let min_duration = crate::store::DATABASE
-            .as_ref()
-            .and_then(|db| db.lock().ok())
-            .and_then(|d| d.get_config("min_session_duration_secs").ok().flatten())
-            .and_then(|v| v.parse::<i64>().ok())
-            .unwrap_or(3);
+        let min_duration =
+            crate::store::with_db(|d| d.get_config("min_session_duration_secs").ok().flatten())
+                .flatten()
+                .and_then(|v| v.parse::<i64>().ok())
+                .unwrap_or(3);
 
         let duration = session.duration_secs();
         if duration >= min_duration {
@@ -229,17 +466,26 @@ impl ActivityStore {
             idle_session.keystrokes = 0;
             idle_session.mouse_clicks = 0;
             idle_session.mouse_scrolls = 0;
+            idle_session.shortcuts = 0;
+            idle_session.double_clicks = 0;
+            idle_session.mouse_distance_px = 0;
+            idle_session.key_breakdown = KeyBreakdown::default();
             idle_session.is_idle = true;
+            idle_session.idle_reason = Some(self.idle_reason());
 
             self.save_session_if_valid(idle_session);
 
             // 3. Create new active session for resumed activity (same window)
-            self.current_session = Some(WindowSession::new(
+            let mut resumed = WindowSession::new(
                 session.window_handle,
                 session.process_id,
                 session.process_name,
                 session.window_title,
-            ));
+            );
+            resumed.url = session.url;
+            resumed.process_path = session.process_path;
+            resumed.monitor = session.monitor;
+            self.current_session = Some(resumed);
 
             tracing::info!(
                 process = %process_name,
@@ -250,6 +496,7 @@ impl ActivityStore {
             let mut idle_session = session;
             idle_session.end_time = Some(Utc::now());
             idle_session.is_idle = true;
+            idle_session.idle_reason = Some(self.idle_reason());
 
             let idle_clone = idle_session.clone();
             self.save_session_if_valid(idle_session);
@@ -264,23 +511,47 @@ impl ActivityStore {
         }
     }
 
+    /// Gets the AFK threshold for the current session's app.
+    ///
+    /// Uses the app's entry in `app_idle_overrides` if any, else the global
+    /// `afk_threshold_secs` (default 300 seconds = 5 minutes).
+    fn idle_threshold(&self) -> i64 {
+        crate::store::with_db(|db| match &self.current_session {
+            Some(session) => db.get_idle_threshold_for_app(&session.process_name),
+            None => db
+                .get_config("afk_threshold_secs")
+                .ok()
+                .flatten()
+                .and_then(|v| v.parse::<i64>().ok())
+                .unwrap_or(300),
+        })
+        .unwrap_or(300)
+    }
+
     /// Checks if user is idle and splits the current session if needed.
     ///
     /// Should be called periodically (e.g., from poller loop).
-    /// If idle for >5 minutes, finalizes current session with idle time set.
-    pub fn check_and_split_on_idle(&mut self) {
-        // Get AFK threshold from config (default 300 seconds = 5 minutes)
-        let afk_threshold = crate::store::DATABASE
-            .as_ref()
-            .and_then(|db| db.lock().ok())
-            .and_then(|d| d.get_config("afk_threshold_secs").ok().flatten())
-            .and_then(|v| v.parse::<i64>().ok())
-            .unwrap_or(300);
-
-        if self.current_session.is_none() {
-            return; // No active session
+    /// If idle for longer than the app's threshold, finalizes current session
+    /// with idle time set. Returns the change to idle when the user first goes
+    /// idle, for the caller to broadcast after releasing the store lock.
+    pub fn check_and_split_on_idle(&mut self) -> Option<IdleStateChange> {
+        // Watching a movie or presenting full-screen isn't idle time
+        if self.foreground_fullscreen && !self.screen_locked && fullscreen_suppresses_idle() {
+            self.last_input_time = Utc::now();
+            return None;
         }
 
+        // No active session
+        self.current_session.as_ref()?;
+
+        // A locked workstation is idle straight away
+        let afk_threshold = if self.screen_locked {
+            -1
+        } else {
+            self.idle_threshold()
+        };
+        let reason = self.idle_reason();
+
         let time_since_last_input = (Utc::now() - self.last_input_time).num_seconds();
 
         if time_since_last_input > afk_threshold {
@@ -290,7 +561,12 @@ impl ActivityStore {
             if session.is_idle {
                 // Update end_time to capture continued idle period
                 session.end_time = Some(Utc::now());
-                return;
+                // Locking or a screensaver starting later explains the whole
+                // idle stretch; a lock outranks the screensaver
+                if reason != IdleReason::Away && session.idle_reason != Some(IdleReason::Locked) {
+                    session.idle_reason = Some(reason);
+                }
+                return None;
             }
 
             // First time going idle - need to split
@@ -323,7 +599,12 @@ impl ActivityStore {
                 idle_session.keystrokes = 0;
                 idle_session.mouse_clicks = 0;
                 idle_session.mouse_scrolls = 0;
+                idle_session.shortcuts = 0;
+                idle_session.double_clicks = 0;
+                idle_session.mouse_distance_px = 0;
+                idle_session.key_breakdown = KeyBreakdown::default();
                 idle_session.is_idle = true;
+                idle_session.idle_reason = Some(reason);
 
                 tracing::info!(
                     process = %process_name,
@@ -336,13 +617,59 @@ impl ActivityStore {
                 // No activity - entire session is idle
                 session.end_time = Some(Utc::now());
                 session.is_idle = true;
+                session.idle_reason = Some(reason);
 
                 tracing::debug!("Session marked as fully idle (no activity)");
 
                 // Keep as current session to track continued idle time
                 self.current_session = Some(session);
             }
+
+            return Some(IdleStateChange {
+                idle: true,
+                since: self.last_input_time,
+                reason: Some(reason),
+            });
         }
+        None
+    }
+
+    /// Splits the current session at midnight, so each day's totals and its
+    /// `daily_integrity` record only include that day's time.
+    ///
+    /// Midnight follows the `timezone` config. Returns true if the session
+    /// was split.
+    pub fn split_at_day_boundary(&mut self, now: DateTime<Utc>) -> bool {
+        let policy = super::timezone_policy();
+        self.split_current_session_at(policy.day_start(policy.date_of(now)))
+    }
+
+    /// Ends the current session at `boundary` and continues it from there
+    /// with the same window. Input counts stay with the first part.
+    fn split_current_session_at(&mut self, boundary: DateTime<Utc>) -> bool {
+        let Some(session) = &self.current_session else {
+            return false;
+        };
+        if session.start_time >= boundary {
+            return false;
+        }
+        let mut first = self.current_session.take().unwrap();
+
+        let mut rest = first.clone();
+        rest.start_time = boundary;
+        rest.end_time = rest.end_time.map(|end| end.max(boundary));
+        rest.clear_input_counts();
+
+        first.end_time = Some(boundary);
+        tracing::info!(
+            process = %first.process_name,
+            boundary = %boundary,
+            "Split session at day boundary"
+        );
+        self.save_session_if_valid(first);
+
+        self.current_session = Some(rest);
+        true
     }
 
     /// Returns the total number of completed sessions.
@@ -370,6 +697,7 @@ impl ActivityStore {
 
             entry.total_keystrokes += session.keystrokes;
             entry.total_clicks += session.mouse_clicks;
+            entry.total_double_clicks += session.double_clicks;
             entry.total_focus_duration_secs += session.duration_secs() as u64;
             // Don't increment session_count for current session
         }
@@ -380,17 +708,47 @@ impl ActivityStore {
     /// Gets a summary of today's activity.
     pub fn get_daily_summary(&self) -> DailySummary {
         let stats = self.compute_application_stats();
+        let total_focus_time_secs: u64 = stats.values().map(|s| s.total_focus_duration_secs).sum();
+        let total_idle_time_secs: u64 = self
+            .completed_sessions
+            .iter()
+            .chain(self.current_session.as_ref())
+            .filter(|s| s.is_idle)
+            .map(|s| s.duration_secs() as u64)
+            .sum();
 
         DailySummary {
             total_keystrokes: stats.values().map(|s| s.total_keystrokes).sum(),
             total_clicks: stats.values().map(|s| s.total_clicks).sum(),
-            total_focus_time_secs: stats.values().map(|s| s.total_focus_duration_secs).sum(),
+            total_focus_time_secs,
+            total_idle_time_secs,
+            active_focus_time_secs: total_focus_time_secs.saturating_sub(total_idle_time_secs),
             app_count: stats.len() as u32,
             session_count: self.completed_sessions.len() as u32
                 + if self.current_session.is_some() { 1 } else { 0 },
         }
     }
 
+    /// Builds the end-of-run summary with the top `top_n` apps by focus time.
+    pub fn get_run_summary(&self, top_n: usize) -> RunSummary {
+        let mut top_apps: Vec<ApplicationStats> =
+            self.compute_application_stats().into_values().collect();
+        top_apps.sort_by(|a, b| {
+            b.total_focus_duration_secs
+                .cmp(&a.total_focus_duration_secs)
+        });
+        top_apps.truncate(top_n);
+
+        RunSummary {
+            ended_at: Utc::now(),
+            summary: self.get_daily_summary(),
+            top_apps,
+            media_time_secs: self.total_media_time_secs(),
+            media_tracks: self.media_history.len() as u32
+                + if self.current_media.is_some() { 1 } else { 0 },
+        }
+    }
+
     /// Serializes the store to JSON.
     pub fn to_json(&self) -> String {
         let data = serde_json::json!({
@@ -405,6 +763,21 @@ impl ActivityStore {
         serde_json::to_string_pretty(&data).unwrap_or_else(|_| "{}".to_string())
     }
 
+    /// Removes the completed session that started at `start_time` in
+    /// `process_name`, e.g. after it was deleted from the database.
+    ///
+    /// Returns true if a session was removed.
+    pub fn remove_completed_session(
+        &mut self,
+        start_time: DateTime<Utc>,
+        process_name: &str,
+    ) -> bool {
+        let before = self.completed_sessions.len();
+        self.completed_sessions
+            .retain(|s| !(s.start_time == start_time && s.process_name == process_name));
+        self.completed_sessions.len() != before
+    }
+
     /// Prunes old sessions to prevent unbounded memory growth.
     ///
     /// # Arguments
@@ -424,6 +797,11 @@ impl ActivityStore {
 
     // === Media Tracking Methods ===
 
+    /// Sets how media titles are compared when detecting a track change.
+    pub fn set_media_title_normalizer(&mut self, normalizer: Option<TitleNormalizer>) {
+        self.media_title_normalizer = normalizer;
+    }
+
     /// Updates the currently playing media.
     ///
     /// If the media has changed (different title/artist), the old session
@@ -431,15 +809,24 @@ impl ActivityStore {
     pub fn update_media(&mut self, media_info: crate::media::MediaInfo) {
         // Check if media has changed
         let media_changed = match &self.current_media {
-            Some(current) => !current.is_same_media(&media_info),
+            Some(current) => {
+                !current.is_same_media_with(&media_info, self.media_title_normalizer.as_ref())
+            }
             None => true,
         };
 
+        if !media_changed && media_info.is_playing() {
+            if let Some(current) = &mut self.current_media {
+                current.last_seen = media_info.timestamp;
+                current.update_timeline(&media_info);
+            }
+        }
+
         if media_changed && media_info.is_playing() {
             // Finalize current media session if exists
             if let Some(mut old_media) = self.current_media.take() {
                 old_media.finalize();
-                self.media_history.push(old_media);
+                self.save_media_if_valid(old_media);
             }
 
             // Start new media session
@@ -457,13 +844,68 @@ impl ActivityStore {
             // Media stopped/paused, finalize current session
             if let Some(mut old_media) = self.current_media.take() {
                 old_media.finalize();
-                self.pending_media.push(old_media.clone());
-                self.media_history.push(old_media);
+                self.save_media_if_valid(old_media);
                 tracing::debug!("Media playback stopped");
             }
         }
     }
 
+    /// Queues a finished media session for save if it meets the
+    /// `min_media_duration_secs` requirement.
+    fn save_media_if_valid(&mut self, media: MediaSession) {
+        let min_duration = min_media_duration_secs();
+
+        let duration = media.duration_secs();
+        if duration >= min_duration {
+            self.pending_media.push(media.clone());
+            self.media_history.push(media);
+        } else {
+            tracing::trace!(
+                duration_secs = duration,
+                min_duration_secs = min_duration,
+                title = %media.media_info.title,
+                "Skipping short media session"
+            );
+        }
+    }
+
+    /// Finalizes the current media session if it has gone stale or run too long.
+    ///
+    /// A session is stale when the player hasn't reported it for
+    /// [`MEDIA_STALE_SECS`]; it then ends at the last report. A session longer
+    /// than `max_secs` (0 = no limit) ends at `max_secs` after its start, so
+    /// at most `max_secs` is counted. Returns true if a session was finalized.
+    pub fn finalize_stale_media(&mut self, now: DateTime<Utc>, max_secs: i64) -> bool {
+        let Some(media) = &self.current_media else {
+            return false;
+        };
+
+        let mut end_time = None;
+        if (now - media.last_seen).num_seconds() > MEDIA_STALE_SECS {
+            end_time = Some(media.last_seen);
+        }
+        if max_secs > 0 {
+            let cap = media.start_time + chrono::Duration::seconds(max_secs);
+            if end_time.unwrap_or(now) > cap {
+                end_time = Some(cap);
+            }
+        }
+        let Some(end_time) = end_time else {
+            return false;
+        };
+
+        if let Some(mut old_media) = self.current_media.take() {
+            old_media.finalize_at(end_time);
+            tracing::debug!(
+                title = %old_media.media_info.title,
+                duration_secs = old_media.duration_secs(),
+                "Finalized stale media session"
+            );
+            self.save_media_if_valid(old_media);
+        }
+        true
+    }
+
     /// Gets a summary of media listening history.
     pub fn get_media_summary(&self) -> Vec<&MediaSession> {
         self.media_history.iter().rev().take(10).collect()
@@ -502,12 +944,47 @@ impl ActivityStore {
         }
         if let Some(mut media) = self.current_media.take() {
             media.finalize();
-            self.pending_media.push(media.clone());
-            self.media_history.push(media);
+            self.save_media_if_valid(media);
         }
+        self.pending_typing.extend(self.typing_minute.take());
     }
 }
 
+/// Truncates a time to the start of its minute.
+fn typing_minute_start(at: DateTime<Utc>) -> DateTime<Utc> {
+    DateTime::from_timestamp(at.timestamp().div_euclid(60) * 60, 0).unwrap_or(at)
+}
+
+/// Seconds without a player update before the current media session is closed.
+pub const MEDIA_STALE_SECS: i64 = 300;
+
+/// Reads the longest media session counted, in seconds (default: 4 hours, 0 = no limit).
+///
+/// Only media sessions are capped; window sessions run until focus, idle
+/// or a day boundary ends them.
+pub fn max_media_session_secs() -> i64 {
+    crate::store::with_db(|d| d.get_config("max_media_session_secs").ok().flatten())
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(14400)
+}
+
+/// Reads the shortest media session saved, in seconds (default: 30).
+pub fn min_media_duration_secs() -> i64 {
+    crate::store::with_db(|d| d.get_config("min_media_duration_secs").ok().flatten())
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30)
+}
+
+/// Reads whether idle splitting is suppressed while a full-screen app is focused.
+fn fullscreen_suppresses_idle() -> bool {
+    crate::store::with_db(|d| d.get_config("fullscreen_suppresses_idle").ok().flatten())
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(false)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -571,6 +1048,156 @@ mod tests {
         assert_eq!(session.mouse_scrolls, 25);
     }
 
+    #[test]
+    fn test_seeded_input_time_survives_first_session() {
+        let mut store = ActivityStore::new();
+        let last_input = Utc::now() - chrono::Duration::hours(2);
+        store.seed_last_input(last_input);
+
+        // The first session keeps the seed, so the away time shows as idle
+        store.switch_session(1, 100, "code.exe", "main.rs");
+        assert_eq!(store.last_input_time, last_input);
+        assert!(store.check_and_split_on_idle().is_some_and(|c| c.idle));
+        assert!(store.current_session.as_ref().unwrap().is_idle);
+
+        // Later sessions reset it as usual
+        store.switch_session(2, 200, "chrome.exe", "Docs");
+        assert!(store.last_input_time > last_input);
+
+        // Seeding once a session exists has no effect
+        store.seed_last_input(last_input);
+        assert!(store.last_input_time > last_input);
+    }
+
+    #[test]
+    fn test_uncounted_input_keeps_session_active() {
+        let mut store = ActivityStore::new();
+        store.switch_session(1, 100, "code.exe", "main.rs");
+        let last_input = Utc::now() - chrono::Duration::hours(2);
+        store.last_input_time = last_input;
+        store.current_session.as_mut().unwrap().start_time =
+            last_input - chrono::Duration::hours(1);
+
+        // Input older than what's known changes nothing
+        assert!(store
+            .record_uncounted_input(last_input - chrono::Duration::minutes(1))
+            .is_none());
+        assert_eq!(store.last_input_time, last_input);
+
+        // Fresh input ends the idle period without counting anything
+        let change = store.record_uncounted_input(Utc::now()).unwrap();
+        assert!(!change.idle);
+        assert!(store.last_input_time > last_input);
+        assert!(store.check_and_split_on_idle().is_none());
+        assert_eq!(store.current_session.as_ref().unwrap().keystrokes, 0);
+    }
+
+    #[test]
+    fn test_idle_state_changes_reported() {
+        let mut store = ActivityStore::new();
+        store.switch_session(1, 100, "code.exe", "main.rs");
+        store.add_input_counts(5, 0, 0);
+
+        // Inactive well past any threshold
+        let last_input = Utc::now() - chrono::Duration::hours(2);
+        store.last_input_time = last_input;
+        store.current_session.as_mut().unwrap().start_time =
+            last_input - chrono::Duration::hours(1);
+
+        let change = store.check_and_split_on_idle().unwrap();
+        assert!(change.idle);
+        assert_eq!(change.since, last_input);
+        assert!(store.current_session.as_ref().unwrap().is_idle);
+        // Only the transition is reported
+        assert_eq!(store.check_and_split_on_idle(), None);
+
+        // Input ends the idle session and starts an active one
+        let change = store.add_input_counts(1, 0, 0).unwrap();
+        assert!(!change.idle);
+        let session = store.current_session.as_ref().unwrap();
+        assert!(!session.is_idle);
+        assert_eq!(session.keystrokes, 1);
+        assert_eq!(store.add_input_counts(1, 0, 0), None);
+    }
+
+    #[test]
+    fn test_idle_reason_follows_lock_state() {
+        let mut store = ActivityStore::new();
+        store.switch_session(1, 100, "code.exe", "main.rs");
+        store.add_input_counts(5, 0, 0);
+
+        // Locking goes idle right away, without waiting for the threshold
+        store.set_screen_locked(true);
+        let change = store.check_and_split_on_idle().unwrap();
+        assert_eq!(change.reason, Some(IdleReason::Locked));
+        let session = store.current_session.as_ref().unwrap();
+        assert!(session.is_idle);
+        assert_eq!(session.idle_reason, Some(IdleReason::Locked));
+
+        // Coming back starts an active session without a reason
+        store.set_screen_locked(false);
+        let change = store.add_input_counts(1, 0, 0).unwrap();
+        assert_eq!(change.reason, None);
+        assert_eq!(store.current_session.as_ref().unwrap().idle_reason, None);
+
+        // Inactivity past the threshold while the screensaver runs
+        store.last_input_time = Utc::now() - chrono::Duration::hours(2);
+        store.current_session.as_mut().unwrap().start_time =
+            store.last_input_time - chrono::Duration::hours(1);
+        store.screensaver_running = true;
+        store.check_and_split_on_idle().unwrap();
+        assert_eq!(
+            store.current_session.as_ref().unwrap().idle_reason,
+            Some(IdleReason::Screensaver)
+        );
+
+        // Locking while already idle upgrades the reason, the screensaver
+        // stopping doesn't downgrade it
+        store.set_screen_locked(true);
+        assert!(store.check_and_split_on_idle().is_none());
+        store.screensaver_running = false;
+        assert!(store.check_and_split_on_idle().is_none());
+        assert_eq!(
+            store.current_session.as_ref().unwrap().idle_reason,
+            Some(IdleReason::Locked)
+        );
+    }
+
+    #[test]
+    fn test_idle_reason_upgraded_when_screensaver_starts() {
+        let mut store = ActivityStore::new();
+        store.switch_session(1, 100, "code.exe", "main.rs");
+        store.last_input_time = Utc::now() - chrono::Duration::hours(2);
+        store.current_session.as_mut().unwrap().start_time =
+            store.last_input_time - chrono::Duration::hours(1);
+        store.check_and_split_on_idle().unwrap();
+        assert_eq!(
+            store.current_session.as_ref().unwrap().idle_reason,
+            Some(IdleReason::Away)
+        );
+
+        store.screensaver_running = true;
+        assert!(store.check_and_split_on_idle().is_none());
+        assert_eq!(
+            store.current_session.as_ref().unwrap().idle_reason,
+            Some(IdleReason::Screensaver)
+        );
+    }
+
+    #[test]
+    fn test_fullscreen_flag_sticks_to_session() {
+        let mut store = ActivityStore::new();
+        store.switch_session(1, 100, "game.exe", "Game");
+
+        store.set_foreground_fullscreen(true);
+        store.set_foreground_fullscreen(false);
+        assert!(!store.foreground_fullscreen);
+        assert!(store.current_session.as_ref().unwrap().is_fullscreen);
+
+        store.switch_session(2, 200, "code.exe", "main.rs");
+        assert!(!store.current_session.as_ref().unwrap().is_fullscreen);
+    }
+
     #[test]
     fn test_current_window_handle() {
         let mut store = ActivityStore::new();
@@ -636,4 +1263,153 @@ mod tests {
         // Should keep the most recent ones
         assert_eq!(store.completed_sessions[0].process_name, "app5.exe");
     }
+
+    #[test]
+    fn test_remove_completed_session() {
+        let mut store = ActivityStore::new();
+        let start = Utc::now() - chrono::Duration::minutes(10);
+        for name in ["code.exe", "chrome.exe"] {
+            let mut session = WindowSession::new(0, 0, name.into(), "Window".into());
+            session.start_time = start;
+            store.completed_sessions.push(session);
+        }
+
+        assert!(!store.remove_completed_session(start, "notepad.exe"));
+        assert!(store.remove_completed_session(start, "code.exe"));
+        assert_eq!(store.completed_sessions.len(), 1);
+        assert_eq!(store.completed_sessions[0].process_name, "chrome.exe");
+    }
+
+    #[test]
+    fn test_split_current_session_at_boundary() {
+        let mut store = ActivityStore::new();
+        store.switch_session(1, 100, "code.exe", "Editor");
+
+        let boundary = Utc::now();
+        {
+            let session = store.current_session.as_mut().unwrap();
+            session.start_time = boundary - chrono::Duration::minutes(15);
+            session.keystrokes = 42;
+            session.category_override = Some(2);
+        }
+
+        // A boundary before the session start doesn't split
+        assert!(!store.split_current_session_at(boundary - chrono::Duration::hours(1)));
+
+        assert!(store.split_current_session_at(boundary));
+        let first = store.completed_sessions.last().unwrap();
+        assert_eq!(first.end_time, Some(boundary));
+        assert_eq!(first.keystrokes, 42);
+
+        let rest = store.current_session.as_ref().unwrap();
+        assert_eq!(rest.start_time, boundary);
+        assert_eq!(rest.process_name, "code.exe");
+        assert_eq!(rest.keystrokes, 0);
+        assert_eq!(rest.category_override, Some(2));
+        assert_eq!(rest.end_time, None);
+
+        // Already split
+        assert!(!store.split_current_session_at(boundary));
+        assert!(!store.split_at_day_boundary(boundary));
+    }
+
+    fn playing(title: &str) -> crate::media::MediaInfo {
+        crate::media::MediaInfo::new(
+            title.to_string(),
+            "Artist".to_string(),
+            "Album".to_string(),
+            "Spotify.exe".to_string(),
+            crate::media::PlaybackStatus::Playing,
+        )
+    }
+
+    #[test]
+    fn test_stale_media_finalized_and_capped() {
+        let now = Utc::now();
+        let mut store = ActivityStore::new();
+
+        // Fresh session within the cap is left alone
+        store.update_media(playing("Song"));
+        assert!(!store.finalize_stale_media(now, 3600));
+        assert!(store.current_media.is_some());
+
+        // Player stopped reporting 20 minutes ago: ends at the last report
+        let media = store.current_media.as_mut().unwrap();
+        media.start_time = now - chrono::Duration::minutes(30);
+        media.last_seen = now - chrono::Duration::minutes(20);
+        assert!(store.finalize_stale_media(now, 3600));
+        assert!(store.current_media.is_none());
+        assert_eq!(store.media_history.last().unwrap().duration_secs(), 600);
+        assert_eq!(store.drain_pending_media().len(), 1);
+
+        // Still reported but open for a day: counted time is capped
+        store.update_media(playing("Long Mix"));
+        let media = store.current_media.as_mut().unwrap();
+        media.start_time = now - chrono::Duration::days(1);
+        media.last_seen = now;
+        assert!(store.finalize_stale_media(now, 3600));
+        assert_eq!(store.media_history.last().unwrap().duration_secs(), 3600);
+        assert_eq!(store.total_media_time_secs(), 600 + 3600);
+    }
+
+    #[test]
+    fn test_short_media_sessions_discarded() {
+        let now = Utc::now();
+        let mut store = ActivityStore::new();
+
+        // A 5-second jingle is dropped when the next track starts
+        store.update_media(playing("Jingle"));
+        store.current_media.as_mut().unwrap().start_time = now - chrono::Duration::seconds(5);
+        store.update_media(playing("Song"));
+        assert!(store.media_history.is_empty());
+        assert!(store.drain_pending_media().is_empty());
+
+        // A 60-second track is kept when playback stops
+        store.current_media.as_mut().unwrap().start_time = now - chrono::Duration::seconds(60);
+        let mut stopped = playing("Song");
+        stopped.playback_status = crate::media::PlaybackStatus::Stopped;
+        store.update_media(stopped);
+        assert_eq!(store.media_history.len(), 1);
+        assert_eq!(store.media_history[0].media_info.title, "Song");
+        assert_eq!(store.drain_pending_media().len(), 1);
+    }
+
+    #[test]
+    fn test_typing_samples_bucket_by_minute() {
+        use chrono::TimeZone;
+
+        let at = |h: u32, m: u32, s: u32| Utc.with_ymd_and_hms(2025, 1, 15, h, m, s).unwrap();
+        let mut store = ActivityStore::new();
+
+        store.record_typing(at(10, 0, 5), 10);
+        store.record_typing(at(10, 0, 50), 5);
+        store.record_typing(at(10, 1, 0), 0);
+        store.record_typing(at(10, 3, 20), 7);
+
+        // The open minute is kept until it ends
+        let drained = store.drain_typing_samples(at(10, 3, 40));
+        assert_eq!(
+            drained,
+            vec![TypingSample {
+                minute: at(10, 0, 0),
+                keystrokes: 15,
+            }]
+        );
+
+        store.record_typing(at(10, 3, 59), 1);
+        let drained = store.drain_typing_samples(at(10, 4, 0));
+        assert_eq!(
+            drained,
+            vec![TypingSample {
+                minute: at(10, 3, 0),
+                keystrokes: 8,
+            }]
+        );
+        assert!(store.drain_typing_samples(at(10, 9, 0)).is_empty());
+
+        // Finalizing at shutdown closes the open minute
+        store.record_typing(at(10, 10, 1), 3);
+        store.finalize_current_session();
+        assert_eq!(store.drain_typing_samples(at(10, 10, 2)).len(), 1);
+    }
 }
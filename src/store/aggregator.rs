@@ -3,8 +3,12 @@
 //! Provides functions for computing aggregated statistics from
 //! activity data.
 
-use super::types::{ApplicationStats, DailySummary, WindowSession};
-use chrono::{DateTime, Timelike, Utc};
+use super::types::{
+    ApplicationStats, CategoryScore, DailySummary, FocusTotals, PaceComparison, ProductivityScore,
+    TypingCadence, TypingSample, WindowSession,
+};
+use crate::database::{DailyPaceTotals, SessionWithDuration};
+use chrono::{DateTime, NaiveDate, TimeZone, Timelike, Utc};
 use std::collections::HashMap;
 
 /// Computes aggregated statistics grouped by application.
@@ -22,6 +26,74 @@ pub fn compute_app_stats(sessions: &[WindowSession]) -> HashMap<String, Applicat
     stats
 }
 
+/// Sums stored sessions plus the current (unsaved) session.
+///
+/// Idle sessions always add to `idle_time_secs`, and to `focus_time_secs`
+/// only when `count_idle_in_focus` is set.
+pub fn compute_focus_totals(
+    sessions: &[SessionWithDuration],
+    current: Option<&WindowSession>,
+    count_idle_in_focus: bool,
+) -> FocusTotals {
+    let mut totals = FocusTotals::default();
+    let mut apps = std::collections::HashSet::new();
+
+    let stored = sessions.iter().map(|s| {
+        (
+            s.process_name.as_str(),
+            s.keystrokes.max(0) as u64,
+            s.clicks.max(0) as u64,
+            s.mouse_distance_px.max(0) as u64,
+            &s.key_breakdown,
+            s.duration_secs,
+            s.is_idle,
+        )
+    });
+    let current = current.map(|s| {
+        (
+            s.process_name.as_str(),
+            s.keystrokes,
+            s.mouse_clicks,
+            s.mouse_distance_px,
+            &s.key_breakdown,
+            s.duration_secs(),
+            s.is_idle,
+        )
+    });
+
+    for (process_name, keystrokes, clicks, mouse_distance, key_breakdown, duration, is_idle) in
+        stored.chain(current)
+    {
+        totals.sessions += 1;
+        totals.keystrokes += keystrokes;
+        totals.clicks += clicks;
+        totals.mouse_distance_px += mouse_distance;
+        totals.key_breakdown.add(key_breakdown);
+        if is_idle {
+            totals.idle_time_secs += duration;
+        } else {
+            totals.active_time_secs += duration;
+            totals.context_switches += 1;
+            totals.longest_focus_secs = totals.longest_focus_secs.max(duration);
+        }
+        if !is_idle || count_idle_in_focus {
+            totals.focus_time_secs += duration;
+        }
+        apps.insert(process_name);
+    }
+
+    totals.unique_apps = apps.len();
+    totals
+}
+
+/// Reads whether idle sessions count toward focus time (default: false).
+pub fn count_idle_in_focus() -> bool {
+    crate::store::with_db(|d| d.get_config("count_idle_in_focus").ok().flatten())
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(false)
+}
+
 /// Computes statistics for a specific time range.
 pub fn compute_stats_for_range(
     sessions: &[WindowSession],
@@ -36,12 +108,16 @@ pub fn compute_stats_for_range(
     let mut total_keystrokes = 0u64;
     let mut total_clicks = 0u64;
     let mut total_focus_secs = 0u64;
+    let mut total_idle_secs = 0u64;
     let mut apps: std::collections::HashSet<String> = std::collections::HashSet::new();
 
     for session in &filtered {
         total_keystrokes += session.keystrokes;
         total_clicks += session.mouse_clicks;
         total_focus_secs += session.duration_secs() as u64;
+        if session.is_idle {
+            total_idle_secs += session.duration_secs() as u64;
+        }
         apps.insert(session.process_name.clone());
     }
 
@@ -49,6 +125,8 @@ pub fn compute_stats_for_range(
         total_keystrokes,
         total_clicks,
         total_focus_time_secs: total_focus_secs,
+        total_idle_time_secs: total_idle_secs,
+        active_focus_time_secs: total_focus_secs - total_idle_secs,
         app_count: apps.len() as u32,
         session_count: filtered.len() as u32,
     }
@@ -93,10 +171,405 @@ pub fn activity_by_hour(sessions: &[WindowSession]) -> [u64; 24] {
     hours
 }
 
+/// Spreads time intervals over a per-day, per-hour grid in `tz`.
+///
+/// Returns one row per day from `from` to `to` (inclusive) with the seconds
+/// spent in each hour. Intervals are split at hour boundaries; parts outside
+/// the date range are dropped.
+pub fn hourly_heatmap<Tz: TimeZone>(
+    intervals: &[(DateTime<Utc>, DateTime<Utc>)],
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: &Tz,
+) -> Vec<[u64; 24]> {
+    let days = (to - from).num_days() + 1;
+    if days <= 0 {
+        return Vec::new();
+    }
+    let mut grid = vec![[0u64; 24]; days as usize];
+
+    for (start, end) in intervals {
+        let end = end.with_timezone(tz);
+        let mut cursor = start.with_timezone(tz);
+        while cursor < end {
+            let hour_start = cursor
+                .with_minute(0)
+                .and_then(|t| t.with_second(0))
+                .and_then(|t| t.with_nanosecond(0))
+                .unwrap_or_else(|| cursor.clone());
+            let next = (hour_start + chrono::Duration::hours(1)).min(end.clone());
+
+            let day = (cursor.date_naive() - from).num_days();
+            if (0..days).contains(&day) {
+                grid[day as usize][cursor.hour() as usize] +=
+                    (next.clone() - cursor.clone()).num_seconds().max(0) as u64;
+            }
+            cursor = next;
+        }
+    }
+
+    grid
+}
+
+/// Minimum average focus time (seconds) before a pace ratio is reported.
+///
+/// Early in the morning the historical average is close to zero, which
+/// would make any activity look like a huge lead.
+pub const MIN_PACE_BASELINE_FOCUS_SECS: f64 = 300.0;
+
+/// Minimum average keystrokes before a keystroke pace ratio is reported.
+pub const MIN_PACE_BASELINE_KEYSTROKES: f64 = 50.0;
+
+/// Compares today's totals against the per-day history at the same time of day.
+///
+/// `history` holds one entry per past day with activity; days without any
+/// sessions are not counted toward the average.
+pub fn compute_pace(
+    focus_secs: i64,
+    keystrokes: i64,
+    history: &[DailyPaceTotals],
+) -> PaceComparison {
+    let history_days = history.len() as u32;
+
+    let (avg_focus_secs, avg_keystrokes) = if history.is_empty() {
+        (0.0, 0.0)
+    } else {
+        let days = history.len() as f64;
+        (
+            history.iter().map(|d| d.focus_secs).sum::<i64>() as f64 / days,
+            history.iter().map(|d| d.keystrokes).sum::<i64>() as f64 / days,
+        )
+    };
+
+    let ratio = |value: i64, avg: f64, baseline: f64| {
+        if history.is_empty() || avg < baseline {
+            None
+        } else {
+            Some(value as f64 / avg)
+        }
+    };
+
+    PaceComparison {
+        focus_secs,
+        keystrokes,
+        avg_focus_secs,
+        avg_keystrokes,
+        focus_ratio: ratio(focus_secs, avg_focus_secs, MIN_PACE_BASELINE_FOCUS_SECS),
+        keystrokes_ratio: ratio(keystrokes, avg_keystrokes, MIN_PACE_BASELINE_KEYSTROKES),
+        history_days,
+    }
+}
+
+/// Computes average and peak keystrokes per minute from per-minute samples.
+///
+/// The average is over minutes with typing, so pauses don't drag it down.
+pub fn compute_typing_cadence(samples: &[TypingSample]) -> TypingCadence {
+    let active: Vec<&TypingSample> = samples.iter().filter(|s| s.keystrokes > 0).collect();
+    let total_keystrokes: u64 = active.iter().map(|s| s.keystrokes).sum();
+    let peak = active.iter().copied().reduce(|best, s| {
+        if s.keystrokes > best.keystrokes {
+            s
+        } else {
+            best
+        }
+    });
+
+    TypingCadence {
+        total_keystrokes,
+        active_minutes: active.len() as u32,
+        avg_kpm: if active.is_empty() {
+            0.0
+        } else {
+            total_keystrokes as f64 / active.len() as f64
+        },
+        peak_kpm: peak.map(|s| s.keystrokes).unwrap_or(0),
+        peak_minute: peak.map(|s| s.minute),
+    }
+}
+
+/// Counts consecutive days of use ending today, from dates most recent first.
+///
+/// A day not used yet today doesn't break the streak: it then counts back
+/// from yesterday. Unparseable dates are ignored.
+pub fn usage_streak(dates_desc: &[String], today: NaiveDate) -> u32 {
+    let mut dates = dates_desc
+        .iter()
+        .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .skip_while(|d| *d > today)
+        .peekable();
+
+    let mut expected = match dates.peek() {
+        Some(&d) if d == today => today,
+        Some(&d) if Some(d) == today.pred_opt() => d,
+        _ => return 0,
+    };
+    let mut streak = 0;
+    for date in dates {
+        if date != expected {
+            break;
+        }
+        streak += 1;
+        match expected.pred_opt() {
+            Some(prev) => expected = prev,
+            None => break,
+        }
+    }
+    streak
+}
+
+/// Coalesces adjacent sessions of the same app into one.
+///
+/// Two neighbouring sessions are merged when they have the same process
+/// (and title, if `match_titles`), idle state and category override, and
+/// the later one starts less than `max_gap_secs` after the earlier one
+/// ends. Counts and durations are summed; the merged session keeps the id
+/// and title of its earliest part. Works for ascending or descending order,
+/// which is preserved.
+pub fn merge_adjacent_sessions(
+    sessions: Vec<SessionWithDuration>,
+    max_gap_secs: i64,
+    match_titles: bool,
+) -> Vec<SessionWithDuration> {
+    let parse = |time: &str| DateTime::parse_from_rfc3339(time).ok();
+    let end_of = |s: &SessionWithDuration| {
+        s.end_time.as_deref().and_then(parse).or_else(|| {
+            parse(&s.start_time).map(|t| t + chrono::Duration::seconds(s.duration_secs))
+        })
+    };
+
+    let mut merged: Vec<SessionWithDuration> = Vec::with_capacity(sessions.len());
+    for session in sessions {
+        let Some(last) = merged.last_mut() else {
+            merged.push(session);
+            continue;
+        };
+
+        let same_window = last.process_name == session.process_name
+            && (!match_titles || last.window_title == session.window_title)
+            && last.is_idle == session.is_idle
+            && last.category_override == session.category_override;
+        let (Some(last_start), Some(start)) = (parse(&last.start_time), parse(&session.start_time))
+        else {
+            merged.push(session);
+            continue;
+        };
+        let (earlier, later) = if last_start <= start {
+            (&*last, &session)
+        } else {
+            (&session, &*last)
+        };
+        let gap = match (end_of(earlier), parse(&later.start_time)) {
+            (Some(end), Some(start)) => (start - end).num_seconds(),
+            _ => i64::MAX,
+        };
+
+        if !same_window || gap >= max_gap_secs {
+            merged.push(session);
+            continue;
+        }
+
+        let previous = std::mem::take(last);
+        let (first, second) = if last_start <= start {
+            (previous, session)
+        } else {
+            (session, previous)
+        };
+        let mut combined = first;
+        combined.end_time = second.end_time.clone();
+        combined.keystrokes += second.keystrokes;
+        combined.clicks += second.clicks;
+        combined.scrolls += second.scrolls;
+        combined.shortcuts += second.shortcuts;
+        combined.is_fullscreen |= second.is_fullscreen;
+        combined.scroll_up += second.scroll_up;
+        combined.scroll_down += second.scroll_down;
+        combined.scroll_left += second.scroll_left;
+        combined.scroll_right += second.scroll_right;
+        combined.mouse_distance_px += second.mouse_distance_px;
+        combined.double_clicks += second.double_clicks;
+        combined.key_breakdown.add(&second.key_breakdown);
+        combined.duration_secs += second.duration_secs;
+        *last = combined;
+    }
+    merged
+}
+
+/// Category weights used when `category_weights` is unset or invalid.
+pub const DEFAULT_CATEGORY_WEIGHTS: &str = r#"{"Work":1.0,"Entertainment":-0.5}"#;
+
+/// Scores focus time by category weight on a 0-100 scale.
+///
+/// `sessions` holds `(category, focus_secs)` pairs. The weighted average of
+/// the weights (unlisted categories count as 0) is clamped to -1..=1 and
+/// mapped onto 0..=100, so a day spent entirely in +1 categories scores 100
+/// and one in neutral categories scores 50.
+pub fn compute_productivity_score(
+    sessions: &[(String, i64)],
+    category_weights: &HashMap<String, f64>,
+) -> ProductivityScore {
+    let mut by_category: HashMap<&str, i64> = HashMap::new();
+    for (category, secs) in sessions {
+        *by_category.entry(category.as_str()).or_default() += (*secs).max(0);
+    }
+
+    let mut categories: Vec<CategoryScore> = by_category
+        .into_iter()
+        .map(|(category, focus_time_secs)| CategoryScore {
+            category: category.to_string(),
+            weight: category_weights.get(category).copied().unwrap_or(0.0),
+            focus_time_secs,
+        })
+        .collect();
+    categories.sort_by(|a, b| {
+        b.focus_time_secs
+            .cmp(&a.focus_time_secs)
+            .then_with(|| a.category.cmp(&b.category))
+    });
+
+    let focus_time_secs: i64 = categories.iter().map(|c| c.focus_time_secs).sum();
+    let score = (focus_time_secs > 0).then(|| {
+        let weighted: f64 = categories
+            .iter()
+            .map(|c| c.weight * c.focus_time_secs as f64)
+            .sum();
+        let average = (weighted / focus_time_secs as f64).clamp(-1.0, 1.0);
+        ((average + 1.0) * 50.0).round() as u32
+    });
+
+    ProductivityScore {
+        score,
+        focus_time_secs,
+        categories,
+    }
+}
+
+/// Reads `category_weights` (a JSON object of category name to weight).
+pub fn category_weights() -> HashMap<String, f64> {
+    let configured = crate::store::with_db(|d| d.get_config("category_weights").ok().flatten())
+        .flatten()
+        .filter(|v| !v.trim().is_empty());
+
+    if let Some(json) = configured {
+        match serde_json::from_str(&json) {
+            Ok(weights) => return weights,
+            Err(e) => {
+                tracing::warn!(error = %e, "category_weights is not a JSON object, using defaults")
+            }
+        }
+    }
+    serde_json::from_str(DEFAULT_CATEGORY_WEIGHTS).unwrap_or_default()
+}
+
+/// Formats a duration in seconds as human units, e.g. `1h 12m 1s`.
+///
+/// Hours are the largest unit; negative durations format as `0s`.
+pub fn format_duration(secs: i64) -> String {
+    let secs = secs.max(0);
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+
+    if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Formats a count with thousands separators, e.g. `12,345`.
+pub fn format_count(n: u64) -> String {
+    let digits = n.to_string();
+    let groups: Vec<&str> = digits
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect();
+    groups.join(",")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_duration_edges() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(59), "59s");
+        assert_eq!(format_duration(60), "1m 0s");
+        assert_eq!(format_duration(3600), "1h 0m 0s");
+        assert_eq!(format_duration(4321), "1h 12m 1s");
+        assert_eq!(format_duration(90061), "25h 1m 1s");
+        assert_eq!(format_duration(-5), "0s");
+    }
+
+    #[test]
+    fn test_format_count_separators() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1,000");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn test_focus_totals_exclude_idle_by_default() {
+        let stored = |name: &str, duration_secs: i64, is_idle: bool| SessionWithDuration {
+            process_name: name.to_string(),
+            keystrokes: 10,
+            clicks: 2,
+            duration_secs,
+            is_idle,
+            ..Default::default()
+        };
+        let sessions = vec![
+            stored("code.exe", 600, false),
+            stored("code.exe", 300, true),
+            stored("chrome.exe", 120, false),
+        ];
+        let mut current = create_test_session("slack.exe", 5, 1);
+        current.is_idle = true;
+
+        let totals = compute_focus_totals(&sessions, Some(&current), false);
+        assert_eq!(totals.sessions, 4);
+        assert_eq!(totals.unique_apps, 3);
+        assert_eq!(totals.keystrokes, 35);
+        assert_eq!(totals.clicks, 7);
+        assert_eq!(totals.focus_time_secs, 720);
+        assert_eq!(totals.active_time_secs, 720);
+        assert_eq!(totals.context_switches, 2);
+        assert_eq!(totals.longest_focus_secs, 600);
+        assert!(totals.idle_time_secs >= 300);
+
+        let with_idle = compute_focus_totals(&sessions, Some(&current), true);
+        assert_eq!(with_idle.focus_time_secs, 720 + totals.idle_time_secs);
+        assert_eq!(with_idle.idle_time_secs, totals.idle_time_secs);
+        assert_eq!(with_idle.active_time_secs, 720);
+    }
+
+    #[test]
+    fn test_stats_for_range_splits_idle_time() {
+        let start = Utc::now() - chrono::Duration::hours(1);
+        let session = |name: &str, offset_mins: i64, duration_mins: i64, is_idle: bool| {
+            let mut s = create_test_session(name, 5, 1);
+            s.start_time = start + chrono::Duration::minutes(offset_mins);
+            s.end_time = Some(s.start_time + chrono::Duration::minutes(duration_mins));
+            s.is_idle = is_idle;
+            s
+        };
+        let sessions = vec![
+            session("code.exe", 0, 20, false),
+            session("code.exe", 20, 15, true),
+            session("chrome.exe", 35, 10, false),
+            session("chrome.exe", 45, 5, true),
+        ];
+
+        let summary = compute_stats_for_range(&sessions, start, Utc::now());
+        assert_eq!(summary.session_count, 4);
+        assert_eq!(summary.total_focus_time_secs, 50 * 60);
+        assert_eq!(summary.total_idle_time_secs, 20 * 60);
+        assert_eq!(summary.active_focus_time_secs, 30 * 60);
+    }
+
     fn create_test_session(process: &str, keys: u64, clicks: u64) -> WindowSession {
         let mut session = WindowSession::new(1, 100, process.to_string(), "Test".to_string());
         session.keystrokes = keys;
@@ -105,6 +578,80 @@ mod tests {
         session
     }
 
+    #[test]
+    fn test_merge_adjacent_sessions() {
+        let stored =
+            |id: i64, name: &str, title: &str, start: &str, end: &str| SessionWithDuration {
+                id,
+                process_name: name.to_string(),
+                window_title: Some(title.to_string()),
+                start_time: format!("2025-01-15T{}:00+00:00", start),
+                end_time: Some(format!("2025-01-15T{}:00+00:00", end)),
+                keystrokes: 10,
+                clicks: 1,
+                duration_secs: 60,
+                ..Default::default()
+            };
+        let sessions = vec![
+            stored(1, "code.exe", "a.rs", "10:00", "10:01"),
+            stored(2, "code.exe", "b.rs", "10:01", "10:02"),
+            stored(3, "chrome.exe", "Docs", "10:02", "10:03"),
+            stored(4, "code.exe", "a.rs", "10:03", "10:04"),
+            stored(5, "code.exe", "a.rs", "10:10", "10:11"),
+        ];
+
+        let merged = merge_adjacent_sessions(sessions.clone(), 30, false);
+        let ids: Vec<i64> = merged.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![1, 3, 4, 5]);
+        assert_eq!(merged[0].keystrokes, 20);
+        assert_eq!(merged[0].duration_secs, 120);
+        assert_eq!(
+            merged[0].end_time.as_deref(),
+            Some("2025-01-15T10:02:00+00:00")
+        );
+
+        // Titles must match too
+        assert_eq!(merge_adjacent_sessions(sessions.clone(), 30, true).len(), 5);
+
+        // Descending order merges the same way and keeps the earliest id
+        let mut desc = sessions;
+        desc.reverse();
+        let merged = merge_adjacent_sessions(desc, 600, false);
+        let ids: Vec<i64> = merged.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![4, 3, 1]);
+        assert_eq!(merged[0].start_time, "2025-01-15T10:03:00+00:00");
+        assert_eq!(
+            merged[0].end_time.as_deref(),
+            Some("2025-01-15T10:11:00+00:00")
+        );
+        assert_eq!(merged[0].clicks, 2);
+    }
+
+    #[test]
+    fn test_hourly_heatmap_splits_at_hour_boundaries() {
+        let at = |d: u32, h: u32, m: u32| Utc.with_ymd_and_hms(2025, 1, d, h, m, 0).unwrap();
+        let from = NaiveDate::from_ymd_opt(2025, 1, 10).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 1, 11).unwrap();
+
+        let intervals = [
+            (at(10, 9, 50), at(10, 10, 20)),
+            // Crosses midnight into the second day
+            (at(10, 23, 30), at(11, 0, 15)),
+            // Before the range
+            (at(9, 12, 0), at(9, 13, 0)),
+        ];
+        let grid = hourly_heatmap(&intervals, from, to, &Utc);
+
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid[0][9], 600);
+        assert_eq!(grid[0][10], 1200);
+        assert_eq!(grid[0][23], 1800);
+        assert_eq!(grid[1][0], 900);
+        assert_eq!(grid.iter().flatten().sum::<u64>(), 4500);
+
+        assert!(hourly_heatmap(&intervals, to, from, &Utc).is_empty());
+    }
+
     #[test]
     fn test_compute_app_stats() {
         let sessions = vec![
@@ -135,4 +682,132 @@ mod tests {
         assert_eq!(top[0].0, "b.exe");
         assert_eq!(top[1].0, "a.exe");
     }
+
+    fn pace_day(date: &str, focus_secs: i64, keystrokes: i64) -> DailyPaceTotals {
+        DailyPaceTotals {
+            date: date.to_string(),
+            keystrokes,
+            focus_secs,
+        }
+    }
+
+    #[test]
+    fn test_compute_productivity_score() {
+        let weights: HashMap<String, f64> = serde_json::from_str(DEFAULT_CATEGORY_WEIGHTS).unwrap();
+        let day = |entries: &[(&str, i64)]| {
+            entries
+                .iter()
+                .map(|(c, s)| (c.to_string(), *s))
+                .collect::<Vec<_>>()
+        };
+
+        let all_work = compute_productivity_score(&day(&[("Work", 3600)]), &weights);
+        assert_eq!(all_work.score, Some(100));
+
+        let neutral = compute_productivity_score(&day(&[("Browser", 600)]), &weights);
+        assert_eq!(neutral.score, Some(50));
+
+        // 2h work (+1) and 2h entertainment (-0.5): average 0.25
+        let mixed = compute_productivity_score(
+            &day(&[("Work", 3600), ("Entertainment", 7200), ("Work", 3600)]),
+            &weights,
+        );
+        assert_eq!(mixed.score, Some(63));
+        assert_eq!(mixed.focus_time_secs, 14400);
+        assert_eq!(mixed.categories.len(), 2);
+        assert_eq!(mixed.categories[0].category, "Entertainment");
+        assert_eq!(mixed.categories[0].weight, -0.5);
+        assert_eq!(mixed.categories[1].focus_time_secs, 7200);
+
+        let empty = compute_productivity_score(&[], &weights);
+        assert_eq!(empty.score, None);
+    }
+
+    #[test]
+    fn test_compute_pace_ratio() {
+        let history = vec![
+            pace_day("2025-01-01", 3000, 400),
+            pace_day("2025-01-02", 5000, 600),
+        ];
+
+        let pace = compute_pace(4800, 250, &history);
+
+        assert_eq!(pace.history_days, 2);
+        assert_eq!(pace.avg_focus_secs, 4000.0);
+        assert_eq!(pace.focus_ratio, Some(1.2));
+        assert_eq!(pace.keystrokes_ratio, Some(0.5));
+    }
+
+    #[test]
+    fn test_compute_pace_without_enough_history() {
+        // No history at all
+        let pace = compute_pace(600, 100, &[]);
+        assert_eq!(pace.history_days, 0);
+        assert!(pace.focus_ratio.is_none());
+        assert!(pace.keystrokes_ratio.is_none());
+
+        // Early morning: history exists but the average is still tiny
+        let history = vec![pace_day("2025-01-01", 60, 5)];
+        let pace = compute_pace(120, 10, &history);
+        assert_eq!(pace.history_days, 1);
+        assert!(pace.focus_ratio.is_none());
+        assert!(pace.keystrokes_ratio.is_none());
+    }
+
+    #[test]
+    fn test_usage_streak() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let dates = |days: &[&str]| days.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(usage_streak(&[], today), 0);
+        assert_eq!(
+            usage_streak(&dates(&["2025-03-10", "2025-03-09", "2025-03-08"]), today),
+            3
+        );
+        // Not used yet today: the streak through yesterday still counts
+        assert_eq!(
+            usage_streak(&dates(&["2025-03-09", "2025-03-08", "2025-03-06"]), today),
+            2
+        );
+        // Last used two days ago: streak is broken
+        assert_eq!(
+            usage_streak(&dates(&["2025-03-08", "2025-03-07"]), today),
+            0
+        );
+        // Across a month boundary
+        assert_eq!(
+            usage_streak(
+                &dates(&["2025-03-01", "2025-02-28", "2025-02-27"]),
+                NaiveDate::from_ymd_opt(2025, 3, 1).unwrap()
+            ),
+            3
+        );
+    }
+
+    #[test]
+    fn test_compute_typing_cadence() {
+        let minute = |m: u32| Utc.with_ymd_and_hms(2025, 1, 15, 10, m, 0).unwrap();
+        let sample = |m, keystrokes| TypingSample {
+            minute: minute(m),
+            keystrokes,
+        };
+
+        let empty = compute_typing_cadence(&[]);
+        assert_eq!(empty.active_minutes, 0);
+        assert_eq!(empty.avg_kpm, 0.0);
+        assert_eq!(empty.peak_minute, None);
+
+        let cadence = compute_typing_cadence(&[
+            sample(0, 40),
+            sample(1, 120),
+            sample(2, 0),
+            sample(5, 120),
+            sample(6, 20),
+        ]);
+        assert_eq!(cadence.total_keystrokes, 300);
+        assert_eq!(cadence.active_minutes, 4);
+        assert_eq!(cadence.avg_kpm, 75.0);
+        assert_eq!(cadence.peak_kpm, 120);
+        assert_eq!(cadence.peak_minute, Some(minute(1)));
+    }
 }
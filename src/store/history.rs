@@ -0,0 +1,82 @@
+//! Bounded history of recent broadcast messages.
+//!
+//! Lets a reconnecting WebSocket client backfill the updates it missed.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+
+/// Number of broadcast messages kept for replay.
+pub const REPLAY_CAPACITY: usize = 500;
+
+/// Longest window a client can ask to replay (seconds).
+pub const MAX_REPLAY_SECS: i64 = 3600;
+
+/// Ring buffer of timestamped messages, oldest first.
+#[derive(Debug)]
+pub struct EventHistory {
+    entries: VecDeque<(DateTime<Utc>, String)>,
+    capacity: usize,
+}
+
+impl EventHistory {
+    /// Creates an empty history holding at most `capacity` messages.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Records a message, dropping the oldest one when full.
+    pub fn push(&mut self, at: DateTime<Utc>, message: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((at, message));
+    }
+
+    /// Returns messages recorded in the last `since_secs` seconds, oldest first.
+    pub fn since(&self, now: DateTime<Utc>, since_secs: i64) -> Vec<String> {
+        let cutoff = now - Duration::seconds(since_secs.clamp(0, MAX_REPLAY_SECS));
+        self.entries
+            .iter()
+            .filter(|(at, _)| *at >= cutoff)
+            .map(|(_, message)| message.clone())
+            .collect()
+    }
+
+    /// Number of messages currently held.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no messages are held.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_returns_recent_messages_in_order() {
+        let now = Utc::now();
+        let mut history = EventHistory::new(3);
+
+        history.push(now - Duration::seconds(600), "stale".to_string());
+        history.push(now - Duration::seconds(120), "a".to_string());
+        history.push(now - Duration::seconds(60), "b".to_string());
+        history.push(now - Duration::seconds(1), "c".to_string());
+
+        // Capacity evicts the oldest entry
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.since(now, 300), vec!["a", "b", "c"]);
+        assert_eq!(history.since(now, 90), vec!["b", "c"]);
+        assert!(history.since(now, 0).is_empty());
+    }
+}
@@ -5,25 +5,35 @@
 
 pub mod activity_store;
 pub mod aggregator;
+pub mod history;
+pub mod timezone;
 pub mod types;
 
 pub use activity_store::*;
 pub use aggregator::*;
+pub use history::*;
+pub use timezone::*;
 pub use types::*;
 
 use crate::crypto::{hash_and_sign_session, KeyManager};
-use crate::database::Database;
+use crate::database::{DailyIntegrityRecord, Database, SessionIntegrity};
+use chrono::{DateTime, NaiveDate, Utc};
+use ed25519_dalek::{SigningKey, VerifyingKey};
 use once_cell::sync::Lazy;
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard};
 
 /// Global thread-safe activity store.
 pub static ACTIVITY_STORE: Lazy<Arc<RwLock<ActivityStore>>> =
     Lazy::new(|| Arc::new(RwLock::new(ActivityStore::new())));
 
 /// Global database connection (initialized on first use).
-pub static DATABASE: Lazy<Option<Arc<Mutex<Database>>>> = Lazy::new(|| match Database::open() {
+///
+/// Tests get a fresh in-memory database instead of the user's.
+pub static DATABASE: Lazy<Option<Arc<Mutex<Database>>>> = Lazy::new(|| match open_database() {
     Ok(db) => {
         tracing::info!("Database initialized successfully");
+        set_timezone_policy(db.timezone_policy());
         Some(Arc::new(Mutex::new(db)))
     }
     Err(e) => {
@@ -35,6 +45,72 @@ pub static DATABASE: Lazy<Option<Arc<Mutex<Database>>>> = Lazy::new(|| match Dat
     }
 });
 
+#[cfg(not(test))]
+fn open_database() -> rusqlite::Result<Database> {
+    Database::open()
+}
+
+#[cfg(test)]
+fn open_database() -> rusqlite::Result<Database> {
+    Database::open_in_memory()
+}
+
+/// Set once a poisoned database lock has been logged.
+static DB_POISON_LOGGED: AtomicBool = AtomicBool::new(false);
+
+/// Runs `f` with the database, or returns `None` if there is none.
+///
+/// A lock poisoned by a panic while it was held is recovered (and the
+/// poison cleared) instead of being treated as unavailable, so one failing
+/// handler doesn't stop persistence for the rest of the run. The first
+/// recovery is logged.
+pub fn with_db<F, R>(f: F) -> Option<R>
+where
+    F: FnOnce(&Database) -> R,
+{
+    let db = lock_db()?;
+    Some(f(&db))
+}
+
+/// Locks the database the same way as [`with_db`], for callers that need
+/// the guard itself. Returns `None` if there is no database.
+pub fn lock_db() -> Option<MutexGuard<'static, Database>> {
+    DATABASE.as_ref().map(|db| lock_recovering(db))
+}
+
+/// Locks the database, recovering from a poisoned lock.
+fn lock_recovering(db: &Mutex<Database>) -> MutexGuard<'_, Database> {
+    db.lock().unwrap_or_else(|poisoned| {
+        if !DB_POISON_LOGGED.swap(true, Ordering::Relaxed) {
+            tracing::warn!("Database lock was poisoned by a panic, recovering");
+        }
+        db.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+/// Set once a poisoned activity store lock has been logged.
+static STORE_POISON_LOGGED: AtomicBool = AtomicBool::new(false);
+
+/// Locks `store` for reading, or returns `None` if a panic poisoned it.
+///
+/// Read-only handlers use this to serve empty or zeroed activity instead of
+/// panicking, the same way they degrade without a database. The first
+/// poisoned read is logged.
+pub fn read_store(store: &RwLock<ActivityStore>) -> Option<RwLockReadGuard<'_, ActivityStore>> {
+    match store.read() {
+        Ok(guard) => Some(guard),
+        Err(_) => {
+            if !STORE_POISON_LOGGED.swap(true, Ordering::Relaxed) {
+                tracing::warn!(
+                    "Activity store lock was poisoned by a panic, serving empty activity"
+                );
+            }
+            None
+        }
+    }
+}
+
 /// Global key manager for integrity signing (initialized on first use).
 pub static KEY_MANAGER: Lazy<Option<KeyManager>> = Lazy::new(|| match KeyManager::init() {
     Ok(km) => Some(km),
@@ -47,19 +123,84 @@ pub static KEY_MANAGER: Lazy<Option<KeyManager>> = Lazy::new(|| match KeyManager
     }
 });
 
+/// When this process started monitoring (forced early in `main`).
+pub static STARTED_AT: Lazy<DateTime<Utc>> = Lazy::new(Utc::now);
+
+/// Set while tracking is suspended (e.g., a privacy app is focused).
+///
+/// While set, no session is active and input counts are discarded.
+pub static TRACKING_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if tracking is currently paused.
+pub fn is_tracking_paused() -> bool {
+    TRACKING_PAUSED.load(Ordering::SeqCst)
+}
+
+/// Set once the keyboard and mouse hooks are installed.
+///
+/// When hook installation fails, OwnMon keeps tracking windows and media
+/// without counting input, and this stays unset.
+pub static INPUT_HOOKS_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if keystrokes, clicks and scrolls are being counted.
+pub fn input_hooks_active() -> bool {
+    INPUT_HOOKS_ACTIVE.load(Ordering::SeqCst)
+}
+
+/// Set while the user has paused monitoring (tray menu or API).
+///
+/// Unlike [`TRACKING_PAUSED`], this stays set until explicitly resumed.
+pub static MONITORING_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if the user has paused monitoring.
+pub fn is_monitoring_paused() -> bool {
+    MONITORING_PAUSED.load(Ordering::SeqCst)
+}
+
+/// Pauses or resumes monitoring.
+///
+/// Pausing ends the current session so the paused gap is not counted.
+/// Returns `false` if monitoring was already in the requested state.
+pub fn set_monitoring_paused(paused: bool) -> bool {
+    if MONITORING_PAUSED.swap(paused, Ordering::SeqCst) == paused {
+        return false;
+    }
+    if paused {
+        if let Ok(mut store) = ACTIVITY_STORE.write() {
+            store.end_current_session();
+        }
+        tracing::info!("Monitoring paused by user");
+    } else {
+        tracing::info!("Monitoring resumed by user");
+    }
+    broadcast_update(
+        "monitoring_paused",
+        &serde_json::json!({ "paused": paused }),
+    );
+    true
+}
+
 /// Global WebSocket broadcast sender (set by HTTP server).
 pub static BROADCAST_TX: once_cell::sync::OnceCell<tokio::sync::broadcast::Sender<String>> =
     once_cell::sync::OnceCell::new();
 
+/// Recent broadcast messages, replayed to WebSocket clients on request.
+pub static BROADCAST_HISTORY: Lazy<Mutex<EventHistory>> =
+    Lazy::new(|| Mutex::new(EventHistory::new(REPLAY_CAPACITY)));
+
 /// Sends an update to all connected WebSocket clients.
 pub fn broadcast_update(update_type: &str, data: &impl serde::Serialize) {
     if let Some(tx) = BROADCAST_TX.get() {
+        let now = chrono::Utc::now();
         let message = serde_json::json!({
             "type": update_type,
             "data": data,
-            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "timestamp": now.to_rfc3339(),
         });
         if let Ok(json) = serde_json::to_string(&message) {
+            if let Ok(mut history) = BROADCAST_HISTORY.lock() {
+                history.push(now, json.clone());
+            }
             let _ = tx.send(json);
         }
     }
@@ -70,20 +211,21 @@ pub fn broadcast_update(update_type: &str, data: &impl serde::Serialize) {
 /// Call this periodically (e.g., every few seconds) and on shutdown.
 /// This is crash-safe: each session is saved as soon as it completes.
 pub fn save_pending_to_db() {
-    let Some(db_arc) = DATABASE.as_ref() else {
-        return;
-    };
-
-    let Ok(db) = db_arc.lock() else {
-        return;
-    };
+    with_db(save_pending_sessions);
+}
 
+/// Drains the store's pending sessions and media into `db`.
+fn save_pending_sessions(db: &Database) {
     // Drain pending items from store
-    let (sessions, media) = {
+    let (sessions, media, typing) = {
         let Ok(mut store) = ACTIVITY_STORE.write() else {
             return;
         };
-        (store.drain_pending_sessions(), store.drain_pending_media())
+        (
+            store.drain_pending_sessions(),
+            store.drain_pending_media(),
+            store.drain_typing_samples(Utc::now()),
+        )
     };
 
     // Get key manager for signing
@@ -114,19 +256,12 @@ pub fn save_pending_to_db() {
                 (None, None, None)
             };
 
-            if let Err(e) = db.save_session(
-                &session.process_name,
-                &session.window_title,
-                session.start_time,
-                end_time,
-                session.keystrokes,
-                session.mouse_clicks,
-                session.mouse_scrolls,
-                session.is_idle,
-                hash.as_deref(),
-                signature.as_deref(),
-                used_prev_hash.as_deref(),
-            ) {
+            let integrity = SessionIntegrity {
+                hash: hash.as_deref(),
+                signature: signature.as_deref(),
+                prev_hash: used_prev_hash.as_deref(),
+            };
+            if let Err(e) = db.save_session(&session, end_time, integrity) {
                 tracing::warn!(?e, "Failed to save session to database");
             } else {
                 // Update prev_hash for next session in chain
@@ -145,13 +280,23 @@ pub fn save_pending_to_db() {
                 &m.media_info.source_app_id,
                 m.start_time,
                 end_time,
+                m.track_duration_secs,
             ) {
                 tracing::warn!(?e, "Failed to save media session to database");
             }
         }
     }
+
+    if !typing.is_empty() {
+        if let Err(e) = db.save_typing_samples(&typing) {
+            tracing::warn!(?e, "Failed to save typing samples to database");
+        }
+    }
 }
 
+/// Number of top apps kept in the end-of-run summary.
+pub const RUN_SUMMARY_TOP_APPS: usize = 5;
+
 /// Finalizes and saves all current activity before shutdown.
 pub fn finalize_and_save() {
     // Finalize current sessions
@@ -162,8 +307,21 @@ pub fn finalize_and_save() {
     // Save all pending
     save_pending_to_db();
 
+    // Persist the end-of-run recap so it survives a restart
+    if let Ok(store) = ACTIVITY_STORE.read() {
+        let summary = store.get_run_summary(RUN_SUMMARY_TOP_APPS);
+        if let Some(Err(e)) = with_db(|db| db.save_run_summary(&summary)) {
+            tracing::warn!(?e, "Failed to save run summary to database");
+        }
+    }
+
+    // Everything is saved, so there is nothing to recover on next start
+    if let Some(Err(e)) = with_db(|db| db.clear_session_checkpoint()) {
+        tracing::warn!(?e, "Failed to clear session checkpoint");
+    }
+
     // Compute daily integrity for today
-    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let today = local_date_string();
     if let Err(e) = compute_daily_integrity(&today) {
         tracing::warn!(error = %e, "Failed to compute daily integrity on shutdown");
     }
@@ -171,45 +329,239 @@ pub fn finalize_and_save() {
     tracing::info!("All pending data saved to database");
 }
 
-/// Computes and saves daily integrity (Merkle root) for a given date.
-/// Call this at end of day or on startup for previous incomplete days.
-pub fn compute_daily_integrity(date: &str) -> Result<(), String> {
-    use crate::crypto::{build_merkle_root, sign_hash};
+/// Saves the current session's running counts, so a crash loses at most
+/// one checkpoint interval of input counts.
+pub fn checkpoint_current_session() {
+    let Some(session) = ACTIVITY_STORE
+        .read()
+        .ok()
+        .and_then(|store| store.current_session.clone())
+    else {
+        return;
+    };
 
-    let Some(db_arc) = DATABASE.as_ref() else {
-        return Err("Database not initialized".to_string());
+    if let Some(Err(e)) = with_db(|db| db.save_session_checkpoint(&session, chrono::Utc::now())) {
+        tracing::warn!(?e, "Failed to save session checkpoint");
+    }
+}
+
+/// Checkpoints the write-ahead log and truncates the `-wal` file, so it
+/// doesn't keep growing under light write load.
+pub fn checkpoint_wal() {
+    with_db(|db| {
+        let before = db.wal_size_bytes();
+        match db.checkpoint_wal() {
+            Ok(true) => tracing::debug!(wal_bytes = before, "WAL checkpointed"),
+            Ok(false) => tracing::debug!(
+                wal_bytes = before,
+                "WAL checkpoint incomplete, database busy"
+            ),
+            Err(e) => tracing::warn!(error = %e, "WAL checkpoint failed"),
+        }
+    });
+}
+
+/// Applies the retention policy: trims in-memory history to `max_sessions`
+/// and deletes database rows older than `retention_days` (0 = keep forever).
+///
+/// With `vacuum_after_prune` on, the file is then vacuumed if anything was
+/// deleted. The vacuum holds the database for its whole run, so it is
+/// opt-in and taken as a separate lock after the deletes.
+pub fn prune_old_data() {
+    let Some((max_sessions, retention_days, vacuum)) = with_db(|db| {
+        let get = |key: &str| -> Option<i64> {
+            db.get_config(key)
+                .ok()
+                .flatten()
+                .and_then(|v| v.parse().ok())
+        };
+        (
+            get("max_sessions").unwrap_or(1000).max(0) as usize,
+            get("retention_days").unwrap_or(0),
+            db.get_config("vacuum_after_prune")
+                .ok()
+                .flatten()
+                .as_deref()
+                == Some("true"),
+        )
+    }) else {
+        return;
+    };
+
+    // The store lock is taken without holding the database lock
+    if let Ok(mut store) = ACTIVITY_STORE.write() {
+        store.prune_old_sessions(max_sessions);
+    }
+
+    if retention_days <= 0 {
+        return;
+    }
+    let Some((sessions, media)) = with_db(|db| {
+        let sessions = db
+            .prune_sessions_older_than(retention_days)
+            .unwrap_or_else(|e| {
+                tracing::warn!(?e, "Failed to prune old sessions");
+                0
+            });
+        let media = db
+            .prune_media_older_than(retention_days)
+            .unwrap_or_else(|e| {
+                tracing::warn!(?e, "Failed to prune old media");
+                0
+            });
+        if let Err(e) = db.prune_typing_samples_older_than(retention_days) {
+            tracing::warn!(?e, "Failed to prune old typing samples");
+        }
+        (sessions, media)
+    }) else {
+        return;
     };
-    let db = db_arc.lock().map_err(|e| e.to_string())?;
 
+    if sessions + media > 0 {
+        tracing::info!(sessions, media, retention_days, "Pruned old activity");
+        if vacuum {
+            if let Some(Err(e)) = with_db(Database::vacuum) {
+                tracing::warn!(?e, "Failed to vacuum database");
+            }
+        }
+    }
+}
+
+/// Restores the session that was running when the app last crashed.
+/// Call this on application startup, before tracking begins.
+pub fn recover_session_checkpoint() {
+    let recovered = with_db(|db| {
+        let Ok(mut store) = ACTIVITY_STORE.write() else {
+            return false;
+        };
+        recover_checkpoint_into(db, &mut store)
+    });
+
+    if recovered == Some(true) {
+        save_pending_to_db();
+    }
+}
+
+/// Moves a saved checkpoint into the store's pending sessions.
+///
+/// Returns false if there was no checkpoint or the session was already saved.
+fn recover_checkpoint_into(db: &Database, store: &mut ActivityStore) -> bool {
+    let session = match db.take_session_checkpoint() {
+        Ok(Some(session)) => session,
+        Ok(None) => return false,
+        Err(e) => {
+            tracing::warn!(?e, "Failed to read session checkpoint");
+            return false;
+        }
+    };
+
+    if db
+        .session_exists(&session.process_name, session.start_time)
+        .unwrap_or(false)
+    {
+        tracing::debug!("Checkpointed session was already saved");
+        return false;
+    }
+
+    tracing::info!(
+        process = %session.process_name,
+        keystrokes = session.keystrokes,
+        "Recovered interrupted session from checkpoint"
+    );
+    store.restore_session(session);
+    true
+}
+
+/// Serializes daily integrity computation so an on-demand request cannot
+/// interleave with the shutdown/startup sealing of the same day.
+static INTEGRITY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Errors that can occur while computing daily integrity.
+#[derive(Debug)]
+pub enum IntegrityError {
+    DatabaseUnavailable,
+    KeyManagerUnavailable,
+    Database(String),
+    MerkleRoot,
+}
+
+impl std::fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityError::DatabaseUnavailable => write!(f, "Database not initialized"),
+            IntegrityError::KeyManagerUnavailable => write!(f, "Key manager not initialized"),
+            IntegrityError::Database(e) => write!(f, "Database error: {}", e),
+            IntegrityError::MerkleRoot => write!(f, "Failed to build Merkle root"),
+        }
+    }
+}
+
+impl std::error::Error for IntegrityError {}
+
+impl From<rusqlite::Error> for IntegrityError {
+    fn from(e: rusqlite::Error) -> Self {
+        IntegrityError::Database(e.to_string())
+    }
+}
+
+/// Computes and saves daily integrity (Merkle root) for a given date.
+/// Call this at end of day or on startup for previous incomplete days.
+///
+/// Returns the saved record, or `None` if the date has no signed sessions.
+pub fn compute_daily_integrity(date: &str) -> Result<Option<DailyIntegrityRecord>, IntegrityError> {
     let Some(km) = KEY_MANAGER.as_ref() else {
-        return Err("Key manager not initialized".to_string());
+        return Err(IntegrityError::KeyManagerUnavailable);
     };
 
+    let _guard = INTEGRITY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    with_db(|db| {
+        let bind_key = db
+            .get_config("integrity_key_fingerprint")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(true);
+
+        compute_daily_integrity_with(db, km.signing_key(), date, bind_key)
+    })
+    .unwrap_or(Err(IntegrityError::DatabaseUnavailable))
+}
+
+/// Computes, signs and saves the daily integrity record using the given database and key.
+///
+/// With `bind_key`, the signing key's fingerprint is stored with the record
+/// and included in the signed payload.
+pub(crate) fn compute_daily_integrity_with(
+    db: &Database,
+    signing_key: &SigningKey,
+    date: &str,
+    bind_key: bool,
+) -> Result<Option<DailyIntegrityRecord>, IntegrityError> {
+    use crate::crypto::{build_merkle_root, daily_sign_data, key_fingerprint, sign_hash};
+
     // Get all session hashes for the date
-    let hashes = db
-        .get_session_hashes_for_date(date)
-        .map_err(|e| e.to_string())?;
+    let hashes = db.get_session_hashes_for_date(date)?;
 
     if hashes.is_empty() {
         tracing::debug!(date, "No sessions to compute integrity for");
-        return Ok(());
+        return Ok(None);
     }
 
     // Build Merkle root
-    let merkle_root =
-        build_merkle_root(&hashes).ok_or_else(|| "Failed to build Merkle root".to_string())?;
+    let merkle_root = build_merkle_root(&hashes).ok_or(IntegrityError::MerkleRoot)?;
 
     // Get previous day's root for chaining
-    let prev_day_root = db.get_previous_day_root(date).map_err(|e| e.to_string())?;
-
-    // Create data to sign: merkle_root + prev_day_root + date
-    let sign_data = format!(
-        "{}|{}|{}",
-        merkle_root,
-        prev_day_root.as_deref().unwrap_or("genesis"),
-        date
+    let prev_day_root = db.get_previous_day_root(date)?;
+
+    // Create data to sign: merkle_root + prev_day_root + date (+ key fingerprint)
+    let fingerprint = bind_key.then(|| key_fingerprint(&signing_key.verifying_key()));
+    let sign_data = daily_sign_data(
+        &merkle_root,
+        prev_day_root.as_deref(),
+        date,
+        fingerprint.as_deref(),
     );
-    let signature = sign_hash(&sign_data, km.signing_key());
+    let signature = sign_hash(&sign_data, signing_key);
 
     // Save to database
     db.save_daily_integrity(
@@ -218,8 +570,8 @@ pub fn compute_daily_integrity(date: &str) -> Result<(), String> {
         prev_day_root.as_deref(),
         hashes.len() as u32,
         &signature,
-    )
-    .map_err(|e| e.to_string())?;
+        fingerprint.as_deref(),
+    )?;
 
     tracing::info!(
         date,
@@ -227,28 +579,337 @@ pub fn compute_daily_integrity(date: &str) -> Result<(), String> {
         "Daily integrity computed and saved"
     );
 
-    Ok(())
+    Ok(db.get_daily_integrity(date)?)
 }
 
-/// Checks for and computes daily integrity for any incomplete previous days.
-/// Call this on application startup.
-pub fn check_and_compute_missing_integrity() {
-    let Some(db_arc) = DATABASE.as_ref() else {
-        return;
+/// Outcome of re-sealing a day with [`recompute_daily_integrity`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IntegrityRecompute {
+    pub date: String,
+    /// Root of the replaced record (`None` if the day wasn't sealed).
+    pub old_merkle_root: Option<String>,
+    pub new_merkle_root: String,
+    /// The new record for `date`.
+    pub record: DailyIntegrityRecord,
+    /// Later sealed days re-signed because their `prev_day_root` changed.
+    pub rechained_dates: Vec<String>,
+}
+
+/// Re-seals a day after its sessions changed, then re-chains later days.
+///
+/// Returns `None` if the date has no signed sessions.
+pub fn recompute_daily_integrity(date: &str) -> Result<Option<IntegrityRecompute>, IntegrityError> {
+    let Some(km) = KEY_MANAGER.as_ref() else {
+        return Err(IntegrityError::KeyManagerUnavailable);
     };
-    let Ok(db) = db_arc.lock() else {
-        return;
+
+    let _guard = INTEGRITY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    with_db(|db| {
+        let bind_key = db
+            .get_config("integrity_key_fingerprint")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(true);
+
+        recompute_daily_integrity_with(db, km.signing_key(), date, bind_key)
+    })
+    .unwrap_or(Err(IntegrityError::DatabaseUnavailable))
+}
+
+/// Re-seals `date` using the given database and key.
+///
+/// Each later sealed day whose `prev_day_root` no longer matches the day
+/// before it is re-signed with the new link, keeping its Merkle root and
+/// sealed range; the walk stops at the first day that still links correctly.
+fn recompute_daily_integrity_with(
+    db: &Database,
+    signing_key: &SigningKey,
+    date: &str,
+    bind_key: bool,
+) -> Result<Option<IntegrityRecompute>, IntegrityError> {
+    use crate::crypto::{daily_sign_data, key_fingerprint, sign_hash};
+
+    let old_merkle_root = db.get_daily_integrity(date)?.map(|r| r.merkle_root);
+    let Some(record) = compute_daily_integrity_with(db, signing_key, date, bind_key)? else {
+        return Ok(None);
+    };
+
+    let fingerprint = bind_key.then(|| key_fingerprint(&signing_key.verifying_key()));
+    let mut prev_root = record.merkle_root.clone();
+    let mut rechained_dates = Vec::new();
+    let later = db
+        .get_all_daily_integrity()?
+        .into_iter()
+        .filter(|r| r.date.as_str() > date);
+    for day in later {
+        if day.prev_day_root.as_deref() == Some(prev_root.as_str()) {
+            break;
+        }
+        let sign_data = daily_sign_data(
+            &day.merkle_root,
+            Some(&prev_root),
+            &day.date,
+            fingerprint.as_deref(),
+        );
+        let signature = sign_hash(&sign_data, signing_key);
+        db.relink_daily_integrity(
+            &day.date,
+            Some(&prev_root),
+            &signature,
+            fingerprint.as_deref(),
+        )?;
+        rechained_dates.push(day.date);
+        prev_root = day.merkle_root;
+    }
+
+    tracing::info!(
+        date,
+        old_root = old_merkle_root.as_deref().unwrap_or("none"),
+        new_root = %record.merkle_root,
+        rechained = rechained_dates.len(),
+        "Daily integrity recomputed"
+    );
+
+    Ok(Some(IntegrityRecompute {
+        date: date.to_string(),
+        old_merkle_root,
+        new_merkle_root: record.merkle_root.clone(),
+        record,
+        rechained_dates,
+    }))
+}
+
+/// Verifies a daily record's signature against a public key.
+///
+/// Records bound to a key fingerprint only verify with that exact key, so a
+/// day signed with a rotated key is reported rather than silently accepted.
+pub fn verify_daily_integrity_record(record: &DailyIntegrityRecord, key: &VerifyingKey) -> bool {
+    use crate::crypto::{daily_sign_data, key_fingerprint, verify_signature};
+
+    if let Some(fp) = &record.key_fingerprint {
+        if *fp != key_fingerprint(key) {
+            return false;
+        }
+    }
+
+    let sign_data = daily_sign_data(
+        &record.merkle_root,
+        record.prev_day_root.as_deref(),
+        &record.date,
+        record.key_fingerprint.as_deref(),
+    );
+    verify_signature(&sign_data, &record.signature, key)
+}
+
+/// A signed session that failed verification.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionVerifyIssue {
+    pub id: i64,
+    pub process_name: String,
+    pub start_time: String,
+    /// The stored hash matches one recomputed from the stored fields.
+    pub hash_valid: bool,
+    /// The signature verifies against the stored hash.
+    pub signature_valid: bool,
+    /// `prev_hash` is the hash of the previous signed session.
+    pub chain_valid: bool,
+}
+
+/// Result of verifying one day's sessions and daily record.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IntegrityReport {
+    pub date: String,
+    pub sessions_checked: usize,
+    pub invalid_sessions: Vec<SessionVerifyIssue>,
+    /// A daily record has been sealed for this date.
+    pub has_daily_record: bool,
+    /// The stored Merkle root matches one rebuilt from the session hashes.
+    pub merkle_root_valid: bool,
+    /// The daily record's signature verifies.
+    pub daily_signature_valid: bool,
+    /// The record's previous-day root matches the preceding daily record.
+    pub prev_day_chain_valid: bool,
+    /// Everything above checked out.
+    pub valid: bool,
+}
+
+/// Verifies a day's sessions and daily record against the current public key.
+pub fn verify_integrity(date: &str) -> Result<IntegrityReport, IntegrityError> {
+    let Some(km) = KEY_MANAGER.as_ref() else {
+        return Err(IntegrityError::KeyManagerUnavailable);
+    };
+
+    let _guard = INTEGRITY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    with_db(|db| verify_integrity_with(db, km.verifying_key(), date))
+        .unwrap_or(Err(IntegrityError::DatabaseUnavailable))
+}
+
+/// Recomputes each session hash, checks signatures and hash chaining, then
+/// checks the daily Merkle root, signature and previous-day link.
+pub fn verify_integrity_with(
+    db: &Database,
+    key: &VerifyingKey,
+    date: &str,
+) -> Result<IntegrityReport, IntegrityError> {
+    use crate::crypto::{build_merkle_root, hash_session_data, verify_signature};
+
+    let sessions = db.get_signed_sessions_for_date(date)?;
+
+    let mut expected_prev = match sessions.first() {
+        Some(first) => db.get_session_hash_before(first.id)?,
+        None => None,
+    };
+
+    let mut invalid_sessions = Vec::new();
+    for session in &sessions {
+        let recomputed = hash_session_data(
+            &session.process_name,
+            session.window_title.as_deref().unwrap_or_default(),
+            &session.start_time,
+            session.end_time.as_deref().unwrap_or_default(),
+            session.keystrokes as u64,
+            session.clicks as u64,
+            session.scrolls as u64,
+            session.prev_hash.as_deref(),
+        );
+        let hash_valid = recomputed == session.hash;
+        let signature_valid = session
+            .signature
+            .as_deref()
+            .is_some_and(|sig| verify_signature(&session.hash, sig, key));
+        let chain_valid = session.prev_hash == expected_prev;
+
+        if !(hash_valid && signature_valid && chain_valid) {
+            invalid_sessions.push(SessionVerifyIssue {
+                id: session.id,
+                process_name: session.process_name.clone(),
+                start_time: session.start_time.clone(),
+                hash_valid,
+                signature_valid,
+                chain_valid,
+            });
+        }
+        expected_prev = Some(session.hash.clone());
+    }
+
+    let record = db.get_daily_integrity(date)?;
+    let (merkle_root_valid, daily_signature_valid, prev_day_chain_valid) = match &record {
+        Some(record) => {
+            let hashes: Vec<String> = sessions.iter().map(|s| s.hash.clone()).collect();
+            let root_valid =
+                build_merkle_root(&hashes).is_some_and(|root| root == record.merkle_root);
+            let prev_day_root = db.get_previous_day_root(date)?;
+            (
+                root_valid,
+                verify_daily_integrity_record(record, key),
+                record.prev_day_root == prev_day_root,
+            )
+        }
+        None => (false, false, false),
     };
 
-    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let valid = invalid_sessions.is_empty()
+        && (sessions.is_empty() && record.is_none()
+            || merkle_root_valid && daily_signature_valid && prev_day_chain_valid);
+
+    Ok(IntegrityReport {
+        date: date.to_string(),
+        sessions_checked: sessions.len(),
+        invalid_sessions,
+        has_daily_record: record.is_some(),
+        merkle_root_valid,
+        daily_signature_valid,
+        prev_day_chain_valid,
+        valid,
+    })
+}
+
+/// Whether a day's sessions have been sealed into a daily record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SealState {
+    Sealed,
+    /// The day has signed sessions but no daily record yet.
+    PendingSeal,
+    NoSessions,
+}
+
+/// Seal status of one day.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DayIntegrityStatus {
+    pub date: String,
+    pub status: SealState,
+    /// Sessions covered by the daily record (sealed days only).
+    pub session_count: Option<u32>,
+    pub merkle_root: Option<String>,
+    /// This is the most recent sealed day.
+    pub latest_sealed: bool,
+}
+
+/// Gets the seal status of every day in `from..=to`, oldest first.
+pub fn integrity_status_with(
+    db: &Database,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> rusqlite::Result<Vec<DayIntegrityStatus>> {
+    let format = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
+
+    let mut records: std::collections::HashMap<String, DailyIntegrityRecord> = db
+        .get_daily_integrity_range(&format(from), &format(to))?
+        .into_iter()
+        .map(|record| (record.date.clone(), record))
+        .collect();
+    let day_after = to.succ_opt().unwrap_or(to);
+    let pending: std::collections::HashSet<String> = db
+        .get_dates_missing_integrity(&format(day_after))?
+        .into_iter()
+        .collect();
+    let latest = db.get_latest_integrity_date()?;
+
+    Ok(from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| {
+            let date = format(date);
+            let latest_sealed = latest.as_deref() == Some(date.as_str());
+            match records.remove(&date) {
+                Some(record) => DayIntegrityStatus {
+                    date,
+                    status: SealState::Sealed,
+                    session_count: Some(record.session_count),
+                    merkle_root: Some(record.merkle_root),
+                    latest_sealed,
+                },
+                None => DayIntegrityStatus {
+                    status: if pending.contains(&date) {
+                        SealState::PendingSeal
+                    } else {
+                        SealState::NoSessions
+                    },
+                    date,
+                    session_count: None,
+                    merkle_root: None,
+                    latest_sealed,
+                },
+            }
+        })
+        .collect())
+}
+
+/// Checks for and computes daily integrity for any incomplete previous days.
+/// Call this on application startup.
+pub fn check_and_compute_missing_integrity() {
+    let today = local_date_string();
 
     // Get dates with sessions but no integrity record
-    let missing_dates = match db.get_dates_missing_integrity(&today) {
-        Ok(dates) => dates,
-        Err(e) => {
+    let missing_dates = match with_db(|db| db.get_dates_missing_integrity(&today)) {
+        Some(Ok(dates)) => dates,
+        Some(Err(e)) => {
             tracing::warn!(error = %e, "Failed to query missing integrity dates");
             return;
         }
+        None => return,
     };
 
     if missing_dates.is_empty() {
@@ -261,9 +922,6 @@ pub fn check_and_compute_missing_integrity() {
         "Found days with missing integrity, computing..."
     );
 
-    // Need to drop db lock before calling compute_daily_integrity (which acquires its own)
-    drop(db);
-
     for date in missing_dates {
         if let Err(e) = compute_daily_integrity(&date) {
             tracing::warn!(date = %date, error = %e, "Failed to compute missing daily integrity");
@@ -275,10 +933,381 @@ pub fn check_and_compute_missing_integrity() {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_poisoned_db_lock_recovered() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let poisoner = Arc::clone(&db);
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("handler panicked while holding the database");
+        })
+        .join();
+        assert!(db.is_poisoned());
+
+        let guard = lock_recovering(&db);
+        assert!(guard.get_config("afk_threshold_secs").unwrap().is_some());
+        drop(guard);
+        assert!(!db.is_poisoned());
+        assert!(db.lock().is_ok());
+    }
+
     #[test]
     fn test_global_store_accessible() {
         // Just verify we can access the global store
         let store = ACTIVITY_STORE.read().unwrap();
         let _ = store.session_count();
     }
+
+    #[test]
+    fn test_compute_daily_integrity_over_seeded_day() {
+        use rand::rngs::OsRng;
+
+        let db = Database::open_in_memory().unwrap();
+        let key = SigningKey::generate(&mut OsRng);
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-15T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        for (i, hash) in ["hash_a", "hash_b", "hash_c"].iter().enumerate() {
+            let mut session = WindowSession::new(0, 0, "code.exe".into(), "main.rs".into());
+            session.start_time = start + chrono::Duration::minutes(i as i64 * 10);
+            let end = session.start_time + chrono::Duration::minutes(5);
+            session.keystrokes = 10;
+
+            let integrity = SessionIntegrity {
+                hash: Some(hash),
+                signature: Some("sig"),
+                prev_hash: None,
+            };
+            db.save_session(&session, end, integrity).unwrap();
+        }
+
+        let record = compute_daily_integrity_with(&db, &key, "2025-01-15", true)
+            .unwrap()
+            .expect("seeded day should produce a record");
+        assert_eq!(record.session_count, 3);
+        assert!(!record.merkle_root.is_empty());
+        assert_eq!(record.prev_day_root, None);
+
+        // A day without sessions has nothing to seal
+        assert!(compute_daily_integrity_with(&db, &key, "2025-01-16", true)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_recompute_integrity_rechains_later_days() {
+        use rand::rngs::OsRng;
+
+        let db = Database::open_in_memory().unwrap();
+        let key = SigningKey::generate(&mut OsRng);
+        let mut ids = Vec::new();
+        for (day, hashes) in [("2025-01-15", ["a1", "a2"]), ("2025-01-16", ["b1", "b2"])] {
+            let start = chrono::DateTime::parse_from_rfc3339(&format!("{}T10:00:00Z", day))
+                .unwrap()
+                .with_timezone(&chrono::Utc);
+            for (i, hash) in hashes.iter().enumerate() {
+                let mut session = WindowSession::new(0, 0, "code.exe".into(), "main.rs".into());
+                session.start_time = start + chrono::Duration::minutes(i as i64 * 10);
+                let end = session.start_time + chrono::Duration::minutes(5);
+                let integrity = SessionIntegrity {
+                    hash: Some(hash),
+                    signature: Some("sig"),
+                    prev_hash: None,
+                };
+                ids.push(db.save_session(&session, end, integrity).unwrap());
+            }
+        }
+        let first = compute_daily_integrity_with(&db, &key, "2025-01-15", true)
+            .unwrap()
+            .unwrap();
+        compute_daily_integrity_with(&db, &key, "2025-01-16", true).unwrap();
+
+        // Nothing changed: same root, later days still link
+        let same = recompute_daily_integrity_with(&db, &key, "2025-01-15", true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            same.old_merkle_root.as_deref(),
+            Some(first.merkle_root.as_str())
+        );
+        assert_eq!(same.new_merkle_root, first.merkle_root);
+        assert!(same.rechained_dates.is_empty());
+
+        db.delete_session(ids[0]).unwrap();
+        let result = recompute_daily_integrity_with(&db, &key, "2025-01-15", true)
+            .unwrap()
+            .unwrap();
+        assert_ne!(result.new_merkle_root, first.merkle_root);
+        assert_eq!(result.record.session_count, 1);
+        assert_eq!(result.rechained_dates, vec!["2025-01-16".to_string()]);
+
+        let next = db.get_daily_integrity("2025-01-16").unwrap().unwrap();
+        assert_eq!(
+            next.prev_day_root.as_deref(),
+            Some(result.new_merkle_root.as_str())
+        );
+        assert!(verify_daily_integrity_record(&next, &key.verifying_key()));
+
+        // A day without signed sessions can't be recomputed
+        assert!(
+            recompute_daily_integrity_with(&db, &key, "2025-01-17", true)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_integrity_status_flags_pending_days() {
+        use rand::rngs::OsRng;
+
+        let db = Database::open_in_memory().unwrap();
+        let key = SigningKey::generate(&mut OsRng);
+        for day in ["2025-01-15", "2025-01-16", "2025-01-18"] {
+            let start = chrono::DateTime::parse_from_rfc3339(&format!("{}T12:00:00Z", day))
+                .unwrap()
+                .with_timezone(&chrono::Utc);
+            let mut session = WindowSession::new(0, 0, "code.exe".into(), "main.rs".into());
+            session.start_time = start;
+            let integrity = SessionIntegrity {
+                hash: Some(day),
+                signature: Some("sig"),
+                prev_hash: None,
+            };
+            db.save_session(&session, start + chrono::Duration::minutes(5), integrity)
+                .unwrap();
+        }
+        compute_daily_integrity_with(&db, &key, "2025-01-15", true).unwrap();
+        let sealed = compute_daily_integrity_with(&db, &key, "2025-01-16", true)
+            .unwrap()
+            .unwrap();
+
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let days = integrity_status_with(&db, day("2025-01-14"), day("2025-01-18")).unwrap();
+        let states: Vec<SealState> = days.iter().map(|d| d.status).collect();
+        assert_eq!(
+            states,
+            vec![
+                SealState::NoSessions,
+                SealState::Sealed,
+                SealState::Sealed,
+                SealState::NoSessions,
+                SealState::PendingSeal,
+            ]
+        );
+        assert_eq!(days[2].session_count, Some(1));
+        assert_eq!(
+            days[2].merkle_root.as_deref(),
+            Some(sealed.merkle_root.as_str())
+        );
+        let latest: Vec<&str> = days
+            .iter()
+            .filter(|d| d.latest_sealed)
+            .map(|d| d.date.as_str())
+            .collect();
+        assert_eq!(latest, vec!["2025-01-16"]);
+    }
+
+    #[test]
+    fn test_verify_integrity_flags_tampered_session() {
+        use crate::crypto::hash_and_sign_session;
+        use rand::rngs::OsRng;
+
+        let db = Database::open_in_memory().unwrap();
+        let key = SigningKey::generate(&mut OsRng);
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-15T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        // Save a chained day the way save_pending_to_db does; the last
+        // session's stored keystrokes differ from what was signed
+        let mut prev_hash: Option<String> = None;
+        for i in 0..3 {
+            let mut session = WindowSession::new(0, 0, "code.exe".into(), "main.rs".into());
+            session.start_time = start + chrono::Duration::minutes(i * 10);
+            session.keystrokes = 10;
+            let end = session.start_time + chrono::Duration::minutes(5);
+            let (hash, signature) = hash_and_sign_session(
+                &key,
+                &session.process_name,
+                &session.window_title,
+                &session.start_time.to_rfc3339(),
+                &end.to_rfc3339(),
+                session.keystrokes,
+                session.mouse_clicks,
+                session.mouse_scrolls,
+                prev_hash.as_deref(),
+            );
+            if i == 2 {
+                session.keystrokes = 9999;
+            }
+            let integrity = SessionIntegrity {
+                hash: Some(&hash),
+                signature: Some(&signature),
+                prev_hash: prev_hash.as_deref(),
+            };
+            db.save_session(&session, end, integrity).unwrap();
+            prev_hash = Some(hash);
+        }
+        compute_daily_integrity_with(&db, &key, "2025-01-15", true).unwrap();
+
+        let report = verify_integrity_with(&db, &key.verifying_key(), "2025-01-15").unwrap();
+        assert_eq!(report.sessions_checked, 3);
+        assert_eq!(report.invalid_sessions.len(), 1);
+        let issue = &report.invalid_sessions[0];
+        assert!(!issue.hash_valid);
+        assert!(issue.signature_valid && issue.chain_valid);
+        assert!(report.merkle_root_valid);
+        assert!(report.daily_signature_valid);
+        assert!(report.prev_day_chain_valid);
+        assert!(!report.valid);
+
+        // A different key fails every signature
+        let other = SigningKey::generate(&mut OsRng).verifying_key();
+        let report = verify_integrity_with(&db, &other, "2025-01-15").unwrap();
+        assert_eq!(report.invalid_sessions.len(), 3);
+        assert!(!report.daily_signature_valid);
+
+        // An empty, unsealed day is trivially valid
+        let report = verify_integrity_with(&db, &key.verifying_key(), "2025-01-16").unwrap();
+        assert!(report.valid && !report.has_daily_record);
+    }
+
+    #[test]
+    fn test_daily_record_carries_key_fingerprint() {
+        use crate::crypto::key_fingerprint;
+        use rand::rngs::OsRng;
+
+        let db = Database::open_in_memory().unwrap();
+        let key = SigningKey::generate(&mut OsRng);
+        let other_key = SigningKey::generate(&mut OsRng);
+
+        let mut session = WindowSession::new(0, 0, "code.exe".into(), "main.rs".into());
+        session.start_time = chrono::DateTime::parse_from_rfc3339("2025-01-15T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let end = session.start_time + chrono::Duration::minutes(5);
+        let integrity = SessionIntegrity {
+            hash: Some("hash_a"),
+            signature: Some("sig"),
+            prev_hash: None,
+        };
+        db.save_session(&session, end, integrity).unwrap();
+
+        let record = compute_daily_integrity_with(&db, &key, "2025-01-15", true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            record.key_fingerprint,
+            Some(key_fingerprint(&key.verifying_key()))
+        );
+        assert!(verify_daily_integrity_record(&record, &key.verifying_key()));
+        assert!(!verify_daily_integrity_record(
+            &record,
+            &other_key.verifying_key()
+        ));
+
+        // Tampering with the stored fingerprint breaks the signature
+        let mut tampered = record.clone();
+        tampered.key_fingerprint = Some(key_fingerprint(&other_key.verifying_key()));
+        assert!(!verify_daily_integrity_record(
+            &tampered,
+            &other_key.verifying_key()
+        ));
+
+        // Unbound (legacy) records still verify with the original payload
+        let legacy = compute_daily_integrity_with(&db, &key, "2025-01-15", false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(legacy.key_fingerprint, None);
+        assert!(verify_daily_integrity_record(&legacy, &key.verifying_key()));
+    }
+
+    #[test]
+    fn test_recover_long_session_partial_counts() {
+        let db = Database::open_in_memory().unwrap();
+        let now = chrono::Utc::now();
+
+        // A two-hour session that was checkpointed a minute before a crash
+        let mut session = WindowSession::new(1, 100, "code.exe".into(), "main.rs".into());
+        session.start_time = now - chrono::Duration::hours(2);
+        session.keystrokes = 5000;
+        session.mouse_clicks = 300;
+        session.shortcuts = 42;
+        session.scroll_down = 17;
+        let checkpoint_time = now - chrono::Duration::minutes(1);
+        db.save_session_checkpoint(&session, checkpoint_time)
+            .unwrap();
+
+        let mut store = ActivityStore::new();
+        assert!(recover_checkpoint_into(&db, &mut store));
+
+        let restored = store.completed_sessions.last().unwrap();
+        assert_eq!(restored.process_name, "code.exe");
+        assert_eq!(restored.start_time, session.start_time);
+        assert_eq!(restored.end_time, Some(checkpoint_time));
+        assert_eq!(restored.keystrokes, 5000);
+        assert_eq!(restored.mouse_clicks, 300);
+        assert_eq!(restored.shortcuts, 42);
+        assert_eq!(restored.scroll_down, 17);
+        assert!(store.has_pending_saves());
+
+        // The checkpoint is consumed
+        assert!(!recover_checkpoint_into(&db, &mut store));
+
+        // A session that was saved before the crash isn't restored twice
+        db.save_session_checkpoint(&session, checkpoint_time)
+            .unwrap();
+        db.save_session(&session, checkpoint_time, SessionIntegrity::default())
+            .unwrap();
+        assert!(!recover_checkpoint_into(&db, &mut store));
+    }
+
+    #[test]
+    fn test_pruned_chain_still_verifies() {
+        use crate::crypto::hash_and_sign_session;
+        use rand::rngs::OsRng;
+
+        let db = Database::open_in_memory().unwrap();
+        let key = SigningKey::generate(&mut OsRng);
+        let now = chrono::Utc::now();
+
+        // One old session and two recent ones, chained the way
+        // save_pending_to_db does
+        let starts = [
+            now - chrono::Duration::days(40),
+            now - chrono::Duration::minutes(30),
+            now - chrono::Duration::minutes(20),
+        ];
+        for start in starts {
+            let mut session = WindowSession::new(0, 0, "code.exe".into(), "main.rs".into());
+            session.start_time = start;
+            let end = start + chrono::Duration::minutes(5);
+            let prev_hash = db.get_last_session_hash().unwrap();
+            let (hash, signature) = hash_and_sign_session(
+                &key,
+                &session.process_name,
+                &session.window_title,
+                &start.to_rfc3339(),
+                &end.to_rfc3339(),
+                0,
+                0,
+                0,
+                prev_hash.as_deref(),
+            );
+            let integrity = SessionIntegrity {
+                hash: Some(&hash),
+                signature: Some(&signature),
+                prev_hash: prev_hash.as_deref(),
+            };
+            db.save_session(&session, end, integrity).unwrap();
+        }
+        let today = date_string_of(starts[1]);
+        compute_daily_integrity_with(&db, &key, &today, true).unwrap();
+
+        assert_eq!(db.prune_sessions_older_than(30).unwrap(), 1);
+        let report = verify_integrity_with(&db, &key.verifying_key(), &today).unwrap();
+        assert!(report.invalid_sessions.is_empty());
+        assert!(report.valid);
+    }
 }
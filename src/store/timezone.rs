@@ -0,0 +1,223 @@
+//! Timezone policy for calendar dates.
+//!
+//! Timestamps are stored in UTC. Whenever a date is derived from one (today,
+//! a day's stats, the day a session is sealed under), it goes through the
+//! `timezone` config: `local` (the system time zone, default), `utc`, or a
+//! fixed offset such as `+07:00`.
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use once_cell::sync::Lazy;
+use std::sync::RwLock;
+
+/// How calendar dates are derived from UTC timestamps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimezonePolicy {
+    /// The system time zone, including daylight saving changes.
+    #[default]
+    Local,
+    Utc,
+    /// A fixed offset from UTC.
+    Fixed(FixedOffset),
+}
+
+impl TimezonePolicy {
+    /// Parses a `timezone` value: `local`, `utc` or `+HH:MM`/`-HH:MM`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        match value.to_lowercase().as_str() {
+            "local" => Some(Self::Local),
+            "utc" => Some(Self::Utc),
+            _ => parse_offset(value).map(Self::Fixed),
+        }
+    }
+
+    /// Reads a policy from a config value, falling back to `local`.
+    pub fn from_config(value: Option<&str>) -> Self {
+        match value.filter(|v| !v.trim().is_empty()) {
+            Some(v) => Self::parse(v).unwrap_or_else(|| {
+                tracing::warn!(value = v, "Invalid timezone, using local time");
+                Self::Local
+            }),
+            None => Self::Local,
+        }
+    }
+
+    /// The value stored in the `timezone` config for this policy.
+    pub fn name(&self) -> String {
+        match self {
+            Self::Local => "local".to_string(),
+            Self::Utc => "utc".to_string(),
+            Self::Fixed(offset) => offset.to_string(),
+        }
+    }
+
+    /// Offset from UTC in seconds at the given time.
+    pub fn offset_secs(&self, time: DateTime<Utc>) -> i32 {
+        match self {
+            Self::Local => time
+                .with_timezone(&chrono::Local)
+                .offset()
+                .local_minus_utc(),
+            Self::Utc => 0,
+            Self::Fixed(offset) => offset.local_minus_utc(),
+        }
+    }
+
+    /// The wall-clock date and time of a timestamp.
+    pub fn local_time(&self, time: DateTime<Utc>) -> NaiveDateTime {
+        time.naive_utc() + chrono::Duration::seconds(self.offset_secs(time) as i64)
+    }
+
+    /// The calendar date of a timestamp.
+    pub fn date_of(&self, time: DateTime<Utc>) -> NaiveDate {
+        self.local_time(time).date()
+    }
+
+    /// When a date starts, in UTC.
+    ///
+    /// If local midnight is skipped by a daylight saving change, the day
+    /// starts at the first valid local time after it.
+    pub fn day_start(&self, date: NaiveDate) -> DateTime<Utc> {
+        let midnight = date.and_time(NaiveTime::MIN);
+        match self {
+            Self::Local => (0..4)
+                .find_map(|hour| {
+                    chrono::Local
+                        .from_local_datetime(&(midnight + chrono::Duration::hours(hour)))
+                        .earliest()
+                })
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_else(|| midnight.and_utc()),
+            Self::Utc => midnight.and_utc(),
+            Self::Fixed(offset) => {
+                (midnight - chrono::Duration::seconds(offset.local_minus_utc() as i64)).and_utc()
+            }
+        }
+    }
+
+    /// The UTC range `[start, end)` covered by a date.
+    pub fn day_range(&self, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let next = date.succ_opt().unwrap_or(date);
+        (self.day_start(date), self.day_start(next))
+    }
+
+    /// SQLite date modifier that converts a stored UTC time to this policy,
+    /// e.g. `date(start_time, ?)`.
+    pub fn sql_modifier(&self) -> String {
+        match self {
+            Self::Local => "localtime".to_string(),
+            Self::Utc => "+0 minutes".to_string(),
+            Self::Fixed(offset) => format!("{:+} minutes", offset.local_minus_utc() / 60),
+        }
+    }
+}
+
+/// Parses `+HH:MM`, `-HH:MM` or `+HH`.
+fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let sign = match value.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let (hours, minutes) = value[1..].split_once(':').unwrap_or((&value[1..], "0"));
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// The policy in effect, loaded from config when the database opens.
+static TIMEZONE_POLICY: Lazy<RwLock<TimezonePolicy>> =
+    Lazy::new(|| RwLock::new(TimezonePolicy::Local));
+
+/// Returns the timezone policy in effect.
+pub fn timezone_policy() -> TimezonePolicy {
+    TIMEZONE_POLICY
+        .read()
+        .map(|policy| *policy)
+        .unwrap_or_default()
+}
+
+/// Replaces the timezone policy (on startup and when `timezone` changes).
+pub fn set_timezone_policy(policy: TimezonePolicy) {
+    if let Ok(mut current) = TIMEZONE_POLICY.write() {
+        *current = policy;
+    }
+}
+
+/// Today's date (`YYYY-MM-DD`) under the timezone policy.
+pub fn local_date_string() -> String {
+    date_string_of(Utc::now())
+}
+
+/// The date (`YYYY-MM-DD`) of a timestamp under the timezone policy.
+pub fn date_string_of(time: DateTime<Utc>) -> String {
+    timezone_policy()
+        .date_of(time)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!(TimezonePolicy::parse("local"), Some(TimezonePolicy::Local));
+        assert_eq!(TimezonePolicy::parse(" UTC "), Some(TimezonePolicy::Utc));
+        assert_eq!(
+            TimezonePolicy::parse("+07:00"),
+            Some(TimezonePolicy::Fixed(
+                FixedOffset::east_opt(7 * 3600).unwrap()
+            ))
+        );
+        assert_eq!(
+            TimezonePolicy::parse("-05:30"),
+            Some(TimezonePolicy::Fixed(
+                FixedOffset::west_opt(5 * 3600 + 1800).unwrap()
+            ))
+        );
+        assert_eq!(
+            TimezonePolicy::parse("+9"),
+            Some(TimezonePolicy::Fixed(
+                FixedOffset::east_opt(9 * 3600).unwrap()
+            ))
+        );
+        assert_eq!(TimezonePolicy::parse("Asia/Tokyo"), None);
+        assert_eq!(TimezonePolicy::parse("+15:00"), None);
+        assert_eq!(TimezonePolicy::from_config(None), TimezonePolicy::Local);
+        assert_eq!(
+            TimezonePolicy::from_config(Some("bogus")),
+            TimezonePolicy::Local
+        );
+    }
+
+    #[test]
+    fn test_fixed_offset_dates_and_ranges() {
+        let policy = TimezonePolicy::parse("+07:00").unwrap();
+        // 18:30 UTC is already the next day at +07:00
+        let time = Utc.with_ymd_and_hms(2025, 1, 15, 18, 30, 0).unwrap();
+        assert_eq!(
+            policy.date_of(time),
+            NaiveDate::from_ymd_opt(2025, 1, 16).unwrap()
+        );
+        assert_eq!(
+            TimezonePolicy::Utc.date_of(time),
+            NaiveDate::from_ymd_opt(2025, 1, 15).unwrap()
+        );
+
+        let (start, end) = policy.day_range(NaiveDate::from_ymd_opt(2025, 1, 16).unwrap());
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 1, 15, 17, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 1, 16, 17, 0, 0).unwrap());
+
+        assert_eq!(policy.sql_modifier(), "+420 minutes");
+        assert_eq!(
+            TimezonePolicy::parse("-05:30").unwrap().sql_modifier(),
+            "-330 minutes"
+        );
+        assert_eq!(TimezonePolicy::Local.sql_modifier(), "localtime");
+    }
+}
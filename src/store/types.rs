@@ -22,9 +22,21 @@ pub struct WindowSession {
     /// Name of the executable (e.g., "chrome.exe").
     pub process_name: String,
 
+    /// Full path of the executable, if the process could be opened.
+    #[serde(default)]
+    pub process_path: Option<String>,
+
     /// Title of the window at session start.
     pub window_title: String,
 
+    /// Address bar URL at session start, for recognized browsers.
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Device name of the monitor showing the window (e.g., `\\.\DISPLAY1`).
+    #[serde(default)]
+    pub monitor: Option<String>,
+
     /// When this window gained focus.
     pub start_time: DateTime<Utc>,
 
@@ -40,8 +52,50 @@ pub struct WindowSession {
     /// Number of mouse scroll events while this window was focused.
     pub mouse_scrolls: u64,
 
+    /// Vertical wheel events by direction (up = away from the user).
+    #[serde(default)]
+    pub scroll_up: u64,
+    #[serde(default)]
+    pub scroll_down: u64,
+
+    /// Horizontal wheel events by direction (not included in `mouse_scrolls`).
+    #[serde(default)]
+    pub scroll_left: u64,
+    #[serde(default)]
+    pub scroll_right: u64,
+
+    /// Number of keyboard shortcuts (Ctrl/Alt/Win + key) while this window was focused.
+    /// Shortcuts are also included in `keystrokes`.
+    #[serde(default)]
+    pub shortcuts: u64,
+
+    /// Number of left/right double-clicks while this window was focused.
+    /// Both presses of a double-click are also included in `mouse_clicks`.
+    #[serde(default)]
+    pub double_clicks: u64,
+
+    /// Mouse cursor travel in pixels while this window was focused.
+    #[serde(default)]
+    pub mouse_distance_px: u64,
+
+    /// Keystrokes by kind of key; the parts add up to `keystrokes`.
+    #[serde(default)]
+    pub key_breakdown: KeyBreakdown,
+
+    /// Whether the window was full-screen at any point during this session.
+    #[serde(default)]
+    pub is_fullscreen: bool,
+
+    /// Category ID set manually for this session, overriding the app's pattern mapping.
+    #[serde(default)]
+    pub category_override: Option<i64>,
+
     /// Whether this session represents idle/AFK time.
     pub is_idle: bool,
+
+    /// Why the user was idle (idle sessions only).
+    #[serde(default)]
+    pub idle_reason: Option<IdleReason>,
 }
 
 impl WindowSession {
@@ -56,13 +110,27 @@ impl WindowSession {
             window_handle,
             process_id,
             process_name,
+            process_path: None,
             window_title,
+            url: None,
+            monitor: None,
             start_time: Utc::now(),
             end_time: None,
             keystrokes: 0,
             mouse_clicks: 0,
             mouse_scrolls: 0,
+            scroll_up: 0,
+            scroll_down: 0,
+            scroll_left: 0,
+            scroll_right: 0,
+            shortcuts: 0,
+            double_clicks: 0,
+            mouse_distance_px: 0,
+            key_breakdown: KeyBreakdown::default(),
+            is_fullscreen: false,
+            category_override: None,
             is_idle: false,
+            idle_reason: None,
         }
     }
 
@@ -71,6 +139,21 @@ impl WindowSession {
         self.end_time = Some(Utc::now());
     }
 
+    /// Zeroes all input counts (for a session continued from another).
+    pub fn clear_input_counts(&mut self) {
+        self.keystrokes = 0;
+        self.mouse_clicks = 0;
+        self.mouse_scrolls = 0;
+        self.scroll_up = 0;
+        self.scroll_down = 0;
+        self.scroll_left = 0;
+        self.scroll_right = 0;
+        self.shortcuts = 0;
+        self.double_clicks = 0;
+        self.mouse_distance_px = 0;
+        self.key_breakdown = KeyBreakdown::default();
+    }
+
     /// Returns the duration of this session in seconds.
     /// Returns 0 if the session is still active.
     pub fn duration_secs(&self) -> i64 {
@@ -81,6 +164,81 @@ impl WindowSession {
     }
 }
 
+/// A change between active and idle, broadcast as `idle_state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdleStateChange {
+    pub idle: bool,
+    /// When the user went idle (last input) or came back.
+    pub since: DateTime<Utc>,
+    /// Why the user went idle; `None` when coming back.
+    #[serde(default)]
+    pub reason: Option<IdleReason>,
+}
+
+/// What the user was doing while idle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdleReason {
+    /// No input past the AFK threshold.
+    Away,
+    /// The workstation was locked or switched to another user.
+    Locked,
+    /// The screensaver was running.
+    Screensaver,
+}
+
+impl IdleReason {
+    /// Parses a reason as stored in the `sessions` table.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "away" => Some(Self::Away),
+            "locked" => Some(Self::Locked),
+            "screensaver" => Some(Self::Screensaver),
+            _ => None,
+        }
+    }
+
+    /// Name stored in the database and used by the API.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Away => "away",
+            Self::Locked => "locked",
+            Self::Screensaver => "screensaver",
+        }
+    }
+}
+
+/// Keystroke counts by kind of key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBreakdown {
+    /// Letters and digits, including the number pad.
+    pub alphanumeric: u64,
+    /// Space, Tab and Enter.
+    pub whitespace: u64,
+    /// Arrows, Home/End and Page Up/Down.
+    pub navigation: u64,
+    /// Shift, Ctrl, Alt, Win, Caps Lock and the function keys.
+    pub modifier: u64,
+    /// Everything else (punctuation, Backspace, Delete, Escape, ...).
+    pub other: u64,
+}
+
+impl KeyBreakdown {
+    /// Adds another breakdown to this one.
+    pub fn add(&mut self, other: &KeyBreakdown) {
+        self.alphanumeric += other.alphanumeric;
+        self.whitespace += other.whitespace;
+        self.navigation += other.navigation;
+        self.modifier += other.modifier;
+        self.other += other.other;
+    }
+
+    /// Total keystrokes across all kinds.
+    pub fn total(&self) -> u64 {
+        self.alphanumeric + self.whitespace + self.navigation + self.modifier + self.other
+    }
+}
+
 /// Aggregated statistics for a single application.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ApplicationStats {
@@ -96,6 +254,10 @@ pub struct ApplicationStats {
     /// Total mouse clicks made while this application was focused.
     pub total_clicks: u64,
 
+    /// Total double-clicks (a subset of the click pairs in `total_clicks`).
+    #[serde(default)]
+    pub total_double_clicks: u64,
+
     /// Number of separate focus sessions for this application.
     pub session_count: u32,
 }
@@ -114,6 +276,7 @@ impl ApplicationStats {
         self.total_focus_duration_secs += session.duration_secs() as u64;
         self.total_keystrokes += session.keystrokes;
         self.total_clicks += session.mouse_clicks;
+        self.total_double_clicks += session.double_clicks;
         self.session_count += 1;
     }
 }
@@ -127,9 +290,17 @@ pub struct DailySummary {
     /// Total mouse clicks today.
     pub total_clicks: u64,
 
-    /// Total focus time in seconds today.
+    /// Total focus time in seconds today, including idle sessions.
     pub total_focus_time_secs: u64,
 
+    /// Time in idle (AFK) sessions today.
+    #[serde(default)]
+    pub total_idle_time_secs: u64,
+
+    /// Focus time excluding idle sessions.
+    #[serde(default)]
+    pub active_focus_time_secs: u64,
+
     /// Number of unique applications used.
     pub app_count: u32,
 
@@ -137,6 +308,131 @@ pub struct DailySummary {
     pub session_count: u32,
 }
 
+/// End-of-run activity recap, persisted at shutdown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    /// When the run ended.
+    pub ended_at: DateTime<Utc>,
+
+    /// Totals for the run.
+    #[serde(flatten)]
+    pub summary: DailySummary,
+
+    /// Top applications by focus time.
+    pub top_apps: Vec<ApplicationStats>,
+
+    /// Total media playback time in seconds.
+    pub media_time_secs: i64,
+
+    /// Number of media tracks played.
+    pub media_tracks: u32,
+}
+
+/// Totals for a set of sessions, as shown by `/api/stats`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FocusTotals {
+    pub sessions: usize,
+    pub unique_apps: usize,
+    pub keystrokes: u64,
+    pub clicks: u64,
+    /// Mouse cursor travel in pixels.
+    pub mouse_distance_px: u64,
+    /// Keystrokes by kind of key.
+    pub key_breakdown: KeyBreakdown,
+    /// Session time, excluding idle sessions unless `count_idle_in_focus` is set.
+    pub focus_time_secs: i64,
+    /// Time in idle sessions.
+    pub idle_time_secs: i64,
+    /// Time in non-idle sessions, regardless of `count_idle_in_focus`.
+    pub active_time_secs: i64,
+    /// Focus changes: the number of non-idle sessions.
+    pub context_switches: usize,
+    /// Longest non-idle session in seconds.
+    pub longest_focus_secs: i64,
+}
+
+/// Today's progress compared with the usual pace at the same time of day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaceComparison {
+    /// Focus time so far today in seconds.
+    pub focus_secs: i64,
+
+    /// Keystrokes so far today.
+    pub keystrokes: i64,
+
+    /// Average focus time by this time of day over the history window.
+    pub avg_focus_secs: f64,
+
+    /// Average keystrokes by this time of day over the history window.
+    pub avg_keystrokes: f64,
+
+    /// Today's focus time relative to the average (1.2 = 20% ahead).
+    /// `None` when there isn't enough history to compare against.
+    pub focus_ratio: Option<f64>,
+
+    /// Today's keystrokes relative to the average.
+    /// `None` when there isn't enough history to compare against.
+    pub keystrokes_ratio: Option<f64>,
+
+    /// Number of past days with activity used for the average.
+    pub history_days: u32,
+}
+
+/// A day's focus score, weighted by category.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProductivityScore {
+    /// Score from 0 to 100 (50 = neutral). `None` without any focus time.
+    pub score: Option<u32>,
+
+    /// Total non-idle focus time in seconds.
+    pub focus_time_secs: i64,
+
+    /// Focus time per category, largest first.
+    pub categories: Vec<CategoryScore>,
+}
+
+/// One category's share of a [`ProductivityScore`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryScore {
+    /// Category name.
+    pub category: String,
+
+    /// Weight applied to this category (0 when unweighted).
+    pub weight: f64,
+
+    /// Focus time in this category in seconds.
+    pub focus_time_secs: i64,
+}
+
+/// Keystrokes typed during one clock minute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypingSample {
+    /// Start of the minute.
+    pub minute: DateTime<Utc>,
+
+    /// Keystrokes counted during the minute.
+    pub keystrokes: u64,
+}
+
+/// Typing speed over a period, from per-minute [`TypingSample`]s.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TypingCadence {
+    /// Keystrokes across all samples.
+    pub total_keystrokes: u64,
+
+    /// Minutes with at least one keystroke.
+    pub active_minutes: u32,
+
+    /// Average keystrokes per minute over the active minutes.
+    pub avg_kpm: f64,
+
+    /// Highest keystrokes in a single minute.
+    pub peak_kpm: u64,
+
+    /// Start of the minute with the highest count (the earliest on a tie).
+    pub peak_minute: Option<DateTime<Utc>>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
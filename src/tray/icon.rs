@@ -1,11 +1,13 @@
 //! Icon creation and management for the system tray.
 
+use image::{ImageFormat, RgbaImage};
+use std::path::{Path, PathBuf};
 use tray_icon::Icon;
 
 /// Creates a default icon for the system tray.
 ///
-/// This generates a simple colored icon programmatically since we don't
-/// have an external icon file. For production, replace with a proper .ico file.
+/// This generates a simple colored icon programmatically. It is used when
+/// no custom icon is present (see [`load_custom_icon`]).
 pub fn create_default_icon() -> Result<Icon, Box<dyn std::error::Error>> {
     // Create a simple 32x32 icon with a gradient
     let size = 32u32;
@@ -46,18 +48,176 @@ pub fn create_default_icon() -> Result<Icon, Box<dyn std::error::Error>> {
     Icon::from_rgba(rgba, size, size).map_err(|e| e.into())
 }
 
+/// Frame size picked from multi-resolution .ico files.
+const PREFERRED_ICON_SIZE: u32 = 32;
+
+/// Size of the ICONDIR header at the start of an .ico file.
+const ICO_HEADER_LEN: usize = 6;
+
+/// Size of each ICONDIRENTRY following the header.
+const ICO_ENTRY_LEN: usize = 16;
+
+/// Returns the path of a user-supplied tray icon (%APPDATA%/ownmon/icon.ico).
+pub fn custom_icon_path() -> PathBuf {
+    crate::database::Database::data_dir().join("icon.ico")
+}
+
+/// Loads the user-supplied tray icon, if one exists and can be decoded.
+pub fn load_custom_icon() -> Option<Icon> {
+    let path = custom_icon_path();
+    if !path.exists() {
+        return None;
+    }
+    match load_icon_from_file(&path) {
+        Ok(icon) => {
+            tracing::info!(path = %path.display(), "Using custom tray icon");
+            Some(icon)
+        }
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "Failed to load custom tray icon");
+            None
+        }
+    }
+}
+
 /// Loads an icon from a file path.
 ///
-/// Supports .ico, .png, and other common formats.
-#[allow(dead_code)]
-#[allow(unused_variables)]
-pub fn load_icon_from_file(path: &std::path::Path) -> Result<Icon, Box<dyn std::error::Error>> {
+/// Supports .ico and .png files. For .ico files with several resolutions,
+/// the 32x32 frame (or the nearest size) is used.
+pub fn load_icon_from_file(path: &Path) -> Result<Icon, Box<dyn std::error::Error>> {
     let image_data = std::fs::read(path)?;
+    let image = decode_icon(&image_data)?;
+    let (width, height) = image.dimensions();
+    Icon::from_rgba(image.into_raw(), width, height).map_err(|e| e.into())
+}
+
+/// Decodes PNG or ICO bytes into RGBA pixels.
+fn decode_icon(data: &[u8]) -> Result<RgbaImage, Box<dyn std::error::Error>> {
+    let format = image::guess_format(data)
+        .map_err(|_| "unrecognized icon format; use an .ico or .png file")?;
+    let image = match format {
+        ImageFormat::Png => image::load_from_memory_with_format(data, ImageFormat::Png)?,
+        ImageFormat::Ico => {
+            let frame = select_ico_frame(data)?;
+            image::load_from_memory_with_format(&frame, ImageFormat::Ico)?
+        }
+        other => {
+            return Err(format!(
+                "unsupported icon format {:?}; use an .ico or .png file",
+                other
+            )
+            .into())
+        }
+    };
+    Ok(image.into_rgba8())
+}
+
+/// Returns a single-frame .ico holding the frame closest to
+/// [`PREFERRED_ICON_SIZE`], preferring the larger of two equally close sizes.
+fn select_ico_frame(data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let invalid = || "invalid .ico file: truncated icon directory";
+    let count = data
+        .get(4..ICO_HEADER_LEN)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+        .ok_or_else(invalid)?;
+    if count == 0 {
+        return Err("invalid .ico file: no images".into());
+    }
 
-    // For .ico files, we need to parse them
-    // For now, just use the default icon
-    // In production, use the `image` crate to decode various formats
+    let mut best: Option<(&[u8], u32)> = None;
+    for index in 0..count {
+        let offset = ICO_HEADER_LEN + index * ICO_ENTRY_LEN;
+        let entry = data
+            .get(offset..offset + ICO_ENTRY_LEN)
+            .ok_or_else(invalid)?;
+        // A stored width of 0 means 256
+        let size = match entry[0] {
+            0 => 256,
+            width => width as u32,
+        };
+        let is_closer = best.is_none_or(|(_, best_size)| {
+            let (diff, best_diff) = (
+                size.abs_diff(PREFERRED_ICON_SIZE),
+                best_size.abs_diff(PREFERRED_ICON_SIZE),
+            );
+            diff < best_diff || (diff == best_diff && size > best_size)
+        });
+        if is_closer {
+            best = Some((entry, size));
+        }
+    }
+    let (entry, _) = best.ok_or_else(invalid)?;
+
+    let len = u32::from_le_bytes([entry[8], entry[9], entry[10], entry[11]]) as usize;
+    let start = u32::from_le_bytes([entry[12], entry[13], entry[14], entry[15]]) as usize;
+    let image = data
+        .get(start..start.saturating_add(len))
+        .ok_or("invalid .ico file: image data out of range")?;
+
+    // Rebuild the file with just this entry, its data right after the directory
+    let mut frame = Vec::with_capacity(ICO_HEADER_LEN + ICO_ENTRY_LEN + image.len());
+    frame.extend_from_slice(&[0, 0, 1, 0, 1, 0]);
+    frame.extend_from_slice(&entry[..12]);
+    frame.extend_from_slice(&((ICO_HEADER_LEN + ICO_ENTRY_LEN) as u32).to_le_bytes());
+    frame.extend_from_slice(image);
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a solid `size` x `size` PNG.
+    fn png(size: u32) -> Vec<u8> {
+        let mut out = std::io::Cursor::new(Vec::new());
+        RgbaImage::from_pixel(size, size, image::Rgba([30, 144, 255, 255]))
+            .write_to(&mut out, ImageFormat::Png)
+            .unwrap();
+        out.into_inner()
+    }
+
+    /// Builds an .ico file with a PNG frame for each size.
+    fn ico(sizes: &[u32]) -> Vec<u8> {
+        let frames: Vec<Vec<u8>> = sizes.iter().map(|&size| png(size)).collect();
+        let mut data = vec![0, 0, 1, 0];
+        data.extend_from_slice(&(sizes.len() as u16).to_le_bytes());
+        let mut offset = ICO_HEADER_LEN + sizes.len() * ICO_ENTRY_LEN;
+        for (&size, frame) in sizes.iter().zip(&frames) {
+            let dim = if size >= 256 { 0 } else { size as u8 };
+            data.extend_from_slice(&[dim, dim, 0, 0, 1, 0, 32, 0]);
+            data.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(offset as u32).to_le_bytes());
+            offset += frame.len();
+        }
+        for frame in frames {
+            data.extend_from_slice(&frame);
+        }
+        data
+    }
+
+    #[test]
+    fn test_decode_icon_picks_nearest_frame_to_32() {
+        let image = decode_icon(&ico(&[16, 48, 32, 256])).unwrap();
+        assert_eq!(image.dimensions(), (32, 32));
+
+        // Without a 32x32 frame, the nearest size wins (larger on a tie)
+        let image = decode_icon(&ico(&[16, 48, 256])).unwrap();
+        assert_eq!(image.dimensions(), (48, 48));
+        let image = decode_icon(&ico(&[24, 40])).unwrap();
+        assert_eq!(image.dimensions(), (40, 40));
 
-    tracing::warn!("Icon loading from file not fully implemented, using default");
-    create_default_icon()
+        let image = decode_icon(&png(64)).unwrap();
+        assert_eq!(image.dimensions(), (64, 64));
+    }
+
+    #[test]
+    fn test_decode_icon_rejects_unsupported_data() {
+        let err = decode_icon(b"not an image").unwrap_err();
+        assert!(err.to_string().contains(".ico or .png"));
+
+        // A valid directory pointing past the end of the file
+        let mut truncated = ico(&[32]);
+        truncated.truncate(ICO_HEADER_LEN + ICO_ENTRY_LEN + 4);
+        assert!(decode_icon(&truncated).is_err());
+    }
 }
@@ -1,9 +1,12 @@
 //! Context menu for the system tray.
 
-use tray_icon::menu::{Menu, MenuId, MenuItem, PredefinedMenuItem};
+use tray_icon::menu::{CheckMenuItem, Menu, MenuId, MenuItem, PredefinedMenuItem};
 
 /// Menu item IDs
 pub const MENU_ID_SHOW_STATS: &str = "show_stats";
+pub const MENU_ID_OPEN_DASHBOARD: &str = "open_dashboard";
+pub const MENU_ID_TOGGLE_PAUSE: &str = "toggle_pause";
+pub const MENU_ID_AUTOSTART: &str = "autostart";
 pub const MENU_ID_EXIT: &str = "exit";
 
 /// Creates the context menu for the system tray.
@@ -18,6 +21,31 @@ pub fn create_tray_menu() -> Menu {
         None,
     );
 
+    // Open Dashboard menu item
+    let open_dashboard = MenuItem::with_id(
+        MenuId::new(MENU_ID_OPEN_DASHBOARD),
+        "Open Dashboard",
+        true,
+        None,
+    );
+
+    // Pause Tracking menu item (toggles)
+    let toggle_pause = MenuItem::with_id(
+        MenuId::new(MENU_ID_TOGGLE_PAUSE),
+        "Pause Tracking",
+        true,
+        None,
+    );
+
+    // Start with Windows (checked when registered)
+    let autostart = CheckMenuItem::with_id(
+        MenuId::new(MENU_ID_AUTOSTART),
+        "Start with Windows",
+        true,
+        crate::autostart::is_autostart_enabled(),
+        None,
+    );
+
     // Separator
     let separator = PredefinedMenuItem::separator();
 
@@ -26,6 +54,9 @@ pub fn create_tray_menu() -> Menu {
 
     // Build menu
     let _ = menu.append(&show_stats);
+    let _ = menu.append(&open_dashboard);
+    let _ = menu.append(&toggle_pause);
+    let _ = menu.append(&autostart);
     let _ = menu.append(&separator);
     let _ = menu.append(&exit);
 
@@ -5,16 +5,25 @@
 
 pub mod icon;
 pub mod menu;
+pub mod notify;
 
 pub use icon::*;
 pub use menu::*;
+pub use notify::*;
 
-use crate::winapi_utils::post_quit_message;
+use crate::winapi_utils::{open_url, post_quit_message};
+use std::cell::RefCell;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tray_icon::menu::{Menu, MenuEvent, MenuItem};
+use tray_icon::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem};
 use tray_icon::{TrayIcon, TrayIconBuilder};
 
+thread_local! {
+    /// The "Start with Windows" item. Menu items can only be used on the
+    /// thread that built the tray, which is also where menu events arrive.
+    static AUTOSTART_ITEM: RefCell<Option<CheckMenuItem>> = const { RefCell::new(None) };
+}
+
 /// Sets up the system tray icon and menu.
 ///
 /// # Arguments
@@ -23,10 +32,23 @@ use tray_icon::{TrayIcon, TrayIconBuilder};
 /// # Returns
 /// The `TrayIcon` instance. Keep this alive for the tray to remain visible.
 pub fn setup_tray(shutdown: Arc<AtomicBool>) -> Result<TrayIcon, Box<dyn std::error::Error>> {
-    let icon = create_default_icon()?;
+    // A user-supplied icon.ico in the data directory replaces the default
+    let icon = match load_custom_icon() {
+        Some(icon) => icon,
+        None => create_default_icon()?,
+    };
+    let autostart = CheckMenuItem::with_id(
+        MENU_ID_AUTOSTART,
+        "Start with Windows",
+        true,
+        crate::autostart::is_autostart_enabled(),
+        None,
+    );
     let menu = Menu::with_items(&[
-        // Future: Could add "Open Dashboard" to launch web UI
-        &MenuItem::with_id("exit", "Exit", true, None),
+        &MenuItem::with_id(MENU_ID_OPEN_DASHBOARD, "Open Dashboard", true, None),
+        &MenuItem::with_id(MENU_ID_TOGGLE_PAUSE, "Pause Tracking", true, None),
+        &autostart,
+        &MenuItem::with_id(MENU_ID_EXIT, "Exit", true, None),
     ])?;
     let tray = TrayIconBuilder::new()
         .with_tooltip("OwnMon - Activity Monitor")
@@ -34,28 +56,29 @@ pub fn setup_tray(shutdown: Arc<AtomicBool>) -> Result<TrayIcon, Box<dyn std::er
         .with_menu(Box::new(menu))
         .build()?;
 
-    // Spawn menu event handler
-    spawn_menu_handler(shutdown);
+    if !attach_balloons_to_tray() {
+        tracing::warn!("Tray icon not found, notifications will only be logged");
+    }
+
+    AUTOSTART_ITEM.with(|item| *item.borrow_mut() = Some(autostart));
+
+    // Menu events are handled on this thread, by its message loop
+    MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+        handle_menu_event(&event.id.0, &shutdown)
+    }));
 
     tracing::info!("System tray initialized");
     Ok(tray)
 }
 
-/// Spawns a thread to handle menu events.
-fn spawn_menu_handler(shutdown: Arc<AtomicBool>) {
-    std::thread::spawn(move || {
-        let receiver = MenuEvent::receiver();
-
-        loop {
-            if let Ok(event) = receiver.try_recv() {
-                handle_menu_event(&event.id.0, &shutdown);
-            }
-
-            if shutdown.load(Ordering::Relaxed) {
-                break;
-            }
-
-            std::thread::sleep(std::time::Duration::from_millis(100));
+/// Sets the "Start with Windows" check mark from the registry.
+///
+/// Clicking the item toggles its check mark on its own, which is wrong if
+/// the registry couldn't be changed.
+fn sync_autostart_item() {
+    AUTOSTART_ITEM.with(|item| {
+        if let Some(item) = item.borrow().as_ref() {
+            item.set_checked(crate::autostart::is_autostart_enabled());
         }
     });
 }
@@ -63,7 +86,44 @@ fn spawn_menu_handler(shutdown: Arc<AtomicBool>) {
 /// Handles a menu item click.
 fn handle_menu_event(menu_id: &str, shutdown: &Arc<AtomicBool>) {
     match menu_id {
-        "exit" => {
+        MENU_ID_OPEN_DASHBOARD => match crate::server::bound_port() {
+            // The server may listen on 0.0.0.0; loopback always reaches it
+            Some(port) => {
+                let url = format!("http://127.0.0.1:{}/", port);
+                if !open_url(&url) {
+                    tracing::warn!(url, "Failed to open dashboard in browser");
+                    show_balloon("OwnMon", "Couldn't open the dashboard in your browser.");
+                }
+            }
+            None => show_balloon("OwnMon", "The HTTP server isn't running."),
+        },
+        MENU_ID_TOGGLE_PAUSE => {
+            let paused = !crate::store::is_monitoring_paused();
+            crate::store::set_monitoring_paused(paused);
+            if paused {
+                show_balloon("OwnMon", "Tracking paused. Click again to resume.");
+            } else {
+                show_balloon("OwnMon", "Tracking resumed.");
+            }
+        }
+        MENU_ID_AUTOSTART => {
+            let enable = !crate::autostart::is_autostart_enabled();
+            let result = if enable {
+                crate::autostart::enable_autostart()
+            } else {
+                crate::autostart::disable_autostart()
+            };
+            match result {
+                Ok(()) if enable => show_balloon("OwnMon", "OwnMon will start with Windows."),
+                Ok(()) => show_balloon("OwnMon", "OwnMon will no longer start with Windows."),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to update autostart");
+                    show_balloon("OwnMon", "Couldn't change the startup setting.");
+                }
+            }
+            sync_autostart_item();
+        }
+        MENU_ID_EXIT => {
             tracing::info!("Exit requested from tray menu");
             shutdown.store(true, Ordering::SeqCst);
             post_quit_message(0);
@@ -0,0 +1,93 @@
+//! Balloon notifications from the notification area.
+//!
+//! `tray-icon` doesn't expose balloon tips, so they are shown on its icon
+//! directly: [`attach_balloons_to_tray`] looks up the window and icon ID it
+//! registered, and [`show_balloon`] modifies that icon.
+
+use crate::winapi_utils::get_window_thread_process_id;
+use std::sync::OnceLock;
+use windows::core::w;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::System::Threading::GetCurrentProcessId;
+use windows::Win32::UI::Shell::{
+    Shell_NotifyIconGetRect, Shell_NotifyIconW, NIF_INFO, NIIF_INFO, NIM_MODIFY, NOTIFYICONDATAW,
+    NOTIFYICONIDENTIFIER,
+};
+use windows::Win32::UI::WindowsAndMessaging::FindWindowExW;
+
+/// Highest icon ID probed when looking for the tray's icon.
+const MAX_TRAY_ICON_ID: u32 = 16;
+
+/// Window handle and icon ID of the tray icon balloons are shown on.
+static TRAY_ICON: OnceLock<(isize, u32)> = OnceLock::new();
+
+/// Copies `text` into a fixed-size, nul-terminated UTF-16 buffer, truncating if needed.
+fn copy_wide(dst: &mut [u16], text: &str) {
+    let max = dst.len().saturating_sub(1);
+    let mut len = 0;
+    for (slot, unit) in dst.iter_mut().zip(text.encode_utf16().take(max)) {
+        *slot = unit;
+        len += 1;
+    }
+    if let Some(terminator) = dst.get_mut(len) {
+        *terminator = 0;
+    }
+}
+
+/// Finds the notification icon `tray-icon` registered for this process, so
+/// balloons are shown on it instead of on an icon of their own.
+///
+/// Call once the tray icon is built. Returns false if it can't be found;
+/// balloons are then only logged.
+pub fn attach_balloons_to_tray() -> bool {
+    let pid = unsafe { GetCurrentProcessId() };
+    let mut hwnd = HWND::default();
+    loop {
+        // `tray-icon` keeps its icon on a hidden window of this class
+        hwnd = match unsafe { FindWindowExW(None, hwnd, w!("tray_icon_app"), None) } {
+            Ok(found) if !found.is_invalid() => found,
+            _ => return false,
+        };
+        if get_window_thread_process_id(hwnd).1 != pid {
+            continue;
+        }
+        // Its icon ID isn't exposed; probe for the one the shell knows
+        for id in 1..=MAX_TRAY_ICON_ID {
+            let identifier = NOTIFYICONIDENTIFIER {
+                cbSize: std::mem::size_of::<NOTIFYICONIDENTIFIER>() as u32,
+                hWnd: hwnd,
+                uID: id,
+                ..Default::default()
+            };
+            if unsafe { Shell_NotifyIconGetRect(&identifier) }.is_ok() {
+                return TRAY_ICON.set((hwnd.0 as isize, id)).is_ok();
+            }
+        }
+    }
+}
+
+/// Shows a balloon notification on the tray icon.
+///
+/// Without a tray icon (see [`attach_balloons_to_tray`]) the notification
+/// is only logged.
+pub fn show_balloon(title: &str, message: &str) {
+    let Some(&(hwnd, id)) = TRAY_ICON.get() else {
+        tracing::info!(title, message, "No tray icon to show a notification on");
+        return;
+    };
+
+    let mut data = NOTIFYICONDATAW {
+        cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+        hWnd: HWND(hwnd as *mut std::ffi::c_void),
+        uID: id,
+        uFlags: NIF_INFO,
+        dwInfoFlags: NIIF_INFO,
+        ..Default::default()
+    };
+    copy_wide(&mut data.szInfoTitle, title);
+    copy_wide(&mut data.szInfo, message);
+
+    if !unsafe { Shell_NotifyIconW(NIM_MODIFY, &data) }.as_bool() {
+        tracing::warn!("Failed to show tray notification");
+    }
+}
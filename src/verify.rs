@@ -0,0 +1,192 @@
+//! Offline integrity audit (`ownmon --verify`).
+//!
+//! Opens the database read-only and checks every day's session hash chain,
+//! session signatures and daily Merkle records against a public key file,
+//! without starting the tracker.
+
+use crate::crypto::KeyManager;
+use crate::database::Database;
+use crate::store::{verify_integrity_with, IntegrityReport};
+use base64::Engine;
+use ed25519_dalek::VerifyingKey;
+use std::path::{Path, PathBuf};
+
+/// Command-line options for `--verify`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyOptions {
+    /// Database to check (default: %APPDATA%/ownmon/activity.db).
+    pub db_path: Option<PathBuf>,
+    /// Public key file with the base64 key (default: the exported `public_key.txt`).
+    pub pubkey_path: Option<PathBuf>,
+}
+
+impl VerifyOptions {
+    /// Parses command-line arguments (without the program name).
+    ///
+    /// Returns `Ok(None)` when `--verify` isn't given, so normal startup
+    /// continues.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut verify = false;
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--verify" => verify = true,
+                "--db" => {
+                    let path = args.next().ok_or("--db needs a path")?;
+                    options.db_path = Some(PathBuf::from(path));
+                }
+                "--pubkey" => {
+                    let path = args.next().ok_or("--pubkey needs a path")?;
+                    options.pubkey_path = Some(PathBuf::from(path));
+                }
+                other if verify => return Err(format!("Unknown argument: {}", other)),
+                _ => {}
+            }
+        }
+
+        if !verify && (options.db_path.is_some() || options.pubkey_path.is_some()) {
+            return Err("--db and --pubkey are only valid with --verify".to_string());
+        }
+        Ok(verify.then_some(options))
+    }
+}
+
+/// Reads a base64 Ed25519 public key from a file.
+pub fn load_public_key(path: &Path) -> Result<VerifyingKey, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read public key {}: {}", path.display(), e))?;
+    decode_public_key(&text)
+}
+
+/// Decodes a base64 Ed25519 public key.
+pub fn decode_public_key(text: &str) -> Result<VerifyingKey, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(text.trim())
+        .map_err(|e| format!("Public key is not valid base64: {}", e))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| "Public key must be 32 bytes".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("Invalid public key: {}", e))
+}
+
+/// Whether a day's report is a real failure.
+///
+/// A day that hasn't been sealed yet (usually today) only fails if one of
+/// its sessions does.
+pub fn report_failed(report: &IntegrityReport) -> bool {
+    !report.invalid_sessions.is_empty() || (report.has_daily_record && !report.valid)
+}
+
+/// Runs the audit and prints a summary.
+///
+/// Returns `Ok(true)` if everything verified.
+pub fn run(options: &VerifyOptions) -> Result<bool, String> {
+    let db_path = options
+        .db_path
+        .clone()
+        .unwrap_or_else(Database::default_path);
+    let pubkey_path = options
+        .pubkey_path
+        .clone()
+        .unwrap_or_else(KeyManager::public_key_path);
+
+    let key = load_public_key(&pubkey_path)?;
+    let db = Database::open_read_only(&db_path)
+        .map_err(|e| format!("Cannot open database {}: {}", db_path.display(), e))?;
+    crate::store::set_timezone_policy(db.timezone_policy());
+    let dates = db
+        .get_integrity_dates()
+        .map_err(|e| format!("Cannot read database: {}", e))?;
+
+    println!("Verifying {}", db_path.display());
+    println!("Public key: {}", pubkey_path.display());
+    println!();
+
+    let mut sessions_checked = 0;
+    let mut failed_days = 0;
+    let mut unsealed_days = 0;
+
+    for date in &dates {
+        let report = verify_integrity_with(&db, &key, date)
+            .map_err(|e| format!("Failed to verify {}: {}", date, e))?;
+        sessions_checked += report.sessions_checked;
+
+        if report_failed(&report) {
+            failed_days += 1;
+            println!("✗ {} ({} sessions)", date, report.sessions_checked);
+            for issue in &report.invalid_sessions {
+                println!(
+                    "    session {} {} {}: hash {}, signature {}, chain {}",
+                    issue.id,
+                    issue.process_name,
+                    issue.start_time,
+                    ok_or_bad(issue.hash_valid),
+                    ok_or_bad(issue.signature_valid),
+                    ok_or_bad(issue.chain_valid),
+                );
+            }
+            if report.has_daily_record {
+                println!(
+                    "    daily record: merkle root {}, signature {}, previous day link {}",
+                    ok_or_bad(report.merkle_root_valid),
+                    ok_or_bad(report.daily_signature_valid),
+                    ok_or_bad(report.prev_day_chain_valid),
+                );
+            }
+        } else if !report.has_daily_record {
+            unsealed_days += 1;
+            println!(
+                "… {} ({} sessions, not sealed yet)",
+                date, report.sessions_checked
+            );
+        }
+    }
+
+    println!();
+    println!(
+        "{} days, {} sessions checked: {} failed, {} not sealed yet",
+        dates.len(),
+        sessions_checked,
+        failed_days,
+        unsealed_days
+    );
+    Ok(failed_days == 0)
+}
+
+fn ok_or_bad(valid: bool) -> &'static str {
+    if valid {
+        "ok"
+    } else {
+        "MISMATCH"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_verify_options() {
+        assert_eq!(VerifyOptions::parse(args(&[])), Ok(None));
+        assert_eq!(
+            VerifyOptions::parse(args(&["--verify"])),
+            Ok(Some(VerifyOptions::default()))
+        );
+        assert_eq!(
+            VerifyOptions::parse(args(&["--verify", "--db", "a.db", "--pubkey", "k.txt"])),
+            Ok(Some(VerifyOptions {
+                db_path: Some(PathBuf::from("a.db")),
+                pubkey_path: Some(PathBuf::from("k.txt")),
+            }))
+        );
+        assert!(VerifyOptions::parse(args(&["--verify", "--db"])).is_err());
+        assert!(VerifyOptions::parse(args(&["--verify", "--bogus"])).is_err());
+        assert!(VerifyOptions::parse(args(&["--db", "a.db"])).is_err());
+    }
+}
@@ -3,7 +3,13 @@
 //! Provides RAII wrappers for Windows low-level hooks to ensure
 //! proper cleanup when hooks go out of scope.
 
-use windows::Win32::Foundation::LRESULT;
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{
+    CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, HANDLE, LRESULT,
+};
+use windows::Win32::System::SystemInformation::GetTickCount;
+use windows::Win32::System::Threading::{CreateMutexW, ReleaseMutex};
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
 use windows::Win32::UI::WindowsAndMessaging::{
     CallNextHookEx, SetWindowsHookExW, UnhookWindowsHookEx, HHOOK, HOOKPROC, WH_KEYBOARD_LL,
     WH_MOUSE_LL, WINDOWS_HOOK_ID,
@@ -92,6 +98,53 @@ impl Drop for HookGuard {
     }
 }
 
+/// Name of the mutex held while OwnMon's input hooks are installed.
+///
+/// Hooks only see input of their own logon session, so the mutex is
+/// per-session: another user signed in on the same PC gets hooks too.
+pub const INPUT_HOOK_MUTEX_NAME: &str = "Local\\OwnMon-InputHooks";
+
+/// RAII guard for a named mutex.
+///
+/// Used to make sure only one process installs input hooks, so input
+/// is never counted twice even if two instances race past the lock file.
+pub struct NamedMutexGuard {
+    handle: HANDLE,
+}
+
+impl NamedMutexGuard {
+    /// Tries to take ownership of the named mutex.
+    ///
+    /// Returns `Ok(None)` if another holder already created it.
+    pub fn try_acquire(name: &str) -> windows::core::Result<Option<Self>> {
+        let handle = unsafe { CreateMutexW(None, true, &HSTRING::from(name))? };
+
+        // An existing mutex is opened rather than created; we don't own it
+        if unsafe { GetLastError() } == ERROR_ALREADY_EXISTS {
+            unsafe {
+                let _ = CloseHandle(handle);
+            }
+            return Ok(None);
+        }
+
+        Ok(Some(Self { handle }))
+    }
+
+    /// Takes the input hook mutex, see [`INPUT_HOOK_MUTEX_NAME`].
+    pub fn acquire_input_hooks() -> windows::core::Result<Option<Self>> {
+        Self::try_acquire(INPUT_HOOK_MUTEX_NAME)
+    }
+}
+
+impl Drop for NamedMutexGuard {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = ReleaseMutex(self.handle);
+            let _ = CloseHandle(self.handle);
+        }
+    }
+}
+
 /// Calls the next hook in the hook chain.
 ///
 /// This must be called at the end of every hook callback to ensure
@@ -109,9 +162,42 @@ pub fn call_next_hook(
     unsafe { CallNextHookEx(None, code, wparam, lparam) }
 }
 
+/// Returns how long ago the last keyboard or mouse input was, system-wide.
+///
+/// Unlike the hooks, this covers input from before OwnMon started.
+pub fn get_idle_duration() -> Option<std::time::Duration> {
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        return None;
+    }
+    // Tick counts wrap after ~49 days
+    let idle_ms = unsafe { GetTickCount() }.wrapping_sub(info.dwTime);
+    Some(std::time::Duration::from_millis(idle_ms as u64))
+}
+
 #[cfg(test)]
 mod tests {
     // Hook tests require a running message loop and are better
     // suited for integration testing. Unit tests for hook
     // installation would hang waiting for messages.
+
+    use super::*;
+
+    #[test]
+    fn test_named_mutex_acquire_and_contend() {
+        let name = format!("Local\\OwnMon-Test-{}", std::process::id());
+
+        let first = NamedMutexGuard::try_acquire(&name).unwrap();
+        assert!(first.is_some());
+
+        // A second holder is refused while the first is alive
+        assert!(NamedMutexGuard::try_acquire(&name).unwrap().is_none());
+
+        // Released on drop, so it can be taken again
+        drop(first);
+        assert!(NamedMutexGuard::try_acquire(&name).unwrap().is_some());
+    }
 }
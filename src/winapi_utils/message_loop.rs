@@ -1,17 +1,32 @@
 //! Windows message loop utilities.
 //!
 //! Provides functions for running and controlling the Windows message pump,
-//! which is required for low-level hooks and system tray functionality.
+//! which is required for low-level hooks, system tray functionality and
+//! session lock notifications.
 
+use once_cell::sync::OnceCell;
 use std::sync::atomic::{AtomicU32, Ordering};
+use windows::core::w;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::RemoteDesktop::{
+    WTSRegisterSessionNotification, WTSUnRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION,
+};
 use windows::Win32::System::Threading::GetCurrentThreadId;
 use windows::Win32::UI::WindowsAndMessaging::{
-    DispatchMessageW, GetMessageW, PostThreadMessageW, TranslateMessage, MSG, WM_QUIT,
+    CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetMessageW,
+    PostThreadMessageW, RegisterClassW, TranslateMessage, HWND_MESSAGE, MSG, WINDOW_EX_STYLE,
+    WINDOW_STYLE, WM_QUIT, WM_WTSSESSION_CHANGE, WNDCLASSW, WTS_CONSOLE_CONNECT,
+    WTS_CONSOLE_DISCONNECT, WTS_REMOTE_CONNECT, WTS_REMOTE_DISCONNECT, WTS_SESSION_LOCK,
+    WTS_SESSION_UNLOCK,
 };
 
 /// Stores the main thread ID for cross-thread quit signaling.
 static MAIN_THREAD_ID: AtomicU32 = AtomicU32::new(0);
 
+/// Called for each lock state change, see [`SessionNotificationGuard`].
+static SESSION_CHANGE_HANDLER: OnceCell<fn(SessionChange)> = OnceCell::new();
+
 /// Runs the Windows message loop until a WM_QUIT message is received.
 ///
 /// This function blocks the calling thread and pumps messages.
@@ -97,9 +112,135 @@ pub fn post_quit_message(exit_code: i32) {
     }
 }
 
+/// A change in whether the user's Windows session is locked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionChange {
+    Locked,
+    Unlocked,
+}
+
+impl SessionChange {
+    /// Maps a `WM_WTSSESSION_CHANGE` event code.
+    ///
+    /// Switching to another user or disconnecting a remote session counts as
+    /// locked; reconnecting counts as unlocked. Other events are ignored.
+    pub fn from_event(event: u32) -> Option<Self> {
+        match event {
+            WTS_SESSION_LOCK | WTS_CONSOLE_DISCONNECT | WTS_REMOTE_DISCONNECT => Some(Self::Locked),
+            WTS_SESSION_UNLOCK | WTS_CONSOLE_CONNECT | WTS_REMOTE_CONNECT => Some(Self::Unlocked),
+            _ => None,
+        }
+    }
+}
+
+/// RAII guard for session lock notifications.
+///
+/// Creates a hidden message-only window registered with
+/// `WTSRegisterSessionNotification`. The notifications arrive through the
+/// message loop of the thread that registered, so that thread must run
+/// [`run_message_loop`].
+pub struct SessionNotificationGuard {
+    hwnd: HWND,
+}
+
+impl SessionNotificationGuard {
+    /// Starts calling `handler` on each lock or unlock.
+    ///
+    /// Only the first handler registered in a process is used.
+    pub fn register(handler: fn(SessionChange)) -> windows::core::Result<Self> {
+        let _ = SESSION_CHANGE_HANDLER.set(handler);
+        let class_name = w!("OwnMonSessionNotifications");
+
+        unsafe {
+            let instance: HINSTANCE = GetModuleHandleW(None)?.into();
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(session_window_proc),
+                hInstance: instance,
+                lpszClassName: class_name,
+                ..Default::default()
+            };
+            // Fails harmlessly if the class is already registered
+            RegisterClassW(&class);
+
+            let hwnd = CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                class_name,
+                w!("OwnMon session notifications"),
+                WINDOW_STYLE::default(),
+                0,
+                0,
+                0,
+                0,
+                HWND_MESSAGE,
+                None,
+                instance,
+                None,
+            )?;
+            if let Err(e) = WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) {
+                let _ = DestroyWindow(hwnd);
+                return Err(e);
+            }
+
+            tracing::info!("Session lock notifications registered");
+            Ok(Self { hwnd })
+        }
+    }
+}
+
+impl Drop for SessionNotificationGuard {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = WTSUnRegisterSessionNotification(self.hwnd);
+            let _ = DestroyWindow(self.hwnd);
+        }
+    }
+}
+
+/// Window procedure of the notification window.
+unsafe extern "system" fn session_window_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WM_WTSSESSION_CHANGE {
+        if let (Some(change), Some(handler)) = (
+            SessionChange::from_event(wparam.0 as u32),
+            SESSION_CHANGE_HANDLER.get(),
+        ) {
+            handler(change);
+        }
+        return LRESULT(0);
+    }
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
 #[cfg(test)]
 mod tests {
-    // Message loop tests are difficult to unit test without
+    // The message loop itself is difficult to unit test without
     // actually running a message loop. Integration tests would
-    // be more appropriate for this module.
+    // be more appropriate for it.
+    use super::*;
+
+    #[test]
+    fn test_session_change_from_event() {
+        assert_eq!(
+            SessionChange::from_event(WTS_SESSION_LOCK),
+            Some(SessionChange::Locked)
+        );
+        assert_eq!(
+            SessionChange::from_event(WTS_CONSOLE_DISCONNECT),
+            Some(SessionChange::Locked)
+        );
+        assert_eq!(
+            SessionChange::from_event(WTS_SESSION_UNLOCK),
+            Some(SessionChange::Unlocked)
+        );
+        assert_eq!(
+            SessionChange::from_event(WTS_REMOTE_CONNECT),
+            Some(SessionChange::Unlocked)
+        );
+        // Logon/logoff and remote control changes are ignored
+        assert_eq!(SessionChange::from_event(0x5), None);
+    }
 }
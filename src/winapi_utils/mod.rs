@@ -1,14 +1,19 @@
 //! Safe wrappers around Windows API calls.
 //!
 //! This module provides safe Rust abstractions over unsafe WinAPI functions
-//! for window enumeration, process information, and message loop handling.
+//! for window enumeration, process information, message loop handling and
+//! reading browser address bars through UI Automation.
 
 pub mod hooks;
 pub mod message_loop;
 pub mod process;
+pub mod shell;
+pub mod uia;
 pub mod window;
 
 pub use hooks::*;
 pub use message_loop::*;
 pub use process::*;
+pub use shell::*;
+pub use uia::*;
 pub use window::*;
@@ -1,12 +1,15 @@
 //! Process-related WinAPI wrappers.
 //!
 //! Provides safe abstractions for retrieving process information
-//! such as executable names.
+//! such as executable names and paths.
 
-use windows::Win32::Foundation::{CloseHandle, HANDLE};
+use windows::core::PWSTR;
+use windows::Win32::Foundation::{CloseHandle, ERROR_SUCCESS, HANDLE};
+use windows::Win32::Storage::Packaging::Appx::GetApplicationUserModelId;
 use windows::Win32::System::ProcessStatus::GetModuleBaseNameW;
 use windows::Win32::System::Threading::{
-    OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_VM_READ,
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    PROCESS_VM_READ,
 };
 
 /// RAII wrapper for Windows process handles.
@@ -81,6 +84,68 @@ pub fn get_process_name(pid: u32) -> Option<String> {
     Some(String::from_utf16_lossy(&buffer[..len as usize]))
 }
 
+/// Gets the full executable path of a process by its process ID.
+///
+/// Returns `None` under the same conditions as [`get_process_name`], e.g.
+/// for elevated processes when running unelevated.
+///
+/// # Arguments
+/// * `pid` - Process ID
+pub fn get_process_path(pid: u32) -> Option<String> {
+    let handle = ProcessHandle::open(pid)?;
+
+    // Long paths can exceed MAX_PATH, so allow the 32K extended-length limit
+    let mut buffer: Vec<u16> = vec![0; 32768];
+    let mut len = buffer.len() as u32;
+
+    unsafe {
+        QueryFullProcessImageNameW(
+            handle.as_raw(),
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut len,
+        )
+    }
+    .ok()?;
+
+    if len == 0 {
+        return None;
+    }
+
+    Some(String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+/// Longest AppUserModelID, excluding the terminating null
+/// (`APPLICATION_USER_MODEL_ID_MAX_LENGTH - 1`).
+const APP_USER_MODEL_ID_MAX_LEN: usize = 130;
+
+/// Gets the AppUserModelID of a packaged (UWP or MSIX) process, such as
+/// `Microsoft.WindowsCalculator_8wekyb3d8bbwe!App`.
+///
+/// Returns `None` for unpackaged processes or if the process can't be opened.
+///
+/// # Arguments
+/// * `pid` - Process ID
+pub fn get_app_user_model_id(pid: u32) -> Option<String> {
+    let handle = ProcessHandle::open(pid)?;
+
+    let mut buffer = [0u16; APP_USER_MODEL_ID_MAX_LEN + 1];
+    let mut len = buffer.len() as u32;
+
+    let result =
+        unsafe { GetApplicationUserModelId(handle.as_raw(), &mut len, PWSTR(buffer.as_mut_ptr())) };
+    if result != ERROR_SUCCESS {
+        return None;
+    }
+
+    // The length includes the terminating null
+    let len = (len as usize)
+        .saturating_sub(1)
+        .min(APP_USER_MODEL_ID_MAX_LEN);
+    let id = String::from_utf16_lossy(&buffer[..len]);
+    (!id.is_empty()).then_some(id)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,6 +169,23 @@ mod tests {
         assert!(name.is_none());
     }
 
+    #[test]
+    fn test_get_current_process_path() {
+        let path = get_process_path(std::process::id()).unwrap();
+        let name = get_process_name(std::process::id()).unwrap();
+
+        // The path ends with the executable name
+        assert!(path.to_lowercase().ends_with(&name.to_lowercase()));
+        assert!(get_process_path(0).is_none());
+    }
+
+    #[test]
+    fn test_app_user_model_id_none_for_unpackaged_process() {
+        // Test binaries aren't packaged
+        assert_eq!(get_app_user_model_id(std::process::id()), None);
+        assert_eq!(get_app_user_model_id(0), None);
+    }
+
     #[test]
     fn test_process_handle_drop() {
         // Just verify we can open and close without leaking
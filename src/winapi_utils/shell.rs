@@ -0,0 +1,28 @@
+//! Shell WinAPI wrappers.
+//!
+//! Provides a safe abstraction for handing URLs to the user's default
+//! browser.
+
+use windows::core::HSTRING;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::UI::Shell::ShellExecuteW;
+use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+/// Opens a URL with its registered handler (the default browser for http).
+///
+/// # Returns
+/// `true` if the shell accepted the request.
+pub fn open_url(url: &str) -> bool {
+    let result = unsafe {
+        ShellExecuteW(
+            HWND::default(),
+            &HSTRING::from("open"),
+            &HSTRING::from(url),
+            None,
+            None,
+            SW_SHOWNORMAL,
+        )
+    };
+    // Values above 32 indicate success
+    result.0 as isize > 32
+}
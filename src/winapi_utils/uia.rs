@@ -0,0 +1,102 @@
+//! UI Automation wrappers.
+//!
+//! Reads the address bar of known browsers so sessions can record the page
+//! URL. Lookups walk the browser's UI tree, so they are only attempted for
+//! the processes in [`BROWSER_PROCESSES`].
+
+use windows::core::VARIANT;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+};
+use windows::Win32::UI::Accessibility::{
+    CUIAutomation, IUIAutomation, IUIAutomationValuePattern, TreeScope_Descendants,
+    UIA_AutomationIdPropertyId, UIA_ControlTypePropertyId, UIA_EditControlTypeId,
+    UIA_ValuePatternId,
+};
+
+/// Browsers whose address bar can be read (lowercase process names).
+pub const BROWSER_PROCESSES: &[&str] = &["chrome.exe", "msedge.exe", "firefox.exe"];
+
+/// Automation ID of Firefox's address bar.
+const FIREFOX_URLBAR_ID: &str = "urlbar-input";
+
+thread_local! {
+    /// UI Automation client for the calling thread (COM is per thread).
+    static AUTOMATION: Option<IUIAutomation> = create_automation();
+}
+
+fn create_automation() -> Option<IUIAutomation> {
+    unsafe {
+        // S_FALSE (already initialized) and RPC_E_CHANGED_MODE both leave
+        // COM usable on this thread.
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        match CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER) {
+            Ok(automation) => Some(automation),
+            Err(e) => {
+                tracing::warn!(error = %e, "UI Automation unavailable, browser URLs not recorded");
+                None
+            }
+        }
+    }
+}
+
+/// Returns true if `process_name` is a browser with a readable address bar.
+pub fn is_known_browser(process_name: &str) -> bool {
+    BROWSER_PROCESSES
+        .iter()
+        .any(|b| b.eq_ignore_ascii_case(process_name))
+}
+
+/// Reads the address bar of a browser window.
+///
+/// Returns `None` for other processes, or if the address bar can't be found
+/// or is empty. The value is returned as shown, so Chromium browsers usually
+/// omit the `https://` scheme.
+///
+/// # Arguments
+/// * `hwnd` - Handle to the browser's top-level window
+/// * `process_name` - Executable name of the window's process
+pub fn get_browser_url(hwnd: HWND, process_name: &str) -> Option<String> {
+    if !is_known_browser(process_name) {
+        return None;
+    }
+    let firefox = process_name.eq_ignore_ascii_case("firefox.exe");
+
+    AUTOMATION.with(|automation| {
+        let automation = automation.as_ref()?;
+        unsafe { read_address_bar(automation, hwnd, firefox) }
+            .map_err(|e| tracing::trace!(error = %e, "Could not read browser address bar"))
+            .ok()
+            .flatten()
+    })
+}
+
+/// Finds the address bar (the first edit control, or Firefox's URL bar) and
+/// reads its value.
+unsafe fn read_address_bar(
+    automation: &IUIAutomation,
+    hwnd: HWND,
+    firefox: bool,
+) -> windows::core::Result<Option<String>> {
+    let window = automation.ElementFromHandle(hwnd)?;
+    let condition = if firefox {
+        automation.CreatePropertyCondition(
+            UIA_AutomationIdPropertyId,
+            &VARIANT::from(FIREFOX_URLBAR_ID),
+        )?
+    } else {
+        automation.CreatePropertyCondition(
+            UIA_ControlTypePropertyId,
+            &VARIANT::from(UIA_EditControlTypeId.0),
+        )?
+    };
+    let address_bar = window.FindFirst(TreeScope_Descendants, &condition)?;
+    let value = address_bar
+        .GetCurrentPatternAs::<IUIAutomationValuePattern>(UIA_ValuePatternId)?
+        .CurrentValue()?
+        .to_string();
+
+    let value = value.trim();
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
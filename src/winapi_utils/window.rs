@@ -1,13 +1,31 @@
 //! Window-related WinAPI wrappers.
 //!
 //! Provides safe abstractions for window enumeration, focus detection,
-//! and window text retrieval.
+//! window text retrieval and application icons.
 
-use windows::Win32::Foundation::HWND;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT, WPARAM};
+use windows::Win32::Graphics::Gdi::{
+    DeleteObject, GetDC, GetDIBits, GetMonitorInfoW, GetObjectW, MonitorFromWindow, ReleaseDC,
+    BITMAP, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HBITMAP, MONITORINFO,
+    MONITORINFOEXW, MONITOR_DEFAULTTONEAREST,
+};
 use windows::Win32::UI::WindowsAndMessaging::{
-    GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+    EnumChildWindows, GetClassNameW, GetDesktopWindow, GetForegroundWindow, GetIconInfo,
+    GetShellWindow, GetWindowRect, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+    IsWindow, SendMessageTimeoutW, SystemParametersInfoW, GCLP_HICON, GCLP_HICONSM,
+    GET_CLASS_LONG_INDEX, HICON, ICONINFO, ICON_BIG, ICON_SMALL2, SMTO_ABORTIFHUNG,
+    SPI_GETSCREENSAVERRUNNING, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS, WM_GETICON,
 };
 
+/// How long to wait for a window to answer `WM_GETICON`.
+const ICON_MESSAGE_TIMEOUT_MS: u32 = 100;
+
+/// PNG icons by lowercase process name.
+static ICON_CACHE: Lazy<Mutex<HashMap<String, Vec<u8>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
 /// Gets the handle of the currently focused (foreground) window.
 ///
 /// Returns `None` if no window has focus (e.g., desktop is focused).
@@ -91,10 +109,367 @@ pub fn get_window_thread_process_id(hwnd: HWND) -> (u32, u32) {
     (thread_id, process_id)
 }
 
+/// Checks whether a window is the desktop (wallpaper/icons) rather than an app.
+///
+/// Focusing the desktop activates the shell's `Progman` or `WorkerW` window.
+pub fn is_desktop_window(hwnd: HWND) -> bool {
+    unsafe {
+        if hwnd == GetDesktopWindow() || hwnd == GetShellWindow() {
+            return true;
+        }
+
+        let mut buffer = [0u16; 16];
+        let len = GetClassNameW(hwnd, &mut buffer);
+        if len <= 0 {
+            return false;
+        }
+
+        let class_name = String::from_utf16_lossy(&buffer[..len as usize]);
+        class_name == "Progman" || class_name == "WorkerW"
+    }
+}
+
+/// Checks whether a window rectangle covers an entire monitor rectangle.
+///
+/// Full-screen windows match or exceed the monitor bounds; maximized windows
+/// stop at the work area (above the taskbar) and don't qualify.
+pub fn rect_covers_monitor(window: &RECT, monitor: &RECT) -> bool {
+    window.left <= monitor.left
+        && window.top <= monitor.top
+        && window.right >= monitor.right
+        && window.bottom >= monitor.bottom
+}
+
+/// Checks whether a window is full-screen on the monitor it's displayed on.
+///
+/// The desktop and shell windows always cover the monitor and are never
+/// considered full-screen.
+pub fn is_fullscreen_window(hwnd: HWND) -> bool {
+    unsafe {
+        if hwnd == GetDesktopWindow() || hwnd == GetShellWindow() {
+            return false;
+        }
+
+        let mut window_rect = RECT::default();
+        if GetWindowRect(hwnd, &mut window_rect).is_err() {
+            return false;
+        }
+
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+            return false;
+        }
+
+        rect_covers_monitor(&window_rect, &info.rcMonitor)
+    }
+}
+
+/// Checks whether the screensaver is currently running.
+pub fn is_screensaver_running() -> bool {
+    let mut running = windows::Win32::Foundation::BOOL(0);
+    unsafe {
+        SystemParametersInfoW(
+            SPI_GETSCREENSAVERRUNNING,
+            0,
+            Some(&mut running as *mut _ as *mut std::ffi::c_void),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+        .is_ok()
+            && running.as_bool()
+    }
+}
+
+/// Gets the device name of the monitor a window is displayed on
+/// (e.g., `\\.\DISPLAY2`).
+///
+/// Windows spanning several monitors belong to the one with the largest
+/// overlap. Returns `None` if the window or its monitor can't be queried.
+pub fn get_window_monitor(hwnd: HWND) -> Option<String> {
+    unsafe {
+        // MONITOR_DEFAULTTONEAREST would still name a monitor for a closed window
+        if !IsWindow(hwnd).as_bool() {
+            return None;
+        }
+
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFOEXW::default();
+        info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+        if !GetMonitorInfoW(
+            monitor,
+            &mut info as *mut MONITORINFOEXW as *mut MONITORINFO,
+        )
+        .as_bool()
+        {
+            return None;
+        }
+
+        let len = info
+            .szDevice
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(info.szDevice.len());
+        let name = String::from_utf16_lossy(&info.szDevice[..len]);
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+/// Gets the AppUserModelID of the app shown in a UWP frame window.
+///
+/// UWP windows are frames owned by `ApplicationFrameHost.exe`; the app's
+/// content is a child window (`Windows.UI.Core.CoreWindow`) owned by the
+/// app's own process, whose AppUserModelID names the package. Returns `None`
+/// if no child belongs to another, packaged process, e.g. while the app is
+/// suspended or for frames that aren't UWP apps.
+pub fn get_uwp_app_id(hwnd: HWND) -> Option<String> {
+    /// Host process ID in, first child window process outside it out.
+    struct Search {
+        host_pid: u32,
+        app_pid: Option<u32>,
+    }
+
+    unsafe extern "system" fn find_app_child(child: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam.0 as *mut Search);
+        let (_, pid) = get_window_thread_process_id(child);
+        if pid != 0 && pid != search.host_pid {
+            search.app_pid = Some(pid);
+            return BOOL(0);
+        }
+        BOOL(1)
+    }
+
+    let (_, host_pid) = get_window_thread_process_id(hwnd);
+    if host_pid == 0 {
+        return None;
+    }
+    let mut search = Search {
+        host_pid,
+        app_pid: None,
+    };
+    unsafe {
+        // Returns false when the callback stops the enumeration early
+        let _ = EnumChildWindows(
+            hwnd,
+            Some(find_app_child),
+            LPARAM(&mut search as *mut Search as isize),
+        );
+    }
+    crate::winapi_utils::get_app_user_model_id(search.app_pid?)
+}
+
+#[cfg(target_pointer_width = "64")]
+unsafe fn class_icon(hwnd: HWND, index: GET_CLASS_LONG_INDEX) -> isize {
+    windows::Win32::UI::WindowsAndMessaging::GetClassLongPtrW(hwnd, index) as isize
+}
+
+#[cfg(not(target_pointer_width = "64"))]
+unsafe fn class_icon(hwnd: HWND, index: GET_CLASS_LONG_INDEX) -> isize {
+    windows::Win32::UI::WindowsAndMessaging::GetClassLongW(hwnd, index) as isize
+}
+
+/// Finds the window's icon handle: `WM_GETICON` first, then the class icon.
+///
+/// The returned icon is owned by the window or its class and must not be
+/// destroyed.
+fn find_window_icon(hwnd: HWND) -> Option<HICON> {
+    unsafe {
+        for kind in [ICON_BIG, ICON_SMALL2] {
+            let mut result = 0usize;
+            let sent = SendMessageTimeoutW(
+                hwnd,
+                WM_GETICON,
+                WPARAM(kind as usize),
+                LPARAM(0),
+                SMTO_ABORTIFHUNG,
+                ICON_MESSAGE_TIMEOUT_MS,
+                Some(&mut result),
+            );
+            if sent.0 != 0 && result != 0 {
+                return Some(HICON(result as *mut std::ffi::c_void));
+            }
+        }
+        [GCLP_HICON, GCLP_HICONSM]
+            .into_iter()
+            .map(|index| class_icon(hwnd, index))
+            .find(|&handle| handle != 0)
+            .map(|handle| HICON(handle as *mut std::ffi::c_void))
+    }
+}
+
+/// Reads a bitmap as top-down 32-bit BGRA pixels.
+unsafe fn bitmap_bgra(bitmap: HBITMAP, width: i32, height: i32) -> Option<Vec<u8>> {
+    let mut info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width,
+            biHeight: -height,
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+
+    let dc = GetDC(None);
+    let lines = GetDIBits(
+        dc,
+        bitmap,
+        0,
+        height as u32,
+        Some(pixels.as_mut_ptr().cast()),
+        &mut info,
+        DIB_RGB_COLORS,
+    );
+    ReleaseDC(None, dc);
+
+    (lines == height).then_some(pixels)
+}
+
+/// Converts an icon to RGBA pixels, returning `(width, height, pixels)`.
+fn icon_to_rgba(icon: HICON) -> Option<(u32, u32, Vec<u8>)> {
+    unsafe {
+        let mut info = ICONINFO::default();
+        GetIconInfo(icon, &mut info).ok()?;
+
+        let pixels = (|| {
+            // Monochrome icons have no color bitmap; not worth supporting
+            if info.hbmColor.is_invalid() {
+                return None;
+            }
+            let mut bitmap = BITMAP::default();
+            let size = std::mem::size_of::<BITMAP>() as i32;
+            if GetObjectW(
+                info.hbmColor,
+                size,
+                Some((&mut bitmap as *mut BITMAP).cast()),
+            ) == 0
+            {
+                return None;
+            }
+            let (width, height) = (bitmap.bmWidth, bitmap.bmHeight);
+            if width <= 0 || height <= 0 {
+                return None;
+            }
+
+            let color = bitmap_bgra(info.hbmColor, width, height)?;
+            let mask = if info.hbmMask.is_invalid() {
+                None
+            } else {
+                bitmap_bgra(info.hbmMask, width, height)
+            };
+            Some((
+                width as u32,
+                height as u32,
+                bgra_to_rgba(&color, mask.as_deref()),
+            ))
+        })();
+
+        if !info.hbmColor.is_invalid() {
+            let _ = DeleteObject(info.hbmColor);
+        }
+        if !info.hbmMask.is_invalid() {
+            let _ = DeleteObject(info.hbmMask);
+        }
+        pixels
+    }
+}
+
+/// Converts BGRA icon pixels to RGBA.
+///
+/// Older icons have no alpha channel (all zero); their transparency comes
+/// from the AND mask instead, where white marks transparent pixels.
+pub fn bgra_to_rgba(color: &[u8], mask: Option<&[u8]>) -> Vec<u8> {
+    let has_alpha = color.chunks_exact(4).any(|px| px[3] != 0);
+    let mut rgba = Vec::with_capacity(color.len());
+    for (i, px) in color.chunks_exact(4).enumerate() {
+        let alpha = if has_alpha {
+            px[3]
+        } else {
+            match mask.and_then(|m| m.get(i * 4)) {
+                Some(&0) | None => 255,
+                Some(_) => 0,
+            }
+        };
+        rgba.extend_from_slice(&[px[2], px[1], px[0], alpha]);
+    }
+    rgba
+}
+
+/// Encodes RGBA pixels as a PNG image.
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Option<Vec<u8>> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().ok()?;
+    writer.write_image_data(rgba).ok()?;
+    writer.finish().ok()?;
+    Some(png)
+}
+
+/// Gets a window's icon as PNG bytes.
+///
+/// Returns `None` if the window has no icon or it can't be read, which is
+/// common for elevated windows and UWP apps hosted by `ApplicationFrameHost`.
+pub fn get_window_icon(hwnd: HWND) -> Option<Vec<u8>> {
+    let icon = find_window_icon(hwnd)?;
+    let (width, height, rgba) = icon_to_rgba(icon)?;
+    encode_png(width, height, &rgba)
+}
+
+/// Returns true if an icon is cached for this process.
+pub fn has_app_icon(process_name: &str) -> bool {
+    ICON_CACHE
+        .lock()
+        .map(|cache| cache.contains_key(&process_name.to_lowercase()))
+        .unwrap_or(false)
+}
+
+/// Caches a process's PNG icon (case-insensitive name).
+pub fn cache_app_icon(process_name: &str, png: Vec<u8>) {
+    if let Ok(mut cache) = ICON_CACHE.lock() {
+        cache.insert(process_name.to_lowercase(), png);
+    }
+}
+
+/// Gets a cached PNG icon by process name (case-insensitive).
+pub fn get_app_icon(process_name: &str) -> Option<Vec<u8>> {
+    ICON_CACHE
+        .lock()
+        .ok()?
+        .get(&process_name.to_lowercase())
+        .cloned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_icon_pixels_and_cache() {
+        // One opaque blue pixel with alpha, one fully transparent
+        let rgba = bgra_to_rgba(&[255, 0, 0, 255, 0, 0, 0, 0], None);
+        assert_eq!(rgba, vec![0, 0, 255, 255, 0, 0, 0, 0]);
+
+        // No alpha channel: transparency comes from the mask
+        let mask = [0, 0, 0, 0, 255, 255, 255, 255];
+        let rgba = bgra_to_rgba(&[0, 255, 0, 0, 0, 255, 0, 0], Some(&mask));
+        assert_eq!(rgba, vec![0, 255, 0, 255, 0, 255, 0, 0]);
+
+        let png = encode_png(2, 1, &rgba).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        cache_app_icon("IconTest.exe", png.clone());
+        assert!(has_app_icon("icontest.exe"));
+        assert_eq!(get_app_icon("ICONTEST.EXE"), Some(png));
+        assert_eq!(get_app_icon("missing.exe"), None);
+    }
+
     #[test]
     fn test_get_foreground_window_returns_some() {
         // In a normal desktop environment, there should always be a foreground window
@@ -111,6 +486,46 @@ mod tests {
         assert!(text.is_empty());
     }
 
+    #[test]
+    fn test_rect_covers_monitor() {
+        let rect = |left, top, right, bottom| RECT {
+            left,
+            top,
+            right,
+            bottom,
+        };
+        let monitor = rect(0, 0, 1920, 1080);
+
+        // Exact match and borderless windows overhanging the edges
+        assert!(rect_covers_monitor(&rect(0, 0, 1920, 1080), &monitor));
+        assert!(rect_covers_monitor(&rect(-8, -8, 1928, 1088), &monitor));
+
+        // Maximized window stopping above the taskbar
+        assert!(!rect_covers_monitor(&rect(-8, -8, 1928, 1040), &monitor));
+        // Regular window
+        assert!(!rect_covers_monitor(&rect(100, 100, 900, 700), &monitor));
+
+        // Second monitor to the right
+        let second = rect(1920, 0, 4480, 1440);
+        assert!(rect_covers_monitor(&rect(1920, 0, 4480, 1440), &second));
+        assert!(!rect_covers_monitor(&monitor, &second));
+    }
+
+    #[test]
+    fn test_is_fullscreen_window_false_on_invalid_handle() {
+        assert!(!is_fullscreen_window(HWND(std::ptr::null_mut())));
+    }
+
+    #[test]
+    fn test_get_window_monitor_none_on_invalid_handle() {
+        assert_eq!(get_window_monitor(HWND(std::ptr::null_mut())), None);
+    }
+
+    #[test]
+    fn test_get_uwp_app_id_none_on_invalid_handle() {
+        assert_eq!(get_uwp_app_id(HWND(std::ptr::null_mut())), None);
+    }
+
     #[test]
     fn test_get_window_thread_process_id_on_invalid_handle() {
         let invalid_hwnd = HWND(std::ptr::null_mut());